@@ -0,0 +1,73 @@
+use accelsim::tracegen;
+use clap::Parser;
+use color_eyre::eyre;
+use std::path::PathBuf;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Direction {
+    /// Convert accelsim traces (kernelslist.g + .traceg) to native traces.
+    AccelsimToNative,
+    /// Convert native traces (commands.json + msgpack) to accelsim traces.
+    NativeToAccelsim,
+}
+
+#[derive(Parser, Debug)]
+pub struct Options {
+    /// Conversion direction.
+    #[arg(value_enum)]
+    pub direction: Direction,
+
+    /// Directory with the native traces (commands.json + msgpack traces).
+    #[arg(long = "native-traces-dir")]
+    pub native_traces_dir: PathBuf,
+
+    /// Directory with the accelsim traces (kernelslist.g + .traceg traces).
+    #[arg(long = "accelsim-traces-dir")]
+    pub accelsim_traces_dir: PathBuf,
+
+    /// Path to the source commands file.
+    ///
+    /// Defaults to kernelslist.g or commands.json in the source trace
+    /// directory, depending on the conversion direction.
+    #[arg(long = "commands")]
+    pub commands: Option<PathBuf>,
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+    let options = Options::parse();
+    println!("options: {:#?}", &options);
+
+    let start = Instant::now();
+    let generated_commands_path = match options.direction {
+        Direction::AccelsimToNative => {
+            utils::fs::create_dirs(&options.native_traces_dir)?;
+            let commands_path = options
+                .commands
+                .clone()
+                .unwrap_or_else(|| options.accelsim_traces_dir.join("kernelslist.g"));
+            tracegen::convert_accelsim_to_box_traces(&tracegen::Conversion {
+                native_commands_path: &commands_path,
+                box_traces_dir: &options.native_traces_dir,
+                accelsim_traces_dir: &options.accelsim_traces_dir,
+            })?
+        }
+        Direction::NativeToAccelsim => {
+            utils::fs::create_dirs(&options.accelsim_traces_dir)?;
+            let commands_path = options
+                .commands
+                .clone()
+                .unwrap_or_else(|| options.native_traces_dir.join("commands.json"));
+            tracegen::convert_box_to_accelsim_traces(&tracegen::Conversion {
+                native_commands_path: &commands_path,
+                box_traces_dir: &options.native_traces_dir,
+                accelsim_traces_dir: &options.accelsim_traces_dir,
+            })?
+        }
+    };
+
+    println!("generated {}", generated_commands_path.display());
+    println!("done after {:?}", start.elapsed());
+    Ok(())
+}
@@ -105,7 +105,7 @@ fn convert_cache_stats(
             let access = AccessStatus((kind, AccessStat::ReservationFailure(reservation_failure)));
             cache_stats.inner.insert(
                 (None, access),
-                per_cache_stat.copied().unwrap_or(0.0) as usize,
+                per_cache_stat.copied().unwrap_or(0.0) as u64,
             );
         }
         for status in RequestStatus::iter() {
@@ -117,7 +117,7 @@ fn convert_cache_stats(
             let access = AccessStatus((kind, AccessStat::Status(status)));
             cache_stats.inner.insert(
                 (None, access),
-                per_cache_stat.copied().unwrap_or(0.0) as usize,
+                per_cache_stat.copied().unwrap_or(0.0) as u64,
             );
         }
     }
@@ -232,6 +232,10 @@ impl TryFrom<Stats> for stats::PerKernel {
                 let dram = stats::DRAM {
                     kernel_info: kernel_info.clone(),
                     bank_accesses,
+                    range_accesses: HashMap::new(),
+                    total_latency: 0,
+                    total_estimated_latency: 0,
+                    num_latency_samples: 0,
                     num_banks: 1,
                     num_cores: 1,
                     num_chips: 1,
@@ -242,6 +246,13 @@ impl TryFrom<Stats> for stats::PerKernel {
                         kernel_name: "".to_string(),
                         kernel_name_mangled: kernel_name.clone(),
                         kernel_launch_id,
+                        // launch configuration is not part of the accelsim log
+                        grid_dim: (0, 0, 0),
+                        block_dim: (0, 0, 0),
+                        shared_mem_bytes: 0,
+                        num_registers: 0,
+                        stream_id: 0,
+                        tenant: None,
                         cycles: stats
                             .get(&(
                                 kernel_name.clone(),
@@ -266,8 +277,19 @@ impl TryFrom<Stats> for stats::PerKernel {
                             ))
                             .copied()
                             .unwrap_or(0.0) as u64,
+                        num_skipped_blocks: 0,
+                        num_reexecuted_instructions: 0,
+                        kernel_launch_wait_cycles: 0,
                         elapsed_millis: 0,
                         is_release_build: stats.is_release_build,
+                        parallel_seed: None,
+                        longest_dependency_chain: 0,
+                        is_persistent_kernel: false,
+                        num_trace_loop_iterations: 0,
+                        max_trace_loop_iterations: 0,
+                        num_dram_throttled_cycles: 0,
+                        num_host_gap_cycles: 0,
+                        num_malformed_trace_records: 0,
                     },
                     accesses: stats::Accesses {
                         kernel_info: kernel_info.clone(),
@@ -275,17 +297,35 @@ impl TryFrom<Stats> for stats::PerKernel {
                     },
                     dram,
                     instructions,
+                    per_pc: stats::PerPC::default(),
+                    loops: stats::Loops::default(),
                     l1i_stats: l1_inst_stats,
                     l1t_stats: l1_tex_stats,
                     l1c_stats: l1_const_stats,
                     l1d_stats: l1_data_stats,
                     l2d_stats: l2_data_stats,
+                    interconn: stats::interconn::Interconn::default(),
+                    schedulers: HashMap::new(),
                     stall_dram_full: 0, // todo
+                    num_l2_bypassed: 0,
+                    stall_interconn_to_shader: 0,
+                    ldst_return_path_stall_cycles: 0,
+                    num_writeback_stalls: HashMap::new(),
+                    num_issued_per_core: HashMap::new(),
+                    num_reorder_conflicts: HashMap::new(),
+                    l2_arbitration_delays: HashMap::new(),
+                    ejection_buffer_occupancy: HashMap::new(),
+                    ldst_response_buffer_occupancy: HashMap::new(),
+                    utilization: stats::utilization::Utilization::default(),
+                    memcopy: stats::Memcopy::default(),
+                    coherence: stats::Coherence::default(),
+                    l2_residency: stats::L2Residency::default(),
                 }
             })
             .collect();
 
         Ok(Self {
+            schema_version: stats::SCHEMA_VERSION,
             inner,
             no_kernel: stats::Stats::empty(),
             config: stats::Config {
@@ -293,6 +333,8 @@ impl TryFrom<Stats> for stats::PerKernel {
                 num_mem_units: 1,
                 num_dram_banks: 1,
                 num_sub_partitions: 1,
+                max_issue_per_core: 0,
+                perfect_scheduler: false,
             },
         })
     }
@@ -105,6 +105,9 @@ pub fn parse_kernel_launch(
         local_mem_addr_limit: 0,
         nvbit_version: String::new(),
         device_properties: trace_model::DeviceProperties::default(),
+        store_value_digest: None,
+        max_registers: None,
+        host_timestamp_nanos: None,
     };
 
     let kernel_trace_path = traces_dir.as_ref().join(&kernel_trace_file_name);
@@ -194,6 +197,9 @@ pub fn parse_memcopy_host_to_device(line: &str) -> eyre::Result<Command> {
         allocation_name: None,
         dest_device_addr,
         num_bytes,
+        // accelsim traces do not record the stream of a copy
+        stream_id: 0,
+        host_timestamp_nanos: None,
     }))
 }
 
@@ -474,6 +480,7 @@ pub fn convert_instruction(
         active_mask: trace_instruction.active_mask,
         addrs: trace_instruction.mem_addresses,
         thread_indices: [(0, 0, 0); 32],
+        store_data_digest: None,
     }))
 }
 
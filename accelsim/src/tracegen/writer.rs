@@ -244,6 +244,9 @@ mod tests {
             nvbit_version: "1.5.5".to_string(),
             trace_file: String::new(),
             device_properties: trace_model::DeviceProperties::default(),
+            store_value_digest: None,
+            max_registers: None,
+            host_timestamp_nanos: None,
         };
         let mut writer = std::io::Cursor::new(Vec::new());
         super::write_kernel_info(&kernel, &mut writer)?;
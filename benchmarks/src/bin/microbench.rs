@@ -0,0 +1,210 @@
+use clap::Parser;
+use color_eyre::eyre;
+use gpucachesim::config;
+use gpucachesim_benchmarks::{pchase, vectoradd};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Suite {
+    /// Pointer chase latency curve over the array size.
+    Latency,
+    /// Streaming bandwidth curve over the array size.
+    Bandwidth,
+    /// Pointer chase latency curve over the access stride.
+    Stride,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[clap(
+    version = option_env!("CARGO_PKG_VERSION").unwrap_or("unknown"),
+    about = "run latency and bandwidth microbenchmarks against the simulator",
+    author = "romnn <contact@romnn.com>",
+)]
+pub struct Options {
+    /// The suite to run.
+    #[clap(value_enum)]
+    pub suite: Suite,
+
+    #[clap(
+        short = 'm',
+        long = "mem",
+        help = "the memory to microbenchmark",
+        default_value = "l1data"
+    )]
+    pub memory: String,
+
+    #[clap(
+        long = "start-size",
+        help = "smallest array size in bytes",
+        default_value_t = 4 * 1024,
+    )]
+    pub start_size_bytes: usize,
+
+    #[clap(
+        long = "end-size",
+        help = "largest array size in bytes",
+        default_value_t = 64 * 1024,
+    )]
+    pub end_size_bytes: usize,
+
+    #[clap(
+        short = 's',
+        long = "stride",
+        help = "access stride in bytes for the latency suite",
+        default_value_t = 128
+    )]
+    pub stride_bytes: usize,
+
+    #[clap(
+        short = 'n',
+        long = "size",
+        help = "array size in bytes for the stride suite",
+        default_value_t = 64 * 1024,
+    )]
+    pub size_bytes: usize,
+
+    #[clap(
+        short = 'k',
+        long = "iterations",
+        help = "number of loads per measurement",
+        default_value_t = 256
+    )]
+    pub iter_size: usize,
+
+    #[clap(
+        short = 'w',
+        long = "warmup",
+        help = "number of warmup iterations",
+        default_value_t = 1
+    )]
+    pub warmup_iterations: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CsvRow {
+    pub suite: String,
+    /// Size of the array in bytes.
+    pub size_bytes: usize,
+    /// Access stride in bytes (latency and stride suites only).
+    pub stride_bytes: Option<usize>,
+    /// Total kernel cycles of the measurement.
+    pub cycles: u64,
+    /// Mean number of cycles per load (latency and stride suites only).
+    pub cycles_per_load: Option<f64>,
+    /// Mean number of bytes transferred per cycle (bandwidth suite only).
+    pub bytes_per_cycle: Option<f64>,
+}
+
+/// Simulate a reconstructed trace and return the total kernel cycles.
+fn simulate(
+    commands: Vec<trace_model::command::Command>,
+    kernel_traces: Vec<(
+        trace_model::command::KernelLaunch,
+        trace_model::MemAccessTrace,
+    )>,
+) -> eyre::Result<u64> {
+    let temp_dir = tempfile::tempdir()?;
+    let traces_dir = temp_dir.path();
+    gpucachesim::exec::write_traces(commands, kernel_traces, traces_dir)?;
+
+    let sim_config = config::gtx1080::build_config(&config::Input::default())?;
+    gpucachesim::init_deadlock_detector();
+    let mut sim = gpucachesim::config::GTX1080::new(Arc::new(sim_config));
+    sim.add_commands(traces_dir.join("commands.json"), traces_dir)?;
+    sim.run()?;
+
+    let stats = sim.stats();
+    let reduced = stats.reduce();
+    Ok(reduced.sim.cycles)
+}
+
+async fn latency(
+    options: &Options,
+    memory: pchase::Memory,
+    size_bytes: usize,
+    stride_bytes: usize,
+) -> eyre::Result<CsvRow> {
+    let (commands, kernel_traces) = pchase::pchase(
+        memory,
+        size_bytes,
+        stride_bytes,
+        options.warmup_iterations,
+        options.iter_size,
+    )
+    .await?;
+    let cycles = simulate(commands, kernel_traces)?;
+    let num_loads = (options.warmup_iterations + 1) * options.iter_size;
+    Ok(CsvRow {
+        suite: format!("{:?}", options.suite),
+        size_bytes,
+        stride_bytes: Some(stride_bytes),
+        cycles,
+        cycles_per_load: Some(cycles as f64 / num_loads as f64),
+        bytes_per_cycle: None,
+    })
+}
+
+async fn bandwidth(options: &Options, size_bytes: usize) -> eyre::Result<CsvRow> {
+    let n = size_bytes / std::mem::size_of::<f32>();
+    let (commands, kernel_traces) = vectoradd::benchmark::<f32>(n).await?;
+    let cycles = simulate(commands, kernel_traces)?;
+    // two streaming reads and one streaming write per element
+    let bytes = 3 * n * std::mem::size_of::<f32>();
+    Ok(CsvRow {
+        suite: format!("{:?}", options.suite),
+        size_bytes,
+        stride_bytes: None,
+        cycles,
+        cycles_per_load: None,
+        bytes_per_cycle: Some(bytes as f64 / cycles as f64),
+    })
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let start = std::time::Instant::now();
+    color_eyre::install()?;
+    gpucachesim::init_logging();
+
+    let options = Options::parse();
+    let memory = pchase::Memory::from_str(&options.memory)?;
+
+    let mut csv_writer = csv::WriterBuilder::new()
+        .flexible(false)
+        .from_writer(std::io::stdout());
+
+    match options.suite {
+        Suite::Latency => {
+            let mut size_bytes = options.start_size_bytes;
+            while size_bytes <= options.end_size_bytes {
+                eprintln!("latency: size={size_bytes} stride={}", options.stride_bytes);
+                let row = latency(&options, memory, size_bytes, options.stride_bytes).await?;
+                csv_writer.serialize(row)?;
+                size_bytes *= 2;
+            }
+        }
+        Suite::Bandwidth => {
+            let mut size_bytes = options.start_size_bytes;
+            while size_bytes <= options.end_size_bytes {
+                eprintln!("bandwidth: size={size_bytes}");
+                let row = bandwidth(&options, size_bytes).await?;
+                csv_writer.serialize(row)?;
+                size_bytes *= 2;
+            }
+        }
+        Suite::Stride => {
+            let mut stride_bytes = std::mem::size_of::<u32>();
+            while stride_bytes <= options.size_bytes / 2 {
+                eprintln!("stride: size={} stride={stride_bytes}", options.size_bytes);
+                let row = latency(&options, memory, options.size_bytes, stride_bytes).await?;
+                csv_writer.serialize(row)?;
+                stride_bytes *= 2;
+            }
+        }
+    }
+    csv_writer.flush()?;
+
+    eprintln!("completed in {:?}", start.elapsed());
+    Ok(())
+}
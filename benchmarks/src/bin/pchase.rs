@@ -192,7 +192,7 @@ where
             // l1_latency: 1,
             l1_latency: 1,
             l1_hit_latency: 80,
-            // l1_banks_hashing_function: CacheSetIndexFunc::LINEAR_SET_FUNCTION,
+            l1_banks_hashing_function: gpucachesim::config::CacheSetIndexFunc::LINEAR_SET_FUNCTION,
             // l1_banks_hashing_function: Box::<cache::set_index::linear::SetIndex>::default(),
             l1_banks_byte_interleaving: 32,
             l1_banks: 1,
@@ -203,12 +203,14 @@ where
                 // 128B cache
                 num_sets: 2,
                 line_size: 32,
+                sector_size: gpucachesim::mem_sub_partition::SECTOR_SIZE,
                 associativity: 1,
                 // num_sets: 4, // 64,
                 // line_size: 128,
                 // associativity: 48, // 6,
                 replacement_policy: gpucachesim::cache::config::ReplacementPolicy::LRU,
                 write_policy: gpucachesim::cache::config::WritePolicy::LOCAL_WB_GLOBAL_WT,
+                write_policy_overrides: gpucachesim::cache::config::WritePolicyOverrides::default(),
                 allocate_policy: gpucachesim::cache::config::AllocatePolicy::ON_MISS,
                 write_allocate_policy:
                     gpucachesim::cache::config::WriteAllocatePolicy::NO_WRITE_ALLOCATE,
@@ -222,6 +224,8 @@ where
                 result_fifo_entries: None,
                 l1_cache_write_ratio_percent: 0,
                 data_port_width: None,
+                record_access_heatmap: false,
+                compression: None,
             }),
         }));
     }
@@ -280,12 +284,12 @@ where
     let l1d_stats = reduced.l1d_stats.reduce();
     // dbg!(&l1d_stats);
 
-    let l1d_read_hits: usize = l1d_stats
+    let l1d_read_hits: u64 = l1d_stats
         .iter()
         .filter(|((_, access), _)| access.is_read() && access.is_hit())
         .map(|(_, count)| count)
         .sum();
-    let l1d_read_misses: usize = l1d_stats
+    let l1d_read_misses: u64 = l1d_stats
         .iter()
         .filter(|((_, access), _)| {
             access.is_read() && (access.is_miss() || access.is_pending_hit())
@@ -7,6 +7,17 @@ fn is_debug() -> bool {
     }
 }
 
+fn git_commit_hash() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 fn main() {
     let build_profile = if is_debug() {
         "debug_build"
@@ -15,4 +26,11 @@ fn main() {
     };
 
     println!("cargo:rustc-cfg=feature={build_profile:?}");
+
+    // embed the git commit so output files are self-describing
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!(
+        "cargo:rustc-env=GIT_COMMIT_HASH={}",
+        git_commit_hash().unwrap_or_else(|| "unknown".to_string())
+    );
 }
@@ -359,6 +359,7 @@ impl TraceGenerator for Tracer {
                     device_ptr: base_addr + addr,
                     fill_l2: options.fill_l2,
                     num_bytes,
+                    host_timestamp_nanos: None,
                 },
             ));
 
@@ -567,6 +568,7 @@ impl TraceGenerator for Tracer {
                 active_mask: trace_model::ActiveMask::ZERO,
                 addrs: [0; 32],
                 thread_indices: [(0, 0, 0); 32],
+                store_data_digest: None,
             };
 
             let mut pc = 0;
@@ -712,6 +714,9 @@ impl TraceGenerator for Tracer {
             local_mem_addr_limit: 0,
             nvbit_version: "none".to_string(),
             device_properties: trace_model::DeviceProperties::default(),
+            store_value_digest: None,
+            max_registers: None,
+            host_timestamp_nanos: None,
         };
         self.commands
             .lock()
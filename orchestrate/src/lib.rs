@@ -0,0 +1,333 @@
+#![allow(clippy::missing_panics_doc, clippy::missing_errors_doc)]
+
+//! Orchestrates multiple simulator processes for a parameter sweep.
+//!
+//! Unlike GNU parallel, the orchestrator caps the total resident memory
+//! of all running simulations and holds back or suspends runs when the
+//! cap would be exceeded, because the memory usage of a single
+//! simulation is hard to predict upfront.
+
+use color_eyre::eyre::{self, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// A single simulation process of a sweep.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Run {
+    /// Unique name of the run, used for log and stats file collection.
+    pub name: String,
+    /// Path to the simulator executable.
+    pub executable: PathBuf,
+    /// Arguments passed to the executable.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Additional environment variables for the process.
+    #[serde(default)]
+    pub env: Vec<(String, String)>,
+    /// Working directory of the process.
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Stats file the run writes, collected into the output directory
+    /// after the run succeeds.
+    #[serde(default)]
+    pub stats_file: Option<PathBuf>,
+}
+
+/// Orchestrator options.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Maximum number of concurrently running processes.
+    ///
+    /// Defaults to the number of physical cores, which also caps the
+    /// total CPU usage since the serial simulator is single threaded.
+    pub max_concurrent: Option<usize>,
+    /// Maximum total resident memory of all running processes in bytes.
+    pub max_memory: Option<u64>,
+    /// Number of times a failed run is restarted before giving up.
+    pub max_restarts: usize,
+    /// Interval between memory and completion checks.
+    pub poll_interval: Duration,
+    /// Directory the log and stats files of completed runs are
+    /// collected into.
+    pub output_dir: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            max_concurrent: None,
+            max_memory: None,
+            max_restarts: 1,
+            poll_interval: Duration::from_millis(500),
+            output_dir: None,
+        }
+    }
+}
+
+/// Outcome of a single run of the sweep.
+#[derive(Debug)]
+pub struct RunResult {
+    pub run: Run,
+    /// Number of times the run was restarted.
+    pub restarts: usize,
+    pub status: Option<std::process::ExitStatus>,
+    /// Location of the collected stats file.
+    pub stats_file: Option<PathBuf>,
+}
+
+impl RunResult {
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.status.is_some_and(|status| status.success())
+    }
+}
+
+/// Resident set size of a process in bytes.
+///
+/// Returns `None` when the process is gone or on unsupported platforms.
+#[must_use]
+pub fn resident_memory(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    let vm_rss = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?;
+    let kilobytes: u64 = vm_rss.trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[derive(Debug)]
+struct Active {
+    run: Run,
+    restarts: usize,
+    child: std::process::Child,
+    started: Instant,
+}
+
+/// Runs the processes of a sweep subject to resource caps.
+#[derive(Debug)]
+pub struct Orchestrator {
+    runs: Vec<Run>,
+    options: Options,
+}
+
+impl Orchestrator {
+    #[must_use]
+    pub fn new(runs: Vec<Run>, options: Options) -> Self {
+        Self { runs, options }
+    }
+
+    /// Runs the full sweep to completion.
+    ///
+    /// Failed runs are restarted up to
+    /// [`max_restarts`](Options::max_restarts) times and reported in the
+    /// results rather than aborting the remaining sweep.
+    pub fn run(&self) -> eyre::Result<Vec<RunResult>> {
+        let max_concurrent = self
+            .options
+            .max_concurrent
+            .unwrap_or_else(num_cpus::get_physical)
+            .max(1);
+
+        if let Some(output_dir) = &self.options.output_dir {
+            utils::fs::create_dirs(output_dir)?;
+        }
+
+        let mut pending: VecDeque<(Run, usize)> =
+            self.runs.iter().cloned().map(|run| (run, 0)).collect();
+        let mut active: Vec<Active> = Vec::new();
+        let mut results: Vec<RunResult> = Vec::new();
+
+        while !(pending.is_empty() && active.is_empty()) {
+            // reap completed runs
+            let mut i = 0;
+            while i < active.len() {
+                let status = active[i].child.try_wait()?;
+                let Some(status) = status else {
+                    i += 1;
+                    continue;
+                };
+                let Active { run, restarts, .. } = active.swap_remove(i);
+                if status.success() {
+                    log::info!("run {} completed", &run.name);
+                    let stats_file = self.collect_stats_file(&run)?;
+                    results.push(RunResult {
+                        run,
+                        restarts,
+                        status: Some(status),
+                        stats_file,
+                    });
+                } else if restarts < self.options.max_restarts {
+                    log::warn!("run {} failed with {status}: restarting", &run.name);
+                    pending.push_back((run, restarts + 1));
+                } else {
+                    log::error!("run {} failed with {status}: giving up", &run.name);
+                    results.push(RunResult {
+                        run,
+                        restarts,
+                        status: Some(status),
+                        stats_file: None,
+                    });
+                }
+            }
+
+            // enforce the memory cap: put the youngest runs back into the
+            // queue until the total resident memory fits again
+            let mut total_memory = self.total_resident_memory(&active);
+            if let Some(max_memory) = self.options.max_memory {
+                while total_memory > max_memory && active.len() > 1 {
+                    let youngest = active
+                        .iter()
+                        .enumerate()
+                        .max_by_key(|(_, active)| active.started)
+                        .map(|(i, _)| i)
+                        .unwrap();
+                    let mut evicted = active.swap_remove(youngest);
+                    log::warn!(
+                        "memory cap of {} exceeded ({}): evicting run {}",
+                        human_bytes::human_bytes(max_memory as f64),
+                        human_bytes::human_bytes(total_memory as f64),
+                        &evicted.run.name,
+                    );
+                    evicted.child.kill().ok();
+                    evicted.child.wait().ok();
+                    // eviction is no fault of the run and does not count
+                    // as a restart
+                    pending.push_front((evicted.run, evicted.restarts));
+                    total_memory = self.total_resident_memory(&active);
+                }
+            }
+
+            // spawn new runs while below the caps
+            let below_memory_cap = self
+                .options
+                .max_memory
+                .is_none_or(|max_memory| total_memory < max_memory);
+            while active.len() < max_concurrent
+                && (active.is_empty() || below_memory_cap)
+                && !pending.is_empty()
+            {
+                let (run, restarts) = pending.pop_front().unwrap();
+                let child = self.spawn(&run)?;
+                active.push(Active {
+                    run,
+                    restarts,
+                    child,
+                    started: Instant::now(),
+                });
+            }
+
+            std::thread::sleep(self.options.poll_interval);
+        }
+        Ok(results)
+    }
+
+    fn total_resident_memory(&self, active: &[Active]) -> u64 {
+        active
+            .iter()
+            .filter_map(|active| resident_memory(active.child.id()))
+            .sum()
+    }
+
+    fn spawn(&self, run: &Run) -> eyre::Result<std::process::Child> {
+        let mut cmd = std::process::Command::new(&run.executable);
+        cmd.args(&run.args);
+        cmd.envs(run.env.iter().map(|(k, v)| (k, v)));
+        if let Some(working_dir) = &run.working_dir {
+            cmd.current_dir(working_dir);
+        }
+        match &self.options.output_dir {
+            Some(output_dir) => {
+                let log_path = output_dir.join(&run.name).with_extension("log");
+                let log_file = std::fs::File::create(&log_path)
+                    .wrap_err_with(|| format!("failed to create {}", log_path.display()))?;
+                cmd.stdout(log_file.try_clone()?);
+                cmd.stderr(log_file);
+            }
+            None => {
+                cmd.stdout(std::process::Stdio::null());
+                cmd.stderr(std::process::Stdio::null());
+            }
+        }
+        log::info!("spawning run {}", &run.name);
+        cmd.spawn()
+            .wrap_err_with(|| format!("failed to spawn run {}", &run.name))
+    }
+
+    fn collect_stats_file(&self, run: &Run) -> eyre::Result<Option<PathBuf>> {
+        let (Some(output_dir), Some(stats_file)) = (&self.options.output_dir, &run.stats_file)
+        else {
+            return Ok(None);
+        };
+        let stats_file = match &run.working_dir {
+            Some(working_dir) if stats_file.is_relative() => working_dir.join(stats_file),
+            _ => stats_file.clone(),
+        };
+        let file_name = stats_file
+            .file_name()
+            .ok_or_else(|| eyre::eyre!("stats file {} has no file name", stats_file.display()))?;
+        let dest_dir = output_dir.join(&run.name);
+        utils::fs::create_dirs(&dest_dir)?;
+        let dest = dest_dir.join(file_name);
+        std::fs::copy(&stats_file, &dest).wrap_err_with(|| {
+            format!(
+                "failed to collect stats file {} of run {}",
+                stats_file.display(),
+                &run.name
+            )
+        })?;
+        Ok(Some(dest))
+    }
+}
+
+/// Parses a human readable size such as `512M` or `16G` into bytes.
+pub fn parse_memory(value: &str) -> eyre::Result<u64> {
+    let value = value.trim();
+    let (digits, unit) = value.split_at(
+        value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len()),
+    );
+    let bytes: u64 = digits
+        .parse()
+        .wrap_err_with(|| format!("bad memory size {value:?}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        other => eyre::bail!("bad memory unit {other:?}"),
+    };
+    Ok(bytes * multiplier)
+}
+
+/// Reads the runs of a sweep from a YAML or JSON file.
+pub fn read_sweep(path: &Path) -> eyre::Result<Vec<Run>> {
+    let reader = utils::fs::open_readable(path)?;
+    let runs: Vec<Run> = serde_yaml::from_reader(reader)
+        .wrap_err_with(|| format!("failed to parse sweep {}", path.display()))?;
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::eyre;
+
+    #[test]
+    fn test_parse_memory() -> eyre::Result<()> {
+        assert_eq!(super::parse_memory("512")?, 512);
+        assert_eq!(super::parse_memory("4K")?, 4096);
+        assert_eq!(super::parse_memory("2 GB")?, 2 * 1024 * 1024 * 1024);
+        assert!(super::parse_memory("fast").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resident_memory() {
+        // our own resident memory must be available on linux
+        if cfg!(target_os = "linux") {
+            assert!(super::resident_memory(std::process::id()).unwrap() > 0);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use clap::Parser;
+use color_eyre::eyre;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Parser, Debug)]
+#[clap(
+    about = "run a sweep of simulations with caps on total memory and CPU usage",
+    version
+)]
+pub struct Options {
+    #[clap(help = "sweep file listing the runs (YAML or JSON)")]
+    pub sweep: PathBuf,
+
+    #[clap(
+        short = 'j',
+        long = "max-concurrent",
+        help = "maximum number of concurrently running simulations [default: physical cores]"
+    )]
+    pub max_concurrent: Option<usize>,
+
+    #[clap(
+        short = 'm',
+        long = "max-memory",
+        help = "maximum total resident memory of all simulations (e.g. 16G)"
+    )]
+    pub max_memory: Option<String>,
+
+    #[clap(
+        long = "max-restarts",
+        default_value = "1",
+        help = "number of times a failed run is restarted"
+    )]
+    pub max_restarts: usize,
+
+    #[clap(
+        long = "poll-interval",
+        default_value = "500",
+        help = "poll interval in milliseconds"
+    )]
+    pub poll_interval_ms: u64,
+
+    #[clap(
+        short = 'o',
+        long = "output",
+        help = "directory the log and stats files are collected into"
+    )]
+    pub output_dir: Option<PathBuf>,
+}
+
+fn main() -> eyre::Result<()> {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+    color_eyre::install()?;
+
+    let options = Options::parse();
+    let runs = orchestrate::read_sweep(&options.sweep)?;
+    let max_memory = options
+        .max_memory
+        .as_deref()
+        .map(orchestrate::parse_memory)
+        .transpose()?;
+
+    let orchestrator = orchestrate::Orchestrator::new(
+        runs,
+        orchestrate::Options {
+            max_concurrent: options.max_concurrent,
+            max_memory,
+            max_restarts: options.max_restarts,
+            poll_interval: Duration::from_millis(options.poll_interval_ms),
+            output_dir: options.output_dir,
+        },
+    );
+    let results = orchestrator.run()?;
+
+    let num_failed = results.iter().filter(|result| !result.success()).count();
+    log::info!("completed {} runs ({} failed)", results.len(), num_failed);
+    if num_failed > 0 {
+        eyre::bail!("{num_failed} runs failed");
+    }
+    Ok(())
+}
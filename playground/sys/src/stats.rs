@@ -259,11 +259,29 @@ impl From<Sim> for stats::sim::Sim {
             kernel_name: String::new(),
             kernel_name_mangled: String::new(),
             kernel_launch_id: 0,
+            // launch configuration is not modeled by the playground
+            grid_dim: (0, 0, 0),
+            block_dim: (0, 0, 0),
+            shared_mem_bytes: 0,
+            num_registers: 0,
+            stream_id: 0,
+            tenant: None,
             cycles: sim.cycles,
             instructions: sim.instructions,
             num_blocks: sim.num_blocks,
+            num_skipped_blocks: 0,
+            num_reexecuted_instructions: 0,
+            kernel_launch_wait_cycles: 0,
             is_release_build: !crate::is_debug(),
             elapsed_millis: 0,
+            parallel_seed: None,
+            longest_dependency_chain: 0,
+            is_persistent_kernel: false,
+            num_trace_loop_iterations: 0,
+            max_trace_loop_iterations: 0,
+            num_dram_throttled_cycles: 0,
+            num_host_gap_cycles: 0,
+            num_malformed_trace_records: 0,
         }
     }
 }
@@ -273,6 +291,8 @@ impl From<StatsBridge> for stats::Stats {
         Self {
             accesses: stats.accesses.into(),
             instructions: stats.instructions.into(),
+            per_pc: stats::PerPC::default(),
+            loops: stats::Loops::default(),
             sim: stats.sim.into(),
             dram: stats.dram.into(),
             l1i_stats: stats.l1i_stats.iter().cloned().collect(),
@@ -280,7 +300,22 @@ impl From<StatsBridge> for stats::Stats {
             l1t_stats: stats.l1t_stats.iter().cloned().collect(),
             l1d_stats: stats.l1d_stats.iter().cloned().collect(),
             l2d_stats: stats.l2d_stats.iter().cloned().collect(),
+            interconn: stats::interconn::Interconn::default(),
+            schedulers: std::collections::HashMap::new(),
             stall_dram_full: 0,
+            num_l2_bypassed: 0,
+            stall_interconn_to_shader: 0,
+            ldst_return_path_stall_cycles: 0,
+            num_writeback_stalls: std::collections::HashMap::new(),
+            num_issued_per_core: std::collections::HashMap::new(),
+            num_reorder_conflicts: std::collections::HashMap::new(),
+            ejection_buffer_occupancy: std::collections::HashMap::new(),
+            l2_arbitration_delays: std::collections::HashMap::new(),
+            ldst_response_buffer_occupancy: std::collections::HashMap::new(),
+            utilization: stats::utilization::Utilization::default(),
+            memcopy: stats::Memcopy::default(),
+            coherence: stats::Coherence::default(),
+            l2_residency: stats::L2Residency::default(),
         }
     }
 }
@@ -0,0 +1,112 @@
+use crate::{Error, ParseError};
+
+pub const QUERY_GPU_FIELDS: &str = "index,name,driver_version,memory.total,compute_cap";
+
+/// Properties of a CUDA device reported by `nvidia-smi`.
+#[derive(PartialEq, Eq, Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Device {
+    pub index: usize,
+    pub name: String,
+    pub driver_version: Option<String>,
+    /// Total device memory in MiB.
+    pub memory_total: Option<u64>,
+    pub compute_capability: Option<String>,
+}
+
+/// Parse the output of
+/// `nvidia-smi --query-gpu=index,name,driver_version,memory.total,compute_cap --format=csv,noheader,nounits`.
+pub fn parse_nvidia_smi_csv(reader: impl std::io::Read) -> Result<Vec<Device>, ParseError> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(reader);
+
+    let mut devices = Vec::new();
+    for record in csv_reader.records() {
+        let record = record?;
+        let field = |idx: usize| record.get(idx).filter(|value| !value.is_empty());
+        let Some(index) = field(0).and_then(|index| index.parse().ok()) else {
+            continue;
+        };
+        devices.push(Device {
+            index,
+            name: field(1).unwrap_or_default().to_string(),
+            driver_version: field(2).map(str::to_string),
+            memory_total: field(3).and_then(|mem| mem.parse().ok()),
+            compute_capability: field(4).map(str::to_string),
+        });
+    }
+    Ok(devices)
+}
+
+/// Enumerate CUDA devices by querying `nvidia-smi`.
+///
+/// # Errors
+/// - When `nvidia-smi` cannot be found.
+/// - When querying the devices fails.
+pub async fn enumerate() -> Result<Vec<Device>, Error> {
+    let nvidia_smi =
+        which::which("nvidia-smi").map_err(|_| Error::MissingProfiler("nvidia-smi".into()))?;
+
+    let mut cmd = async_process::Command::new(&nvidia_smi);
+    cmd.args([
+        format!("--query-gpu={QUERY_GPU_FIELDS}"),
+        "--format=csv,noheader,nounits".to_string(),
+    ]);
+    let result = cmd.output().await?;
+    let raw_log = utils::decode_utf8!(result.stdout);
+    if !result.status.success() {
+        return Err(Error::Command {
+            raw_log,
+            source: utils::CommandError::new(&cmd, result),
+        });
+    }
+    parse_nvidia_smi_csv(std::io::Cursor::new(&raw_log))
+        .map_err(|source| Error::Parse { raw_log, source })
+}
+
+/// Look up a single device by index.
+pub async fn find(index: usize) -> Result<Option<Device>, Error> {
+    let devices = enumerate().await?;
+    Ok(devices.into_iter().find(|device| device.index == index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_nvidia_smi_csv, Device};
+    use color_eyre::eyre;
+    use similar_asserts as diff;
+    use std::io::Cursor;
+
+    #[test]
+    fn parse_query_gpu_csv() -> eyre::Result<()> {
+        let log = "0, NVIDIA GeForce GTX 1080, 510.47.03, 8192, 6.1
+1, NVIDIA TITAN Xp, 510.47.03, 12288, 6.1
+";
+        let devices = parse_nvidia_smi_csv(Cursor::new(log))?;
+        diff::assert_eq!(devices.len(), 2);
+        diff::assert_eq!(
+            devices[0],
+            Device {
+                index: 0,
+                name: "NVIDIA GeForce GTX 1080".to_string(),
+                driver_version: Some("510.47.03".to_string()),
+                memory_total: Some(8192),
+                compute_capability: Some("6.1".to_string()),
+            }
+        );
+        diff::assert_eq!(devices[1].index, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_query_gpu_csv_missing_fields() -> eyre::Result<()> {
+        let log = "0, NVIDIA GeForce GTX 1080\n";
+        let devices = parse_nvidia_smi_csv(Cursor::new(log))?;
+        diff::assert_eq!(devices.len(), 1);
+        diff::assert_eq!(devices[0].driver_version, None);
+        diff::assert_eq!(devices[0].memory_total, None);
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 #![allow(clippy::missing_panics_doc, clippy::missing_errors_doc)]
+pub mod device;
+pub mod multi;
 pub mod nsight;
 pub mod nvprof;
+pub mod request;
 
 use color_eyre::{eyre, Section, SectionExt};
 use serde::Deserialize;
@@ -22,6 +25,30 @@ pub enum ParseError {
 
     #[error(transparent)]
     Json(#[from] JsonError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Magic bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Transparently decompress a gzip-compressed reader.
+///
+/// Profiling logs archived from clusters are usually gzipped: when the
+/// stream starts with the gzip magic bytes it is wrapped in a gzip
+/// decoder, otherwise the reader is passed through unchanged.
+pub fn decompress<R>(reader: &mut R) -> Result<Box<dyn std::io::BufRead + '_>, ParseError>
+where
+    R: std::io::BufRead,
+{
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(std::io::BufReader::new(
+            flate2::bufread::GzDecoder::new(reader),
+        )))
+    } else {
+        Ok(Box::new(reader))
+    }
 }
 
 #[derive(Debug)]
@@ -166,6 +193,25 @@ where
     #[serde(bound(deserialize = "T: serde::Deserialize<'de>"))]
     value: Option<T>,
     unit: Option<String>,
+    /// Minimum over all invocations.
+    ///
+    /// Only captured when profiling in aggregate mode, where the
+    /// profiler emits min/max/avg columns over all invocations of a
+    /// kernel instead of one value per invocation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_option_number_from_string")]
+    #[serde(bound(deserialize = "T: serde::Deserialize<'de>"))]
+    min: Option<T>,
+    /// Maximum over all invocations (aggregate profiling mode only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_option_number_from_string")]
+    #[serde(bound(deserialize = "T: serde::Deserialize<'de>"))]
+    max: Option<T>,
+    /// Average over all invocations (aggregate profiling mode only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_option_number_from_string")]
+    #[serde(bound(deserialize = "T: serde::Deserialize<'de>"))]
+    avg: Option<T>,
 }
 
 impl<T> Metric<T>
@@ -176,7 +222,36 @@ where
     pub fn new(value: impl Into<Option<T>>, unit: impl Into<Option<String>>) -> Self {
         let value: Option<T> = value.into();
         let unit: Option<String> = unit.into();
-        Self { value, unit }
+        Self {
+            value,
+            unit,
+            min: None,
+            max: None,
+            avg: None,
+        }
+    }
+
+    /// The measured value.
+    ///
+    /// In aggregate profiling mode this is the average over all
+    /// invocations.
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Minimum over all invocations (aggregate profiling mode only).
+    pub fn min(&self) -> Option<&T> {
+        self.min.as_ref()
+    }
+
+    /// Maximum over all invocations (aggregate profiling mode only).
+    pub fn max(&self) -> Option<&T> {
+        self.max.as_ref()
+    }
+
+    /// Average over all invocations (aggregate profiling mode only).
+    pub fn avg(&self) -> Option<&T> {
+        self.avg.as_ref()
     }
 }
 
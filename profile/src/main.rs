@@ -21,12 +21,16 @@ pub struct NvprofOptions {
 
     #[clap(long = "nvprof-path", help = "path to nvprof")]
     pub nvprof_path: Option<PathBuf>,
+
+    #[clap(long = "device", help = "index of the CUDA device to profile")]
+    pub device: Option<usize>,
 }
 
 impl From<NvprofOptions> for profile::nvprof::Options {
     fn from(options: NvprofOptions) -> Self {
         Self {
             nvprof_path: options.nvprof_path,
+            device: options.device,
         }
     }
 }
@@ -39,12 +43,16 @@ pub struct NsightOptions {
 
     #[clap(long = "nsight-path", help = "path to nsight")]
     pub nsight_path: Option<PathBuf>,
+
+    #[clap(long = "device", help = "index of the CUDA device to profile")]
+    pub device: Option<usize>,
 }
 
 impl From<NsightOptions> for profile::nsight::Options {
     fn from(options: NsightOptions) -> Self {
         Self {
             nsight_path: options.nsight_path,
+            device: options.device,
         }
     }
 }
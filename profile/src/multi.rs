@@ -0,0 +1,151 @@
+use crate::{nvprof, Error, Metric};
+use std::path::Path;
+
+/// Aggregated value of a single metric across repeated profiler runs.
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AggregateMetric<T> {
+    pub mean: f64,
+    /// Sample standard deviation (zero for a single run).
+    pub std: f64,
+    pub min: T,
+    pub max: T,
+    pub unit: Option<String>,
+}
+
+impl<T> AggregateMetric<T>
+where
+    T: Copy + PartialOrd + Into<f64>,
+{
+    #[must_use]
+    pub fn from_values(values: &[T], unit: Option<String>) -> Option<Self> {
+        let (&first, rest) = values.split_first()?;
+        let (mut min, mut max) = (first, first);
+        for &value in rest {
+            if value < min {
+                min = value;
+            }
+            if value > max {
+                max = value;
+            }
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().map(|&value| value.into()).sum::<f64>() / n;
+        let std = if values.len() > 1 {
+            let var = values
+                .iter()
+                .map(|&value| (value.into() - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1.0);
+            var.sqrt()
+        } else {
+            0.0
+        };
+        Some(Self {
+            mean,
+            std,
+            min,
+            max,
+            unit,
+        })
+    }
+}
+
+/// Output of profiling the same executable multiple times.
+///
+/// Single-run hardware numbers are noisy, hence validation thresholds
+/// are based on aggregates with variance estimates across runs.
+#[derive(PartialEq, Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct MultiOutput {
+    pub runs: Vec<nvprof::Output>,
+}
+
+impl MultiOutput {
+    /// Aggregate a single metric of kernel launch `kernel` across all runs.
+    ///
+    /// The metric is selected by typed field access, e.g.
+    /// `|m| &m.dram_read_transactions`.
+    ///
+    /// Returns `None` when no run recorded a value for the metric.
+    pub fn aggregate<T>(
+        &self,
+        kernel: usize,
+        select: impl Fn(&nvprof::Metrics) -> &Metric<T>,
+    ) -> Option<AggregateMetric<T>>
+    where
+        T: Copy + PartialOrd + Into<f64> + std::str::FromStr,
+        <T as std::str::FromStr>::Err: std::fmt::Display,
+    {
+        let metrics: Vec<&Metric<T>> = self
+            .runs
+            .iter()
+            .filter_map(|run| run.metrics.get(kernel))
+            .map(&select)
+            .collect();
+        let values: Vec<T> = metrics.iter().filter_map(|metric| metric.value).collect();
+        let unit = metrics.iter().find_map(|metric| metric.unit.clone());
+        AggregateMetric::from_values(&values, unit)
+    }
+}
+
+/// Profile test application `runs` times using the nvprof profiler.
+///
+/// # Errors
+/// When any single profiler run fails.
+pub async fn nvprof_multi<A>(
+    executable: impl AsRef<Path>,
+    args: A,
+    options: &nvprof::Options,
+    runs: usize,
+) -> Result<MultiOutput, Error>
+where
+    A: Clone + IntoIterator,
+    <A as IntoIterator>::Item: AsRef<std::ffi::OsStr>,
+{
+    let mut output = MultiOutput::default();
+    for _ in 0..runs {
+        output
+            .runs
+            .push(nvprof::nvprof(executable.as_ref(), args.clone(), options).await?);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AggregateMetric, MultiOutput};
+    use crate::{nvprof, Metric};
+    use similar_asserts as diff;
+
+    #[test]
+    fn aggregate_from_values() {
+        let agg = AggregateMetric::from_values(&[1.0, 2.0, 3.0], None).unwrap();
+        diff::assert_eq!(agg.mean, 2.0);
+        diff::assert_eq!(agg.std, 1.0);
+        diff::assert_eq!(agg.min, 1.0);
+        diff::assert_eq!(agg.max, 3.0);
+
+        assert!(AggregateMetric::<f64>::from_values(&[], None).is_none());
+
+        let single = AggregateMetric::from_values(&[42.0], None).unwrap();
+        diff::assert_eq!(single.std, 0.0);
+    }
+
+    #[test]
+    fn aggregate_across_runs() {
+        let run = |transactions: f32| nvprof::Output {
+            metrics: vec![nvprof::Metrics {
+                ipc: Metric::new(transactions, None),
+                ..nvprof::Metrics::default()
+            }],
+            ..nvprof::Output::default()
+        };
+        let output = MultiOutput {
+            runs: vec![run(100.0), run(110.0), run(90.0)],
+        };
+        let agg = output.aggregate(0, |m| &m.ipc).unwrap();
+        diff::assert_eq!(agg.mean, 100.0);
+        diff::assert_eq!(agg.min, 90.0);
+        diff::assert_eq!(agg.max, 110.0);
+        assert!(output.aggregate(1, |m| &m.ipc).is_none());
+    }
+}
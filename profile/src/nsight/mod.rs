@@ -23,6 +23,8 @@ pub use metrics::{Float, Metrics};
 pub struct Output {
     pub raw_metrics_log: String,
     pub metrics: Vec<Metrics>,
+    /// Properties of the profiled device, if a specific device was selected.
+    pub device: Option<crate::device::Device>,
 }
 
 macro_rules! optional {
@@ -71,7 +73,9 @@ pub fn parse_nsight_csv<M>(reader: &mut impl std::io::BufRead) -> Result<Vec<M>,
 where
     M: serde::de::DeserializeOwned,
 {
-    let mut csv_reader = seek_to_csv(reader)?;
+    // archived logs are often gzipped
+    let mut reader = crate::decompress(reader)?;
+    let mut csv_reader = seek_to_csv(&mut reader)?;
     let mut records = csv_reader.deserialize();
 
     let mut entries = Vec::new();
@@ -86,10 +90,7 @@ where
                 assert_eq!(unit_metric, value_metric);
                 (
                     unit_metric.clone(),
-                    Metric {
-                        value: optional!(value).cloned(),
-                        unit: optional!(unit).cloned(),
-                    },
+                    Metric::new(optional!(value).cloned(), optional!(unit).cloned()),
                 )
             })
             .collect();
@@ -279,6 +280,7 @@ pub async fn profile_all_metrics<A>(
     nsight: impl AsRef<Path>,
     executable: impl AsRef<Path>,
     args: A,
+    device: Option<usize>,
 ) -> Result<(String, Vec<Metrics>), Error>
 where
     A: IntoIterator,
@@ -292,6 +294,9 @@ where
 
     let cmd_args = build_nsight_args(executable.as_ref(), &*args)?;
     cmd.args(&cmd_args);
+    if let Some(device) = device {
+        cmd.env("CUDA_VISIBLE_DEVICES", device.to_string());
+    }
 
     log::debug!(
         "profile command: {} {}",
@@ -327,6 +332,11 @@ where
 #[derive(Debug, Clone)]
 pub struct Options {
     pub nsight_path: Option<PathBuf>,
+    /// Index of the CUDA device to profile on multi-GPU machines.
+    ///
+    /// The index is exported as `CUDA_VISIBLE_DEVICES` for the
+    /// profiled process.
+    pub device: Option<usize>,
 }
 
 /// Profile test application using `nv-nsight-cu-cli` profiler.
@@ -372,12 +382,18 @@ where
         .canonicalize()
         .map_err(|_| Error::MissingExecutable(executable.as_ref().into()))?;
 
+    let device = match options.device {
+        Some(index) => crate::device::find(index).await.ok().flatten(),
+        None => None,
+    };
+
     let (raw_metrics_log, metrics) =
-        profile_all_metrics(&nsight, &executable, args.clone()).await?;
+        profile_all_metrics(&nsight, &executable, args.clone(), options.device).await?;
 
     Ok(Output {
         raw_metrics_log,
         metrics,
+        device,
     })
 }
 
@@ -1,6 +1,8 @@
 use crate::Metric;
 
 #[derive(PartialEq, Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+// aggregate mode profiles only record a subset of the columns
+#[serde(default)]
 pub struct Metrics {
     #[serde(rename = "Device")]
     pub device: Metric<String>,
@@ -16,6 +16,8 @@ pub struct Output {
     pub raw_commands_log: String,
     pub metrics: Vec<Metrics>,
     pub commands: Vec<Command>,
+    /// Properties of the profiled device, if a specific device was selected.
+    pub device: Option<crate::device::Device>,
 }
 
 macro_rules! optional {
@@ -63,29 +65,109 @@ pub fn parse_nvprof_csv<M>(reader: &mut impl std::io::BufRead) -> Result<Vec<M>,
 where
     M: serde::de::DeserializeOwned,
 {
-    let mut csv_reader = seek_to_csv(reader)?;
+    // archived logs are often gzipped
+    let mut reader = crate::decompress(reader)?;
+    let mut csv_reader = seek_to_csv(&mut reader)?;
+    // in aggregate mode (without --print-gpu-trace), nvprof emits one
+    // row per metric with min/max/avg columns over all invocations
+    let aggregate = csv_reader
+        .headers()?
+        .iter()
+        .any(|header| header == "Metric Name" || header == "Event Name");
     let mut records = csv_reader.deserialize();
 
     let mut entries = Vec::new();
-    let units: IndexMap<String, String> = records.next().ok_or(ParseError::MissingUnits)??;
+    if aggregate {
+        // pivot the rows into one set of metrics per kernel
+        let mut kernels: IndexMap<String, HashMap<String, Metric<String>>> = IndexMap::new();
+        while let Some(row) = records.next().transpose()? {
+            let row: IndexMap<String, String> = row;
+            let cell = |column: &str| row.get(column).and_then(|value| optional!(value)).cloned();
+            let name = cell("Metric Name")
+                .or_else(|| cell("Event Name"))
+                .ok_or(ParseError::MissingMetrics)?;
+            let metrics = kernels
+                .entry(cell("Kernel").unwrap_or_default())
+                .or_default();
+            for id in ["Device", "Kernel"] {
+                if let Some(value) = cell(id) {
+                    metrics.insert(id.to_string(), Metric::new(value, None));
+                }
+            }
+            let avg = cell("Avg");
+            metrics.insert(
+                name,
+                Metric {
+                    // the average is the representative single value
+                    value: avg.clone(),
+                    unit: None,
+                    min: cell("Min"),
+                    max: cell("Max"),
+                    avg,
+                },
+            );
+        }
+        for metrics in kernels.into_values() {
+            entries.push(deserialize_metrics(metrics)?);
+        }
+        return Ok(entries);
+    }
+
+    let mut units: IndexMap<String, String> = records.next().ok_or(ParseError::MissingUnits)??;
+
+    // multi-device and multi-context logs contain one section per
+    // device or context
+    let mut current_device: Option<String> = None;
+    let mut current_context: Option<String> = None;
 
     while let Some(values) = records.next().transpose()? {
         assert_eq!(units.len(), values.len());
-        let metrics: HashMap<String, Metric<String>> = units
+
+        // a repeated header row starts a new section, followed by a
+        // new units row
+        let is_header = values.iter().all(|(column, value)| column == value);
+        if is_header {
+            units = records.next().ok_or(ParseError::MissingUnits)??;
+            continue;
+        }
+
+        // a row with only the device (and context) column set announces
+        // the device the following records were collected on
+        let is_device_change = values
+            .get("Device")
+            .is_some_and(|device| !device.is_empty())
+            && values
+                .iter()
+                .filter(|(column, _)| !matches!(column.as_str(), "Device" | "Context"))
+                .all(|(_, value)| value.is_empty());
+        if is_device_change {
+            current_device = values.get("Device").cloned();
+            current_context = values.get("Context").and_then(|c| optional!(c)).cloned();
+            continue;
+        }
+
+        let mut metrics: HashMap<String, Metric<String>> = units
             .iter()
             .zip(values.iter())
             .map(|((unit_metric, unit), (value_metric, value))| {
                 assert_eq!(unit_metric, value_metric);
                 (
                     unit_metric.clone(),
-                    Metric {
-                        value: optional!(value).cloned(),
-                        unit: optional!(unit).cloned(),
-                    },
+                    Metric::new(optional!(value).cloned(), optional!(unit).cloned()),
                 )
             })
             .collect();
 
+        // tag records with the device and context of the current section
+        for (column, section_value) in [("Device", &current_device), ("Context", &current_context)]
+        {
+            if let (Some(section_value), Some(metric)) = (section_value, metrics.get_mut(column)) {
+                if metric.value.is_none() {
+                    metric.value = Some(section_value.clone());
+                }
+            }
+        }
+
         {
             let mut metrics: Vec<_> = metrics.clone().into_iter().collect();
             metrics.sort_by_key(|(name, _value)| name.clone());
@@ -95,23 +177,29 @@ where
             }
         }
 
-        // this is kind of hacky..
-        let serialized = serde_json::to_string(&metrics)?;
-        let deser = &mut serde_json::Deserializer::from_str(&serialized);
-        let metrics: M = serde_path_to_error::deserialize(deser).map_err(|source| {
-            let path = source.path().clone();
-            ParseError::Json(JsonError {
-                source: source.into_inner(),
-                values: Some(metrics),
-                path: Some(path),
-            })
-        })?;
-        entries.push(metrics);
+        entries.push(deserialize_metrics(metrics)?);
     }
 
     Ok(entries)
 }
 
+fn deserialize_metrics<M>(metrics: HashMap<String, Metric<String>>) -> Result<M, ParseError>
+where
+    M: serde::de::DeserializeOwned,
+{
+    // this is kind of hacky..
+    let serialized = serde_json::to_string(&metrics)?;
+    let deser = &mut serde_json::Deserializer::from_str(&serialized);
+    serde_path_to_error::deserialize(deser).map_err(|source| {
+        let path = source.path().clone();
+        ParseError::Json(JsonError {
+            source: source.into_inner(),
+            values: Some(metrics),
+            path: Some(path),
+        })
+    })
+}
+
 pub fn build_metrics_args(
     executable: &Path,
     args: &[String],
@@ -182,6 +270,7 @@ pub async fn profile_all_metrics<A>(
     executable: impl AsRef<Path>,
     args: A,
     log_file_path: impl AsRef<Path>,
+    device: Option<usize>,
 ) -> Result<(String, Vec<Metrics>), Error>
 where
     A: IntoIterator,
@@ -195,6 +284,9 @@ where
     let cmd_args = build_metrics_args(executable.as_ref(), &*args, log_file_path.as_ref())?;
     let mut cmd = async_process::Command::new(nvprof.as_ref());
     cmd.args(&cmd_args);
+    if let Some(device) = device {
+        cmd.env("CUDA_VISIBLE_DEVICES", device.to_string());
+    }
 
     log::debug!(
         "profile command: {} {}",
@@ -236,6 +328,7 @@ pub async fn profile_commands<A>(
     executable: impl AsRef<Path>,
     args: A,
     log_file_path: impl AsRef<Path>,
+    device: Option<usize>,
 ) -> Result<(String, Vec<Command>), Error>
 where
     A: IntoIterator,
@@ -249,6 +342,9 @@ where
     let cmd_args = build_command_args(executable.as_ref(), &*args, log_file_path.as_ref())?;
     let mut cmd = async_process::Command::new(nvprof.as_ref());
     cmd.args(&cmd_args);
+    if let Some(device) = device {
+        cmd.env("CUDA_VISIBLE_DEVICES", device.to_string());
+    }
 
     log::debug!(
         "profile command: {} {}",
@@ -294,6 +390,11 @@ where
 #[derive(Debug, Clone)]
 pub struct Options {
     pub nvprof_path: Option<PathBuf>,
+    /// Index of the CUDA device to profile on multi-GPU machines.
+    ///
+    /// The index is exported as `CUDA_VISIBLE_DEVICES` for the
+    /// profiled process.
+    pub device: Option<usize>,
 }
 
 /// Profile test application using nvprof profiler.
@@ -342,17 +443,29 @@ where
         .canonicalize()
         .map_err(|_| Error::MissingExecutable(executable.as_ref().into()))?;
 
-    let (raw_metrics_log, metrics) =
-        profile_all_metrics(&nvprof, &executable, args.clone(), &log_file_path).await?;
+    let device = match options.device {
+        Some(index) => crate::device::find(index).await.ok().flatten(),
+        None => None,
+    };
+
+    let (raw_metrics_log, metrics) = profile_all_metrics(
+        &nvprof,
+        &executable,
+        args.clone(),
+        &log_file_path,
+        options.device,
+    )
+    .await?;
 
     let (raw_commands_log, commands) =
-        profile_commands(&nvprof, &executable, args, &log_file_path).await?;
+        profile_commands(&nvprof, &executable, args, &log_file_path, options.device).await?;
 
     Ok(Output {
         raw_metrics_log,
         raw_commands_log,
         metrics,
         commands,
+        device,
     })
 }
 
@@ -392,6 +505,80 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_gzip_compressed_log() -> eyre::Result<()> {
+        use std::io::Write;
+        let bytes = include_bytes!("../../tests/nvprof_vectoradd_100_32_metrics_all.txt");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(bytes)?;
+        let compressed = encoder.finish()?;
+        let mut log_reader = Cursor::new(compressed);
+        let metrics: Vec<super::Metrics> = parse_nvprof_csv(&mut log_reader)?;
+        diff::assert_eq!(metrics.len(), 1);
+        diff::assert_eq!(metrics[0].dram_read_transactions, Metric::new(223, None));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_multi_device_metrics() -> eyre::Result<()> {
+        let bytes = include_bytes!("../../tests/nvprof_vectoradd_100_32_metrics_multi_device.txt");
+        let log = String::from_utf8_lossy(bytes).to_string();
+        dbg!(&log);
+        let mut log_reader = Cursor::new(bytes);
+        let metrics: Vec<super::Metrics> = parse_nvprof_csv(&mut log_reader)?;
+        diff::assert_eq!(metrics.len(), 2);
+        diff::assert_eq!(
+            metrics[0].device,
+            Metric::new("NVIDIA GeForce GTX 1080 (0)".to_string(), None)
+        );
+        diff::assert_eq!(metrics[0].context, Metric::new(1, None));
+        diff::assert_eq!(metrics[0].elapsed_cycles_sm, Metric::new(89_855, None));
+        diff::assert_eq!(metrics[0].dram_read_transactions, Metric::new(223, None));
+        // the second section tags its records with the device-change row
+        diff::assert_eq!(
+            metrics[1].device,
+            Metric::new("NVIDIA GeForce GTX 1080 Ti (1)".to_string(), None)
+        );
+        diff::assert_eq!(metrics[1].context, Metric::new(2, None));
+        diff::assert_eq!(metrics[1].stream, Metric::new(14, None));
+        diff::assert_eq!(metrics[1].elapsed_cycles_sm, Metric::new(90_210, None));
+        diff::assert_eq!(metrics[1].dram_read_transactions, Metric::new(219, None));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_aggregate_metrics() -> eyre::Result<()> {
+        let bytes = include_bytes!("../../tests/nvprof_vectoradd_100_32_metrics_aggregate.txt");
+        let log = String::from_utf8_lossy(bytes).to_string();
+        dbg!(&log);
+        let mut log_reader = Cursor::new(bytes);
+        let mut metrics: Vec<super::Metrics> = parse_nvprof_csv(&mut log_reader)?;
+        diff::assert_eq!(metrics.len(), 1);
+        let metrics = metrics.remove(0);
+        dbg!(&metrics);
+        diff::assert_eq!(
+            metrics.device,
+            Metric::new("NVIDIA GeForce GTX 1080 (0)".to_string(), None)
+        );
+        diff::assert_eq!(
+            metrics.kernel,
+            Metric::new("_Z6vecAddIfEvPT_S1_S1_i".to_string(), None)
+        );
+        diff::assert_eq!(metrics.ipc.value(), Some(&0.163_650));
+        diff::assert_eq!(metrics.ipc.min(), Some(&0.157_883));
+        diff::assert_eq!(metrics.ipc.max(), Some(&0.169_418));
+        diff::assert_eq!(metrics.ipc.avg(), Some(&0.163_650));
+        diff::assert_eq!(metrics.dram_read_transactions.value(), Some(&223));
+        diff::assert_eq!(metrics.dram_read_transactions.min(), Some(&220));
+        diff::assert_eq!(metrics.dram_read_transactions.max(), Some(&226));
+        diff::assert_eq!(metrics.dram_write_transactions.avg(), Some(&0));
+        diff::assert_eq!(metrics.l2_read_transactions.avg(), Some(&66));
+        // columns not recorded in aggregate mode stay empty
+        diff::assert_eq!(metrics.context, Metric::new(None, None));
+        diff::assert_eq!(metrics.elapsed_cycles_sm, Metric::new(None, None));
+        Ok(())
+    }
+
     #[test]
     fn parse_commands() -> eyre::Result<()> {
         use super::metrics::Command;
@@ -0,0 +1,199 @@
+use indexmap::{IndexMap, IndexSet};
+
+/// Hardware unit a metric is collected from.
+///
+/// Metrics of the same pass can be collected together in a single
+/// kernel replay, while metrics from different passes force the
+/// profiler to replay the kernel.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum Pass {
+    Sm,
+    Dram,
+    L2,
+    Tex,
+    Scheduler,
+}
+
+macro_rules! metric_names {
+    ($($variant:ident => ($name:literal, $pass:ident),)*) => {
+        /// Typed names of known nvprof metrics.
+        ///
+        /// Using the typed variants instead of free-form strings guards
+        /// against typos at compile time and maps each metric to the
+        /// typed field of the parsed [`crate::nvprof::Metrics`] struct
+        /// with the same name.
+        #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        pub enum MetricName {
+            $($variant,)*
+        }
+
+        impl MetricName {
+            /// The metric name as passed to `nvprof --metrics`.
+            #[must_use]
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            /// The collection pass this metric is grouped into.
+            #[must_use]
+            pub const fn pass(self) -> Pass {
+                match self {
+                    $(Self::$variant => Pass::$pass,)*
+                }
+            }
+        }
+    };
+}
+
+metric_names!(
+    ElapsedCyclesSm => ("elapsed_cycles_sm", Sm),
+    InstPerWarp => ("inst_per_warp", Sm),
+    Ipc => ("ipc", Sm),
+    IssuedIpc => ("issued_ipc", Sm),
+    IssueSlots => ("issue_slots", Sm),
+    IssueSlotUtilization => ("issue_slot_utilization", Sm),
+    EligibleWarpsPerCycle => ("eligible_warps_per_cycle", Scheduler),
+    UniqueWarpsLaunched => ("unique_warps_launched", Scheduler),
+    InstReplayOverhead => ("inst_replay_overhead", Sm),
+    InstIssued => ("inst_issued", Sm),
+    InstExecuted => ("inst_executed", Sm),
+    InstFp16 => ("inst_fp_16", Sm),
+    InstFp32 => ("inst_fp_32", Sm),
+    InstFp64 => ("inst_fp_64", Sm),
+    InstInteger => ("inst_integer", Sm),
+    LdstIssued => ("ldst_issued", Sm),
+    LdstExecuted => ("ldst_executed", Sm),
+    StallInstFetch => ("stall_inst_fetch", Scheduler),
+    StallExecDependency => ("stall_exec_dependency", Scheduler),
+    StallMemoryDependency => ("stall_memory_dependency", Scheduler),
+    StallSync => ("stall_sync", Scheduler),
+    StallMemoryThrottle => ("stall_memory_throttle", Scheduler),
+    StallNotSelected => ("stall_not_selected", Scheduler),
+    TexCacheHitRate => ("tex_cache_hit_rate", Tex),
+    TexCacheTransactions => ("tex_cache_transactions", Tex),
+    GlobalHitRate => ("global_hit_rate", Tex),
+    LocalHitRate => ("local_hit_rate", Tex),
+    L2TexReadHitRate => ("l2_tex_read_hit_rate", L2),
+    L2TexWriteHitRate => ("l2_tex_write_hit_rate", L2),
+    L2TexReadTransactions => ("l2_tex_read_transactions", L2),
+    L2TexWriteTransactions => ("l2_tex_write_transactions", L2),
+    L2ReadTransactions => ("l2_read_transactions", L2),
+    L2WriteTransactions => ("l2_write_transactions", L2),
+    L2AtomicTransactions => ("l2_atomic_transactions", L2),
+    AtomicTransactions => ("atomic_transactions", Sm),
+    GldTransactions => ("gld_transactions", Sm),
+    GstTransactions => ("gst_transactions", Sm),
+    SharedLoadTransactions => ("shared_load_transactions", Sm),
+    SharedStoreTransactions => ("shared_store_transactions", Sm),
+    LocalLoadTransactions => ("local_load_transactions", Sm),
+    LocalStoreTransactions => ("local_store_transactions", Sm),
+    DramReadBytes => ("dram_read_bytes", Dram),
+    DramWriteBytes => ("dram_write_bytes", Dram),
+    DramReadTransactions => ("dram_read_transactions", Dram),
+    DramWriteTransactions => ("dram_write_transactions", Dram),
+);
+
+impl std::fmt::Display for MetricName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A programmatic selection of metrics to profile.
+///
+/// ```
+/// use profile::request::{MetricName, ProfileRequest};
+/// let request = ProfileRequest::new()
+///     .metric(MetricName::DramReadTransactions)
+///     .metric(MetricName::DramWriteTransactions);
+/// assert_eq!(request.nvprof_args(), ["--metrics", "dram_read_transactions,dram_write_transactions"]);
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct ProfileRequest {
+    metrics: IndexSet<MetricName>,
+}
+
+impl ProfileRequest {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a single metric to the request.
+    #[must_use]
+    pub fn metric(mut self, metric: MetricName) -> Self {
+        self.metrics.insert(metric);
+        self
+    }
+
+    /// Add multiple metrics to the request.
+    #[must_use]
+    pub fn metrics(mut self, metrics: impl IntoIterator<Item = MetricName>) -> Self {
+        self.metrics.extend(metrics);
+        self
+    }
+
+    /// Selected metrics in insertion order.
+    pub fn selected(&self) -> impl Iterator<Item = MetricName> + '_ {
+        self.metrics.iter().copied()
+    }
+
+    /// Group the selected metrics by collection pass.
+    #[must_use]
+    pub fn passes(&self) -> IndexMap<Pass, Vec<MetricName>> {
+        let mut passes: IndexMap<Pass, Vec<MetricName>> = IndexMap::new();
+        for metric in &self.metrics {
+            passes.entry(metric.pass()).or_default().push(*metric);
+        }
+        passes
+    }
+
+    /// Arguments selecting the requested metrics for `nvprof`.
+    ///
+    /// When no metrics are selected, all metrics are profiled.
+    #[must_use]
+    pub fn nvprof_args(&self) -> Vec<String> {
+        let metrics = if self.metrics.is_empty() {
+            "all".to_string()
+        } else {
+            self.metrics
+                .iter()
+                .map(|metric| metric.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        vec!["--metrics".to_string(), metrics]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MetricName, Pass, ProfileRequest};
+    use similar_asserts as diff;
+
+    #[test]
+    fn group_metrics_into_passes() {
+        let request = ProfileRequest::new()
+            .metric(MetricName::DramReadTransactions)
+            .metric(MetricName::Ipc)
+            .metric(MetricName::DramWriteTransactions);
+        let passes = request.passes();
+        diff::assert_eq!(passes.len(), 2);
+        diff::assert_eq!(
+            passes[&Pass::Dram],
+            vec![
+                MetricName::DramReadTransactions,
+                MetricName::DramWriteTransactions
+            ]
+        );
+        diff::assert_eq!(passes[&Pass::Sm], vec![MetricName::Ipc]);
+    }
+
+    #[test]
+    fn nvprof_args_empty_selection_profiles_all() {
+        diff::assert_eq!(ProfileRequest::new().nvprof_args(), ["--metrics", "all"]);
+    }
+}
@@ -0,0 +1,21 @@
+use clap::Parser;
+use color_eyre::eyre;
+use std::path::PathBuf;
+
+/// Render a pipeline occupancy dump (pipeview) as a per-stage timeline.
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about = None)]
+struct Options {
+    /// Pipeview dump file written by the simulator
+    #[arg(value_name = "DUMP_FILE")]
+    pub dump_file: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+    let options = Options::parse();
+    let reader = utils::fs::open_readable(options.dump_file)?;
+    let rendered = gpucachesim::pipeview::render(reader)?;
+    print!("{rendered}");
+    Ok(())
+}
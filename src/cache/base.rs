@@ -1,7 +1,6 @@
 use crate::sync::{Arc, Mutex};
 use crate::{
-    address, cache, config, interconn as ic, mem_fetch,
-    mem_sub_partition::SECTOR_SIZE,
+    address, cache, config, interconn as ic, logging, mem_fetch,
     mshr::{self, MSHR},
     tag_array,
 };
@@ -84,11 +83,8 @@ where
             self.accelsim_compat,
         );
 
-        debug_assert!(matches!(
-            self.cache_config.mshr_kind,
-            mshr::Kind::ASSOC | mshr::Kind::SECTOR_ASSOC
-        ));
         let mshrs = mshr::Table::new(
+            self.cache_config.mshr_kind,
             self.cache_config.mshr_entries,
             self.cache_config.mshr_max_merge,
         );
@@ -140,7 +136,7 @@ where
         let mut evicted = None;
 
         let mshr_addr = self.cache_controller.mshr_addr(fetch.addr());
-        let mshr_hit = self.mshrs.get(mshr_addr).is_some();
+        let mshr_hit = self.mshrs.can_merge(mshr_addr, &fetch.access.sector_mask);
         let mshr_full = self.mshrs.full(mshr_addr);
 
         assert_eq!(unused_addr, fetch.addr());
@@ -151,7 +147,9 @@ where
         //         &self.name, fetch, fetch.uid, mshr_hit, mshr_full, self.miss_queue_full(), unused_addr, fetch.addr(), block_addr, mshr_addr,
         //     );
         // }
-        log::debug!(
+        crate::debug_scoped!(
+            logging::Component::Cache { name: &self.name },
+            time,
             "{}::baseline_cache::send_read_request({}, uid={}) (mshr_hit={}, mshr_full={}, miss_queue_full={}, addr={}, fetch addr={}, block={}, mshr_addr={})",
             &self.name, fetch, fetch.uid, mshr_hit, mshr_full, self.miss_queue_full(), unused_addr, fetch.addr(), block_addr, mshr_addr, 
         );
@@ -204,7 +202,7 @@ where
 
             self.mshrs.add(mshr_addr, fetch.clone());
 
-            let is_sector_cache = self.cache_config.mshr_kind == mshr::Kind::SECTOR_ASSOC;
+            let is_sector_cache = self.cache_config.mshr_kind.is_sectored();
 
             let key = FetchKey {
                 addr: mshr_addr,
@@ -219,11 +217,9 @@ where
                     addr: fetch.addr(),
                     cache_index,
                     data_size: fetch.data_size(),
-                    pending_reads: if is_sector_cache {
-                        self.cache_config.line_size / SECTOR_SIZE
-                    } else {
-                        0
-                    } as usize,
+                    // sectored caches track misses at sector granularity,
+                    // hence each pending request is filled by a single read
+                    pending_reads: usize::from(is_sector_cache),
                 },
             );
 
@@ -287,7 +283,9 @@ impl<B, CC, S> crate::engine::cycle::Component for Base<B, CC, S> {
             return;
         };
 
-        log::debug!(
+        crate::debug_scoped!(
+            logging::Component::Cache { name: &self.name },
+            cycle,
             "{}::baseline cache::cycle miss queue={:?}",
             self.name,
             style(
@@ -309,7 +307,9 @@ impl<B, CC, S> crate::engine::cycle::Component for Base<B, CC, S> {
             };
             if top_level_memory_port.can_send(&[packet_size]) {
                 let fetch = self.miss_queue.pop_front().unwrap();
-                log::debug!(
+                crate::debug_scoped!(
+                    logging::Component::Cache { name: &self.name },
+                    cycle,
                     "{}::baseline cache::memport::push({}, data size={}, control size={})",
                     &self.name,
                     fetch.addr(),
@@ -387,6 +387,11 @@ where
         self.tag_array.invalidate();
     }
 
+    /// Invalidate the line holding `addr`, if present.
+    pub fn invalidate_addr(&mut self, addr: address) -> bool {
+        self.tag_array.invalidate_addr(addr)
+    }
+
     /// Checks if fetch is waiting to be filled by lower memory level
     #[must_use]
     pub fn waiting_for_fill(&self, fetch: &mem_fetch::MemFetch) -> bool {
@@ -405,8 +410,10 @@ where
     ///
     /// bandwidth restictions should be modeled in the caller.
     pub fn fill(&mut self, mut fetch: mem_fetch::MemFetch, time: u64) {
-        let is_sector_cache = self.cache_config.mshr_kind == mshr::Kind::SECTOR_ASSOC;
-        log::debug!(
+        let is_sector_cache = self.cache_config.mshr_kind.is_sectored();
+        crate::debug_scoped!(
+            logging::Component::Cache { name: &self.name },
+            time,
             "{}::baseline_cache::fill({}, addr={}) (is sector={})",
             self.name,
             fetch,
@@ -415,10 +422,18 @@ where
         );
 
         if is_sector_cache {
-            todo!("sector assoc cache");
-            // let original_fetch = fetch.original_fetch.as_ref().unwrap();
-            // let pending = self.pending.get_mut(original_fetch).unwrap();
-            // pending.pending_reads -= 1;
+            let key = FetchKey {
+                addr: self.cache_controller.mshr_addr(fetch.addr()),
+                access_kind: fetch.access_kind(),
+                is_write: fetch.is_write(),
+            };
+            if let Some(pending) = self.pending.get_mut(&key) {
+                pending.pending_reads = pending.pending_reads.saturating_sub(1);
+                if pending.pending_reads > 0 {
+                    // wait for the remaining sector reads of this request
+                    return;
+                }
+            }
         }
 
         // dbg!(fetch.to_string());
@@ -318,6 +318,189 @@ impl Block for Line {
     }
 }
 
+pub mod tag {
+    use super::Status;
+    use crate::{address, mem_fetch, mem_sub_partition::SECTOR_SIZE};
+    use bitvec::array::BitArray;
+
+    /// Tag-only cache line.
+    ///
+    /// Keeps the per-sector status needed for hit, miss, and eviction
+    /// decisions, but none of the per-sector timestamps, on-fill flags,
+    /// or dirty byte masks of [`super::sector::Block`]. The smaller
+    /// lines cut the memory needed for cache geometry sweeps in the
+    /// tag-only replay mode (see [`crate::cache_only`]).
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct Block<const N: usize> {
+        pub tag: u64,
+        pub block_addr: address,
+        pub allocation_id: Option<usize>,
+
+        pub status: [Status; N],
+        set_modified_on_fill: [bool; N],
+
+        alloc_time: u64,
+        pub last_access_time: u64,
+    }
+
+    impl<const N: usize> std::fmt::Display for Block<N> {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.debug_struct("TagBlock")
+                .field("addr", &self.block_addr)
+                .field("status", &self.status)
+                .finish()
+        }
+    }
+
+    impl<const N: usize> Default for Block<N> {
+        fn default() -> Self {
+            Self {
+                tag: 0,
+                block_addr: 0,
+                allocation_id: None,
+                status: [Status::INVALID; N],
+                set_modified_on_fill: [false; N],
+                alloc_time: 0,
+                last_access_time: 0,
+            }
+        }
+    }
+
+    impl<const N: usize> Block<N> {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    impl<const N: usize> super::Block for Block<N> {
+        fn block_addr(&self) -> address {
+            self.block_addr
+        }
+
+        fn tag(&self) -> address {
+            self.tag
+        }
+
+        fn allocation_id(&self, _sector_idx: usize) -> Option<usize> {
+            self.allocation_id
+        }
+
+        fn allocate_sector(&mut self, sector_idx: usize, time: u64) {
+            assert!(self.is_valid());
+            // this should be the case only for fetch-on-write policy
+            self.set_modified_on_fill[sector_idx] = self.status[sector_idx] == Status::MODIFIED;
+            self.status[sector_idx] = Status::RESERVED;
+            self.last_access_time = time;
+        }
+
+        fn allocate(
+            &mut self,
+            tag: address,
+            block_addr: address,
+            sector_idx: usize,
+            allocation_id: Option<usize>,
+            time: u64,
+        ) {
+            self.tag = tag;
+            self.block_addr = block_addr;
+            self.allocation_id = allocation_id;
+            self.status = [Status::INVALID; N];
+            self.set_modified_on_fill = [false; N];
+            self.status[sector_idx] = Status::RESERVED;
+            self.alloc_time = time;
+            self.last_access_time = time;
+        }
+
+        fn fill(&mut self, sector_idx: usize, _byte_mask: &mem_fetch::ByteMask, _time: u64) {
+            self.status[sector_idx] = if self.set_modified_on_fill[sector_idx] {
+                Status::MODIFIED
+            } else {
+                Status::VALID
+            };
+        }
+
+        fn set_last_access_time(&mut self, time: u64, _sector_idx: usize) {
+            self.last_access_time = time;
+        }
+
+        fn set_byte_mask(&mut self, _mask: &mem_fetch::ByteMask) {
+            // tag-only lines do not track dirty bytes
+        }
+
+        fn set_status(&mut self, status: Status, sector_idx: usize) {
+            self.status[sector_idx] = status;
+        }
+
+        fn status(&self, sector_idx: usize) -> Status {
+            self.status[sector_idx]
+        }
+
+        fn is_valid(&self) -> bool {
+            !self.is_invalid()
+        }
+
+        fn is_modified(&self) -> bool {
+            self.status.contains(&Status::MODIFIED)
+        }
+
+        fn is_invalid(&self) -> bool {
+            self.status.iter().all(|s| *s == Status::INVALID)
+        }
+
+        fn is_reserved(&self) -> bool {
+            self.status.contains(&Status::RESERVED)
+        }
+
+        fn is_readable(&self, _sector_idx: usize) -> bool {
+            true
+        }
+
+        fn set_readable(&mut self, _readable: bool, _sector_idx: usize) {
+            // tag-only lines are always readable
+        }
+
+        fn alloc_time(&self) -> u64 {
+            self.alloc_time
+        }
+
+        fn sector_alloc_time(&self, _sector_idx: usize) -> u64 {
+            self.alloc_time
+        }
+
+        fn last_access_time(&self) -> u64 {
+            self.last_access_time
+        }
+
+        fn last_sector_access_time(&self, _sector_idx: usize) -> u64 {
+            self.last_access_time
+        }
+
+        fn modified_size(&self) -> u32 {
+            let num_modified_sectors = self
+                .status
+                .iter()
+                .filter(|s| **s == Status::MODIFIED)
+                .count();
+            num_modified_sectors as u32 * SECTOR_SIZE
+        }
+
+        fn dirty_byte_mask(&self) -> mem_fetch::ByteMask {
+            // tag-only lines do not track dirty bytes
+            BitArray::ZERO
+        }
+
+        fn dirty_sector_mask(&self) -> mem_fetch::SectorMask {
+            let mut dirty_sector_mask = mem_fetch::SectorMask::ZERO;
+            for sector in 0..N {
+                if self.status[sector] == Status::MODIFIED {
+                    dirty_sector_mask.set(sector, true);
+                }
+            }
+            dirty_sector_mask
+        }
+    }
+}
+
 pub mod sector {
     use super::Status;
     use crate::{address, mem_fetch, mem_sub_partition::SECTOR_SIZE};
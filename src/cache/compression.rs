@@ -0,0 +1,227 @@
+//! Cache line compression models.
+//!
+//! Compression allows a cache to hold more lines than its physical
+//! capacity: a line compressed to half its size only occupies half a
+//! physical line. The models compute the compressed size of a line from
+//! its data values. Since the traces used for simulation do not usually
+//! record data values, each algorithm also has an expected compression
+//! ratio used to scale the effective cache capacity when no data is
+//! available.
+
+use serde::{Deserialize, Serialize};
+
+/// A cache line compression algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Algorithm {
+    /// Base-delta-immediate compression (Pekhimenko et al., PACT'12).
+    ///
+    /// Represents a line as one base value plus narrow per-element
+    /// deltas.
+    Bdi,
+    /// Frequent pattern compression (Alameldeen and Wood, TR'04).
+    ///
+    /// Compresses each 32-bit word using a three bit prefix selecting
+    /// one of a fixed set of frequent patterns.
+    Fpc,
+    /// Fixed compression ratio applied to every line.
+    Fixed { ratio: f64 },
+}
+
+impl Algorithm {
+    /// The compressed size in bytes of a line with the given data.
+    #[must_use]
+    pub fn compressed_size(&self, line: &[u8]) -> usize {
+        let size = match self {
+            Self::Bdi => bdi_compressed_size(line),
+            Self::Fpc => fpc_compressed_size(line),
+            Self::Fixed { ratio } => fixed_compressed_size(line.len(), *ratio),
+        };
+        size.min(line.len())
+    }
+
+    /// The expected compressed size in bytes of a line of the given
+    /// size.
+    ///
+    /// Used when the data values of the line are not known.
+    #[must_use]
+    pub fn expected_size(&self, line_size: usize) -> usize {
+        fixed_compressed_size(line_size, self.expected_ratio())
+    }
+
+    /// The expected compression ratio of the algorithm.
+    ///
+    /// Used to scale the effective cache capacity when the trace does
+    /// not record data values. The default ratios for BDI and FPC are
+    /// the averages reported for the respective algorithms.
+    #[must_use]
+    pub fn expected_ratio(&self) -> f64 {
+        match self {
+            Self::Bdi => 1.5,
+            Self::Fpc => 1.5,
+            Self::Fixed { ratio } => *ratio,
+        }
+    }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = color_eyre::eyre::Report;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        use color_eyre::eyre::WrapErr;
+        match value.to_ascii_lowercase().as_str() {
+            "bdi" => Ok(Self::Bdi),
+            "fpc" => Ok(Self::Fpc),
+            ratio => {
+                let ratio = ratio
+                    .parse()
+                    .wrap_err_with(|| format!("bad compression algorithm: {value}"))?;
+                Ok(Self::Fixed { ratio })
+            }
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+fn fixed_compressed_size(line_size: usize, ratio: f64) -> usize {
+    assert!(ratio >= 1.0, "compression ratio must be at least 1");
+    (line_size as f64 / ratio).ceil() as usize
+}
+
+/// Compressed size in bytes of a line under base-delta-immediate.
+///
+/// The line is encoded as one base value plus one delta per element,
+/// trying all (base size, delta size) combinations of the original
+/// proposal. Lines of all zeros or one repeated value are special
+/// cased.
+#[must_use]
+pub fn bdi_compressed_size(line: &[u8]) -> usize {
+    if line.iter().all(|byte| *byte == 0) {
+        return 1;
+    }
+    if line.chunks_exact(8).all(|chunk| chunk == &line[..8]) {
+        return 8;
+    }
+    let mut best = line.len();
+    for (base_size, delta_size) in [(8, 1), (8, 2), (8, 4), (4, 1), (4, 2), (2, 1)] {
+        if !line.len().is_multiple_of(base_size) {
+            continue;
+        }
+        let mut elements = line.chunks_exact(base_size).map(|chunk| {
+            let mut bytes = [0u8; 8];
+            bytes[..base_size].copy_from_slice(chunk);
+            i64::from_le_bytes(bytes)
+        });
+        let base = elements.next().unwrap();
+        let max_delta = 1i64 << (8 * delta_size - 1);
+        let compressible = elements.all(|element| {
+            let delta = element.wrapping_sub(base);
+            (-max_delta..max_delta).contains(&delta)
+        });
+        if compressible {
+            let num_elements = line.len() / base_size;
+            best = best.min(base_size + num_elements * delta_size);
+        }
+    }
+    best
+}
+
+/// Compressed size in bytes of a line under frequent pattern
+/// compression.
+///
+/// Each 32-bit word is compressed to a three bit prefix plus a payload
+/// depending on the matched pattern.
+#[must_use]
+pub fn fpc_compressed_size(line: &[u8]) -> usize {
+    let mut bits: usize = 0;
+    for chunk in line.chunks(4) {
+        let mut bytes = [0u8; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let word = u32::from_le_bytes(bytes);
+        let payload_bits = if word == 0 {
+            // zero word
+            0
+        } else if sign_extends(word, 4) {
+            // 4-bit sign-extended
+            4
+        } else if sign_extends(word, 8) {
+            // 8-bit sign-extended
+            8
+        } else if sign_extends(word, 16) {
+            // 16-bit sign-extended
+            16
+        } else if word & 0xFFFF == 0 {
+            // halfword padded with a zero halfword
+            16
+        } else if word.to_le_bytes().iter().all(|byte| *byte == bytes[0]) {
+            // repeated bytes
+            8
+        } else {
+            // uncompressed
+            32
+        };
+        bits += 3 + payload_bits;
+    }
+    bits.div_ceil(8)
+}
+
+/// Whether the word sign-extends from its low `num_bits` bits.
+fn sign_extends(word: u32, num_bits: u32) -> bool {
+    let shift = 32 - num_bits;
+    ((word as i32) << shift) >> shift == word as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::diff;
+
+    #[test]
+    fn test_bdi() {
+        // all zeros
+        diff::assert_eq!(have: super::bdi_compressed_size(&[0u8; 128]), want: 1);
+
+        // one repeated value
+        let repeated: Vec<u8> = std::iter::repeat(0xDEAD_BEEF_u64.to_le_bytes())
+            .take(16)
+            .flatten()
+            .collect();
+        diff::assert_eq!(have: super::bdi_compressed_size(&repeated), want: 8);
+
+        // small deltas on a large base: pointers into the same region
+        let pointers: Vec<u8> = (0..16u64)
+            .flat_map(|idx| (0x7f00_0000_0000 + idx * 8).to_le_bytes())
+            .collect();
+        // 8 byte base + 16 deltas of 1 byte
+        diff::assert_eq!(have: super::bdi_compressed_size(&pointers), want: 8 + 16);
+
+        // incompressible
+        let random: Vec<u8> = (0..16u64)
+            .flat_map(|idx| (idx.wrapping_mul(0x9E37_79B9_7F4A_7C15)).to_le_bytes())
+            .collect();
+        diff::assert_eq!(have: super::bdi_compressed_size(&random), want: 128);
+    }
+
+    #[test]
+    fn test_fpc() {
+        // all zeros: 3 bits per word
+        diff::assert_eq!(have: super::fpc_compressed_size(&[0u8; 128]), want: 12);
+
+        // small integers: 3 + 8 bits per word
+        let small: Vec<u8> = (0..32u32)
+            .flat_map(|value| (value + 16).to_le_bytes())
+            .collect();
+        diff::assert_eq!(have: super::fpc_compressed_size(&small), want: 44);
+
+        // incompressible
+        let random: Vec<u8> = (0..32u32)
+            .flat_map(|idx| (idx.wrapping_mul(0x9E37_79B9) | 0x0101_0011).to_le_bytes())
+            .collect();
+        diff::assert_eq!(have: super::fpc_compressed_size(&random), want: 140);
+    }
+
+    #[test]
+    fn test_fixed() {
+        let algorithm = super::Algorithm::Fixed { ratio: 2.0 };
+        diff::assert_eq!(have: algorithm.compressed_size(&[0xFFu8; 128]), want: 64);
+        diff::assert_eq!(have: algorithm.expected_ratio(), want: 2.0);
+    }
+}
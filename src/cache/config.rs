@@ -1,4 +1,4 @@
-use crate::config;
+use crate::{config, mem_fetch};
 
 use serde::{Deserialize, Serialize};
 
@@ -26,6 +26,35 @@ pub enum WritePolicy {
     LOCAL_WB_GLOBAL_WT, // L
 }
 
+/// Per-access-kind overrides of the cache write policy.
+///
+/// Some architectures treat e.g. global writes as write-through at the
+/// L2 while local writes remain write-back.
+/// An override replaces the default write policy of the cache for all
+/// accesses of the corresponding kind.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub struct WritePolicyOverrides {
+    pub global: Option<WritePolicy>,
+    pub local: Option<WritePolicy>,
+    pub texture: Option<WritePolicy>,
+}
+
+impl WritePolicyOverrides {
+    /// The write policy override for an access kind, if any.
+    #[must_use]
+    pub fn get(&self, kind: mem_fetch::access::Kind) -> Option<WritePolicy> {
+        use mem_fetch::access::Kind;
+        match kind {
+            Kind::GLOBAL_ACC_R | Kind::GLOBAL_ACC_W => self.global,
+            Kind::LOCAL_ACC_R | Kind::LOCAL_ACC_W => self.local,
+            Kind::TEXTURE_ACC_R => self.texture,
+            _ => None,
+        }
+    }
+}
+
 /// A cache allocate policy.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AllocatePolicy {
@@ -59,9 +88,20 @@ pub struct Config {
     /// Cache write policy.
     pub write_policy: WritePolicy,
 
+    /// Per-access-kind overrides of the cache write policy.
+    pub write_policy_overrides: WritePolicyOverrides,
+
     /// Cache line size.
     pub line_size: u32,
 
+    /// Sector size.
+    ///
+    /// The line is split into `line_size / sector_size` sectors.
+    pub sector_size: u32,
+
+    /// Number of sectors per cache line (1 for non-sectored caches).
+    pub num_sectors: usize,
+
     /// Cache associativity.
     pub associativity: usize,
 
@@ -85,8 +125,17 @@ pub struct Config {
     /// NOTE: CAN BE COMPUTED from sets and associativity.
     pub total_lines: usize,
 
+    /// Optional cache line compression model.
+    ///
+    /// Compression scales the effective capacity of the cache by the
+    /// expected compression ratio of the algorithm.
+    pub compression: Option<super::compression::Algorithm>,
+
     /// Accelsim compat mode.
     pub accelsim_compat: bool,
+
+    /// Record per-set access and miss counts for heatmap export.
+    pub record_access_heatmap: bool,
 }
 
 // impl From<&config::Cache> for Config {
@@ -110,23 +159,58 @@ pub struct Config {
 // }
 
 impl Config {
+    #[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
     pub fn new(config: &config::Cache, accelsim_compat: bool) -> Self {
+        // compressed lines occupy fewer bytes, such that more lines fit
+        // into the same physical capacity: model this by scaling the
+        // associativity with the expected compression ratio
+        let associativity = match config.compression {
+            Some(compression) => {
+                ((config.associativity as f64 * compression.expected_ratio()) as usize).max(1)
+            }
+            None => config.associativity,
+        };
+        assert!(
+            config.line_size % config.sector_size == 0,
+            "cache line size must be a multiple of the sector size"
+        );
+        assert!(
+            config.num_sectors() <= crate::mem_sub_partition::NUM_SECTORS,
+            "a cache line must not have more than {} sectors",
+            crate::mem_sub_partition::NUM_SECTORS,
+        );
         Self {
             // set_index_function: Arc::<crate::set_index::linear::SetIndex>::default(),
             write_policy: config.write_policy,
+            write_policy_overrides: config.write_policy_overrides,
             write_allocate_policy: config.write_allocate_policy,
             allocate_policy: config.allocate_policy,
             replacement_policy: config.replacement_policy,
-            associativity: config.associativity,
+            associativity,
             num_sets: config.num_sets,
             atom_size: config.atom_size(),
             miss_queue_size: config.miss_queue_size,
             mshr_kind: config.mshr_kind,
-            total_lines: config.num_sets * config.associativity,
+            total_lines: config.num_sets * associativity,
             line_size: config.line_size,
+            sector_size: config.sector_size,
+            num_sectors: config.num_sectors(),
+            compression: config.compression,
             accelsim_compat,
+            record_access_heatmap: config.record_access_heatmap,
         }
     }
+
+    /// The effective write policy for an access of the given kind.
+    ///
+    /// Per-access-kind overrides take precedence over the default write
+    /// policy of the cache.
+    #[must_use]
+    pub fn write_policy_for(&self, kind: mem_fetch::access::Kind) -> WritePolicy {
+        self.write_policy_overrides
+            .get(kind)
+            .unwrap_or(self.write_policy)
+    }
 }
 
 // impl CacheConfig {
@@ -109,7 +109,12 @@ pub mod pascal {
 
         // #[inline]
         fn mshr_addr(&self, addr: address) -> address {
-            addr & !address::from(self.config.atom_size - 1)
+            if self.config.mshr_kind.is_sectored() {
+                // misses are tracked at sector granularity
+                addr & !address::from(self.config.sector_size - 1)
+            } else {
+                addr & !address::from(self.config.atom_size - 1)
+            }
         }
     }
 
@@ -120,7 +125,7 @@ pub mod pascal {
     pub struct L1DataCacheController {
         inner: DataCacheController,
         set_index_function: cache::set_index::pascal::SetIndex,
-        banks_set_index_function: cache::set_index::linear::SetIndex,
+        banks_set_index_function: cache::set_index::dynamic::SetIndex,
 
         #[allow(dead_code)]
         l1_latency: usize,
@@ -140,7 +145,8 @@ pub mod pascal {
         ) -> Self {
             let mut set_index_function = cache::set_index::pascal::SetIndex::default();
             set_index_function.accelsim_compat_mode = accelsim_compat_mode;
-            let banks_set_index_function = cache::set_index::linear::SetIndex::new(
+            let banks_set_index_function = cache::set_index::dynamic::SetIndex::new(
+                l1_config.l1_banks_hashing_function,
                 l1_config.l1_banks,
                 l1_config.l1_banks_byte_interleaving,
             );
@@ -1,9 +1,7 @@
 use crate::sync::{Arc, Mutex};
 use crate::{
-    address, cache, config, interconn as ic, mcu, mem_fetch,
-    mem_sub_partition::{NUM_SECTORS, SECTOR_SIZE},
-    mshr::MSHR,
-    tag_array,
+    address, cache, config, interconn as ic, mcu, mem_fetch, mem_sub_partition::NUM_SECTORS,
+    mshr::MSHR, tag_array,
 };
 
 use cache::block::Block;
@@ -43,6 +41,9 @@ pub struct Data<MC, CC, S> {
     write_alloc_type: AccessKind,
     /// Specifies type of writeback request (e.g., L1 or L2)
     write_back_type: AccessKind,
+
+    /// Named address ranges the cache stats are broken down by
+    named_address_ranges: Vec<config::NamedAddressRange>,
 }
 
 impl<MC, CC, S> Builder<MC, CC, S>
@@ -65,6 +66,7 @@ where
             mem_controller: self.mem_controller,
             write_alloc_type: self.write_alloc_type,
             write_back_type: self.write_back_type,
+            named_address_ranges: self.config.named_address_ranges.clone(),
         }
     }
 }
@@ -123,6 +125,49 @@ where
         cache::RequestStatus::HIT
     }
 
+    /// Write-through hit: write to cache and also send request to lower
+    /// level memory.
+    fn write_hit_write_through(
+        &mut self,
+        addr: address,
+        cache_index: usize,
+        fetch: &mem_fetch::MemFetch,
+        time: u64,
+        events: &mut Vec<cache::Event>,
+        probe_status: cache::RequestStatus,
+    ) -> cache::RequestStatus {
+        if self.inner.miss_queue_full() {
+            let mut stats = self.inner.stats.lock();
+            let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+            kernel_stats.inc(
+                fetch.allocation_id(),
+                fetch.access_kind(),
+                cache::AccessStat::ReservationFailure(cache::ReservationFailure::MISS_QUEUE_FULL),
+                1,
+            );
+            // cannot handle request this cycle
+            return cache::RequestStatus::RESERVATION_FAIL;
+        }
+
+        let status =
+            self.write_hit_write_back(addr, cache_index, fetch, time, events, probe_status);
+        debug_assert_eq!(status, cache::RequestStatus::HIT);
+
+        {
+            let mut stats = self.inner.stats.lock();
+            let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+            *kernel_stats
+                .num_write_through_sent
+                .entry(fetch.access_kind().into())
+                .or_insert(0) += 1;
+        }
+
+        let event = cache::Event::WriteRequestSent;
+        self.send_write_request(fetch.clone(), event, time, events);
+
+        status
+    }
+
     /// Write-evict hit.
     /// Send request to lower level memory and invalidate corresponding block
     #[allow(dead_code)]
@@ -184,13 +229,14 @@ where
     }
 
     fn update_readable(&mut self, fetch: &mem_fetch::MemFetch, cache_index: usize) {
+        let num_sectors = self.inner.cache_config.num_sectors;
+        let sector_size = self.inner.cache_config.sector_size as usize;
         let block = self.inner.tag_array.get_block_mut(cache_index);
-        for sector in 0..NUM_SECTORS as usize {
+        for sector in 0..num_sectors {
             let sector_mask = &fetch.access.sector_mask;
             if sector_mask[sector] {
                 let dirty_byte_mask = &block.dirty_byte_mask();
-                let bytes = &dirty_byte_mask
-                    [sector * SECTOR_SIZE as usize..(sector + 1) * SECTOR_SIZE as usize];
+                let bytes = &dirty_byte_mask[sector * sector_size..(sector + 1) * sector_size];
 
                 // TODO: test if this is equal
                 // let mut all_set = true;
@@ -302,7 +348,10 @@ where
             false,
         );
 
-        let writeback_policy = self.inner.cache_config.write_policy;
+        let writeback_policy = self
+            .inner
+            .cache_config
+            .write_policy_for(fetch.access_kind());
         log::debug!(
             "handling READ MISS for {} (should miss={})",
             fetch,
@@ -438,7 +487,10 @@ where
         //  (write miss, read request, write back request)
         //
         //  Conservatively ensure the worst-case request can be handled this cycle
-        let mshr_hit = self.inner.mshrs.get(mshr_addr).is_some();
+        let mshr_hit = self
+            .inner
+            .mshrs
+            .can_merge(mshr_addr, &fetch.access.sector_mask);
         let mshr_free = !self.inner.mshrs.full(mshr_addr);
         let mshr_full = !self.inner.miss_queue_can_fit(2);
         let mshr_miss_but_free = !mshr_hit && mshr_free && !self.inner.miss_queue_full();
@@ -535,8 +587,11 @@ where
             //     "evicted block: {:?}",
             //     evicted.as_ref().map(|e| e.block_addr)
             // );
-            let not_write_through =
-                self.inner.cache_config.write_policy != cache::config::WritePolicy::WRITE_THROUGH;
+            let not_write_through = self
+                .inner
+                .cache_config
+                .write_policy_for(fetch.access_kind())
+                != cache::config::WritePolicy::WRITE_THROUGH;
 
             if let Some(evicted) = evicted {
                 if evicted.writeback && not_write_through {
@@ -630,12 +685,16 @@ where
         probe_status: cache::RequestStatus,
     ) -> cache::RequestStatus {
         use cache::config::WritePolicy;
-        let func = match self.inner.cache_config.write_policy {
+        let write_policy = self
+            .inner
+            .cache_config
+            .write_policy_for(fetch.access_kind());
+        let func = match write_policy {
             // TODO: make read only policy deprecated
             // READ_ONLY is now a separate cache class, config is deprecated
             WritePolicy::READ_ONLY => unimplemented!("todo: remove the read only cache write policy / writable data cache set as READ_ONLY"),
             WritePolicy::WRITE_BACK => Self::write_hit_write_back,
-            WritePolicy::WRITE_THROUGH => unimplemented!("WritePolicy::WRITE_THROUGH"),
+            WritePolicy::WRITE_THROUGH => Self::write_hit_write_through,
             WritePolicy::WRITE_EVICT => unimplemented!("WritePolicy::WRITE_EVICT"),
             WritePolicy::LOCAL_WB_GLOBAL_WT => unimplemented!("WritePolicy::LOCAL_WB_GLOBAL_WT"),
             // WritePolicy::LOCAL_WB_GLOBAL_WT => Self::write_hit_global_write_evict_local_write_back,
@@ -783,13 +842,20 @@ where
         self.inner.tag_array.write_state(csv_writer)
     }
 
+    fn write_access_heatmap(
+        &self,
+        csv_writer: &mut csv::Writer<std::io::BufWriter<std::fs::File>>,
+    ) -> eyre::Result<()> {
+        self.inner.tag_array.write_access_heatmap(csv_writer)
+    }
+
     fn access(
         &mut self,
         addr: address,
         fetch: mem_fetch::MemFetch,
         events: &mut Vec<cache::Event>,
         time: u64,
-    ) -> cache::RequestStatus {
+    ) -> cache::AccessOutcome {
         let super::base::Base {
             ref cache_controller,
             ref cache_config,
@@ -837,16 +903,27 @@ where
         } else {
             cache::select_status(probe_status, access_status)
         };
+        let count = if self.inner.cache_config.accelsim_compat {
+            1
+        } else {
+            fetch.access.num_transactions()
+        };
         kernel_stats.inc(
             allocation_id,
             access_kind,
             cache::AccessStat::Status(access_stat),
-            if self.inner.cache_config.accelsim_compat {
-                1
-            } else {
-                fetch.access.num_transactions()
-            },
+            count,
         );
+        for (range_id, range) in self.named_address_ranges.iter().enumerate() {
+            if range.matches(allocation_id, addr) {
+                kernel_stats.inc_range(
+                    range_id,
+                    access_kind,
+                    cache::AccessStat::Status(access_stat),
+                    count,
+                );
+            }
+        }
 
         if crate::DEBUG_PRINT
             && (probe_status, access_status)
@@ -877,7 +954,8 @@ where
         //     allocation_id,
         //     stats::cache::AccessStatus((access_kind.into(), access_stat.into())),
         // ));
-        access_status
+        let set_index = self.inner.cache_controller.set_index(addr);
+        cache::AccessOutcome::new(access_status, set_index, events)
     }
 
     fn write_allocate_policy(&self) -> cache::config::WriteAllocatePolicy {
@@ -908,6 +986,10 @@ where
         self.inner.invalidate();
     }
 
+    fn invalidate_addr(&mut self, addr: address) -> bool {
+        self.inner.invalidate_addr(addr)
+    }
+
     fn flush(&mut self) -> usize {
         self.inner.flush()
     }
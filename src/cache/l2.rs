@@ -8,10 +8,30 @@ use std::collections::VecDeque;
 #[derive(Clone)]
 pub struct L2DataCacheController<MC, CC> {
     accelsim_compat: bool,
+    /// Compute the set index from the partition-relative address (see
+    /// [`config::L2SetIndexing`]).
+    partition_address_indexing: bool,
     memory_controller: MC,
     cache_controller: CC,
 }
 
+impl<MC, CC> L2DataCacheController<MC, CC> {
+    #[must_use]
+    pub fn new(
+        accelsim_compat: bool,
+        partition_address_indexing: bool,
+        memory_controller: MC,
+        cache_controller: CC,
+    ) -> Self {
+        Self {
+            accelsim_compat,
+            partition_address_indexing,
+            memory_controller,
+            cache_controller,
+        }
+    }
+}
+
 impl<MC, CC> cache::CacheController for L2DataCacheController<MC, CC>
 where
     MC: mcu::MemoryController,
@@ -29,12 +49,11 @@ where
 
     // #[inline]
     fn set_index(&self, addr: address) -> u64 {
-        let partition_addr = if true || self.accelsim_compat {
+        let partition_addr = if self.accelsim_compat || self.partition_address_indexing {
             self.memory_controller.memory_partition_address(addr)
         } else {
             addr
         };
-        // println!("partition address for addr {} is {}", addr, partition_addr);
         self.cache_controller.set_index(partition_addr)
     }
 
@@ -49,12 +68,25 @@ where
     }
 }
 
+/// Source for the data values of a cache line of the given address and
+/// size.
+pub type DataSource = Box<dyn Fn(address, u32) -> Option<Vec<u8>> + Send + Sync>;
+
 /// Generic data cache.
 #[allow(clippy::module_name_repetitions)]
 pub struct DataL2 {
     pub sub_partition_id: usize,
     pub partition_id: usize,
     pub cache_config: Arc<config::L2DCache>,
+    /// Optional cache line compression model (see [`config::Cache`]).
+    compression: Option<cache::compression::Algorithm>,
+    /// Optional source for the data values of a cache line.
+    ///
+    /// Traces do not usually record data values. When a source is
+    /// provided, the compression model is computed over the actual line
+    /// data; otherwise the expected compression ratio of the algorithm
+    /// is assumed.
+    pub data_source: Option<DataSource>,
     pub inner: super::data::Data<
         mcu::MemoryControllerUnit,
         L2DataCacheController<
@@ -78,12 +110,14 @@ impl DataL2 {
         let default_cache_controller = cache::controller::pascal::DataCacheController::new(
             cache::Config::new(cache_config.inner.as_ref(), config.accelsim_compat),
         );
-        let cache_controller = L2DataCacheController {
-            accelsim_compat: config.accelsim_compat,
-            memory_controller: mem_controller.clone(),
-            cache_controller: default_cache_controller,
-        };
-        let inner = super::data::Builder {
+        let cache_controller = L2DataCacheController::new(
+            config.accelsim_compat,
+            config.l2_partition_address_indexing(),
+            mem_controller.clone(),
+            default_cache_controller,
+        );
+        let l2_residency_window = config.l2_residency_window.clone();
+        let mut inner = super::data::Builder {
             name,
             stats,
             config,
@@ -94,10 +128,14 @@ impl DataL2 {
             write_back_type: AccessKind::L2_WRBK_ACC,
         }
         .build();
+        // the residency window only applies to the L2 tag arrays
+        inner.inner.tag_array.l2_residency_window = l2_residency_window;
         Self {
             inner,
             sub_partition_id,
             partition_id,
+            compression: cache_config.inner.compression,
+            data_source: None,
             cache_config,
         }
     }
@@ -136,6 +174,13 @@ impl super::Cache<stats::cache::PerKernel> for DataL2 {
         self.inner.inner.tag_array.write_state(csv_writer)
     }
 
+    fn write_access_heatmap(
+        &self,
+        csv_writer: &mut csv::Writer<std::io::BufWriter<std::fs::File>>,
+    ) -> eyre::Result<()> {
+        self.inner.inner.tag_array.write_access_heatmap(csv_writer)
+    }
+
     // #[inline]
     fn write_allocate_policy(&self) -> cache::config::WriteAllocatePolicy {
         self.inner.write_allocate_policy()
@@ -181,7 +226,7 @@ impl super::Cache<stats::cache::PerKernel> for DataL2 {
         fetch: mem_fetch::MemFetch,
         events: &mut Vec<super::event::Event>,
         time: u64,
-    ) -> super::RequestStatus {
+    ) -> super::AccessOutcome {
         self.inner.access(addr, fetch, events, time)
     }
 
@@ -192,6 +237,23 @@ impl super::Cache<stats::cache::PerKernel> for DataL2 {
 
     // #[inline]
     fn fill(&mut self, fetch: mem_fetch::MemFetch, time: u64) {
+        if let Some(compression) = self.compression {
+            let line_size = self.cache_config.inner.line_size;
+            let data = self
+                .data_source
+                .as_ref()
+                .and_then(|source| source(fetch.addr(), line_size));
+            let compressed_size = match data {
+                Some(ref data) => compression.compressed_size(data),
+                // the trace does not record data values: assume the
+                // expected compression ratio of the algorithm
+                None => compression.expected_size(line_size as usize),
+            };
+            let mut stats = self.inner.inner.stats.lock();
+            let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+            kernel_stats.num_fill_bytes_uncompressed += u64::from(line_size);
+            kernel_stats.num_fill_bytes_compressed += compressed_size as u64;
+        }
         self.inner.fill(fetch, time);
     }
 
@@ -242,6 +304,7 @@ mod tests {
         );
         let l2_cache_controller = super::L2DataCacheController {
             accelsim_compat: false,
+            partition_address_indexing: true,
             memory_controller,
             cache_controller,
         };
@@ -250,4 +313,55 @@ mod tests {
         assert_eq!(l2_cache_controller.set_index(block_addr), 1);
         Ok(())
     }
+
+    #[test]
+    fn test_l2d_partition_address_indexing_avoids_set_camping() -> eyre::Result<()> {
+        use crate::mcu::MemoryController;
+        use std::collections::HashSet;
+
+        let accelsim_compat = false;
+        let config = crate::config::GPU::default();
+        let l2_cache_config = &config.data_cache_l2.as_ref().unwrap().inner;
+
+        let memory_controller = crate::mcu::MemoryControllerUnit::new(&config)?;
+        let cache_controller = || {
+            crate::cache::controller::pascal::DataCacheController::new(crate::cache::Config::new(
+                l2_cache_config.as_ref(),
+                accelsim_compat,
+            ))
+        };
+        let partitioned = super::L2DataCacheController {
+            accelsim_compat,
+            partition_address_indexing: true,
+            memory_controller: memory_controller.clone(),
+            cache_controller: cache_controller(),
+        };
+        let raw = super::L2DataCacheController {
+            accelsim_compat,
+            partition_address_indexing: false,
+            memory_controller: memory_controller.clone(),
+            cache_controller: cache_controller(),
+        };
+
+        // one L2 slice only ever sees the addresses of its sub
+        // partition, which have fixed partition and sub partition bits
+        let base: crate::address = 34_887_082_112;
+        let line_size = u64::from(l2_cache_config.line_size);
+        let mut partitioned_sets = HashSet::new();
+        let mut raw_sets = HashSet::new();
+        for i in 0..8192 {
+            let addr = base + i * line_size;
+            if memory_controller.to_physical_address(addr).sub_partition != 0 {
+                continue;
+            }
+            partitioned_sets.insert(partitioned.set_index(addr));
+            raw_sets.insert(raw.set_index(addr));
+        }
+
+        // partition-relative indexing spreads the slice's addresses
+        // over more sets, while raw indexing camps on the sets
+        // compatible with the fixed partition bits
+        assert!(partitioned_sets.len() > raw_sets.len());
+        Ok(())
+    }
 }
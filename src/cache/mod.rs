@@ -1,6 +1,7 @@
 pub mod bandwidth;
 pub mod base;
 pub mod block;
+pub mod compression;
 pub mod config;
 pub mod controller;
 pub mod data;
@@ -8,6 +9,7 @@ pub mod event;
 pub mod l2;
 pub mod readonly;
 pub mod set_index;
+pub mod shared;
 
 pub use config::Config;
 #[allow(clippy::module_name_repetitions)]
@@ -19,6 +21,7 @@ pub use readonly::ReadOnly;
 
 use super::{address, mem_fetch};
 use crate::sync::{Arc, Mutex};
+use crate::tag_array;
 use color_eyre::eyre;
 use std::collections::VecDeque;
 
@@ -82,6 +85,49 @@ impl From<ReservationFailure> for stats::cache::ReservationFailure {
     }
 }
 
+/// Outcome of a cache access.
+///
+/// Extends the plain [`RequestStatus`] with information that callers
+/// previously had to re-derive from the event list: the requests sent
+/// to the lower memory level, the block evicted by a writeback and the
+/// set the access mapped to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessOutcome {
+    /// Status of the access.
+    pub status: RequestStatus,
+    /// Index of the set the access mapped to.
+    pub set_index: u64,
+    /// Block evicted by the access and written back to the lower level.
+    pub evicted: Option<tag_array::EvictedBlockInfo>,
+    /// Whether a read request was sent to the lower level.
+    pub read_sent: bool,
+    /// Whether a write request was sent to the lower level.
+    pub write_sent: bool,
+    /// Whether a write-allocate request was sent to the lower level.
+    pub write_allocate_sent: bool,
+}
+
+impl AccessOutcome {
+    /// Assemble the outcome of an access from the events it generated.
+    #[must_use]
+    pub fn new(status: RequestStatus, set_index: u64, events: &[Event]) -> Self {
+        Self {
+            status,
+            set_index,
+            evicted: event::was_writeback_sent(events).cloned(),
+            read_sent: event::was_read_sent(events),
+            write_sent: event::was_write_sent(events),
+            write_allocate_sent: event::was_writeallocate_sent(events),
+        }
+    }
+
+    /// Whether the access merged into a pending MSHR entry.
+    #[must_use]
+    pub fn mshr_merged(&self) -> bool {
+        self.status == RequestStatus::MSHR_HIT
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum AccessStat {
     ReservationFailure(ReservationFailure),
@@ -150,6 +196,12 @@ pub trait Cache<S>: crate::engine::cycle::Component + Send + Sync + Bandwidth +
         csv_writer: &mut csv::Writer<std::io::BufWriter<std::fs::File>>,
     ) -> eyre::Result<()>;
 
+    /// Write per-set access heatmap of the cache to csv file
+    fn write_access_heatmap(
+        &self,
+        csv_writer: &mut csv::Writer<std::io::BufWriter<std::fs::File>>,
+    ) -> eyre::Result<()>;
+
     /// Access the cache.
     fn access(
         &mut self,
@@ -157,7 +209,21 @@ pub trait Cache<S>: crate::engine::cycle::Component + Send + Sync + Bandwidth +
         _fetch: mem_fetch::MemFetch,
         _events: &mut Vec<event::Event>,
         _time: u64,
-    ) -> RequestStatus;
+    ) -> AccessOutcome;
+
+    /// Access the cache, returning only the [`RequestStatus`].
+    ///
+    /// Thin shim over [`Cache::access`] for callers that do not need
+    /// the full [`AccessOutcome`].
+    fn access_status(
+        &mut self,
+        addr: address,
+        fetch: mem_fetch::MemFetch,
+        events: &mut Vec<event::Event>,
+        time: u64,
+    ) -> RequestStatus {
+        self.access(addr, fetch, events, time).status
+    }
 
     /// Get a list of all ready accesses.
     ///
@@ -186,6 +252,17 @@ pub trait Cache<S>: crate::engine::cycle::Component + Send + Sync + Bandwidth +
     /// Invalidate the cache.
     fn invalidate(&mut self);
 
+    /// Invalidate the line holding `addr`, if present.
+    ///
+    /// Used by the L1 coherence protocol to apply an invalidation sent
+    /// from the sharer directory at the L2.
+    ///
+    /// # Returns
+    /// Whether a line was invalidated.
+    fn invalidate_addr(&mut self, _addr: address) -> bool {
+        false
+    }
+
     /// Force access to the tag array only
     fn force_tag_access(
         &mut self,
@@ -86,6 +86,13 @@ impl cache::Cache<stats::cache::PerKernel> for ReadOnly {
         self.inner.tag_array.write_state(csv_writer)
     }
 
+    fn write_access_heatmap(
+        &self,
+        csv_writer: &mut csv::Writer<std::io::BufWriter<std::fs::File>>,
+    ) -> eyre::Result<()> {
+        self.inner.tag_array.write_access_heatmap(csv_writer)
+    }
+
     // #[inline]
     fn has_ready_accesses(&self) -> bool {
         self.inner.has_ready_accesses()
@@ -112,7 +119,7 @@ impl cache::Cache<stats::cache::PerKernel> for ReadOnly {
         fetch: mem_fetch::MemFetch,
         events: &mut Vec<cache::Event>,
         time: u64,
-    ) -> cache::RequestStatus {
+    ) -> cache::AccessOutcome {
         let cache::base::Base {
             ref cache_config,
             ref cache_controller,
@@ -216,7 +223,8 @@ impl cache::Cache<stats::cache::PerKernel> for ReadOnly {
             cache::AccessStat::Status(access_stat),
             1,
         );
-        access_status
+        let set_index = self.inner.cache_controller.set_index(addr);
+        cache::AccessOutcome::new(access_status, set_index, events)
     }
 
     // #[inline]
@@ -486,6 +486,53 @@ pub mod pascal {
     }
 }
 
+pub mod dynamic {
+    use crate::config::CacheSetIndexFunc;
+
+    /// Set index function selected by the cache configuration.
+    ///
+    /// Dispatches to one of the statically known set index functions
+    /// based on the configured hashing function kind.
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    pub enum SetIndex {
+        Fermi(super::fermi::SetIndex),
+        Ipoly(super::ipoly::SetIndex),
+        Linear(super::linear::SetIndex),
+        BitwiseXor(super::bitwise_xor::SetIndex),
+    }
+
+    impl SetIndex {
+        pub fn new(func: CacheSetIndexFunc, num_sets: usize, line_size: usize) -> Self {
+            match func {
+                CacheSetIndexFunc::FERMI_HASH_SET_FUNCTION => {
+                    Self::Fermi(super::fermi::SetIndex::new(num_sets, line_size))
+                }
+                CacheSetIndexFunc::HASH_IPOLY_FUNCTION => {
+                    Self::Ipoly(super::ipoly::SetIndex::new(num_sets, line_size))
+                }
+                CacheSetIndexFunc::LINEAR_SET_FUNCTION => {
+                    Self::Linear(super::linear::SetIndex::new(num_sets, line_size))
+                }
+                CacheSetIndexFunc::BITWISE_XORING_FUNCTION => {
+                    Self::BitwiseXor(super::bitwise_xor::SetIndex::new(num_sets, line_size))
+                }
+            }
+        }
+    }
+
+    impl super::SetIndexer for SetIndex {
+        // #[inline]
+        fn compute_set_index(&self, addr: super::address) -> u64 {
+            match self {
+                Self::Fermi(set_index) => set_index.compute_set_index(addr),
+                Self::Ipoly(set_index) => set_index.compute_set_index(addr),
+                Self::Linear(set_index) => set_index.compute_set_index(addr),
+                Self::BitwiseXor(set_index) => set_index.compute_set_index(addr),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::cache::set_index::SetIndexer;
@@ -559,4 +606,24 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_dynamic() {
+        use crate::config::CacheSetIndexFunc;
+        let num_sets = 4;
+        let line_size = 128;
+        let linear = super::linear::SetIndex::new(num_sets, line_size);
+        let dynamic = super::dynamic::SetIndex::new(
+            CacheSetIndexFunc::LINEAR_SET_FUNCTION,
+            num_sets,
+            line_size,
+        );
+        for set in 0..num_sets {
+            let addr = (set * line_size + 32) as u64;
+            assert_eq!(
+                dynamic.compute_set_index(addr),
+                linear.compute_set_index(addr)
+            );
+        }
+    }
 }
@@ -0,0 +1,285 @@
+//! A cache shared by multiple cores.
+//!
+//! Each sharing core owns a [`Port`] to the same underlying cache,
+//! which allows e.g. sharing one L1 data cache between all cores of a
+//! cluster. The number of accesses admitted per cycle over all ports is
+//! arbitrated; accesses beyond the limit fail with
+//! [`RESERVATION_FAIL`](super::RequestStatus::RESERVATION_FAIL) and are
+//! retried by their core. Ready accesses are routed back to the port of
+//! their issuing core.
+
+use crate::sync::{Arc, Mutex};
+use crate::{address, cache, mem_fetch};
+use color_eyre::eyre;
+use std::collections::{HashMap, VecDeque};
+
+/// State of a cache shared by multiple cores.
+pub struct State<S> {
+    cache: Box<dyn cache::Cache<S>>,
+    /// Ready accesses staged for delivery to the port of their issuing
+    /// core.
+    staged: HashMap<usize, VecDeque<mem_fetch::MemFetch>>,
+    /// The last cycle the shared cache was advanced.
+    last_cycle: Option<u64>,
+    /// Accesses admitted in the current cycle.
+    ports_used: usize,
+    /// Accesses admitted per cycle over all sharing cores.
+    num_ports: usize,
+}
+
+impl<S> State<S> {
+    #[must_use]
+    pub fn new(cache: Box<dyn cache::Cache<S>>, num_ports: usize) -> Self {
+        assert!(num_ports > 0, "shared cache needs at least one port");
+        Self {
+            cache,
+            staged: HashMap::new(),
+            last_cycle: None,
+            ports_used: 0,
+            num_ports,
+        }
+    }
+}
+
+/// Shared reference to the lazily initialized state of a shared cache.
+///
+/// The first core of the sharing group builds the cache and the
+/// remaining cores attach their ports to it.
+pub type Ref<S> = Arc<Mutex<Option<State<S>>>>;
+
+/// Per-core port to a cache shared by multiple cores.
+pub struct Port<CC, S> {
+    name: String,
+    /// Global core id of the core owning this port.
+    core_id: usize,
+    /// Whether the owning core built the shared cache.
+    ///
+    /// Cache state dumps are only written through the primary port to
+    /// avoid duplication.
+    primary: bool,
+    /// Per-port copy of the cache controller of the shared cache.
+    controller: CC,
+    stats: Arc<Mutex<S>>,
+    write_allocate_policy: cache::config::WriteAllocatePolicy,
+    state: Ref<S>,
+    /// Ready accesses issued by the owning core.
+    ready: VecDeque<mem_fetch::MemFetch>,
+}
+
+impl<CC, S: 'static> Port<CC, S> {
+    /// Attach a new port to an already initialized shared cache.
+    #[must_use]
+    pub fn new(name: String, core_id: usize, primary: bool, controller: CC, state: Ref<S>) -> Self {
+        let (stats, write_allocate_policy) = {
+            let guard = state.lock();
+            let shared = guard.as_ref().expect("shared cache is initialized");
+            (
+                Arc::clone(shared.cache.per_kernel_stats()),
+                shared.cache.write_allocate_policy(),
+            )
+        };
+        Self {
+            name,
+            core_id,
+            primary,
+            controller,
+            stats,
+            write_allocate_policy,
+            state,
+            ready: VecDeque::new(),
+        }
+    }
+}
+
+impl<CC, S> std::fmt::Debug for Port<CC, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Port")
+            .field("name", &self.name)
+            .field("core_id", &self.core_id)
+            .field("primary", &self.primary)
+            .finish()
+    }
+}
+
+impl<CC, S: 'static> crate::engine::cycle::Component for Port<CC, S> {
+    fn cycle(&mut self, cycle: u64) {
+        let mut state = self.state.lock();
+        let state = state.as_mut().expect("shared cache is initialized");
+        // the first port to reach the new cycle advances the shared cache
+        if state.last_cycle != Some(cycle) {
+            state.cache.cycle(cycle);
+            state.last_cycle = Some(cycle);
+            state.ports_used = 0;
+        }
+        // route ready accesses back to the port of their issuing core
+        while let Some(fetch) = state.cache.next_access() {
+            let core_id = fetch.core_id.expect("fetch has a core id");
+            state.staged.entry(core_id).or_default().push_back(fetch);
+        }
+        if let Some(staged) = state.staged.get_mut(&self.core_id) {
+            self.ready.append(staged);
+        }
+    }
+}
+
+impl<CC, S: 'static> cache::Bandwidth for Port<CC, S> {
+    fn has_free_data_port(&self) -> bool {
+        let state = self.state.lock();
+        state
+            .as_ref()
+            .expect("shared cache is initialized")
+            .cache
+            .has_free_data_port()
+    }
+
+    fn has_free_fill_port(&self) -> bool {
+        let state = self.state.lock();
+        state
+            .as_ref()
+            .expect("shared cache is initialized")
+            .cache
+            .has_free_fill_port()
+    }
+}
+
+impl<CC> cache::Cache<stats::cache::PerKernel> for Port<CC, stats::cache::PerKernel>
+where
+    CC: cache::CacheController + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn per_kernel_stats(&self) -> &Arc<Mutex<stats::cache::PerKernel>> {
+        &self.stats
+    }
+
+    fn controller(&self) -> &dyn cache::CacheController {
+        &self.controller
+    }
+
+    fn write_state(
+        &self,
+        csv_writer: &mut csv::Writer<std::io::BufWriter<std::fs::File>>,
+    ) -> eyre::Result<()> {
+        if !self.primary {
+            return Ok(());
+        }
+        let state = self.state.lock();
+        state
+            .as_ref()
+            .expect("shared cache is initialized")
+            .cache
+            .write_state(csv_writer)
+    }
+
+    fn write_access_heatmap(
+        &self,
+        csv_writer: &mut csv::Writer<std::io::BufWriter<std::fs::File>>,
+    ) -> eyre::Result<()> {
+        if !self.primary {
+            return Ok(());
+        }
+        let state = self.state.lock();
+        state
+            .as_ref()
+            .expect("shared cache is initialized")
+            .cache
+            .write_access_heatmap(csv_writer)
+    }
+
+    fn access(
+        &mut self,
+        addr: address,
+        fetch: mem_fetch::MemFetch,
+        events: &mut Vec<cache::Event>,
+        time: u64,
+    ) -> cache::AccessOutcome {
+        let mut state = self.state.lock();
+        let state = state.as_mut().expect("shared cache is initialized");
+        if state.ports_used >= state.num_ports {
+            // all ports are taken this cycle: the core must retry
+            let mut stats = self.stats.lock();
+            let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+            kernel_stats.num_shared_cache_port_conflicts += 1;
+            let set_index = state.cache.controller().set_index(addr);
+            return cache::AccessOutcome::new(
+                cache::RequestStatus::RESERVATION_FAIL,
+                set_index,
+                events,
+            );
+        }
+        state.ports_used += 1;
+        state.cache.access(addr, fetch, events, time)
+    }
+
+    fn ready_accesses(&self) -> Option<&VecDeque<mem_fetch::MemFetch>> {
+        Some(&self.ready)
+    }
+
+    fn has_ready_accesses(&self) -> bool {
+        !self.ready.is_empty()
+    }
+
+    fn next_access(&mut self) -> Option<mem_fetch::MemFetch> {
+        self.ready.pop_front()
+    }
+
+    fn fill(&mut self, fetch: mem_fetch::MemFetch, time: u64) {
+        let mut state = self.state.lock();
+        state
+            .as_mut()
+            .expect("shared cache is initialized")
+            .cache
+            .fill(fetch, time);
+    }
+
+    fn flush(&mut self) -> usize {
+        let mut state = self.state.lock();
+        state
+            .as_mut()
+            .expect("shared cache is initialized")
+            .cache
+            .flush()
+    }
+
+    fn invalidate(&mut self) {
+        let mut state = self.state.lock();
+        state
+            .as_mut()
+            .expect("shared cache is initialized")
+            .cache
+            .invalidate();
+    }
+
+    fn waiting_for_fill(&self, fetch: &mem_fetch::MemFetch) -> bool {
+        let state = self.state.lock();
+        state
+            .as_ref()
+            .expect("shared cache is initialized")
+            .cache
+            .waiting_for_fill(fetch)
+    }
+
+    fn write_allocate_policy(&self) -> cache::config::WriteAllocatePolicy {
+        self.write_allocate_policy
+    }
+
+    fn num_used_lines(&self) -> usize {
+        let state = self.state.lock();
+        state
+            .as_ref()
+            .expect("shared cache is initialized")
+            .cache
+            .num_used_lines()
+    }
+
+    fn num_total_lines(&self) -> usize {
+        let state = self.state.lock();
+        state
+            .as_ref()
+            .expect("shared cache is initialized")
+            .cache
+            .num_total_lines()
+    }
+}
@@ -0,0 +1,415 @@
+//! Cache-hierarchy-only simulation.
+//!
+//! Replays the memory access stream extracted from a trace through the
+//! L1 data caches and the L2 cache slices in trace order, without
+//! modeling cores, the interconnect, or DRAM. Every access completes
+//! instantly: misses are filled right away, as if the next level
+//! returned the data in the same cycle.
+//!
+//! This is orders of magnitude faster than the timing simulation and
+//! produces the hit and miss statistics needed for cache geometry
+//! sweeps. The approximations are:
+//!
+//! - accesses are replayed in trace order, not in issue order, so MSHR
+//!   merges, reservation fails and inter-warp reordering are not
+//!   modeled.
+//! - write policies are not modeled: writes allocate and fill the
+//!   accessed line just like reads.
+//! - threadblocks are assigned to cores round-robin by their flat
+//!   block id.
+//!
+//! In tag-only mode the hierarchy uses minimal tag-only cache lines
+//! (see [`cache::block::tag::Block`]) that drop the per-sector
+//! timestamps and dirty byte masks, cutting the memory needed for
+//! capacity and associativity sweeps with large caches.
+
+use crate::sync::Arc;
+use crate::{
+    address, allocation, cache, config, instruction, kernel, mcu, mem_fetch,
+    mem_sub_partition::NUM_SECTORS, tag_array, FromConfig,
+};
+
+use cache::CacheController;
+use color_eyre::eyre;
+use mcu::MemoryController;
+use mem_fetch::access::Kind as AccessKind;
+use tag_array::Access;
+use trace_model::command::Command;
+
+use std::path::Path;
+
+/// Cache line type used by all levels of the hierarchy.
+type Line = cache::block::sector::Block<NUM_SECTORS>;
+
+/// Cache controller of the L2 slices.
+type L2Controller = cache::l2::L2DataCacheController<
+    mcu::MemoryControllerUnit,
+    cache::controller::pascal::DataCacheController,
+>;
+
+/// A single functional cache: a tag array and its statistics.
+struct CacheModel<B, CC> {
+    controller: CC,
+    tag_array: tag_array::TagArray<B, CC>,
+    stats: stats::cache::PerKernel,
+    allocate_policy: cache::config::AllocatePolicy,
+    accelsim_compat: bool,
+}
+
+impl<B, CC> CacheModel<B, CC>
+where
+    B: cache::block::Block,
+    CC: cache::CacheController + Clone,
+{
+    fn new(cache_config: &config::Cache, controller: CC, accelsim_compat: bool) -> Self {
+        Self {
+            tag_array: tag_array::TagArray::new(cache_config, controller.clone(), accelsim_compat),
+            controller,
+            stats: stats::cache::PerKernel::default(),
+            allocate_policy: cache_config.allocate_policy,
+            accelsim_compat,
+        }
+    }
+
+    /// Replay a single access against the tag array.
+    ///
+    /// Misses are filled immediately, such that subsequent accesses to
+    /// the same line hit.
+    fn access(&mut self, fetch: &mem_fetch::MemFetch, time: u64) -> cache::RequestStatus {
+        let block_addr = self.controller.block_addr(fetch.addr());
+        let tag_array::AccessStatus {
+            cache_index,
+            status,
+            ..
+        } = self.tag_array.access(block_addr, fetch, time);
+
+        if let cache::RequestStatus::MISS | cache::RequestStatus::SECTOR_MISS = status {
+            // the next level returns data instantly: fill right away
+            match cache_index {
+                Some(cache_index)
+                    if self.allocate_policy == cache::config::AllocatePolicy::ON_MISS =>
+                {
+                    self.tag_array.fill_on_miss(
+                        cache_index,
+                        block_addr,
+                        &fetch.access.sector_mask,
+                        &fetch.access.byte_mask,
+                        time,
+                    );
+                }
+                _ => {
+                    self.tag_array.fill_on_fill(
+                        block_addr,
+                        &fetch.access.sector_mask,
+                        &fetch.access.byte_mask,
+                        fetch.allocation_id(),
+                        fetch.is_write(),
+                        time,
+                    );
+                }
+            }
+        }
+
+        let kernel_stats = self.stats.get_mut(fetch.kernel_launch_id());
+        let count = if self.accelsim_compat {
+            1
+        } else {
+            fetch.access.num_transactions()
+        };
+        kernel_stats.inc(
+            fetch.allocation_id(),
+            fetch.access_kind(),
+            cache::AccessStat::Status(status),
+            count,
+        );
+        status
+    }
+}
+
+/// Functional cache hierarchy.
+///
+/// Holds one L1 data cache per core and one L2 slice per memory sub
+/// partition, indexed like in the timing simulator.
+pub struct CacheHierarchy<B> {
+    config: Arc<config::GPU>,
+    mem_controller: mcu::MemoryControllerUnit,
+    l1_data: Vec<CacheModel<B, cache::controller::pascal::DataCacheController>>,
+    l2_data: Vec<CacheModel<B, L2Controller>>,
+    allocations: allocation::Allocations,
+    /// Monotonic access counter used as the LRU timestamp.
+    time: u64,
+}
+
+impl<B> CacheHierarchy<B>
+where
+    B: cache::block::Block,
+{
+    pub fn new(config: Arc<config::GPU>) -> eyre::Result<Self> {
+        crate::fidelity::approximated(
+            "cache-only replay ignores timing, write policies, and MSHRs",
+        );
+        let mem_controller = mcu::MemoryControllerUnit::new(&config)?;
+
+        let l1_config = config.data_cache_l1.as_ref().expect("have L1 data cache");
+        let l1_data = (0..config.total_cores())
+            .map(|_| {
+                let controller = cache::controller::pascal::DataCacheController::new(
+                    cache::Config::new(l1_config.inner.as_ref(), config.accelsim_compat),
+                );
+                CacheModel::new(l1_config.inner.as_ref(), controller, config.accelsim_compat)
+            })
+            .collect();
+
+        let l2_config = config.data_cache_l2.as_ref().expect("have L2 data cache");
+        let l2_data = (0..config.total_sub_partitions())
+            .map(|_| {
+                let controller = cache::l2::L2DataCacheController::new(
+                    config.accelsim_compat,
+                    config.l2_partition_address_indexing(),
+                    mem_controller.clone(),
+                    cache::controller::pascal::DataCacheController::new(cache::Config::new(
+                        l2_config.inner.as_ref(),
+                        config.accelsim_compat,
+                    )),
+                );
+                CacheModel::new(l2_config.inner.as_ref(), controller, config.accelsim_compat)
+            })
+            .collect();
+
+        Ok(Self {
+            config,
+            mem_controller,
+            l1_data,
+            l2_data,
+            allocations: allocation::Allocations::default(),
+            time: 0,
+        })
+    }
+
+    /// Replay a single access through L1 and, on a miss, through L2.
+    fn replay_access(
+        &mut self,
+        core_id: usize,
+        warp_id: usize,
+        access: mem_fetch::access::MemAccess,
+    ) {
+        self.time += 1;
+
+        let physical_addr = self.mem_controller.to_physical_address(access.addr);
+        let partition_addr = self.mem_controller.memory_partition_address(access.addr);
+        let cluster_id = core_id / self.config.num_cores_per_simt_cluster;
+
+        let fetch = mem_fetch::Builder {
+            instr: None,
+            access,
+            warp_id,
+            core_id: Some(core_id),
+            cluster_id: Some(cluster_id),
+            physical_addr,
+            partition_addr,
+        }
+        .build();
+
+        let l1_status = self.l1_data[core_id].access(&fetch, self.time);
+        if let cache::RequestStatus::MISS | cache::RequestStatus::SECTOR_MISS = l1_status {
+            let sub_partition_id = fetch.sub_partition_id();
+            let _ = self.l2_data[sub_partition_id].access(&fetch, self.time);
+        }
+    }
+
+    /// Install a copied address range in the L2.
+    ///
+    /// Mirrors [`crate::MockSimulator::fill_l2`], except that the lines
+    /// are filled directly without going through the interconnect.
+    fn fill_l2_on_memcopy(&mut self, addr: address, num_bytes: u64) {
+        let l2_config = self
+            .config
+            .data_cache_l2
+            .as_ref()
+            .expect("have L2 data cache");
+        let chunk_size = u64::from(l2_config.inner.sector_size);
+        let num_sectors = l2_config.inner.num_sectors() as u64;
+        let num_chunks = num_bytes.div_ceil(chunk_size);
+
+        for chunk in 0..num_chunks {
+            self.time += 1;
+            let write_addr = addr + chunk * chunk_size;
+            let sector = (write_addr / chunk_size) % num_sectors;
+            let mut sector_mask = mem_fetch::SectorMask::ZERO;
+            sector_mask.set(sector as usize, true);
+            let byte_mask = mem_fetch::ByteMask::ZERO;
+
+            let physical_addr = self.mem_controller.to_physical_address(write_addr);
+            let sub_partition_id = physical_addr.sub_partition as usize;
+            let l2 = &mut self.l2_data[sub_partition_id];
+            let block_addr = l2.controller.block_addr(write_addr);
+            let allocation_id = self
+                .allocations
+                .get(&write_addr)
+                .map(|allocation| allocation.id);
+            let is_write = true;
+            l2.tag_array.fill_on_fill(
+                block_addr,
+                &sector_mask,
+                &byte_mask,
+                allocation_id,
+                is_write,
+                self.time,
+            );
+        }
+    }
+
+    /// Replay the memory accesses of a kernel launch.
+    fn replay_kernel(&mut self, launch: &trace_model::command::KernelLaunch, traces_dir: &Path) {
+        let kernel = kernel::trace::KernelTrace::new(
+            launch.clone(),
+            traces_dir,
+            self.config.lenient_trace_loading,
+        );
+        if let Some(device) = self.config.trace_device {
+            let kernel_device = kernel.device_id();
+            if kernel_device.is_some_and(|kernel_device| kernel_device != device) {
+                log::info!(
+                    "skip kernel {} traced on device {} (simulating device {})",
+                    launch.id,
+                    kernel_device.unwrap(),
+                    device
+                );
+                return;
+            }
+        }
+
+        let num_cores = self.config.total_cores() as u64;
+        while let Some(entry) = kernel.next_trace_entry() {
+            if !entry.instr_is_mem {
+                continue;
+            }
+            let mut instr = instruction::WarpInstruction::from_trace(&kernel, &entry, &self.config);
+            if !(instr.is_load() || instr.is_store()) {
+                continue;
+            }
+            let block = trace_model::Point::new(entry.block_id.clone(), launch.grid.clone());
+            let core_id = (block.id() % num_cores) as usize;
+
+            let Some(accesses) = instr.generate_mem_accesses(&self.config) else {
+                continue;
+            };
+            for mut access in accesses {
+                // only global and local accesses go through the data caches
+                match access.kind {
+                    AccessKind::GLOBAL_ACC_R
+                    | AccessKind::GLOBAL_ACC_W
+                    | AccessKind::LOCAL_ACC_R
+                    | AccessKind::LOCAL_ACC_W => {}
+                    _ => continue,
+                }
+                access.allocation = self.allocations.get(&access.addr).cloned();
+                self.replay_access(core_id, instr.warp_id, access);
+            }
+        }
+    }
+
+    /// Collect the per-cache statistics.
+    fn into_stats(self, kernels: &[trace_model::command::KernelLaunch]) -> stats::PerKernel {
+        let mut stats = stats::PerKernel::new(stats::Config::from_config(&self.config));
+
+        for (core_id, l1_cache) in self.l1_data.iter().enumerate() {
+            for (kernel_launch_id, cache_stats) in l1_cache.stats.as_ref().iter().enumerate() {
+                let kernel_stats = stats.get_mut(Some(kernel_launch_id));
+                kernel_stats.l1d_stats[core_id] = cache_stats.clone();
+            }
+            stats.no_kernel.l1d_stats[core_id] = l1_cache.stats.no_kernel.clone();
+        }
+        for (sub_partition_id, l2_cache) in self.l2_data.iter().enumerate() {
+            for (kernel_launch_id, cache_stats) in l2_cache.stats.as_ref().iter().enumerate() {
+                let kernel_stats = stats.get_mut(Some(kernel_launch_id));
+                kernel_stats.l2d_stats[sub_partition_id] = cache_stats.clone();
+            }
+            stats.no_kernel.l2d_stats[sub_partition_id] = l2_cache.stats.no_kernel.clone();
+        }
+
+        for launch in kernels {
+            let kernel_info = stats::KernelInfo {
+                name: launch.unmangled_name.clone(),
+                mangled_name: launch.mangled_name.clone(),
+                launch_id: launch.id as usize,
+            };
+            let kernel_stats = stats.get_mut(Some(kernel_info.launch_id));
+            kernel_stats.sim.kernel_name = kernel_info.name.clone();
+            kernel_stats.sim.kernel_name_mangled = kernel_info.mangled_name.clone();
+            kernel_stats.sim.kernel_launch_id = kernel_info.launch_id;
+            crate::set_launch_metadata(&mut kernel_stats.sim, launch);
+            kernel_stats.l1d_stats.kernel_info = kernel_info.clone();
+            kernel_stats.l2d_stats.kernel_info = kernel_info;
+        }
+        stats
+    }
+}
+
+/// Replay the memory accesses of all traced commands through the cache
+/// hierarchy.
+///
+/// In tag-only mode the caches use minimal tag-only lines, which track
+/// neither dirty bytes nor per-sector timestamps.
+pub fn replay(
+    config: Arc<config::GPU>,
+    commands_path: impl AsRef<Path>,
+    traces_dir: impl AsRef<Path>,
+    tag_only: bool,
+) -> eyre::Result<stats::PerKernel> {
+    if tag_only {
+        crate::fidelity::approximated(
+            "tag-only replay does not track dirty bytes or per-sector timestamps",
+        );
+        replay_commands::<cache::block::tag::Block<NUM_SECTORS>>(config, commands_path, traces_dir)
+    } else {
+        replay_commands::<Line>(config, commands_path, traces_dir)
+    }
+}
+
+fn replay_commands<B>(
+    config: Arc<config::GPU>,
+    commands_path: impl AsRef<Path>,
+    traces_dir: impl AsRef<Path>,
+) -> eyre::Result<stats::PerKernel>
+where
+    B: cache::block::Block,
+{
+    let commands = crate::parse_commands(commands_path.as_ref())?;
+    let mut hierarchy = CacheHierarchy::<B>::new(config)?;
+
+    let mut kernels = Vec::new();
+    for cmd in &commands {
+        match cmd {
+            Command::MemcpyHtoD(trace_model::command::MemcpyHtoD {
+                dest_device_addr,
+                num_bytes,
+                ..
+            }) => {
+                if hierarchy.config.fill_l2_on_memcopy {
+                    hierarchy.fill_l2_on_memcopy(*dest_device_addr, *num_bytes);
+                }
+            }
+            Command::MemAlloc(trace_model::command::MemAlloc {
+                allocation_name,
+                device_ptr,
+                fill_l2,
+                num_bytes,
+                ..
+            }) => {
+                let alloc_range = *device_ptr..(*device_ptr + *num_bytes);
+                hierarchy
+                    .allocations
+                    .insert(alloc_range, allocation_name.clone());
+                if *fill_l2 {
+                    hierarchy.fill_l2_on_memcopy(*device_ptr, *num_bytes);
+                }
+            }
+            Command::KernelLaunch(launch) => {
+                hierarchy.replay_kernel(launch, traces_dir.as_ref());
+                kernels.push(launch.clone());
+            }
+        }
+    }
+    Ok(hierarchy.into_stats(&kernels))
+}
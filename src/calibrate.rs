@@ -0,0 +1,178 @@
+//! Calibration of config parameters against hardware counters.
+//!
+//! Given microbenchmarks that were both traced and profiled on real
+//! hardware, a grid search over selected latency/bandwidth parameters
+//! (e.g. DRAM latency, ROP latency) replays every benchmark for every
+//! grid point and picks the point minimizing the mean relative cycle
+//! error versus the hardware counters.
+
+use color_eyre::eyre;
+use itertools::Itertools;
+use std::path::PathBuf;
+
+/// A microbenchmark used for calibration.
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    /// Trace directory of the benchmark.
+    pub traces_dir: PathBuf,
+    /// Cycle count measured on real hardware.
+    pub target_cycles: u64,
+}
+
+/// A parameter searched during calibration.
+///
+/// The path is a dotted path into the serialized config (see
+/// [`crate::config::GPU::apply_override`]), so any config field can be
+/// calibrated without a dedicated flag.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub path: String,
+    /// Candidate values, as JSON literals.
+    pub candidates: Vec<String>,
+}
+
+impl Parameter {
+    /// The default search grid for a parameter: the configured value
+    /// scaled by 50% to 150% in 25% steps.
+    pub fn default_grid(base: &crate::config::GPU, path: &str) -> eyre::Result<Self> {
+        let serialized = serde_json::to_value(base)?;
+        let current = path
+            .split('.')
+            .try_fold(&serialized, |value, segment| value.get(segment))
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| eyre::eyre!("config field {path:?} is not an integer"))?;
+        let candidates = [2, 3, 4, 5, 6]
+            .iter()
+            .map(|scale| (current * scale / 4).max(1).to_string())
+            .dedup()
+            .collect();
+        Ok(Self {
+            path: path.to_string(),
+            candidates,
+        })
+    }
+}
+
+/// The outcome of evaluating one grid point.
+#[derive(Debug, Clone)]
+pub struct GridPoint {
+    /// The `key=value` overrides of this grid point.
+    pub overrides: Vec<String>,
+    /// Simulated cycles per benchmark, in benchmark order.
+    pub cycles: Vec<u64>,
+    /// Mean relative cycle error versus the hardware counters.
+    pub mean_relative_error: f64,
+}
+
+/// The result of a calibration run.
+#[derive(Debug)]
+pub struct Calibration {
+    /// All evaluated grid points, in evaluation order.
+    pub grid: Vec<GridPoint>,
+    /// Index of the grid point with the smallest error.
+    pub best: usize,
+}
+
+impl Calibration {
+    #[must_use]
+    pub fn best(&self) -> &GridPoint {
+        &self.grid[self.best]
+    }
+
+    /// The base config with the best overrides applied.
+    pub fn calibrated_config(&self, base: &crate::config::GPU) -> eyre::Result<crate::config::GPU> {
+        let mut config = base.clone();
+        for assignment in &self.best().overrides {
+            config.apply_override(assignment)?;
+        }
+        Ok(config)
+    }
+}
+
+/// Grid search the parameters to minimize cycle error on the benchmarks.
+///
+/// Every combination of candidate values is simulated for every
+/// benchmark, so the grid grows multiplicatively with each parameter:
+/// keep the candidate lists short and the benchmarks small.
+pub fn calibrate(
+    base: &crate::config::GPU,
+    benchmarks: &[Benchmark],
+    parameters: &[Parameter],
+) -> eyre::Result<Calibration> {
+    eyre::ensure!(!benchmarks.is_empty(), "need at least one benchmark");
+    eyre::ensure!(!parameters.is_empty(), "need at least one parameter");
+
+    let mut grid: Vec<GridPoint> = Vec::new();
+    let mut best: Option<usize> = None;
+
+    let assignments = parameters
+        .iter()
+        .map(|param| {
+            param
+                .candidates
+                .iter()
+                .map(|value| format!("{}={}", param.path, value))
+                .collect::<Vec<_>>()
+        })
+        .multi_cartesian_product();
+
+    for overrides in assignments {
+        let mut config = base.clone();
+        for assignment in &overrides {
+            config.apply_override(assignment)?;
+        }
+        let config = std::sync::Arc::new(config);
+
+        let mut cycles = Vec::with_capacity(benchmarks.len());
+        let mut relative_error_sum = 0.0;
+        for benchmark in benchmarks {
+            let simulated = simulate_cycles(&config, benchmark)?;
+            let target = benchmark.target_cycles;
+            #[allow(clippy::cast_precision_loss)]
+            let relative_error = simulated.abs_diff(target) as f64 / target as f64;
+            relative_error_sum += relative_error;
+            cycles.push(simulated);
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let mean_relative_error = relative_error_sum / benchmarks.len() as f64;
+
+        eprintln!(
+            "calibrate: {} => mean relative error {:.4}",
+            overrides.join(" "),
+            mean_relative_error
+        );
+
+        let point = GridPoint {
+            overrides,
+            cycles,
+            mean_relative_error,
+        };
+        if best.is_none_or(|best: usize| point.mean_relative_error < grid[best].mean_relative_error)
+        {
+            best = Some(grid.len());
+        }
+        grid.push(point);
+    }
+
+    Ok(Calibration {
+        grid,
+        best: best.unwrap(),
+    })
+}
+
+/// Replay a benchmark and return the simulated kernel cycles.
+fn simulate_cycles(
+    config: &std::sync::Arc<crate::config::GPU>,
+    benchmark: &Benchmark,
+) -> eyre::Result<u64> {
+    let (traces_dir, commands_path) = crate::trace_commands(&benchmark.traces_dir)?;
+    let mut sim = crate::config::GTX1080::new(std::sync::Arc::clone(config));
+    sim.add_commands(commands_path, traces_dir)?;
+    sim.run()?;
+    let stats = sim.stats();
+    Ok(stats
+        .as_ref()
+        .iter()
+        .map(|kernel_stats| kernel_stats.sim.cycles)
+        .sum())
+}
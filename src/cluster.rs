@@ -1,4 +1,4 @@
-use super::{config, interconn as ic, mcu, mem_fetch, Core, MockSimulator};
+use super::{config, fifo::Fifo, interconn as ic, mcu, mem_fetch, Core, MockSimulator};
 use console::style;
 use crossbeam::utils::CachePadded;
 
@@ -19,7 +19,15 @@ pub struct Cluster<I> {
     pub core_sim_order: Arc<Mutex<VecDeque<usize>>>,
     pub block_issue_next_core: Mutex<usize>,
     // pub response_fifo: VecDeque<mem_fetch::MemFetch>,
-    pub response_fifo: RwLock<VecDeque<mem_fetch::MemFetch>>,
+    pub response_fifo: RwLock<Fifo<mem_fetch::MemFetch>>,
+
+    /// Outstanding sectors on the LD/ST return path.
+    ///
+    /// Only used with a configured return path bandwidth (see
+    /// [`config::GPU::ldst_return_bandwidth_sectors`]): a forwarded
+    /// response occupies the return path for its data size in sectors,
+    /// and further responses wait until the path has drained.
+    pub ldst_return_path_busy_sectors: Mutex<u64>,
 }
 
 impl<I> Cluster<I>
@@ -38,6 +46,12 @@ where
         let num_cores = config.num_cores_per_simt_cluster;
         let block_issue_next_core = num_cores - 1;
         let core_sim_order = (0..num_cores).collect();
+        // lazily initialized by the first core of the cluster
+        let shared_l1: Option<crate::cache::shared::Ref<stats::cache::PerKernel>> =
+            match config.l1_cache_sharing {
+                config::CacheSharing::Private => None,
+                config::CacheSharing::Cluster => Some(Arc::new(Mutex::new(None))),
+            };
         let cores = (0..num_cores)
             .map(|core_id| {
                 let id = config.global_core_id(cluster_id, core_id);
@@ -50,6 +64,7 @@ where
                     Arc::clone(stats),
                     Arc::clone(config),
                     Arc::clone(mem_controller),
+                    shared_l1.clone(),
                 );
                 Arc::new(RwLock::new(core))
             })
@@ -63,7 +78,11 @@ where
             cores,
             core_sim_order: Arc::new(Mutex::new(core_sim_order)),
             block_issue_next_core: Mutex::new(block_issue_next_core),
-            response_fifo: RwLock::new(VecDeque::new()),
+            response_fifo: RwLock::new(Fifo::new(
+                None,
+                Some(config.num_cluster_ejection_buffer_size),
+            )),
+            ldst_return_path_busy_sectors: Mutex::new(0),
         };
         cluster.reinit();
         cluster
@@ -109,8 +128,14 @@ where
             .cyan()
         );
 
+        // the return path drains its configured bandwidth every cycle
+        if let Some(bandwidth) = self.config.ldst_return_bandwidth_sectors {
+            let mut busy_sectors = self.ldst_return_path_busy_sectors.lock();
+            *busy_sectors = busy_sectors.saturating_sub(bandwidth);
+        }
+
         // Handle received package
-        if let Some(fetch) = response_fifo.front() {
+        if let Some(fetch) = response_fifo.first() {
             let core_id = self
                 .config
                 .global_core_id_to_core_id(fetch.core_id.unwrap());
@@ -128,36 +153,69 @@ where
             );
 
             match fetch.access_kind() {
+                _ if core.failed => {
+                    // a failed core accepts no responses: drop and retire
+                    // the fetch so the request tracker drains
+                    let fetch = response_fifo.dequeue().unwrap();
+                    log::debug!("dropped fetch {} for failed core {:?}", fetch, core.id());
+                    mem_fetch::tracker::retired(&fetch);
+                }
                 AccessKind::INST_ACC_R => {
                     // forward instruction fetch response to core
                     if core.fetch_unit_response_buffer_full() {
                         log::debug!("instr access fetch {} NOT YET ACCEPTED", fetch);
                     } else {
-                        let fetch = response_fifo.pop_front().unwrap();
+                        let fetch = response_fifo.dequeue().unwrap();
                         log::debug!("accepted instr access fetch {}", fetch);
                         core.accept_fetch_response(fetch, cycle);
                     }
                 }
                 _ if !core.ldst_unit_response_buffer_full() => {
-                    // Forward load store unit response to core
-                    let fetch = response_fifo.pop_front().unwrap();
-                    log::debug!("accepted ldst unit fetch {}", fetch);
-                    // m_memory_stats->memlatstat_read_done(mf);
-                    core.accept_ldst_unit_response(fetch, cycle);
+                    let mut busy_sectors = self.ldst_return_path_busy_sectors.lock();
+                    if self.config.ldst_return_bandwidth_sectors.is_some() && *busy_sectors > 0 {
+                        // the previous response still occupies the
+                        // return path
+                        log::debug!("ldst unit fetch {} waits for return path", fetch);
+                        let mut stats = self.stats.lock();
+                        stats.no_kernel.ldst_return_path_stall_cycles += 1;
+                    } else {
+                        // Forward load store unit response to core
+                        let fetch = response_fifo.dequeue().unwrap();
+                        if self.config.ldst_return_bandwidth_sectors.is_some() {
+                            *busy_sectors += u64::from(fetch.data_size())
+                                .div_ceil(u64::from(crate::mem_sub_partition::SECTOR_SIZE));
+                        }
+                        log::debug!("accepted ldst unit fetch {}", fetch);
+                        // m_memory_stats->memlatstat_read_done(mf);
+                        core.accept_ldst_unit_response(fetch, cycle);
+                    }
                 }
                 _ => {
                     log::debug!("ldst unit fetch {} NOT YET ACCEPTED", fetch);
+                    let mut stats = self.stats.lock();
+                    stats
+                        .no_kernel
+                        .ldst_response_buffer_occupancy
+                        .entry(core.core_id)
+                        .or_default()
+                        .full_stall_cycles += 1;
                 }
             }
         }
 
-        let eject_buffer_size = self.config.num_cluster_ejection_buffer_size;
-        if response_fifo.len() >= eject_buffer_size {
+        if response_fifo.full() {
             log::debug!(
-                "skip: ejection buffer full ({}/{})",
+                "skip: ejection buffer full ({}/{:?})",
                 response_fifo.len(),
-                eject_buffer_size
+                response_fifo.capacity()
             );
+            let mut stats = self.stats.lock();
+            stats
+                .no_kernel
+                .ejection_buffer_occupancy
+                .entry(self.cluster_id)
+                .or_default()
+                .full_stall_cycles += 1;
             return;
         }
 
@@ -178,7 +236,15 @@ where
         debug_assert_eq!(fetch.cluster_id, Some(self.cluster_id));
 
         fetch.status = mem_fetch::Status::IN_CLUSTER_TO_SHADER_QUEUE;
-        response_fifo.push_back(fetch);
+        response_fifo.enqueue(fetch);
+
+        let mut stats = self.stats.lock();
+        let occupancy = stats
+            .no_kernel
+            .ejection_buffer_occupancy
+            .entry(self.cluster_id)
+            .or_default();
+        occupancy.high_water_mark = occupancy.high_water_mark.max(response_fifo.len() as u64);
     }
 
     pub fn cache_flush(&self) {
@@ -31,13 +31,15 @@ impl Default for GTX1080 {
 
 impl GTX1080 {
     pub fn new(config: Arc<config::GPU>) -> Self {
-        let interconn = Arc::new(ic::ToyInterconnect::new(
+        let interconn = Arc::new(ic::ToyInterconnect::with_capacity(
             config.num_simt_clusters,
             config.total_sub_partitions(),
+            Some(config.num_cluster_ejection_buffer_size),
         ));
         let mut sim = MockSimulator::new(interconn, Arc::clone(&config));
 
         sim.log_after_cycle = config.log_after_cycle;
+        sim.reset_stats_at_cycle = config.reset_stats_at_cycle;
         Self { config, sim }
     }
 }
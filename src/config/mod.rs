@@ -38,19 +38,18 @@ pub enum CacheKind {
     Sector, // S
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct L2DCache {
     pub inner: Arc<Cache>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct L1DCache {
     /// L1 Hit Latency
     pub l1_latency: usize, // 1
     pub l1_hit_latency: usize, // 80
     /// l1 banks hashing function
-    // pub l1_banks_hashing_function: Box<dyn cache::set_index::SetIndexer>, // 0
-    // pub l1_banks_hashing_function: CacheSetIndexFunc, // 0
+    pub l1_banks_hashing_function: CacheSetIndexFunc, // 0
     /// l1 banks byte interleaving granularity
     pub l1_banks_byte_interleaving: usize, // 32
     /// The number of L1 cache banks
@@ -96,15 +95,24 @@ impl L1DCache {
 }
 
 /// `CacheConfig` configures a generic cache
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Cache {
     pub kind: CacheKind,
     pub num_sets: usize,
     pub line_size: u32,
+    /// Sector size in bytes.
+    ///
+    /// A sectored cache line is split into `line_size / sector_size`
+    /// sectors that are fetched and tracked individually. The line size
+    /// must be a multiple of the sector size, and a line must not have
+    /// more than [`mem_sub_partition::NUM_SECTORS`] sectors.
+    pub sector_size: u32,
     pub associativity: usize,
 
     pub replacement_policy: cache::config::ReplacementPolicy,
     pub write_policy: cache::config::WritePolicy,
+    /// Per-access-kind overrides of the write policy.
+    pub write_policy_overrides: cache::config::WritePolicyOverrides,
     pub allocate_policy: cache::config::AllocatePolicy,
     pub write_allocate_policy: cache::config::WriteAllocatePolicy,
     // pub set_index_function: CacheSetIndexFunc,
@@ -118,6 +126,13 @@ pub struct Cache {
 
     /// L1D write ratio
     pub l1_cache_write_ratio_percent: usize, // 0
+    /// Record per-set access and miss counts for heatmap export.
+    pub record_access_heatmap: bool,
+    /// Optional cache line compression model.
+    ///
+    /// Compression scales the effective capacity of the cache by the
+    /// expected compression ratio of the algorithm.
+    pub compression: Option<cache::compression::Algorithm>,
 
     // private (should be used with accessor methods)
     pub data_port_width: Option<usize>,
@@ -209,12 +224,26 @@ impl Cache {
     #[must_use]
     pub fn atom_size(&self) -> u32 {
         if self.kind == CacheKind::Sector {
-            mem_sub_partition::SECTOR_SIZE
+            self.sector_size
         } else {
             self.line_size
         }
     }
 
+    /// Number of sectors per cache line.
+    ///
+    /// Non-sectored caches are treated as having a single sector
+    /// spanning the full line.
+    // #[inline]
+    #[must_use]
+    pub fn num_sectors(&self) -> usize {
+        if self.kind == CacheKind::Sector {
+            (self.line_size / self.sector_size) as usize
+        } else {
+            1
+        }
+    }
+
     // // do not use enabled but options
     // // #[inline]
     // #[must_use]
@@ -259,7 +288,12 @@ impl Cache {
     // #[inline]
     #[must_use]
     pub fn mshr_addr(&self, addr: address) -> address {
-        addr & !u64::from(self.line_size - 1)
+        if self.mshr_kind.is_sectored() {
+            // misses are tracked at sector granularity
+            addr & !u64::from(self.sector_size - 1)
+        } else {
+            addr & !u64::from(self.line_size - 1)
+        }
     }
 
     // // detect invalid configuration
@@ -301,7 +335,7 @@ impl Cache {
 /// DRAM Timing Options
 ///
 /// {nbk:tCCD:tRRD:tRCD:tRAS:tRP:tRC:CL:WL:tCDLR:tWR:nbkgrp:tCCDL:tRTPL}
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TimingOptions {
     pub num_banks: usize,
     // pub t_ccd: usize,
@@ -331,8 +365,121 @@ pub enum Parallelization {
     },
 }
 
+/// Arbitration policy at the memory sub partition input.
+///
+/// Selects the next request that enters the L2 out of the ready
+/// requests in the sub partition input queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemoryArbitration {
+    /// Requests enter the L2 in arrival order.
+    #[default]
+    Fifo,
+    /// Demand reads are prioritized over writes and writebacks.
+    ReadFirst {
+        /// Number of ready writes that triggers a write drain.
+        ///
+        /// Without a drain threshold, a steady read stream would defer
+        /// writes until the input queue fills up.
+        write_drain_threshold: usize,
+        /// Maximum number of cycles a ready request may be deferred.
+        ///
+        /// The oldest request wins the arbitration once it exceeds this
+        /// age, which prevents starvation of any class.
+        max_age: u64,
+    },
+}
+
+/// Ordering enforced when completed instructions write back and retire.
+///
+/// Hardware generations differ in how strictly instruction completion
+/// is ordered: relaxing or enforcing the order changes when scoreboard
+/// registers are released and therefore when dependent instructions can
+/// issue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WritebackOrdering {
+    /// Instructions retire as soon as they complete, possibly out of
+    /// program order.
+    #[default]
+    Relaxed,
+    /// Instructions of a warp retire in program order per functional
+    /// unit category.
+    ///
+    /// A completed instruction waits for older instructions of the same
+    /// warp that issued to the same functional unit category to retire
+    /// first, but may overtake instructions of other categories.
+    PerUnitInOrder,
+    /// Instructions of a warp retire in program order.
+    ///
+    /// A completed instruction waits for all older instructions of the
+    /// same warp to retire first.
+    InOrder,
+}
+
+/// Tie break among equally prioritized warps within a scheduler.
+///
+/// The warp scheduler priority function (e.g. greedy-then-oldest)
+/// leaves warps with the same priority in an unspecified relative
+/// order.  How these ties are broken is a second-order scheduling
+/// choice that still measurably changes cache behavior, since it
+/// decides which warp streams through the cache first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SchedulerTieBreak {
+    /// The warp with the lowest supervised warp slot wins.
+    ///
+    /// This matches the ordering of accelsim and must be used in
+    /// accelsim compat mode.
+    #[default]
+    LowestWarpId,
+    /// The first tied warp after the last issued warp wins.
+    ///
+    /// Distributes issue slots round-robin over tied warps instead of
+    /// favoring low warp ids.
+    RoundRobin,
+    /// Ties are broken by a seeded pseudo-random permutation that
+    /// changes every scheduler cycle.
+    ///
+    /// Deterministic for a fixed seed.
+    Random { seed: u64 },
+}
+
+/// Address bits used to compute the L2 set index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum L2SetIndexing {
+    /// Index with the partition-relative address.
+    ///
+    /// The partition and sub partition bits are excluded from the set
+    /// index, since they are constant within one L2 slice.
+    #[default]
+    PartitionAddress,
+    /// Index with the raw address.
+    ///
+    /// The bits that select the partition also contribute to the set
+    /// index. With multiple partitions, each L2 slice only ever sees
+    /// addresses with fixed partition bits and camps on a fraction of
+    /// its sets.
+    RawAddress,
+    /// Select the indexing based on the partition count.
+    ///
+    /// The partition and sub partition bits are excluded whenever there
+    /// is more than one sub partition.
+    Automatic,
+}
+
+/// Sharing granularity of the L1 data cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum CacheSharing {
+    /// Each core has a private L1 data cache.
+    #[default]
+    Private,
+    /// All cores in a cluster share one L1 data cache.
+    ///
+    /// The shared cache's ports are arbitrated between the sharing
+    /// cores (see [`GPU::l1_cache_shared_ports`]).
+    Cluster,
+}
+
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClockFrequencies {
     pub core_freq_hz: u64,
     pub interconn_freq_hz: u64,
@@ -367,11 +514,553 @@ impl ClockFrequenciesBuilder {
     }
 }
 
+/// When a point of the DVFS schedule takes effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DvfsTrigger {
+    /// Switch frequencies when the given cycle is reached.
+    Cycle(u64),
+    /// Switch frequencies when the kernel with the given id is launched.
+    KernelLaunch(u64),
+}
+
+/// A single point of a DVFS schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DvfsPoint {
+    pub trigger: DvfsTrigger,
+    pub clock_frequencies: ClockFrequencies,
+}
+
+/// Which kernels a boundary action applies to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum KernelMatcher {
+    /// The kernel with the given launch id.
+    LaunchId(u64),
+    /// Kernels whose unmangled name matches the regex.
+    NameRegex(String),
+}
+
+impl KernelMatcher {
+    pub fn matches(&self, launch: &trace_model::command::KernelLaunch) -> eyre::Result<bool> {
+        Ok(match self {
+            Self::LaunchId(id) => launch.id == *id,
+            Self::NameRegex(pattern) => {
+                regex::Regex::new(pattern)?.is_match(&launch.unmangled_name)
+            }
+        })
+    }
+}
+
+/// When a kernel boundary action is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KernelBoundary {
+    /// When the kernel is launched.
+    Launch,
+    /// When the kernel completes.
+    Completion,
+}
+
+/// The action applied at a kernel boundary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BoundaryAction {
+    /// Invalidate the L1 caches.
+    FlushL1,
+    /// Flush dirty lines from the L2 caches.
+    FlushL2,
+    /// Reset the accumulated statistics.
+    ResetStats,
+    /// Switch to the given clock frequencies.
+    SetClocks(ClockFrequencies),
+}
+
+/// An action applied at the boundary of selected kernels.
+///
+/// Unlike the global [`GPU::flush_l1_cache`] and [`GPU::flush_l2_cache`]
+/// flags, boundary actions apply only to the kernels selected by the
+/// matcher, e.g. flushing the caches around a specific kernel or
+/// resetting the stats once a warmup kernel completed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelBoundaryAction {
+    pub kernel: KernelMatcher,
+    pub boundary: KernelBoundary,
+    pub action: BoundaryAction,
+}
+
+/// An injected core failure (resilience experiments).
+///
+/// At the given cycle the core stops executing and its resident blocks
+/// are harvested: once the recovery penalty elapsed, they are re-issued
+/// from their first instruction on other cores. Instructions the blocks
+/// had already executed are counted as re-executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoreFailure {
+    /// Global core id of the failing core.
+    pub core_id: usize,
+    /// Cycle at which the core fails.
+    pub cycle: u64,
+    /// Cycles until the harvested blocks may restart on other cores.
+    pub recovery_penalty: u64,
+}
+
+/// Operand collector and register file configuration of an architecture.
+///
+/// Bundles the operand collector parameter block (collector units and
+/// in/out ports per operand collector class), the register file banking
+/// and the operand collector related pipeline widths, which must be
+/// kept consistent with each other. Use
+/// [`GPU::apply_operand_collector_preset`] to apply a preset and
+/// [`GPU::validate_operand_collector`] to check the result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperandCollectorPreset {
+    /// Use the specialized per-class collector sets in addition to the
+    /// generic set.
+    pub enable_specialized_operand_collector: bool,
+    /// Use the sub core model, where each scheduler owns an issue
+    /// register (requires the decode-to-collector widths to match the
+    /// scheduler count).
+    pub sub_core_model: bool,
+    pub num_units_sp: usize,
+    pub num_units_dp: usize,
+    pub num_units_sfu: usize,
+    pub num_units_int: usize,
+    pub num_units_tensor_core: usize,
+    pub num_units_mem: usize,
+    pub num_units_gen: usize,
+    pub num_in_ports_sp: usize,
+    pub num_in_ports_dp: usize,
+    pub num_in_ports_sfu: usize,
+    pub num_in_ports_int: usize,
+    pub num_in_ports_tensor_core: usize,
+    pub num_in_ports_mem: usize,
+    pub num_in_ports_gen: usize,
+    pub num_out_ports_sp: usize,
+    pub num_out_ports_dp: usize,
+    pub num_out_ports_sfu: usize,
+    pub num_out_ports_int: usize,
+    pub num_out_ports_tensor_core: usize,
+    pub num_out_ports_mem: usize,
+    pub num_out_ports_gen: usize,
+    pub num_reg_banks: usize,
+    pub reg_file_port_throughput: usize,
+    /// Widths of the decode-to-collector and collector-to-execute
+    /// pipeline stages.
+    pub pipeline_widths: Vec<(PipelineStage, usize)>,
+}
+
+impl OperandCollectorPreset {
+    /// GTX 1080 (Pascal): specialized collector sets per class.
+    #[must_use]
+    pub fn gtx1080() -> Self {
+        Self {
+            enable_specialized_operand_collector: true,
+            sub_core_model: false,
+            num_units_sp: 20,
+            num_units_dp: 0,
+            num_units_sfu: 4,
+            num_units_int: 0,
+            num_units_tensor_core: 0,
+            num_units_mem: 8,
+            num_units_gen: 0,
+            num_in_ports_sp: 4,
+            num_in_ports_dp: 0,
+            num_in_ports_sfu: 1,
+            num_in_ports_int: 0,
+            num_in_ports_tensor_core: 0,
+            num_in_ports_mem: 1,
+            num_in_ports_gen: 0,
+            num_out_ports_sp: 4,
+            num_out_ports_dp: 0,
+            num_out_ports_sfu: 1,
+            num_out_ports_int: 0,
+            num_out_ports_tensor_core: 0,
+            num_out_ports_mem: 1,
+            num_out_ports_gen: 0,
+            num_reg_banks: 32,
+            reg_file_port_throughput: 1,
+            pipeline_widths: vec![
+                (PipelineStage::ID_OC_SP, 4),
+                (PipelineStage::ID_OC_DP, 0),
+                (PipelineStage::ID_OC_INT, 0),
+                (PipelineStage::ID_OC_SFU, 1),
+                (PipelineStage::ID_OC_MEM, 1),
+                (PipelineStage::OC_EX_SP, 4),
+                (PipelineStage::OC_EX_DP, 0),
+                (PipelineStage::OC_EX_INT, 0),
+                (PipelineStage::OC_EX_SFU, 1),
+                (PipelineStage::OC_EX_MEM, 1),
+                (PipelineStage::ID_OC_TENSOR_CORE, 0),
+                (PipelineStage::OC_EX_TENSOR_CORE, 0),
+            ],
+        }
+    }
+
+    /// TITAN X (Pascal): one generic collector set sized for the four
+    /// warp schedulers and SIMD units.
+    ///
+    /// This is the configuration the defaults are tuned for.
+    #[must_use]
+    pub fn titanx() -> Self {
+        Self {
+            enable_specialized_operand_collector: false,
+            sub_core_model: true,
+            num_units_sp: 20,
+            num_units_dp: 0,
+            num_units_sfu: 4,
+            num_units_int: 0,
+            num_units_tensor_core: 4,
+            num_units_mem: 8,
+            num_units_gen: 8,
+            num_in_ports_sp: 4,
+            num_in_ports_dp: 0,
+            num_in_ports_sfu: 1,
+            num_in_ports_int: 0,
+            num_in_ports_tensor_core: 1,
+            num_in_ports_mem: 1,
+            num_in_ports_gen: 8,
+            num_out_ports_sp: 4,
+            num_out_ports_dp: 0,
+            num_out_ports_sfu: 1,
+            num_out_ports_int: 0,
+            num_out_ports_tensor_core: 1,
+            num_out_ports_mem: 1,
+            num_out_ports_gen: 8,
+            num_reg_banks: 16,
+            reg_file_port_throughput: 2,
+            pipeline_widths: vec![
+                (PipelineStage::ID_OC_SP, 4),
+                (PipelineStage::ID_OC_DP, 0),
+                (PipelineStage::ID_OC_INT, 0),
+                (PipelineStage::ID_OC_SFU, 4),
+                (PipelineStage::ID_OC_MEM, 4),
+                (PipelineStage::OC_EX_SP, 4),
+                (PipelineStage::OC_EX_DP, 0),
+                (PipelineStage::OC_EX_INT, 0),
+                (PipelineStage::OC_EX_SFU, 4),
+                (PipelineStage::OC_EX_MEM, 4),
+                (PipelineStage::ID_OC_TENSOR_CORE, 0),
+                (PipelineStage::OC_EX_TENSOR_CORE, 0),
+            ],
+        }
+    }
+
+    /// RTX 3070 (Ampere): one generic collector set with all pipeline
+    /// classes enabled.
+    #[must_use]
+    pub fn rtx3070() -> Self {
+        Self {
+            enable_specialized_operand_collector: false,
+            sub_core_model: false,
+            num_units_sp: 4,
+            num_units_dp: 0,
+            num_units_sfu: 4,
+            num_units_int: 0,
+            num_units_tensor_core: 4,
+            num_units_mem: 2,
+            num_units_gen: 8,
+            num_in_ports_sp: 1,
+            num_in_ports_dp: 0,
+            num_in_ports_sfu: 1,
+            num_in_ports_int: 0,
+            num_in_ports_tensor_core: 1,
+            num_in_ports_mem: 1,
+            num_in_ports_gen: 8,
+            num_out_ports_sp: 1,
+            num_out_ports_dp: 0,
+            num_out_ports_sfu: 1,
+            num_out_ports_int: 0,
+            num_out_ports_tensor_core: 1,
+            num_out_ports_mem: 1,
+            num_out_ports_gen: 8,
+            num_reg_banks: 8,
+            reg_file_port_throughput: 2,
+            pipeline_widths: vec![
+                (PipelineStage::ID_OC_SP, 4),
+                (PipelineStage::ID_OC_DP, 4),
+                (PipelineStage::ID_OC_INT, 4),
+                (PipelineStage::ID_OC_SFU, 4),
+                (PipelineStage::ID_OC_MEM, 4),
+                (PipelineStage::OC_EX_SP, 4),
+                (PipelineStage::OC_EX_DP, 4),
+                (PipelineStage::OC_EX_INT, 4),
+                (PipelineStage::OC_EX_SFU, 4),
+                (PipelineStage::OC_EX_MEM, 4),
+                (PipelineStage::ID_OC_TENSOR_CORE, 4),
+                (PipelineStage::OC_EX_TENSOR_CORE, 4),
+            ],
+        }
+    }
+
+    /// Look up a preset by architecture name.
+    pub fn from_name(name: &str) -> eyre::Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "gtx1080" => Ok(Self::gtx1080()),
+            "titanx" => Ok(Self::titanx()),
+            "rtx3070" => Ok(Self::rtx3070()),
+            other => Err(eyre::eyre!(
+                "unknown operand collector preset {other:?} (have gtx1080, titanx, rtx3070)"
+            )),
+        }
+    }
+}
+
+/// DRAM power/thermal throttling policy.
+///
+/// Models a thermally limited memory system: when the achieved DRAM
+/// bandwidth stays above `bandwidth_threshold` for a full measurement
+/// window, the DRAM clock frequency is scaled down by `frequency_scale`.
+/// Once the bandwidth of a window drops back below the threshold, the
+/// nominal frequency is restored.
+///
+/// The DRAM frequency only has an effect when
+/// [`GPU::simulate_clock_domains`] is enabled.
+/// Like cycle based DVFS points, throttling is only applied in serial
+/// simulation mode.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DramThrottling {
+    /// Fraction of peak DRAM bandwidth above which throttling kicks in.
+    pub bandwidth_threshold: f64,
+    /// Length of the measurement window in cycles.
+    pub window: u64,
+    /// Factor applied to the DRAM clock frequency while throttled.
+    pub frequency_scale: f64,
+}
+
+/// Energy weights for the instruction-level energy estimate.
+///
+/// All weights are in picojoules per event.
+/// The defaults are rough, technology-agnostic estimates: the estimate
+/// is meant for quick comparisons between configurations, not as a
+/// replacement for a full power model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyWeights {
+    /// Energy per executed thread instruction, by opcode category.
+    ///
+    /// Categories are keyed by their [`opcodes::ArchOp`] name,
+    /// e.g. `"SP_OP"` or `"DP_OP"`.
+    pub op_energy: HashMap<String, f64>,
+    /// Energy per executed thread instruction of a category without an
+    /// explicit weight.
+    pub default_op_energy: f64,
+    /// Energy per L1 cache access (instruction, constant, texture, data).
+    pub l1_access_energy: f64,
+    /// Energy per L2 cache access.
+    pub l2_access_energy: f64,
+    /// Energy per DRAM access.
+    pub dram_access_energy: f64,
+}
+
+impl Default for EnergyWeights {
+    fn default() -> Self {
+        let op_energy = [
+            (opcodes::ArchOp::INT_OP, 0.5),
+            (opcodes::ArchOp::ALU_OP, 0.5),
+            (opcodes::ArchOp::SP_OP, 1.0),
+            (opcodes::ArchOp::DP_OP, 4.0),
+            (opcodes::ArchOp::SFU_OP, 2.0),
+            (opcodes::ArchOp::LOAD_OP, 1.0),
+            (opcodes::ArchOp::STORE_OP, 1.0),
+        ]
+        .into_iter()
+        .map(|(op, energy)| (format!("{op:?}"), energy))
+        .collect();
+        Self {
+            op_energy,
+            default_op_energy: 1.0,
+            l1_access_energy: 25.0,
+            l2_access_energy: 50.0,
+            dram_access_energy: 640.0,
+        }
+    }
+}
+
+/// Addresses a named address range refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddressRangeSelector {
+    /// All addresses of the allocation with the given id.
+    Allocation { id: usize },
+    /// All addresses in `start..end`.
+    Range { start: address, end: address },
+}
+
+/// A named address range.
+///
+/// Cache and DRAM stats are additionally broken down per named range,
+/// such that the accesses to a single matrix or tensor can be isolated
+/// in the results.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedAddressRange {
+    pub name: String,
+    pub selector: AddressRangeSelector,
+}
+
+/// Pipeline visualization dump (pipeview) config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pipeview {
+    /// Cluster id of the observed core.
+    pub cluster_id: usize,
+    /// Core id (within its cluster) of the observed core.
+    pub core_id: usize,
+    /// File the per-cycle occupancy records are written to.
+    pub out_file: std::path::PathBuf,
+}
+
+/// DRAM request trace dump config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DramTrace {
+    /// File the DRAM requests are written to.
+    pub out_file: std::path::PathBuf,
+    /// Line format of the trace.
+    pub format: DramTraceFormat,
+}
+
+/// Line format of a DRAM request trace (see [`crate::dram_trace`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DramTraceFormat {
+    /// `<hex addr> <READ|WRITE> <cycle>` per line (DRAMsim3 stream trace).
+    Dramsim3,
+    /// `<hex addr> <R|W>` per line (Ramulator memory trace).
+    Ramulator,
+    /// CSV carrying the mapped chip, bank, row and column per request.
+    Csv,
+}
+
+impl DramTraceFormat {
+    pub fn from_name(name: &str) -> eyre::Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dramsim3" => Ok(Self::Dramsim3),
+            "ramulator" => Ok(Self::Ramulator),
+            "csv" => Ok(Self::Csv),
+            other => Err(eyre::eyre!(
+                "unknown DRAM trace format {other:?} (have dramsim3, ramulator, csv)"
+            )),
+        }
+    }
+}
+
+/// Occupancy of a single core (SM) for a given kernel resource usage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Occupancy {
+    /// Maximum resident blocks limited by the thread count per core.
+    pub by_thread_limit: usize,
+    /// Maximum resident blocks limited by shared memory usage.
+    pub by_shared_mem_limit: Option<usize>,
+    /// Maximum resident blocks limited by register usage.
+    pub by_register_limit: Option<usize>,
+}
+
+impl Occupancy {
+    /// Maximum number of resident blocks per core.
+    #[must_use]
+    pub fn blocks_per_core(&self) -> usize {
+        [
+            Some(self.by_thread_limit),
+            self.by_shared_mem_limit,
+            self.by_register_limit,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(usize::MAX)
+    }
+}
+
+impl NamedAddressRange {
+    #[must_use]
+    pub fn matches(&self, allocation_id: Option<usize>, addr: address) -> bool {
+        match self.selector {
+            AddressRangeSelector::Allocation { id } => allocation_id == Some(id),
+            AddressRangeSelector::Range { start, end } => (start..end).contains(&addr),
+        }
+    }
+}
+
+/// Configuration overrides for a group of clusters.
+///
+/// Cluster groups make the GPU heterogeneous: the clusters of a group
+/// are built from the base config with the overrides of the group
+/// applied (see [`GPU::apply_override`]), e.g. more schedulers per core
+/// or a different L1 size. Fields describing the shared topology
+/// (cluster and core counts, memory partitions) must not be overridden
+/// per group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClusterGroup {
+    /// Cluster ids the group applies to.
+    pub clusters: std::ops::Range<usize>,
+    /// Dotted-path `key=value` overrides (see [`GPU::apply_override`]).
+    pub overrides: Vec<String>,
+}
+
+/// A tenant in a multi-tenant simulation.
+///
+/// Tenants group the kernels of co-located applications by their CUDA
+/// stream or kernel launch id. The tenant priority is enforced at the
+/// block scheduler, the L2 sub partition input, and the DRAM issue
+/// arbitration: requests of a higher-priority tenant are served first.
+/// Priorities are strict and can starve lower-priority tenants.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tenant {
+    /// CUDA streams whose kernels belong to the tenant.
+    pub streams: Vec<u64>,
+    /// Kernel launch ids that belong to the tenant.
+    pub kernels: Vec<u64>,
+    /// Relative priority of the tenant (higher wins).
+    pub priority: usize,
+}
+
+/// An L2 residency window.
+///
+/// Cache lines holding addresses in `start..end` are persistent in the
+/// L2: they are skipped during replacement victim selection as long as
+/// the persistent lines occupy at most `max_ways_percent` of the ways
+/// of their set. This mirrors the set-aside L2 capacity configured
+/// through the CUDA `accessPolicyWindow` on A100 and later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L2ResidencyWindow {
+    /// First address of the window.
+    pub start: address,
+    /// One past the last address of the window.
+    pub end: address,
+    /// Maximum percentage of the ways of each L2 set that persistent
+    /// lines may occupy before they become evictable again.
+    ///
+    /// The bound keeps streaming accesses outside the window cacheable
+    /// and prevents a window larger than the L2 from locking up entire
+    /// sets.
+    pub max_ways_percent: usize,
+    /// Restrict the window to a single kernel launch id.
+    ///
+    /// Accesses of other kernels neither count towards the residency
+    /// stats nor respect the priority of persistent lines, which
+    /// approximates a per-kernel access policy window. `None` applies
+    /// the window to all kernels.
+    pub kernel_launch_id: Option<usize>,
+}
+
+impl L2ResidencyWindow {
+    #[must_use]
+    pub fn contains(&self, addr: address) -> bool {
+        (self.start..self.end).contains(&addr)
+    }
+
+    /// Whether the window applies to accesses of the given kernel.
+    #[must_use]
+    pub fn matches_kernel(&self, kernel_launch_id: Option<usize>) -> bool {
+        match self.kernel_launch_id {
+            Some(id) => kernel_launch_id == Some(id),
+            None => true,
+        }
+    }
+}
+
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GPU {
     /// Log after cycle
     pub log_after_cycle: Option<u64>,
+    /// Reset all collected statistics once this cycle is reached.
+    ///
+    /// Excludes a warmup phase from measurement without splitting the
+    /// simulation into multiple runs.
+    pub reset_stats_at_cycle: Option<u64>,
     /// Accelsim compatibility mode.
     ///
     /// This must be set when running lockstep tests.
@@ -381,17 +1070,100 @@ pub struct GPU {
     pub parallelization: Parallelization,
     /// Simulate memory instructions only
     pub memory_only: bool,
+    /// Latency in cycles after which non-memory instructions release
+    /// their scoreboard registers in memory-only mode.
+    ///
+    /// When set, compute instructions are kept in the trace and modeled
+    /// as scoreboard updates only, such that memory dependencies and
+    /// issue ordering are preserved while the functional units are still
+    /// skipped.
+    pub memory_only_compute_latency: Option<u64>,
+    /// Arbitration policy at the memory sub partition input.
+    pub memory_arbitration: MemoryArbitration,
+    /// Ordering enforced when completed instructions retire.
+    ///
+    /// When stricter than [`WritebackOrdering::Relaxed`], completed
+    /// instructions are held in a reorder buffer at the writeback stage
+    /// until all older instructions of the scope have retired.
+    pub writeback_ordering: WritebackOrdering,
+    /// Replay only memcopy commands (memory system standalone mode).
+    ///
+    /// Kernel launches are skipped entirely and only the memcopy
+    /// commands are replayed through the L2/DRAM path, regardless of
+    /// [`GPU::fill_l2_on_memcopy`] and the L2 prefetch threshold.
+    pub memcopy_only: bool,
+    /// Replay the host-side gaps between commands recorded in the trace.
+    ///
+    /// Traces captured with host timestamps (see
+    /// [`trace_model::command::KernelLaunch::host_timestamp_nanos`])
+    /// record when each command was issued by the CPU. Replaying the
+    /// gaps models CPU think-time between CUDA calls, such that the
+    /// reported cycles estimate end-to-end application time instead of
+    /// the sum of the kernel times. Has no effect on traces without
+    /// host timestamps.
+    pub host_gaps: bool,
+    /// Skip malformed trace records instead of failing.
+    ///
+    /// Skipped records are counted per kernel (see
+    /// [`stats::Sim::num_malformed_trace_records`]) and reported, such
+    /// that broken traces can still be replayed and inspected.
+    pub lenient_trace_loading: bool,
+    /// Device id to filter the trace for.
+    ///
+    /// Traces captured from applications using multiple devices contain
+    /// the command streams of all devices.
+    /// When set, only kernels that were traced on the given device are
+    /// simulated.
+    pub trace_device: Option<u32>,
     /// Simulate different clock domains of core, memory, and interconnect subsystems.
     pub simulate_clock_domains: bool,
     /// Simulation threads
     ///
     /// If no value is provided, the number of physical cores is used.
     pub simulation_threads: Option<usize>,
+    /// Seed for the nondeterministic parallel simulation mode.
+    ///
+    /// The seed controls the dispatch order of the core cycle tasks of
+    /// each wave. Together with [`GPU::pin_threads_to_clusters`] and a
+    /// single worker thread, runs with the same seed replay the same
+    /// interleaving exactly; with more threads the dispatch schedule is
+    /// still reproduced but thread timing can perturb the interleaving.
+    pub parallelization_seed: Option<u64>,
+    /// Pin worker threads to a fixed set of clusters.
+    ///
+    /// Instead of dispatching one task per core into the worker pool,
+    /// each worker thread simulates a fixed chunk of the clusters, such
+    /// that the thread to cluster mapping does not depend on work
+    /// stealing.
+    pub pin_threads_to_clusters: bool,
+    /// Abort the simulation when its resident memory exceeds this many bytes.
+    ///
+    /// The limit is enforced using samples of the jemalloc statistics, so it
+    /// requires the "jemalloc" feature and has no effect otherwise.
+    pub simulation_memory_limit_bytes: Option<u64>,
+    /// Abort the simulation when a kernel runs for more than this many cycles.
+    ///
+    /// Safety limit for persistent kernels, which run few blocks forever
+    /// and would otherwise never terminate the simulation.
+    pub max_kernel_cycles: Option<u64>,
     /// Deadlock check
     pub deadlock_check: bool,
     /// Deadlock check
     pub l2_prefetch_percent: Option<f32>,
+    /// Pipeline visualization dump (pipeview).
+    ///
+    /// When set, the selected core writes the occupancy of its pipeline
+    /// registers to the given file, one line per core cycle.
+    /// See [`crate::pipeview`].
+    pub pipeview: Option<Pipeview>,
+    /// DRAM request trace dump.
+    ///
+    /// When set, every request issued to DRAM is written to the given
+    /// file in a format consumable by an external DRAM simulator.
+    /// See [`crate::dram_trace`].
+    pub dram_trace: Option<DramTrace>,
 
+    #[serde(skip)]
     pub memory_controller_unit: std::sync::OnceLock<mcu::MemoryControllerUnit>,
     /// The SM number to pass to ptxas when getting register usage for
     /// computing GPU occupancy.
@@ -402,6 +1174,38 @@ pub struct GPU {
     pub warp_size: usize,
     /// Clock frequencies
     pub clock_frequencies: ClockFrequencies,
+    /// Dynamic voltage and frequency scaling (DVFS) schedule.
+    ///
+    /// Points are applied in order.
+    /// Cycle based points are only applied in serial simulation mode.
+    pub dvfs_schedule: Vec<DvfsPoint>,
+    /// DRAM power/thermal throttling policy.
+    pub dram_throttling: Option<DramThrottling>,
+    /// Energy weights for the instruction-level energy estimate.
+    pub energy_weights: EnergyWeights,
+    /// Named address ranges the cache and DRAM stats are additionally
+    /// broken down by.
+    ///
+    /// The range id used in the stats is the index into this list.
+    pub named_address_ranges: Vec<NamedAddressRange>,
+    /// Address range prioritized during L2 replacement (see
+    /// [`L2ResidencyWindow`]).
+    pub l2_residency_window: Option<L2ResidencyWindow>,
+    /// Per-group config overrides for heterogeneous clusters (see
+    /// [`ClusterGroup`]).
+    pub cluster_groups: Vec<ClusterGroup>,
+    /// Tenants of a multi-tenant simulation (see [`Tenant`]).
+    ///
+    /// Kernels not covered by any tenant run at the lowest priority.
+    pub tenants: Vec<Tenant>,
+    /// Actions applied at kernel boundaries (see
+    /// [`KernelBoundaryAction`]).
+    pub kernel_boundary_actions: Vec<KernelBoundaryAction>,
+    /// Injected core failures (see [`CoreFailure`]).
+    ///
+    /// Failures are applied in order and only in serial simulation
+    /// mode.
+    pub core_failures: Vec<CoreFailure>,
     /// per-shader read-only L1 texture cache config
     pub tex_cache_l1: Option<Arc<Cache>>,
     /// per-shader read-only L1 constant memory cache config
@@ -410,11 +1214,46 @@ pub struct GPU {
     pub inst_cache_l1: Option<Arc<Cache>>,
     /// per-shader L1 data cache config
     pub data_cache_l1: Option<Arc<L1DCache>>,
+    /// Sharing granularity of the L1 data cache.
+    pub l1_cache_sharing: CacheSharing,
+    /// Number of accesses admitted per cycle to a cluster-shared L1
+    /// data cache over all sharing cores.
+    ///
+    /// Only used when [`GPU::l1_cache_sharing`] is
+    /// [`CacheSharing::Cluster`].
+    pub l1_cache_shared_ports: usize,
+    /// Keep the L1 data caches coherent with a simple write-invalidate
+    /// protocol.
+    ///
+    /// A sharer directory at each L2 slice tracks which cores may hold
+    /// a line; a global write invalidates the copies of all other
+    /// sharers. Real GPUs do not keep their L1s coherent, hence this is
+    /// off by default and only intended for research.
+    pub l1_coherence: bool,
     /// unified banked L2 data cache config
     pub data_cache_l2: Option<Arc<L2DCache>>,
+    /// Address bits used to compute the L2 set index.
+    pub l2_set_indexing: L2SetIndexing,
 
     /// Shared memory latency
     pub shared_memory_latency: usize,
+    /// Dual-path issue in the load store unit.
+    ///
+    /// Shared-memory operations dispatch into their own issue path and
+    /// no longer contend with global and local operations for the
+    /// single dispatch register, as in recent architectures with a
+    /// separate MIO path. The paths stall independently and their
+    /// throughputs are set by [`Self::ldst_shared_path_width`] and
+    /// [`Self::ldst_global_path_width`].
+    pub ldst_dual_path_issue: bool,
+    /// Shared-memory bank-conflict waves processed per cycle.
+    ///
+    /// Only used with dual-path issue.
+    pub ldst_shared_path_width: usize,
+    /// Global/local memory accesses injected per cycle.
+    ///
+    /// Only used with dual-path issue.
+    pub ldst_global_path_width: usize,
     /// SP unit max latency
     pub max_sp_latency: usize,
     /// Int unit max latency
@@ -427,6 +1266,14 @@ pub struct GPU {
     pub global_mem_skip_l1_data_cache: bool,
     /// enable perfect memory mode (no cache miss)
     pub perfect_mem: bool,
+    /// Idealized scheduler mode for upper-bound analysis.
+    ///
+    /// Issues any ready warp without structural constraints: warps are
+    /// not limited to a single issuing warp per scheduler cycle, the
+    /// per-warp issue width, or the dual issue exec unit restriction.
+    /// Data dependencies (scoreboard) and memory latency still apply,
+    /// such that the resulting IPC is an upper bound for the kernel.
+    pub perfect_scheduler: bool,
     // -gpgpu_cache:dl1PrefL1                 none # per-shader L1 data cache config  {<nsets>:<bsize>:<assoc>,<rep>:<wr>:<alloc>:<wr_alloc>,<mshr>:<N>:<merge>,<mq> | none}
     // -gpgpu_cache:dl1PrefShared                 none # per-shader L1 data cache config  {<nsets>:<bsize>:<assoc>,<rep>:<wr>:<alloc>:<wr_alloc>,<mshr>:<N>:<merge>,<mq> | none}
     /// Number of registers per shader core.
@@ -447,6 +1294,15 @@ pub struct GPU {
     pub num_cluster_ejection_buffer_size: usize, // 8
     /// number of response packets in ld/st unit ejection buffer
     pub num_ldst_response_buffer_size: usize, //  2
+    /// Return path bandwidth from the memory side into a core, in
+    /// 32 byte sectors per cycle.
+    ///
+    /// A response occupies the return path for its data size in
+    /// sectors before the next response can be forwarded into the
+    /// LD/ST unit; larger responses therefore drain over multiple
+    /// cycles and expose read-bandwidth limits. `None` leaves
+    /// response draining unconstrained.
+    pub ldst_return_bandwidth_sectors: Option<u64>,
     /// Size of shared memory per thread block or CTA (default 48kB)
     pub shared_memory_per_block: usize, // 49152
     /// Size of shared memory per shader core (default 16kB)
@@ -544,11 +1400,11 @@ pub struct GPU {
     // ID_OC_SP,ID_OC_DP,ID_OC_INT,ID_OC_SFU,ID_OC_MEM,OC_EX_SP,OC_EX_DP,
     // OC_EX_INT,OC_EX_SFU,OC_EX_MEM,EX_WB,ID_OC_TENSOR_CORE,OC_EX_TENSOR_CORE
     //
-    pub pipeline_widths: HashMap<PipelineStage, usize>, // 4,0,0,1,1,4,0,0,1,1,6
+    pub pipeline_widths: indexmap::IndexMap<PipelineStage, usize>, // 4,0,0,1,1,4,0,0,1,1,6
     /// Number of SP units
-    pub num_sp_units: usize, //
+    pub num_sp_units: usize,  //
     /// Number of DP units
-    pub num_dp_units: usize, // 0
+    pub num_dp_units: usize,  // 0
     /// Number of INT units
     pub num_int_units: usize, // 0
 
@@ -560,6 +1416,8 @@ pub struct GPU {
     pub num_tensor_core_units: usize, // 0
     /// Scheduler configuration: < lrr | gto | two_level_active > If two_level_active:<num_active_warps>:<inner_prioritization>:<outer_prioritization>For complete list of prioritization values see shader.h enum scheduler_prioritization_typeDefault: gto
     pub scheduler: CoreSchedulerKind, // gto
+    /// Tie break among equally prioritized warps within a scheduler.
+    pub scheduler_tie_break: SchedulerTieBreak,
     /// Support concurrent kernels on a SM (default = disabled)
     pub concurrent_kernel_sm: bool, // 0
     /// perfect inst and const cache mode, so all inst and const hits in the cache(default = disabled)
@@ -570,6 +1428,14 @@ pub struct GPU {
     pub reg_file_port_throughput: usize, // 1
     /// Fill the L2 cache on memcpy
     pub fill_l2_on_memcopy: bool, // true
+    /// Number of DMA copy engines.
+    ///
+    /// Copies occupy a copy engine for their modeled duration. Copies
+    /// on non-default streams overlap with kernels and with each other
+    /// subject to engine availability; a copy on the default stream
+    /// synchronizes with all outstanding work (see
+    /// [`trace_model::command::MemcpyHtoD::stream_id`]).
+    pub num_copy_engines: usize, // 2
     /// simple_dram_model with fixed latency and BW
     // pub simple_dram_model: bool, // 0
     /// DRAM scheduler kind. 0 = fifo, 1 = FR-FCFS (default)
@@ -607,8 +1473,30 @@ pub struct GPU {
     pub dram_timing_options: TimingOptions,
     /// ROP queue latency (default 85)
     pub l2_rop_latency: u64, // 220
+    /// L2 hit latency
+    ///
+    /// Cycles from the tag lookup of an L2 hit until its reply enters
+    /// the L2-to-interconnect queue. Zero keeps the legacy behavior
+    /// where the hit latency is shaped by [`GPU::l2_rop_latency`] and
+    /// queue delays alone.
+    pub l2_hit_latency: u64,
+    /// L2 tag lookup latency
+    ///
+    /// Pipeline overhead of the L2 tag lookup, applied to hits on top
+    /// of [`GPU::l2_hit_latency`] and to misses before they proceed to
+    /// DRAM.
+    pub l2_tag_lookup_latency: u64,
     /// DRAM latency (default 30)
     pub dram_latency: usize, // 100
+    /// Estimate the DRAM latency with an M/D/1 queueing model.
+    ///
+    /// Instead of delaying each request by the fixed
+    /// [`GPU::dram_latency`], the latency is estimated as the fixed
+    /// access latency plus the expected M/D/1 queueing delay for the
+    /// arrival rate measured so far (see [`crate::dram::LatencyEstimator`]).
+    /// The estimator is also updated when disabled, such that the
+    /// estimated latency can be calibrated against the detailed model.
+    pub dram_latency_estimate: bool,
     /// dual_bus_interface (default = 0)
     pub dram_dual_bus_interface: bool, // 0
     /// dram_bnk_indexing_policy
@@ -659,6 +1547,9 @@ pub struct GPU {
     pub trace_opcode_latency_initiation_sfu: (usize, usize), // 4, 1
     /// Opcode latencies and initiation for tensor in trace driven mode (latency,initiation)
     pub trace_opcode_latency_initiation_tensor: (usize, usize), // 4, 1
+    /// Opcode latencies and initiation for warp-level primitives
+    /// (shuffle, vote, ballot) in trace driven mode (latency,initiation)
+    pub trace_opcode_latency_initiation_shfl: (usize, usize),
 }
 
 pub static WORD_SIZE: address = 4;
@@ -718,29 +1609,30 @@ impl GPU {
         self.dram_burst_length * self.dram_buswidth * self.num_dram_chips_per_memory_controller
     }
 
-    /// Compute maximum number of blocks that a kernel can run
+    /// Compute the occupancy of a single core (SM) for a kernel.
     ///
-    /// Depends on the following constraints:
-    /// -
-    pub fn max_blocks(&self, kernel: &dyn Kernel) -> eyre::Result<usize> {
-        let threads_per_block = kernel.config().threads_per_block();
-        let threads_per_block = pad_to_multiple(threads_per_block, self.warp_size);
+    /// Unlike [`GPU::max_blocks`], this does not require a trace: the
+    /// limits are derived from the kernel resource usage alone and are
+    /// not capped by the launch grid.
+    #[must_use]
+    pub fn occupancy(
+        &self,
+        threads_per_block: usize,
+        num_registers: usize,
+        shared_mem_bytes: usize,
+    ) -> Occupancy {
+        // zero-thread launches occur in filtered traces: avoid dividing
+        // by zero and let the (empty) kernel complete without blocks
+        let threads_per_block = pad_to_multiple(threads_per_block.max(1), self.warp_size);
         // limit by n_threads/shader
         let by_thread_limit = self.max_threads_per_core / threads_per_block;
 
         // limit by shmem/shader
-        let by_shared_mem_limit = if kernel.config().shared_mem_bytes > 0 {
-            Some(self.shared_memory_size as usize / kernel.config().shared_mem_bytes as usize)
-        } else {
-            None
-        };
+        let by_shared_mem_limit = (self.shared_memory_size as usize).checked_div(shared_mem_bytes);
 
         // limit by register count, rounded up to multiple of 4.
-        let by_register_limit = if kernel.config().num_registers > 0 {
-            Some(
-                self.shader_registers
-                    / (threads_per_block * ((kernel.config().num_registers + 3) & !3) as usize),
-            )
+        let by_register_limit = if num_registers > 0 {
+            Some(self.shader_registers / (threads_per_block * ((num_registers + 3) & !3)))
         } else {
             None
         };
@@ -748,16 +1640,27 @@ impl GPU {
         // limit by CTA
         // let _by_block_limit = self.max_concurrent_blocks_per_core;
 
-        // find the minimum
-        let mut limit = [
-            Some(by_thread_limit),
+        Occupancy {
+            by_thread_limit,
             by_shared_mem_limit,
             by_register_limit,
-        ]
-        .into_iter()
-        .flatten()
-        .min()
-        .unwrap_or(usize::MAX);
+        }
+    }
+
+    /// Compute maximum number of blocks that a kernel can run
+    ///
+    /// Depends on the following constraints:
+    /// -
+    pub fn max_blocks(&self, kernel: &dyn Kernel) -> eyre::Result<usize> {
+        let launch = kernel.config();
+        let occupancy = self.occupancy(
+            launch.threads_per_block(),
+            launch.occupancy_registers() as usize,
+            launch.shared_mem_bytes as usize,
+        );
+
+        // find the minimum
+        let mut limit = occupancy.blocks_per_core();
         // result = gs_min2(result, result_shmem);
         // result = gs_min2(result, result_regs);
         // result = gs_min2(result, result_cta);
@@ -835,6 +1738,206 @@ impl GPU {
         Ok(limit)
     }
 
+    /// Apply a generic `key=value` config override.
+    ///
+    /// The key is a dotted path into the serialized config, e.g.
+    /// `num_schedulers_per_core=4` or `data_cache_l2.inner.num_sets=128`,
+    /// such that sweeps can vary any field without a dedicated CLI flag.
+    /// The value is parsed as JSON and falls back to a plain string,
+    /// so enum variants can be given without quotes
+    /// (e.g. `scheduler=LRR`).
+    pub fn apply_override(&mut self, assignment: &str) -> eyre::Result<()> {
+        let (path, value) = assignment
+            .split_once('=')
+            .ok_or(eyre::eyre!("expected key=value, got {assignment:?}"))?;
+
+        let mut serialized = serde_json::to_value(&*self)?;
+        let mut current = &mut serialized;
+        for segment in path.split('.') {
+            current = match current {
+                serde_json::Value::Object(fields) => fields
+                    .get_mut(segment)
+                    .ok_or(eyre::eyre!("unknown config field {segment:?} in {path:?}"))?,
+                serde_json::Value::Array(elements) => {
+                    let idx: usize = segment.parse()?;
+                    elements
+                        .get_mut(idx)
+                        .ok_or(eyre::eyre!("index {idx} out of bounds in {path:?}"))?
+                }
+                other => {
+                    eyre::bail!("config field {segment:?} in {path:?}: cannot index into {other}")
+                }
+            };
+        }
+        *current = serde_json::from_str(value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+        *self = serde_json::from_value(serialized)?;
+        Ok(())
+    }
+
+    /// Apply an operand collector and register file preset.
+    pub fn apply_operand_collector_preset(&mut self, preset: &OperandCollectorPreset) {
+        self.enable_specialized_operand_collector = preset.enable_specialized_operand_collector;
+        self.sub_core_model = preset.sub_core_model;
+        self.operand_collector_num_units_sp = preset.num_units_sp;
+        self.operand_collector_num_units_dp = preset.num_units_dp;
+        self.operand_collector_num_units_sfu = preset.num_units_sfu;
+        self.operand_collector_num_units_int = preset.num_units_int;
+        self.operand_collector_num_units_tensor_core = preset.num_units_tensor_core;
+        self.operand_collector_num_units_mem = preset.num_units_mem;
+        self.operand_collector_num_units_gen = preset.num_units_gen;
+        self.operand_collector_num_in_ports_sp = preset.num_in_ports_sp;
+        self.operand_collector_num_in_ports_dp = preset.num_in_ports_dp;
+        self.operand_collector_num_in_ports_sfu = preset.num_in_ports_sfu;
+        self.operand_collector_num_in_ports_int = preset.num_in_ports_int;
+        self.operand_collector_num_in_ports_tensor_core = preset.num_in_ports_tensor_core;
+        self.operand_collector_num_in_ports_mem = preset.num_in_ports_mem;
+        self.operand_collector_num_in_ports_gen = preset.num_in_ports_gen;
+        self.operand_collector_num_out_ports_sp = preset.num_out_ports_sp;
+        self.operand_collector_num_out_ports_dp = preset.num_out_ports_dp;
+        self.operand_collector_num_out_ports_sfu = preset.num_out_ports_sfu;
+        self.operand_collector_num_out_ports_int = preset.num_out_ports_int;
+        self.operand_collector_num_out_ports_tensor_core = preset.num_out_ports_tensor_core;
+        self.operand_collector_num_out_ports_mem = preset.num_out_ports_mem;
+        self.operand_collector_num_out_ports_gen = preset.num_out_ports_gen;
+        self.num_reg_banks = preset.num_reg_banks;
+        self.reg_file_port_throughput = preset.reg_file_port_throughput;
+        for (stage, width) in &preset.pipeline_widths {
+            self.pipeline_widths.insert(*stage, *width);
+        }
+    }
+
+    /// Validate that the operand collector ports cover the configured
+    /// pipeline widths.
+    ///
+    /// Each decode-to-collector stage with a nonzero width needs at
+    /// least as many collector in ports as its width, and each
+    /// collector-to-execute stage as many out ports. A stage with
+    /// fewer ports silently throttles the pipeline below its
+    /// configured width, which is easy to miss when hand-setting the
+    /// many operand collector fields.
+    pub fn validate_operand_collector(&self) -> eyre::Result<()> {
+        let classes = [
+            (
+                PipelineStage::ID_OC_SP,
+                PipelineStage::OC_EX_SP,
+                self.operand_collector_num_in_ports_sp,
+                self.operand_collector_num_out_ports_sp,
+            ),
+            (
+                PipelineStage::ID_OC_DP,
+                PipelineStage::OC_EX_DP,
+                self.operand_collector_num_in_ports_dp,
+                self.operand_collector_num_out_ports_dp,
+            ),
+            (
+                PipelineStage::ID_OC_SFU,
+                PipelineStage::OC_EX_SFU,
+                self.operand_collector_num_in_ports_sfu,
+                self.operand_collector_num_out_ports_sfu,
+            ),
+            (
+                PipelineStage::ID_OC_INT,
+                PipelineStage::OC_EX_INT,
+                self.operand_collector_num_in_ports_int,
+                self.operand_collector_num_out_ports_int,
+            ),
+            (
+                PipelineStage::ID_OC_TENSOR_CORE,
+                PipelineStage::OC_EX_TENSOR_CORE,
+                self.operand_collector_num_in_ports_tensor_core,
+                self.operand_collector_num_out_ports_tensor_core,
+            ),
+            (
+                PipelineStage::ID_OC_MEM,
+                PipelineStage::OC_EX_MEM,
+                self.operand_collector_num_in_ports_mem,
+                self.operand_collector_num_out_ports_mem,
+            ),
+        ];
+        for (in_stage, out_stage, class_in_ports, class_out_ports) in classes {
+            // the generic collector set serves all classes
+            let mut in_ports = self.operand_collector_num_in_ports_gen;
+            let mut out_ports = self.operand_collector_num_out_ports_gen;
+            if self.enable_specialized_operand_collector {
+                in_ports += class_in_ports;
+                out_ports += class_out_ports;
+            }
+            let in_width = self.pipeline_widths.get(&in_stage).copied().unwrap_or(0);
+            let out_width = self.pipeline_widths.get(&out_stage).copied().unwrap_or(0);
+            if in_width > in_ports {
+                eyre::bail!(
+                    "pipeline width of {:?} is {} but only {} operand collector in ports serve it",
+                    in_stage,
+                    in_width,
+                    in_ports,
+                );
+            }
+            if out_width > out_ports {
+                eyre::bail!(
+                    "pipeline width of {:?} is {} but only {} operand collector out ports serve it",
+                    out_stage,
+                    out_width,
+                    out_ports,
+                );
+            }
+        }
+        if self.sub_core_model {
+            // in the sub core model, each scheduler owns an issue register
+            for stage in [
+                PipelineStage::ID_OC_SP,
+                PipelineStage::ID_OC_SFU,
+                PipelineStage::ID_OC_MEM,
+            ] {
+                let width = self.pipeline_widths.get(&stage).copied().unwrap_or(0);
+                if width != self.num_schedulers_per_core {
+                    eyre::bail!(
+                        "sub core model requires the pipeline width of {:?} to match the {} schedulers per core, got {}",
+                        stage,
+                        self.num_schedulers_per_core,
+                        width,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The effective config of a cluster.
+    ///
+    /// Applies the overrides of all cluster groups containing the
+    /// cluster to the base config. Clusters not covered by any group
+    /// share the base config.
+    pub fn cluster_config(self: &Arc<Self>, cluster_id: usize) -> eyre::Result<Arc<Self>> {
+        let groups: Vec<_> = self
+            .cluster_groups
+            .iter()
+            .filter(|group| group.clusters.contains(&cluster_id))
+            .collect();
+        if groups.is_empty() {
+            return Ok(Arc::clone(self));
+        }
+        let mut config = (**self).clone();
+        for group in groups {
+            for assignment in &group.overrides {
+                config.apply_override(assignment)?;
+            }
+        }
+        Ok(Arc::new(config))
+    }
+
+    /// The tenant a kernel launch belongs to.
+    ///
+    /// A launch belongs to the first tenant listing its stream or its
+    /// kernel launch id. Returns the index into [`GPU::tenants`], or
+    /// `None` when no tenant covers the launch.
+    #[must_use]
+    pub fn tenant_for_launch(&self, launch: &trace_model::command::KernelLaunch) -> Option<usize> {
+        self.tenants.iter().position(|tenant| {
+            tenant.streams.contains(&launch.stream_id) || tenant.kernels.contains(&launch.id)
+        })
+    }
+
     pub fn get_latencies(&self, arch_op_category: opcodes::ArchOp) -> (usize, usize) {
         use opcodes::ArchOp;
 
@@ -865,6 +1968,18 @@ impl GPU {
             ArchOp::TENSOR_CORE_OP => {
                 (latency, initiation_interval) = self.trace_opcode_latency_initiation_tensor;
             }
+            ArchOp::SPECIALIZED_UNIT_1_OP
+            | ArchOp::SPECIALIZED_UNIT_2_OP
+            | ArchOp::SPECIALIZED_UNIT_3_OP
+            | ArchOp::SPECIALIZED_UNIT_4_OP
+            | ArchOp::SPECIALIZED_UNIT_5_OP
+            | ArchOp::SPECIALIZED_UNIT_6_OP
+            | ArchOp::SPECIALIZED_UNIT_7_OP
+            | ArchOp::SPECIALIZED_UNIT_8_OP => {
+                crate::fidelity::approximated(
+                    "specialized unit op defaults to single cycle latency",
+                );
+            }
             _ => {}
         }
 
@@ -881,7 +1996,7 @@ impl GPU {
 }
 
 /// Cache set indexing function kind.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CacheSetIndexFunc {
     FERMI_HASH_SET_FUNCTION, // H
     HASH_IPOLY_FUNCTION,     // P
@@ -986,21 +2101,46 @@ impl GPU {
     pub fn total_sub_partitions(&self) -> usize {
         self.num_memory_controllers * self.num_sub_partitions_per_memory_controller
     }
+
+    /// Whether the L2 set index is computed from the partition-relative
+    /// address (see [`L2SetIndexing`]).
+    #[must_use]
+    pub fn l2_partition_address_indexing(&self) -> bool {
+        match self.l2_set_indexing {
+            L2SetIndexing::PartitionAddress => true,
+            L2SetIndexing::RawAddress => false,
+            L2SetIndexing::Automatic => self.total_sub_partitions() > 1,
+        }
+    }
 }
 
 impl Default for GPU {
     fn default() -> Self {
         Self {
             log_after_cycle: None,
+            reset_stats_at_cycle: None,
             parallelization: Parallelization::Serial,
             memory_only: false,
+            memory_only_compute_latency: None,
+            memory_arbitration: MemoryArbitration::default(),
+            writeback_ordering: WritebackOrdering::default(),
+            memcopy_only: false,
+            host_gaps: true,
+            lenient_trace_loading: false,
+            trace_device: None,
             accelsim_compat: false,
             simulate_clock_domains: false,
             simulation_threads: None,
+            parallelization_seed: None,
+            pin_threads_to_clusters: false,
+            simulation_memory_limit_bytes: None,
+            max_kernel_cycles: None,
             deadlock_check: false,
             // l2_prefetch_percent: None, // for TitanX
             l2_prefetch_percent: Some(90.0), // for TitanX
             // l2_prefetch_percent: 25.0, // for GTX 1080
+            pipeview: None,
+            dram_trace: None,
             memory_controller_unit: std::sync::OnceLock::new(),
             occupancy_sm_number: 60,
             max_threads_per_core: 2048,
@@ -1016,6 +2156,15 @@ impl Default for GPU {
                 // dram_freq_hz: 1251 * MHz, // GTX1080
             }
             .build(),
+            dvfs_schedule: Vec::new(),
+            dram_throttling: None,
+            energy_weights: EnergyWeights::default(),
+            named_address_ranges: Vec::new(),
+            l2_residency_window: None,
+            cluster_groups: Vec::new(),
+            tenants: Vec::new(),
+            kernel_boundary_actions: Vec::new(),
+            core_failures: Vec::new(),
             // N:16:128:24,L:R:m:N:L,F:128:4,128:2
             // {<nsets>:<bsize>:<assoc>,<rep>:<wr>:<alloc>:<wr_alloc>,<mshr>:<N>:<merge>,<mq>:<rf>}
             tex_cache_l1: Some(Arc::new(Cache {
@@ -1023,9 +2172,11 @@ impl Default for GPU {
                 kind: CacheKind::Normal,
                 num_sets: 4, // 16,
                 line_size: 128,
+                sector_size: mem_sub_partition::SECTOR_SIZE,
                 associativity: 48, // 24,
                 replacement_policy: cache::config::ReplacementPolicy::LRU,
                 write_policy: cache::config::WritePolicy::READ_ONLY,
+                write_policy_overrides: cache::config::WritePolicyOverrides::default(),
                 allocate_policy: cache::config::AllocatePolicy::ON_MISS,
                 write_allocate_policy: cache::config::WriteAllocatePolicy::NO_WRITE_ALLOCATE,
                 // set_index_function: CacheSetIndexFunc::LINEAR_SET_FUNCTION,
@@ -1036,6 +2187,8 @@ impl Default for GPU {
                 miss_queue_size: 128,
                 result_fifo_entries: Some(2),
                 l1_cache_write_ratio_percent: 0,
+                record_access_heatmap: false,
+                compression: None,
                 data_port_width: None,
             })),
             // N:128:64:2,L:R:f:N:L,A:2:64,4
@@ -1045,9 +2198,11 @@ impl Default for GPU {
                 kind: CacheKind::Normal,
                 num_sets: 128,
                 line_size: 64,
+                sector_size: mem_sub_partition::SECTOR_SIZE,
                 associativity: 2,
                 replacement_policy: cache::config::ReplacementPolicy::LRU,
                 write_policy: cache::config::WritePolicy::READ_ONLY,
+                write_policy_overrides: cache::config::WritePolicyOverrides::default(),
                 allocate_policy: cache::config::AllocatePolicy::ON_FILL,
                 write_allocate_policy: cache::config::WriteAllocatePolicy::NO_WRITE_ALLOCATE,
                 // set_index_function: CacheSetIndexFunc::LINEAR_SET_FUNCTION,
@@ -1058,6 +2213,8 @@ impl Default for GPU {
                 miss_queue_size: 4,
                 result_fifo_entries: None,
                 l1_cache_write_ratio_percent: 0,
+                record_access_heatmap: false,
+                compression: None,
                 data_port_width: None,
             })),
             // N:8:128:4,L:R:f:N:L,A:2:48,4
@@ -1070,9 +2227,11 @@ impl Default for GPU {
                 kind: CacheKind::Normal,
                 num_sets: 8,
                 line_size: 256,
+                sector_size: mem_sub_partition::SECTOR_SIZE,
                 associativity: 4,
                 replacement_policy: cache::config::ReplacementPolicy::LRU,
                 write_policy: cache::config::WritePolicy::READ_ONLY,
+                write_policy_overrides: cache::config::WritePolicyOverrides::default(),
                 allocate_policy: cache::config::AllocatePolicy::ON_FILL,
                 write_allocate_policy: cache::config::WriteAllocatePolicy::NO_WRITE_ALLOCATE,
                 // set_index_function: CacheSetIndexFunc::LINEAR_SET_FUNCTION,
@@ -1083,6 +2242,8 @@ impl Default for GPU {
                 miss_queue_size: 4,
                 result_fifo_entries: None,
                 l1_cache_write_ratio_percent: 0,
+                record_access_heatmap: false,
+                compression: None,
                 data_port_width: None,
             })),
             // N:64:128:6,L:L:m:N:H,A:128:8,8
@@ -1093,8 +2254,7 @@ impl Default for GPU {
             data_cache_l1: Some(Arc::new(L1DCache {
                 l1_latency: 1,
                 l1_hit_latency: 81,
-                // l1_banks_hashing_function: CacheSetIndexFunc::LINEAR_SET_FUNCTION,
-                // l1_banks_hashing_function: Box::<cache::set_index::linear::SetIndex>::default(),
+                l1_banks_hashing_function: CacheSetIndexFunc::LINEAR_SET_FUNCTION,
                 l1_banks_byte_interleaving: 32,
                 l1_banks: 1,
                 inner: Arc::new(Cache {
@@ -1103,9 +2263,11 @@ impl Default for GPU {
                     // kind: CacheKind::Normal,
                     num_sets: 4, // 64,
                     line_size: 128,
+                    sector_size: mem_sub_partition::SECTOR_SIZE,
                     associativity: 48, // 6,
                     replacement_policy: cache::config::ReplacementPolicy::LRU,
                     write_policy: cache::config::WritePolicy::LOCAL_WB_GLOBAL_WT,
+                    write_policy_overrides: cache::config::WritePolicyOverrides::default(),
                     allocate_policy: cache::config::AllocatePolicy::ON_MISS,
                     write_allocate_policy: cache::config::WriteAllocatePolicy::NO_WRITE_ALLOCATE,
                     // set_index_function: CacheSetIndexFunc::FERMI_HASH_SET_FUNCTION,
@@ -1117,10 +2279,15 @@ impl Default for GPU {
                     miss_queue_size: 4,
                     result_fifo_entries: None,
                     l1_cache_write_ratio_percent: 0,
+                    record_access_heatmap: false,
+                    compression: None,
                     // l1_cache_write_ratio_percent: 50,
                     data_port_width: None,
                 }),
             })),
+            l1_cache_sharing: CacheSharing::Private,
+            l1_cache_shared_ports: 1,
+            l1_coherence: false,
             // N:64:128:16,L:B:m:W:L,A:1024:1024,4:0,32
             // {<nsets>:<bsize>:<assoc>,<rep>:<wr>:<alloc>:<wr_alloc>,<mshr>:<N>:<merge>,<mq>}
             data_cache_l2: Some(Arc::new(L2DCache {
@@ -1139,9 +2306,11 @@ impl Default for GPU {
                     // associativity: 16,
                     num_sets: 64,
                     line_size: 128,
+                    sector_size: mem_sub_partition::SECTOR_SIZE,
                     associativity: 16,
                     replacement_policy: cache::config::ReplacementPolicy::LRU,
                     write_policy: cache::config::WritePolicy::WRITE_BACK,
+                    write_policy_overrides: cache::config::WritePolicyOverrides::default(),
                     allocate_policy: cache::config::AllocatePolicy::ON_MISS,
                     write_allocate_policy: cache::config::WriteAllocatePolicy::WRITE_ALLOCATE,
                     // set_index_function: CacheSetIndexFunc::LINEAR_SET_FUNCTION,
@@ -1152,10 +2321,16 @@ impl Default for GPU {
                     miss_queue_size: 4,
                     result_fifo_entries: None, // 0 is none?
                     l1_cache_write_ratio_percent: 0,
+                    record_access_heatmap: false,
+                    compression: None,
                     data_port_width: Some(32),
                 }),
             })),
+            l2_set_indexing: L2SetIndexing::default(),
             shared_memory_latency: 24, // 3 for GTX1080
+            ldst_dual_path_issue: false,
+            ldst_shared_path_width: 1,
+            ldst_global_path_width: 1,
             // TODO: make this better, or just parse accelsim configs
             max_sp_latency: 13,
             max_int_latency: 4,
@@ -1163,6 +2338,7 @@ impl Default for GPU {
             max_sfu_latency: 8.max(330),
             global_mem_skip_l1_data_cache: false,
             perfect_mem: false,
+            perfect_scheduler: false,
             shader_registers: 65536,
             registers_per_block: 8192,
             ignore_resources_limitation: false,
@@ -1174,6 +2350,7 @@ impl Default for GPU {
             num_cores_per_simt_cluster: 1,
             num_cluster_ejection_buffer_size: 32, // 8 for GTX1080
             num_ldst_response_buffer_size: 2,
+            ldst_return_bandwidth_sectors: None,
             shared_memory_per_block: 48 * KB as usize,
             shared_memory_size: 96 * KB as u32,
             shared_memory_option: false,
@@ -1221,7 +2398,7 @@ impl Default for GPU {
             max_instruction_issue_per_warp: 2,
             dual_issue_only_to_different_exec_units: true,
             simt_core_sim_order: SchedulingOrder::RoundRobin,
-            pipeline_widths: HashMap::from_iter([
+            pipeline_widths: indexmap::IndexMap::from_iter([
                 (PipelineStage::ID_OC_SP, 4),
                 (PipelineStage::ID_OC_DP, 0),
                 (PipelineStage::ID_OC_INT, 0),
@@ -1244,11 +2421,13 @@ impl Default for GPU {
             num_tensor_core_avail: 0,
             num_tensor_core_units: 0,
             scheduler: CoreSchedulerKind::GTO,
+            scheduler_tie_break: SchedulerTieBreak::default(),
             concurrent_kernel_sm: false,
             perfect_inst_const_cache: false, // true
             inst_fetch_throughput: 1,
             reg_file_port_throughput: 2, // 1 for GTX1080
             fill_l2_on_memcopy: true,
+            num_copy_engines: 2,
             // simple_dram_model: false,
             dram_scheduler: DRAMSchedulerKind::FrFcfs,
             dram_partition_queue_interconn_to_l2: 8,
@@ -1272,7 +2451,10 @@ impl Default for GPU {
             // l2_rop_latency: 1,
             // dram_latency: 1,
             l2_rop_latency: 210, // was 120
+            l2_hit_latency: 0,
+            l2_tag_lookup_latency: 0,
             dram_latency: 190,   // was 100
+            dram_latency_estimate: false,
             dram_dual_bus_interface: false,
             dram_bank_indexing_policy: DRAMBankIndexPolicy::Normal,
             dram_bank_group_indexing_policy: DRAMBankGroupIndexPolicy::LowerBits,
@@ -1314,6 +2496,9 @@ impl Default for GPU {
             trace_opcode_latency_initiation_sfu: (14, 4), // (4, 1)
             /// does not have tensor units
             trace_opcode_latency_initiation_tensor: (usize::MAX, 1),
+            // shuffles move data between lanes over the shared memory
+            // datapath and are much slower than plain integer ops
+            trace_opcode_latency_initiation_shfl: (32, 2),
             // trace_opcode_latency_initiation_int: (4, 1),
             // trace_opcode_latency_initiation_sp: (4, 1),
             // trace_opcode_latency_initiation_dp: (20, 8), // (4, 1)
@@ -1480,6 +2665,26 @@ mod tests {
         assert_eq!(l1i_cache_config.mshr_addr(4_026_531_848), 4_026_531_840);
         assert_eq!(l1i_cache_config.mshr_addr(4_026_531_992), 4_026_531_968);
     }
+
+    #[test]
+    fn test_operand_collector_presets_validate() -> color_eyre::eyre::Result<()> {
+        let mut config = super::GPU::default();
+        config.validate_operand_collector()?;
+        for preset in [
+            super::OperandCollectorPreset::gtx1080(),
+            super::OperandCollectorPreset::titanx(),
+            super::OperandCollectorPreset::rtx3070(),
+        ] {
+            config.apply_operand_collector_preset(&preset);
+            config.validate_operand_collector()?;
+        }
+
+        // too few generic in ports for the configured pipeline width
+        config.apply_operand_collector_preset(&super::OperandCollectorPreset::titanx());
+        config.operand_collector_num_in_ports_gen = 2;
+        assert!(config.validate_operand_collector().is_err());
+        Ok(())
+    }
 }
 
 #[derive(Debug, Default, serde::Deserialize)]
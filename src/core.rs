@@ -1,6 +1,6 @@
 use super::{
     address, barrier, cache, config, func_unit as fu, instruction::WarpInstruction,
-    interconn as ic, kernel::Kernel, mcu, mem_fetch, opcodes, operand_collector as opcoll,
+    interconn as ic, kernel::Kernel, logging, mcu, mem_fetch, opcodes, operand_collector as opcoll,
     register_set, scheduler, scoreboard, warp,
 };
 use crate::sync::{Mutex, RwLock};
@@ -43,6 +43,26 @@ impl InstrFetchBuffer {
 
 type ResultBus = BitArr!(for fu::MAX_ALU_LATENCY);
 
+/// A thread block harvested from a failed core.
+///
+/// Holds everything needed to re-issue the block on another core: the
+/// kernel it belongs to and the trace instructions of its warps.
+/// Active masks and thread state are reconstructed at re-issue, like
+/// for a regular block issue.
+#[derive(Debug)]
+pub struct MigratedBlock {
+    pub kernel: Arc<dyn Kernel>,
+    /// Hardware block slot the block occupied on the failed core.
+    pub block_hw_id: usize,
+    /// Warp instructions already issued before the failure.
+    ///
+    /// The re-issued block starts over from its first instruction, so
+    /// these count as re-executed.
+    pub num_executed_instructions: u64,
+    /// Trace instructions of the block's warps, in warp order.
+    pub warps: Vec<VecDeque<WarpInstruction>>,
+}
+
 pub trait WarpIssuer {
     fn issue_warp(
         &self,
@@ -100,7 +120,12 @@ where
         let mut next_instr = warp.ibuffer_take().unwrap();
         warp.ibuffer_step();
 
-        log::debug!(
+        crate::debug_scoped!(
+            logging::Component::Core {
+                cluster_id: self.cluster_id,
+                core_id: self.core_id,
+            },
+            cycle,
             "{} by scheduler {} to pipeline[{:?}][{}] {}",
             style(format!(
                 "cycle {:02} issue {} for warp {}",
@@ -123,6 +148,15 @@ where
         next_instr.issue_cycle = Some(cycle);
         next_instr.dispatch_delay_cycles = next_instr.initiation_interval;
         next_instr.scheduler_id = Some(scheduler_id);
+        warp.instruction_issued(&next_instr);
+
+        *self
+            .stats
+            .lock()
+            .get_mut(Some(next_instr.kernel_launch_id))
+            .num_issued_per_core
+            .entry(self.core_id)
+            .or_insert(0) += 1;
 
         let mut pipe_reg_mut = next_instr;
 
@@ -214,7 +248,12 @@ where
 
         let pipe_reg_ref = pipe_reg_mut;
 
-        log::debug!(
+        crate::debug_scoped!(
+            logging::Component::Core {
+                cluster_id: self.cluster_id,
+                core_id: self.core_id,
+            },
+            cycle,
             "{} (done={} ({}/{}), functional done={}, hardware done={}, stores done={} ({} stores), instr in pipeline = {}, active_threads={})",
             style(format!("checking if warp {} did exit", warp.warp_id)).yellow(),
             warp.done(),
@@ -242,7 +281,12 @@ where
             warp.waiting_for_memory_barrier = true;
         }
 
-        log::debug!(
+        crate::debug_scoped!(
+            logging::Component::Core {
+                cluster_id: self.cluster_id,
+                core_id: self.core_id,
+            },
+            cycle,
             "{} ({:?}) for instr {}",
             style(format!(
                 "reserving {} registers",
@@ -255,6 +299,18 @@ where
 
         self.scoreboard.try_write().reserve_all(&pipe_reg_ref);
 
+        if self.config.memory_only && !pipe_reg_ref.is_memory_instruction() {
+            if let Some(latency) = self.config.memory_only_compute_latency {
+                // model the compute instruction as a scoreboard update only:
+                // it never reaches a functional unit and releases its output
+                // registers after the fixed latency
+                self.pending_scoreboard_releases
+                    .lock()
+                    .push_back((cycle + latency, pipe_reg_ref));
+                return Ok(());
+            }
+        }
+
         *pipe_reg = Some(pipe_reg_ref);
 
         // log::debug!(
@@ -301,7 +357,18 @@ where
     }
 }
 
-#[derive(strum::EnumIter, strum::EnumCount, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(
+    strum::EnumIter,
+    strum::EnumCount,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 #[repr(usize)]
 pub enum PipelineStage {
     /// Instruction Decode -> Operand Collector stage for single precision unit
@@ -482,8 +549,39 @@ pub struct Core<I> {
     pub schedulers: Vec<Arc<Mutex<dyn scheduler::Scheduler>>>,
     pub scheduler_issue_priority: usize,
 
+    /// Busy and idle cycles of this core.
+    ///
+    /// The core is busy as long as it has threads in flight.
+    pub utilization: stats::utilization::Counters,
+
+    /// Busy and idle cycles per functional unit.
+    ///
+    /// Indexed like `functional_units`.
+    pub fu_utilization: Vec<stats::utilization::Counters>,
+
     /// Custom callback handler that is called when a fetch is returned to its issuer.
     pub fetch_return_callback: Option<Box<dyn Fn(u64, &mem_fetch::MemFetch) + Send + Sync>>,
+
+    /// Pipeline occupancy dump (pipeview), if this core is selected.
+    pub pipeview: Option<crate::pipeview::Writer>,
+
+    /// Non-memory instructions awaiting their scoreboard release in
+    /// memory-only mode (see `memory_only_compute_latency`).
+    pub pending_scoreboard_releases: Mutex<VecDeque<(u64, WarpInstruction)>>,
+
+    /// Completed instructions held back to enforce the configured
+    /// writeback ordering (see [`config::WritebackOrdering`]).
+    ///
+    /// The buffer is unbounded: it models the retirement delay, not a
+    /// capacity limit, and cannot deadlock.
+    pub reorder_buffer: VecDeque<WarpInstruction>,
+
+    /// Whether the core was marked failed by an injected core failure
+    /// (see [`config::CoreFailure`]).
+    ///
+    /// A failed core no longer cycles, accepts no new blocks and drops
+    /// all memory responses destined for it.
+    pub failed: bool,
 }
 
 #[allow(clippy::missing_fields_in_debug)]
@@ -510,6 +608,7 @@ where
         stats: Arc<Mutex<stats::PerKernel>>,
         config: Arc<config::GPU>,
         mem_controller: Arc<dyn mcu::MemoryController>,
+        shared_l1: Option<cache::shared::Ref<stats::cache::PerKernel>>,
     ) -> Self {
         let thread_state: Vec<_> = (0..config.max_threads_per_core).map(|_| None).collect();
 
@@ -613,6 +712,7 @@ where
             config.clone(),
             mem_controller.clone(),
             stats.clone(),
+            shared_l1,
         );
         let load_store_unit = Arc::new(Mutex::new(load_store_unit));
 
@@ -722,6 +822,12 @@ where
             .build(),
         );
 
+        let pipeview = config
+            .pipeview
+            .as_ref()
+            .filter(|pipeview| (pipeview.cluster_id, pipeview.core_id) == (cluster_id, core_id))
+            .map(|pipeview| crate::pipeview::Writer::open(&pipeview.out_file).unwrap());
+
         Self {
             core_id,
             cluster_id,
@@ -760,9 +866,15 @@ where
             thread_state,
             schedulers,
             scheduler_issue_priority: 0,
+            utilization: stats::utilization::Counters::default(),
+            fu_utilization: vec![stats::utilization::Counters::default(); functional_units.len()],
             functional_units,
             issue_ports,
             fetch_return_callback: None,
+            pipeview,
+            pending_scoreboard_releases: Mutex::new(VecDeque::new()),
+            reorder_buffer: VecDeque::new(),
+            failed: false,
         }
     }
 
@@ -864,6 +976,15 @@ where
         unit.invalidate();
     }
 
+    /// Invalidate the L1 data cache line holding `addr`, if present.
+    ///
+    /// Applies a coherence invalidation sent from the sharer directory
+    /// at the L2.
+    pub fn l1_invalidate_addr(&mut self, addr: address) -> bool {
+        let mut unit = self.load_store_unit.try_lock();
+        unit.invalidate_addr(addr)
+    }
+
     #[must_use]
     // #[inline]
     pub fn ldst_unit_response_buffer_full(&self) -> bool {
@@ -911,6 +1032,9 @@ where
 
     // #[inline]
     pub fn can_issue_block(&self, kernel: &dyn Kernel) -> bool {
+        if self.failed {
+            return false;
+        }
         let max_blocks = self.config.max_blocks(kernel).unwrap();
         if self.config.concurrent_kernel_sm {
             if max_blocks < 1 {
@@ -1086,6 +1210,162 @@ where
         self.init_warps(free_block_hw_id, start_thread, end_thread, block_id, kernel);
         self.num_active_blocks += 1;
     }
+
+    /// Mark the core as failed and harvest its resident blocks.
+    ///
+    /// The harvested blocks can be re-issued on other cores using
+    /// [`Core::issue_migrated_block`].  The running block count of the
+    /// kernel is not touched: the failed core never completes the
+    /// blocks, the core they migrate to does.
+    pub fn fail(&mut self) -> Vec<MigratedBlock> {
+        debug_assert!(!self.failed);
+        self.failed = true;
+
+        let warp_size = self.config.warp_size;
+        let warps_per_block = self.thread_block_size / warp_size;
+
+        let mut migrated = Vec::new();
+        for (block_hw_id, num_threads_in_block) in self.block_status.iter().enumerate() {
+            if *num_threads_in_block == 0 {
+                continue;
+            }
+            let start_warp = block_hw_id * warps_per_block;
+            let end_warp = start_warp + warps_per_block;
+
+            let mut kernel = None;
+            let mut num_executed_instructions = 0;
+            let mut warps = Vec::with_capacity(warps_per_block);
+            for warp in &self.warps[start_warp..end_warp] {
+                let mut warp = warp.try_lock();
+                kernel = kernel.or_else(|| warp.kernel.clone());
+                num_executed_instructions += warp.trace_pc as u64;
+                warps.push(std::mem::take(&mut warp.trace_instructions));
+            }
+            let kernel = kernel.expect("resident block has a kernel");
+            migrated.push(MigratedBlock {
+                kernel,
+                block_hw_id,
+                num_executed_instructions,
+                warps,
+            });
+        }
+
+        // wipe the core state: the core never cycles again, so in-flight
+        // pipeline, scoreboard and barrier state is left behind as-is
+        self.block_status.fill(0);
+        self.num_active_blocks = 0;
+        self.num_active_warps = 0;
+        self.num_active_threads = 0;
+        self.active_thread_mask.fill(false);
+        self.occupied_hw_thread_ids.fill(false);
+        self.occupied_block_to_hw_thread_id.clear();
+        for thread in &mut self.thread_state {
+            *thread = None;
+        }
+        for warp in &self.warps {
+            let mut warp = warp.try_lock();
+            // clear outstanding hazards first: `reset` asserts that no
+            // instructions are in flight, which does not hold for a
+            // core that fails mid-execution
+            warp.num_outstanding_stores = 0;
+            warp.num_outstanding_atomics = 0;
+            warp.num_instr_in_pipeline = 0;
+            warp.in_flight_instructions.clear();
+            warp.waiting_for_memory_barrier = false;
+            warp.kernel = None;
+            warp.instr_buffer.fill(None);
+            warp.reset();
+            warp.clear();
+        }
+        migrated
+    }
+
+    /// Issue a block harvested from a failed core.
+    ///
+    /// Mirrors [`Core::issue_block`], except that the warp trace
+    /// instructions come from the migrated block instead of the kernel
+    /// trace and the block restarts from its first instruction.  The
+    /// caller must have checked [`Core::can_issue_block`].
+    #[tracing::instrument(name = "core_issue_migrated_block")]
+    pub fn issue_migrated_block(&mut self, block: MigratedBlock, cycle: u64) {
+        let kernel = Arc::clone(&block.kernel);
+        self.current_kernel_max_blocks = self.config.max_blocks(&*kernel).unwrap();
+        self.thread_block_size = self.config.threads_per_block_padded(&*kernel);
+
+        let free_block_hw_id = self
+            .block_status
+            .iter()
+            .position(|num_threads_in_block| *num_threads_in_block == 0)
+            .expect("core can issue block");
+
+        let thread_block_size = kernel.config().threads_per_block();
+        let padded_thread_block_size = self.config.threads_per_block_padded(&*kernel);
+        let start_thread = free_block_hw_id * padded_thread_block_size;
+        let end_thread = start_thread + thread_block_size;
+
+        self.reinit(start_thread, end_thread, false);
+
+        log::debug!(
+            "core {:?}: issue migrated block (hw {} on failed core) from kernel {}",
+            self.id(),
+            block.block_hw_id,
+            kernel,
+        );
+
+        let mut warps_mask = WarpMask::ZERO;
+        let mut num_threads_in_block = 0;
+        for i in start_thread..end_thread {
+            self.thread_state[i] = Some(ThreadState {
+                active: true,
+                pc: 0,
+            });
+            warps_mask.set(i / self.config.warp_size, true);
+            num_threads_in_block += 1;
+        }
+
+        // the failed core never decremented the running block count of
+        // the kernel, hence it is not incremented here either
+        self.block_status[free_block_hw_id] = num_threads_in_block;
+
+        self.barriers
+            .try_write()
+            .allocate(free_block_hw_id as u64, warps_mask);
+
+        let start_warp = start_thread / self.config.warp_size;
+        let end_warp = end_thread.div_ceil(self.config.warp_size);
+        let mut block_warps = block.warps.into_iter();
+        for warp_id in start_warp..end_warp {
+            let mut num_active = 0;
+            let mut local_active_thread_mask = warp::ActiveMask::ZERO;
+            for warp_thread_id in 0..self.config.warp_size {
+                let hwtid = warp_id * self.config.warp_size + warp_thread_id;
+                if hwtid < end_thread {
+                    num_active += 1;
+                    debug_assert!(!self.active_thread_mask[hwtid]);
+                    self.active_thread_mask.set(hwtid, true);
+                    local_active_thread_mask.set(warp_thread_id, true);
+                }
+            }
+
+            let mut warp = self.warps[warp_id].try_lock();
+            warp.init(
+                free_block_hw_id as u64,
+                warp_id,
+                self.dynamic_warp_id,
+                local_active_thread_mask,
+                Arc::clone(&kernel),
+            );
+            warp.trace_instructions = block_warps.next().unwrap_or_default();
+            warp.trace_pc = 0;
+            warp.compute_dependencies();
+            drop(warp);
+
+            self.dynamic_warp_id += 1;
+            self.num_active_warps += 1;
+            self.num_active_threads += num_active;
+        }
+        self.num_active_blocks += 1;
+    }
 }
 
 // PRIVATE
@@ -1288,6 +1568,7 @@ where
 
                 self.instr_fetch_buffer.valid = true;
                 // warp.set_last_fetch(m_gpu->gpu_sim_cycle);
+                mem_fetch::tracker::retired(&fetch);
             } else {
                 // find an active warp with space in
                 // instruction buffer that is not
@@ -1473,7 +1754,7 @@ where
                             cache::RequestStatus::HIT
                         } else {
                             let mut events = Vec::new();
-                            self.instr_l1_cache.access(
+                            self.instr_l1_cache.access_status(
                                 ppc as address,
                                 fetch.clone(),
                                 &mut events,
@@ -1497,6 +1778,11 @@ where
                         } else {
                             debug_assert_eq!(status, cache::RequestStatus::RESERVATION_FAIL);
                         }
+                        if status != cache::RequestStatus::MISS {
+                            // hits are consumed right away and failed
+                            // accesses are retried with a new fetch
+                            mem_fetch::tracker::retired(&fetch);
+                        }
                         break;
                     }
                 }
@@ -1611,15 +1897,16 @@ where
         //
         // m_stats->m_last_num_sim_insn[m_sid] = m_stats->m_num_sim_insn[m_sid];
         // m_stats->m_last_num_sim_winsn[m_sid] = m_stats->m_num_sim_winsn[m_sid];
-        while let Some(mut ready) = exec_writeback_pipeline
+        while let Some(ready) = exec_writeback_pipeline
             .get_ready_mut()
             .and_then(|(_, r)| r.take())
         {
             log::debug!("ready for writeback: {}", ready);
 
-            // Right now, the writeback stage drains all waiting instructions
-            // assuming there are enough ports in the register file or the
-            // conflicts are resolved at issue.
+            // The writeback stage drains at most `pipeline_widths[EX_WB]`
+            // instructions per cycle, since the EX_WB register set has one
+            // slot per configured writeback port. Functional units that
+            // cannot allocate a slot stall and retry in a later cycle.
             //
             // The operand collector writeback can generally generate a stall
             // However, here, the pipelines should be un-stallable. This is
@@ -1631,10 +1918,26 @@ where
             // To handle this case, we ignore the return value (thus allowing
             // no stalling).
             //
-            self.operand_collector.try_lock().writeback(&mut ready);
-            self.scoreboard.try_write().release_all(&ready);
-            self.warps[ready.warp_id].try_lock().num_instr_in_pipeline -= 1;
-            warp_inst_complete(&mut ready, &self.stats);
+            if self.config.writeback_ordering == config::WritebackOrdering::Relaxed {
+                if !self.warps[ready.warp_id]
+                    .try_lock()
+                    .is_oldest_in_flight(&ready, false)
+                {
+                    // an older instruction of the warp is still in
+                    // flight: this writeback is out of program order
+                    *self
+                        .stats
+                        .lock()
+                        .get_mut(Some(ready.kernel_launch_id))
+                        .num_reorder_conflicts
+                        .entry(format!("{:?}", ready.opcode.category))
+                        .or_insert(0) += 1;
+                }
+                self.retire_instruction(ready);
+            } else {
+                // retirement is deferred until the ordering allows it
+                self.reorder_buffer.push_back(ready);
+            }
 
             //   m_gpu->gpu_sim_insn_last_update_sid = m_sid;
             //   m_gpu->gpu_sim_insn_last_update = m_gpu->gpu_sim_cycle;
@@ -1643,6 +1946,61 @@ where
             // preg = m_pipeline_reg[EX_WB].get_ready();
             //   pipe_reg = (preg == NULL) ? NULL : *preg;
         }
+        drop(exec_writeback_pipeline);
+
+        if self.config.writeback_ordering != config::WritebackOrdering::Relaxed {
+            self.retire_reorder_buffer();
+        }
+    }
+
+    /// Retire buffered instructions whose ordering constraint cleared.
+    ///
+    /// Instructions leave the reorder buffer once they are the oldest
+    /// in-flight instruction of their warp (or of their functional unit
+    /// category, depending on the configured scope). Instructions that
+    /// remain held count a reorder conflict per cycle.
+    fn retire_reorder_buffer(&mut self) {
+        let same_category_only =
+            self.config.writeback_ordering == config::WritebackOrdering::PerUnitInOrder;
+        let mut reorder_buffer = std::mem::take(&mut self.reorder_buffer);
+        loop {
+            let ready = reorder_buffer.iter().position(|instr| {
+                self.warps[instr.warp_id]
+                    .try_lock()
+                    .is_oldest_in_flight(instr, same_category_only)
+            });
+            let Some(idx) = ready else {
+                break;
+            };
+            let instr = reorder_buffer.remove(idx).unwrap();
+            self.retire_instruction(instr);
+        }
+        if !reorder_buffer.is_empty() {
+            let mut stats = self.stats.lock();
+            for instr in &reorder_buffer {
+                *stats
+                    .get_mut(Some(instr.kernel_launch_id))
+                    .num_reorder_conflicts
+                    .entry(format!("{:?}", instr.opcode.category))
+                    .or_insert(0) += 1;
+            }
+        }
+        self.reorder_buffer = reorder_buffer;
+    }
+
+    /// Retire a completed instruction at the writeback stage.
+    ///
+    /// Writes back the destination registers, releases the scoreboard
+    /// and updates the per-warp in-flight tracking and stats.
+    fn retire_instruction(&self, mut ready: WarpInstruction) {
+        self.operand_collector.try_lock().writeback(&mut ready);
+        self.scoreboard.try_write().release_all(&ready);
+        {
+            let mut warp = self.warps[ready.warp_id].try_lock();
+            warp.num_instr_in_pipeline -= 1;
+            warp.instruction_retired(ready.uid);
+        }
+        warp_inst_complete(&mut ready, &self.stats);
     }
 
     #[tracing::instrument]
@@ -1680,7 +2038,8 @@ where
             );
 
             fu.cycle(cycle);
-            fu.active_lanes_in_pipeline();
+            self.fu_utilization[fu_id]
+                .add_cycle(fu.active_lanes_in_pipeline() > 0 || fu.occupied().any());
 
             log::debug!(
                 "fu[{:03}] {:<10} after \t{:?}={}",
@@ -1819,9 +2178,18 @@ where
             kernel.next_threadblock_traces(selected_warps, &self.config)
         );
         if have_block {
+            let mut longest_chain = 0;
+            for warp in &*selected_warps {
+                let mut warp = warp.try_lock();
+                warp.compute_dependencies();
+                longest_chain = longest_chain.max(warp.dep_graph.critical_path_len());
+            }
+
             let mut stats = self.stats.lock();
             let kernel_stats = stats.get_mut(Some(kernel.id() as usize));
             kernel_stats.sim.num_blocks += 1;
+            kernel_stats.sim.longest_dependency_chain =
+                kernel_stats.sim.longest_dependency_chain.max(longest_chain);
         }
         log::debug!(
             "initialized traces {}..{} of {} warps",
@@ -1894,7 +2262,15 @@ where
 {
     #[tracing::instrument(name = "core_cycle")]
     fn cycle(&mut self, cycle: u64) {
-        log::debug!(
+        if self.failed {
+            return;
+        }
+        crate::debug_scoped!(
+            logging::Component::Core {
+                cluster_id: self.cluster_id,
+                core_id: self.core_id,
+            },
+            cycle,
             "{} \tactive={}, not completed={} ldst unit response buffer={}",
             style(format!(
                 "cycle {:03} core {:?}: core cycle",
@@ -1942,6 +2318,29 @@ where
         //     return;
         // }
 
+        // release the scoreboard registers of compute instructions modeled
+        // as fixed latency scoreboard updates in memory-only mode
+        {
+            let mut pending = self.pending_scoreboard_releases.lock();
+            while let Some((ready_cycle, _)) = pending.front() {
+                if *ready_cycle > cycle {
+                    break;
+                }
+                let (_, mut instr) = pending.pop_front().unwrap();
+                self.scoreboard.try_write().release_all(&instr);
+                {
+                    let mut warp = self.warps[instr.warp_id].try_lock();
+                    warp.num_instr_in_pipeline -= 1;
+                    warp.instruction_retired(instr.uid);
+                }
+                warp_inst_complete(&mut instr, &self.stats);
+            }
+        }
+
+        // a core without threads in flight has no work and could be
+        // clock gated
+        self.utilization.add_cycle(self.not_completed() > 0);
+
         // m_stats->shader_cycles[m_sid]++;
         // "writeback"
         // self.writeback(cycle);
@@ -1960,6 +2359,10 @@ where
             crate::timeit!("core::decode", self.decode(cycle));
             crate::timeit!("core::fetch", self.fetch(cycle));
         }
+
+        if let Some(pipeview) = &mut self.pipeview {
+            pipeview.record(cycle, &self.pipeline_reg).unwrap();
+        }
     }
 }
 
@@ -1969,6 +2372,20 @@ pub fn warp_inst_complete(instr: &mut WarpInstruction, stats: &Mutex<stats::PerK
     let mut stats = stats.lock();
     let kernel_stats = stats.get_mut(Some(instr.kernel_launch_id as usize));
     kernel_stats.sim.instructions += instr.active_thread_count() as u64;
+    // count warp-level primitives separately from their (integer) category
+    let op_count_key = if instr.opcode.op.is_warp_level_primitive() {
+        "WARP_PRIMITIVE_OP".to_string()
+    } else {
+        format!("{:?}", instr.opcode.category)
+    };
+    *kernel_stats
+        .instructions
+        .op_counts
+        .entry(op_count_key)
+        .or_insert(0) += instr.active_thread_count() as u64;
+    let pc_stats = kernel_stats.per_pc.get_mut(instr.pc);
+    pc_stats.num_issued += 1;
+    pc_stats.num_thread_instructions += instr.active_thread_count() as u64;
     // crate::WIP_STATS.lock().warp_instructions += 1;
 }
 
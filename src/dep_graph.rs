@@ -0,0 +1,153 @@
+//! Register dependency extraction from traced warp instructions.
+//!
+//! Traces contain the architectural source and destination registers of
+//! every instruction, so the complete dependency graph of a warp is
+//! known at load time.
+//! The graph gives schedulers and the scoreboard access to precise
+//! dependencies and is the basis for critical path analysis (the
+//! longest dependency chain of a kernel).
+use crate::instruction::WarpInstruction;
+use std::collections::HashMap;
+
+/// Register dependency graph of the traced instructions of one warp.
+///
+/// Nodes are indices into the trace instructions of the warp (trace pc)
+/// and edges point from an instruction to the earlier instructions it
+/// depends on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Indices of the earlier instructions each instruction depends on.
+    dependencies: Vec<Vec<usize>>,
+}
+
+impl DependencyGraph {
+    #[must_use]
+    pub fn new<'a>(instructions: impl IntoIterator<Item = &'a WarpInstruction>) -> Self {
+        Self::from_registers(
+            instructions
+                .into_iter()
+                .map(|instr| (instr.inputs().copied(), instr.outputs().copied())),
+        )
+    }
+
+    /// Build the dependency graph from (source, destination) register sets.
+    ///
+    /// An instruction depends on the last writer of each of its source
+    /// registers (read after write), the last writer of each of its
+    /// destination registers (write after write), and the readers of its
+    /// destination registers since their last write (write after read).
+    pub fn from_registers<S, D>(instructions: impl IntoIterator<Item = (S, D)>) -> Self
+    where
+        S: IntoIterator<Item = u32>,
+        D: IntoIterator<Item = u32>,
+    {
+        let mut last_writer: HashMap<u32, usize> = HashMap::new();
+        let mut readers_since_write: HashMap<u32, Vec<usize>> = HashMap::new();
+        let mut dependencies = Vec::new();
+
+        for (i, (src_regs, dest_regs)) in instructions.into_iter().enumerate() {
+            let mut deps = Vec::new();
+            let src_regs: Vec<u32> = src_regs.into_iter().collect();
+            for reg in &src_regs {
+                if let Some(&writer) = last_writer.get(reg) {
+                    deps.push(writer);
+                }
+            }
+            for reg in dest_regs {
+                if let Some(&writer) = last_writer.get(&reg) {
+                    deps.push(writer);
+                }
+                if let Some(readers) = readers_since_write.remove(&reg) {
+                    deps.extend(readers);
+                }
+                last_writer.insert(reg, i);
+            }
+            for reg in src_regs {
+                readers_since_write.entry(reg).or_default().push(i);
+            }
+            deps.sort_unstable();
+            deps.dedup();
+            dependencies.push(deps);
+        }
+        Self { dependencies }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.dependencies.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
+    }
+
+    /// Indices of the earlier instructions that the instruction at
+    /// `trace_pc` depends on.
+    #[must_use]
+    pub fn dependencies(&self, trace_pc: usize) -> &[usize] {
+        self.dependencies
+            .get(trace_pc)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Length of the longest dependency chain (critical path) in
+    /// instructions.
+    #[must_use]
+    pub fn critical_path_len(&self) -> u64 {
+        let mut chain_len = vec![0u64; self.dependencies.len()];
+        for (i, deps) in self.dependencies.iter().enumerate() {
+            let longest_dep = deps.iter().map(|&dep| chain_len[dep]).max().unwrap_or(0);
+            chain_len[i] = longest_dep + 1;
+        }
+        chain_len.into_iter().max().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DependencyGraph;
+    use utils::diff;
+
+    #[test]
+    fn test_dependencies() {
+        // r3 = r1 + r2; r4 = r3 * r3; r3 = r5; r6 = r3 + r4
+        let graph = DependencyGraph::from_registers([
+            (vec![1, 2], vec![3]),
+            (vec![3, 3], vec![4]),
+            (vec![5], vec![3]),
+            (vec![3, 4], vec![6]),
+        ]);
+        // read after write
+        diff::assert_eq!(have: graph.dependencies(1), want: vec![0]);
+        // write after write and write after read
+        diff::assert_eq!(have: graph.dependencies(2), want: vec![0, 1]);
+        diff::assert_eq!(have: graph.dependencies(3), want: vec![1, 2]);
+        diff::assert_eq!(have: graph.dependencies(0), want: Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let graph = DependencyGraph::from_registers::<Vec<u32>, Vec<u32>>([]);
+        diff::assert_eq!(have: graph.critical_path_len(), want: 0);
+
+        // two independent chains of length 2 and one unrelated instruction
+        let graph = DependencyGraph::from_registers([
+            (vec![1], vec![2]),
+            (vec![3], vec![4]),
+            (vec![2], vec![5]),
+            (vec![4], vec![6]),
+            (vec![7], vec![8]),
+        ]);
+        diff::assert_eq!(have: graph.critical_path_len(), want: 2);
+
+        // a serial chain
+        let graph = DependencyGraph::from_registers([
+            (vec![1], vec![2]),
+            (vec![2], vec![3]),
+            (vec![3], vec![4]),
+        ]);
+        diff::assert_eq!(have: graph.critical_path_len(), want: 3);
+    }
+}
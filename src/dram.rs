@@ -1,6 +1,95 @@
 use super::mem_fetch;
 use crate::config;
 use crate::sync::{Arc, Mutex};
+use std::collections::VecDeque;
+
+/// Cap on the channel utilization used by the M/D/1 estimate.
+///
+/// The closed-form queueing delay diverges as the utilization
+/// approaches 1, so saturated channels are clamped to this value.
+pub const MAX_UTILIZATION: f64 = 0.99;
+
+/// M/D/1 queueing estimate of the DRAM request latency.
+///
+/// The DRAM channel is modeled as a single server with a deterministic
+/// service time (the data bus transfer time of one request) and Poisson
+/// arrivals at the rate measured so far. The estimated latency of a
+/// request is the fixed access latency plus the expected M/D/1 queueing
+/// delay `rho * s / (2 * (1 - rho))` for channel utilization `rho` and
+/// service time `s`.
+///
+/// The estimator also measures the observed latency of requests through
+/// the detailed latency queue, such that the estimate can be calibrated
+/// against the detailed model (see [`config::GPU::dram_latency_estimate`]).
+#[derive(Debug, Clone)]
+pub struct LatencyEstimator {
+    /// Fixed DRAM access latency in cycles.
+    access_latency: u64,
+    /// Deterministic service time of the data bus in cycles.
+    service_time: f64,
+    /// Number of requests issued to this DRAM channel.
+    arrivals: u64,
+    /// Cycle of the first request issued to this DRAM channel.
+    first_arrival_cycle: Option<u64>,
+    /// Issue cycle and estimated latency of the requests that are still
+    /// in the DRAM latency queue (FIFO, parallel to the queue).
+    in_flight: VecDeque<(u64, u64)>,
+}
+
+impl LatencyEstimator {
+    #[must_use]
+    pub fn new(config: &config::GPU) -> Self {
+        // the data bus is busy for the burst transfer time of one request
+        let service_time =
+            config.dram_burst_length as f64 / config.dram_data_command_freq_ratio as f64;
+        Self {
+            access_latency: config.dram_latency as u64,
+            service_time,
+            arrivals: 0,
+            first_arrival_cycle: None,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Measured arrival rate in requests per cycle.
+    #[must_use]
+    pub fn arrival_rate(&self, cycle: u64) -> f64 {
+        let Some(first_arrival_cycle) = self.first_arrival_cycle else {
+            return 0.0;
+        };
+        let elapsed = cycle.saturating_sub(first_arrival_cycle) + 1;
+        self.arrivals as f64 / elapsed as f64
+    }
+
+    /// Estimated latency of a request issued at `cycle`.
+    ///
+    /// Fixed access latency plus the expected M/D/1 queueing delay for
+    /// the arrival rate measured so far.
+    #[must_use]
+    pub fn estimate(&self, cycle: u64) -> u64 {
+        let utilization = (self.arrival_rate(cycle) * self.service_time).min(MAX_UTILIZATION);
+        let queueing_delay = utilization * self.service_time / (2.0 * (1.0 - utilization));
+        self.access_latency + queueing_delay.round() as u64
+    }
+
+    /// Record a request issued to DRAM at `cycle`.
+    pub fn issue(&mut self, cycle: u64, estimated_latency: u64) {
+        self.first_arrival_cycle.get_or_insert(cycle);
+        self.arrivals += 1;
+        self.in_flight.push_back((cycle, estimated_latency));
+    }
+
+    /// Record the oldest in-flight request leaving DRAM at `cycle`.
+    ///
+    /// Returns the observed and the estimated latency of the request.
+    pub fn complete(&mut self, cycle: u64) -> (u64, u64) {
+        let (issue_cycle, estimated_latency) = self
+            .in_flight
+            .pop_front()
+            .expect("completed dram request was issued");
+        (cycle - issue_cycle, estimated_latency)
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Config {
@@ -22,6 +111,7 @@ pub struct DRAM {
     // config: Arc<config::GPU>,
     // mrqq: FifoQueue<Request>,
     // scheduler: FrfcfsScheduler,
+    named_address_ranges: Vec<config::NamedAddressRange>,
     stats: Arc<Mutex<stats::PerKernel>>,
 }
 
@@ -44,6 +134,7 @@ impl DRAM {
             },
             // mrqq,
             // scheduler,
+            named_address_ranges: config.named_address_ranges.clone(),
             stats,
         }
     }
@@ -74,6 +165,16 @@ impl DRAM {
         );
 
         kernel_stats.dram.bank_accesses[idx] += 1;
+
+        for (range_id, range) in self.named_address_ranges.iter().enumerate() {
+            if range.matches(fetch.allocation_id(), fetch.addr()) {
+                *kernel_stats
+                    .dram
+                    .range_accesses
+                    .entry((range_id, fetch.access_kind().into()))
+                    .or_insert(0) += 1;
+            }
+        }
         // } else {
         //     log::warn!(
         //         "dram access without kernel launch id: {} ({:?}) data size={} uid={}",
@@ -0,0 +1,83 @@
+//! Per-request DRAM trace dump.
+//!
+//! Each line of a dump records one request at the time it is issued to
+//! DRAM, in a format consumable by an external DRAM simulator: the
+//! DRAMsim3 stream trace (`0x100 READ 42`), the Ramulator memory trace
+//! (`0x100 R`), or a CSV format that additionally carries the chip,
+//! bank, row and column the address was mapped to.
+//!
+//! For higher-fidelity memory timing than offline trace replay, the
+//! [`Backend`] trait is the in-process counterpart: an implementation
+//! wrapping one of those simulators replaces the built-in DRAM latency
+//! model (see [`crate::MockSimulator::set_dram_backend`]).
+
+use crate::{config, mem_fetch};
+use std::io::Write as _;
+use std::path::Path;
+
+/// Integration point for an external DRAM timing backend.
+///
+/// An implementation wraps a detailed DRAM simulator such as DRAMsim3
+/// or Ramulator. Each request issued to DRAM is handed to the backend,
+/// and the returned latency replaces the built-in latency model when
+/// scheduling the response.
+pub trait Backend: std::fmt::Debug + Send + Sync + 'static {
+    /// Issue one request to the backend.
+    ///
+    /// Returns the completion latency of the request in DRAM cycles.
+    fn issue(&mut self, cycle: u64, fetch: &mem_fetch::MemFetch) -> u64;
+}
+
+/// Writes one record per request issued to DRAM.
+pub struct Writer {
+    out: std::io::BufWriter<std::fs::File>,
+    format: config::DramTraceFormat,
+    wrote_header: bool,
+}
+
+impl std::fmt::Debug for Writer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Writer").finish()
+    }
+}
+
+impl Writer {
+    pub fn open(
+        path: impl AsRef<Path>,
+        format: config::DramTraceFormat,
+    ) -> Result<Self, utils::fs::Error> {
+        Ok(Self {
+            out: utils::fs::open_writable(path)?,
+            format,
+            wrote_header: false,
+        })
+    }
+
+    /// Record one request issued to DRAM.
+    pub fn record(&mut self, cycle: u64, fetch: &mem_fetch::MemFetch) -> std::io::Result<()> {
+        let addr = fetch.addr();
+        match self.format {
+            config::DramTraceFormat::Dramsim3 => {
+                let kind = if fetch.is_write() { "WRITE" } else { "READ" };
+                writeln!(self.out, "{addr:#x} {kind} {cycle}")
+            }
+            config::DramTraceFormat::Ramulator => {
+                let kind = if fetch.is_write() { "W" } else { "R" };
+                writeln!(self.out, "{addr:#x} {kind}")
+            }
+            config::DramTraceFormat::Csv => {
+                if !self.wrote_header {
+                    writeln!(self.out, "cycle,address,kind,chip,bank,row,column")?;
+                    self.wrote_header = true;
+                }
+                let kind = if fetch.is_write() { "WRITE" } else { "READ" };
+                let physical = &fetch.physical_addr;
+                writeln!(
+                    self.out,
+                    "{cycle},{addr:#x},{kind},{},{},{},{}",
+                    physical.chip, physical.bk, physical.row, physical.col
+                )
+            }
+        }
+    }
+}
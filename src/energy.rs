@@ -0,0 +1,108 @@
+//! Instruction-level energy estimation.
+//!
+//! Multiplies the executed instruction counts per opcode category and the
+//! cache and DRAM access counts with the configurable energy weights of
+//! the config.
+//!
+//! This is much coarser than a full power model (no static power, no
+//! per-structure activity factors), but useful for quick comparisons
+//! between configurations.
+
+use crate::config;
+
+/// Energy estimate for a single kernel.
+///
+/// All values are in picojoules.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct KernelEnergy {
+    /// Energy consumed by executed instructions.
+    pub instructions: f64,
+    /// Energy consumed by L1 cache accesses.
+    pub l1_accesses: f64,
+    /// Energy consumed by L2 cache accesses.
+    pub l2_accesses: f64,
+    /// Energy consumed by DRAM accesses.
+    pub dram_accesses: f64,
+}
+
+impl KernelEnergy {
+    #[must_use]
+    pub fn total(&self) -> f64 {
+        self.instructions + self.l1_accesses + self.l2_accesses + self.dram_accesses
+    }
+}
+
+/// Estimate the energy consumed by a single kernel.
+#[must_use]
+pub fn estimate(stats: &stats::Stats, weights: &config::EnergyWeights) -> KernelEnergy {
+    let instructions = stats
+        .instructions
+        .op_counts
+        .iter()
+        .map(|(category, &count)| {
+            let weight = weights
+                .op_energy
+                .get(category)
+                .copied()
+                .unwrap_or(weights.default_op_energy);
+            weight * count as f64
+        })
+        .sum();
+
+    let num_l1_accesses = stats.l1i_stats.total_accesses()
+        + stats.l1c_stats.total_accesses()
+        + stats.l1t_stats.total_accesses()
+        + stats.l1d_stats.total_accesses();
+    let l1_accesses = num_l1_accesses as f64 * weights.l1_access_energy;
+
+    let l2_accesses = stats.l2d_stats.total_accesses() as f64 * weights.l2_access_energy;
+
+    let num_dram_accesses = stats.dram.total_reads() + stats.dram.total_writes();
+    let dram_accesses = num_dram_accesses as f64 * weights.dram_access_energy;
+
+    KernelEnergy {
+        instructions,
+        l1_accesses,
+        l2_accesses,
+        dram_accesses,
+    }
+}
+
+/// Estimate the energy consumed per kernel.
+#[must_use]
+pub fn estimate_per_kernel(
+    stats: &stats::PerKernel,
+    weights: &config::EnergyWeights,
+) -> Vec<KernelEnergy> {
+    stats
+        .inner
+        .iter()
+        .map(|kernel_stats| estimate(kernel_stats, weights))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config;
+
+    #[test]
+    fn test_estimate_instruction_energy() {
+        let weights = config::EnergyWeights {
+            default_op_energy: 1.0,
+            ..config::EnergyWeights::default()
+        };
+        let mut stats = stats::Stats::empty();
+        stats.instructions.op_counts.insert("DP_OP".to_string(), 10);
+        stats
+            .instructions
+            .op_counts
+            .insert("EXIT_OPS".to_string(), 5);
+
+        let energy = super::estimate(&stats, &weights);
+        // 10 DP instructions with an explicit weight plus 5 instructions
+        // falling back to the default weight
+        let want = 10.0 * weights.op_energy["DP_OP"] + 5.0 * weights.default_op_energy;
+        assert!((energy.instructions - want).abs() < f64::EPSILON);
+        assert!((energy.total() - want).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,128 @@
+//! Long-format data export for per-kernel comparison plots.
+//!
+//! Combines the per-kernel simulation statistics and (optionally)
+//! hardware profiler metrics into rows of (kernel, metric, value,
+//! source), such that plotting scripts can group and facet by metric
+//! without bespoke merging logic for every metric.
+
+use color_eyre::eyre;
+
+/// Source of an exported value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Simulation,
+    Profile,
+}
+
+/// A single row of the long-format export.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Row {
+    pub kernel: String,
+    pub metric: String,
+    pub value: f64,
+    pub source: Source,
+}
+
+/// Export rows for the per-kernel simulation statistics.
+#[must_use]
+pub fn simulation_rows(stats: &stats::PerKernel) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for kernel_stats in stats.as_ref() {
+        let sim = &kernel_stats.sim;
+        let l1d = kernel_stats.l1d_stats.reduce();
+        let l2d = kernel_stats.l2d_stats.reduce();
+        let cycles = sim.cycles as f64;
+        let instructions = sim.instructions as f64;
+        let metrics = [
+            ("cycles", cycles),
+            ("instructions", instructions),
+            ("blocks", sim.num_blocks as f64),
+            (
+                "ipc",
+                if sim.cycles > 0 {
+                    instructions / cycles
+                } else {
+                    0.0
+                },
+            ),
+            ("l1d_accesses", l1d.num_accesses() as f64),
+            ("l1d_hit_rate", f64::from(l1d.hit_rate())),
+            ("l2d_accesses", l2d.num_accesses() as f64),
+            ("l2d_hit_rate", f64::from(l2d.hit_rate())),
+            ("l2d_read_hit_rate", f64::from(l2d.global_read_hit_rate())),
+            ("l2d_write_hit_rate", f64::from(l2d.global_write_hit_rate())),
+            ("dram_reads", kernel_stats.dram.total_reads() as f64),
+            ("dram_writes", kernel_stats.dram.total_writes() as f64),
+        ];
+        rows.extend(metrics.into_iter().map(|(metric, value)| Row {
+            kernel: sim.kernel_name.clone(),
+            metric: metric.to_string(),
+            value,
+            source: Source::Simulation,
+        }));
+    }
+    rows
+}
+
+/// Export rows for hardware profiler metrics.
+///
+/// Accepts the generic layout of the metric files written by the
+/// validation tooling: a list of per-kernel objects mapping metric
+/// names to a value with an optional unit (see [`profile::Metric`]).
+/// Non-numeric metrics are skipped.
+///
+/// [`profile::Metric`]: https://docs.rs/profile
+#[must_use]
+pub fn profile_rows(metrics: &[serde_json::Value]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for per_kernel_metrics in metrics {
+        let Some(per_kernel_metrics) = per_kernel_metrics.as_object() else {
+            continue;
+        };
+        // nvprof names the kernel metric "Kernel", nsight "kernel_name"
+        let kernel = ["Kernel", "kernel_name"]
+            .iter()
+            .filter_map(|name| per_kernel_metrics.get(*name))
+            .filter_map(|metric| metric.get("value"))
+            .filter_map(serde_json::Value::as_str)
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        for (metric, value) in per_kernel_metrics {
+            let Some(value) = value.get("value") else {
+                continue;
+            };
+            let value = match value {
+                serde_json::Value::Number(num) => num.as_f64(),
+                // some profilers report numbers as strings with
+                // thousands separators
+                serde_json::Value::String(s) => s.replace(',', "").parse().ok(),
+                _ => None,
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            rows.push(Row {
+                kernel: kernel.clone(),
+                metric: metric.clone(),
+                value,
+                source: Source::Profile,
+            });
+        }
+    }
+    rows
+}
+
+/// Write rows as long-format CSV.
+pub fn write_csv(rows: &[Row], writer: impl std::io::Write) -> eyre::Result<()> {
+    let mut csv_writer = csv::WriterBuilder::new()
+        .flexible(false)
+        .from_writer(writer);
+    for row in rows {
+        csv_writer.serialize(row)?;
+    }
+    csv_writer.flush()?;
+    Ok(())
+}
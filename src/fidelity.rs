@@ -0,0 +1,54 @@
+//! Model fidelity tracking.
+//!
+//! Several code paths approximate hardware behavior rather than modeling
+//! it exactly, e.g. unhandled cache operators fall back to the default
+//! policy and instruction latencies use a coarse per-opcode workaround.
+//! Every approximation encountered during a run is counted in a central
+//! registry, such that a summary can be printed at the end of the run
+//! and users know how trustworthy the results are.
+
+use crate::sync::Mutex;
+use std::collections::HashMap;
+
+pub static APPROXIMATIONS: once_cell::sync::Lazy<Mutex<HashMap<&'static str, u64>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::default()));
+
+/// Count an encountered approximation.
+pub fn approximated(what: &'static str) {
+    *APPROXIMATIONS.lock().entry(what).or_insert(0) += 1;
+}
+
+/// Summary of all approximations encountered so far.
+///
+/// Returns `None` when no approximations were encountered.
+#[must_use]
+pub fn summary() -> Option<String> {
+    use std::fmt::Write;
+    let approximations = APPROXIMATIONS.lock();
+    if approximations.is_empty() {
+        return None;
+    }
+    let mut entries: Vec<_> = approximations.iter().collect();
+    entries.sort();
+
+    let mut out = String::new();
+    writeln!(
+        &mut out,
+        "MODEL FIDELITY: the following behavior was approximated:"
+    )
+    .ok();
+    for (what, count) in entries {
+        writeln!(&mut out, "  {count:>12}x {what}").ok();
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_summary() {
+        super::approximated("test approximation");
+        let summary = super::summary().expect("have summary");
+        assert!(summary.contains("test approximation"));
+    }
+}
@@ -65,6 +65,11 @@ impl<T> Fifo<T> {
         self.inner.pop_front()
     }
 
+    /// Remove and return the entry at `index`.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.inner.remove(index)
+    }
+
     #[must_use]
     pub fn first(&self) -> Option<&T> {
         self.inner.get(0)
@@ -100,6 +105,54 @@ impl<T> Fifo<T> {
             None => true,
         }
     }
+
+    /// Maximum number of entries the queue can hold.
+    ///
+    /// `None` means the queue is unbounded.
+    #[must_use]
+    pub fn capacity(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    /// Number of free slots left in the queue.
+    ///
+    /// `None` means the queue is unbounded.
+    #[must_use]
+    pub fn free(&self) -> Option<usize> {
+        self.max_size
+            .map(|max| max.saturating_sub(self.inner.len()))
+    }
+}
+
+impl<T> Fifo<(u64, T)> {
+    /// Enqueue an entry that becomes ready at `ready_time`.
+    ///
+    /// Entries must be enqueued in order of their readiness: the queue
+    /// preserves insertion order and only ever dequeues from the front.
+    pub fn enqueue_at(&mut self, ready_time: u64, value: T) {
+        self.inner.push_back((ready_time, value));
+    }
+
+    /// The first entry if it is ready at `time`.
+    #[must_use]
+    pub fn first_ready(&self, time: u64) -> Option<&T> {
+        match self.inner.front() {
+            Some((ready_time, value)) if time >= *ready_time => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Remove and return the first entry if it is ready at `time`.
+    pub fn dequeue_ready(&mut self, time: u64) -> Option<T> {
+        self.first_ready(time)?;
+        self.inner.pop_front().map(|(_, value)| value)
+    }
+
+    /// Remove and return all entries from the front of the queue that
+    /// are ready at `time`.
+    pub fn drain_ready(&mut self, time: u64) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(move || self.dequeue_ready(time))
+    }
 }
 
 impl<P> ic::Connection<P> for Fifo<P>
@@ -134,3 +187,29 @@ where
         Box::new(self.inner.drain(..))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Fifo;
+    use utils::diff;
+
+    #[test]
+    fn test_time_based_readiness() {
+        let mut fifo: Fifo<(u64, &str)> = Fifo::new(None, Some(4));
+        fifo.enqueue_at(10, "a");
+        fifo.enqueue_at(12, "b");
+        fifo.enqueue_at(20, "c");
+
+        assert_eq!(fifo.first_ready(9), None);
+        assert_eq!(fifo.first_ready(10), Some(&"a"));
+        assert_eq!(fifo.free(), Some(1));
+
+        diff::assert_eq!(
+            have: fifo.drain_ready(15).collect::<Vec<_>>(),
+            want: vec!["a", "b"]
+        );
+        assert_eq!(fifo.dequeue_ready(19), None);
+        assert_eq!(fifo.dequeue_ready(20), Some("c"));
+        assert_eq!(fifo.free(), Some(4));
+    }
+}
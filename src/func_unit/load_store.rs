@@ -2,7 +2,7 @@ use crate::sync::{Arc, Mutex, RwLock};
 use crate::{
     address, cache, config, func_unit as fu,
     instruction::{CacheOperator, MemorySpace, WarpInstruction},
-    interconn as ic, mcu, mem_fetch, mem_sub_partition, mshr, operand_collector as opcoll,
+    interconn as ic, mcu, mem_fetch, mshr, operand_collector as opcoll,
     register_set::{self},
     scoreboard::{Access, Scoreboard},
     warp,
@@ -48,6 +48,22 @@ pub struct LoadStoreUnit {
     pub mem_port: ic::Port<mem_fetch::MemFetch>,
     inner: fu::PipelinedSimdUnit,
 
+    /// Dispatch register of the shared-memory issue path.
+    ///
+    /// Only used with dual-path issue (see
+    /// [`config::GPU::ldst_dual_path_issue`]): shared-memory operations
+    /// dispatch into their own register and no longer contend with
+    /// global and local operations for the single dispatch register.
+    shared_dispatch_reg: Option<WarpInstruction>,
+    /// Busy and idle cycles of the shared-memory issue path.
+    ///
+    /// Only recorded with dual-path issue.
+    pub shared_path_utilization: stats::utilization::Counters,
+    /// Busy and idle cycles of the global/local issue path.
+    ///
+    /// Only recorded with dual-path issue.
+    pub global_path_utilization: stats::utilization::Counters,
+
     /// Operand collector
     operand_collector: Arc<Mutex<opcoll::RegisterFileUnit>>,
 
@@ -121,6 +137,7 @@ impl LoadStoreUnit {
         // mem_controller: Arc<dyn mcu::MemoryController>,
         mem_controller: MC,
         stats: Arc<Mutex<stats::PerKernel>>,
+        shared_l1: Option<cache::shared::Ref<stats::cache::PerKernel>>,
     ) -> Self
     where
         MC: mcu::MemoryController + Clone,
@@ -132,6 +149,7 @@ impl LoadStoreUnit {
             None,
             pipeline_depth,
             config.clone(),
+            Arc::clone(&stats),
             0,
         );
         debug_assert!(config.shared_memory_latency > 1);
@@ -145,44 +163,79 @@ impl LoadStoreUnit {
                 l1_latency_queue =
                     box_slice![box_slice![None; l1_config.l1_latency]; l1_config.l1_banks];
 
-                // initialize l1 data cache
-                let cache_stats = Arc::new(Mutex::new(stats::cache::PerKernel::default()));
-                // let mem_controller = crate::mcu::MemoryControllerUnit::new(&config).unwrap();
-
                 let cache_controller = cache::controller::pascal::L1DataCacheController::new(
                     cache::Config::new(l1_config.inner.as_ref(), config.accelsim_compat),
                     l1_config,
                     config.accelsim_compat,
                 );
 
-                let mut data_cache: cache::data::Data<
-                    MC,
-                    // Arc<dyn mcu::MemoryController>,
-                    cache::controller::pascal::L1DataCacheController,
-                    stats::cache::PerKernel,
-                > = cache::data::Builder {
-                    // name: format!("ldst-unit-{cluster_id}-{core_id}-L1-DATA-CACHE"),
-                    name: format!(
+                let build_data_cache = |name: String| {
+                    // initialize l1 data cache
+                    let cache_stats = Arc::new(Mutex::new(stats::cache::PerKernel::default()));
+                    // let mem_controller = crate::mcu::MemoryControllerUnit::new(&config).unwrap();
+
+                    let mut data_cache: cache::data::Data<
+                        MC,
+                        // Arc<dyn mcu::MemoryController>,
+                        cache::controller::pascal::L1DataCacheController,
+                        stats::cache::PerKernel,
+                    > = cache::data::Builder {
+                        name,
+                        // core_id,
+                        // cluster_id,
+                        stats: cache_stats,
+                        config: Arc::clone(&config),
+                        mem_controller: mem_controller.clone(),
+                        // &(mem_controller as Arc<dyn mcu::MemoryController>),
+                        // mem_controller: Arc::clone(&mem_controller),
+                        cache_controller: cache_controller.clone(),
+                        cache_config: Arc::clone(&l1_config.inner),
+                        write_alloc_type: AccessKind::L1_WR_ALLOC_R,
+                        write_back_type: AccessKind::L1_WRBK_ACC,
+                    }
+                    .build();
+                    data_cache.set_top_port(mem_port.clone());
+                    // let _: &dyn cache::Cache<stats::cache::PerKernel> = &data_cache;
+                    data_cache
+                };
+
+                if let Some(shared_l1) = shared_l1 {
+                    // the L1 is shared by all cores of the cluster: the first
+                    // core builds the cache, the remaining cores attach to it.
+                    // misses go out through the port of the building core.
+                    let primary = {
+                        let mut state = shared_l1.lock();
+                        let primary = state.is_none();
+                        if primary {
+                            let data_cache = build_data_cache(format!(
+                                "cluster-{cluster_id}-{}",
+                                style("SHARED-L1D-CACHE").green()
+                            ));
+                            *state = Some(cache::shared::State::new(
+                                Box::new(data_cache),
+                                config.l1_cache_shared_ports,
+                            ));
+                        }
+                        primary
+                    };
+                    let port = cache::shared::Port::new(
+                        format!(
+                            "ldst-unit-{cluster_id}-{core_id}-{}",
+                            style("SHARED-L1D-PORT").green()
+                        ),
+                        core_id,
+                        primary,
+                        cache_controller,
+                        shared_l1,
+                    );
+                    Some(Box::new(port))
+                } else {
+                    let data_cache = build_data_cache(format!(
                         "ldst-unit-{cluster_id}-{core_id}-{}",
                         style("L1D-CACHE").green()
-                    ),
-                    // core_id,
-                    // cluster_id,
-                    stats: cache_stats,
-                    config: Arc::clone(&config),
-                    mem_controller: mem_controller.clone(),
-                    // &(mem_controller as Arc<dyn mcu::MemoryController>),
-                    // mem_controller: Arc::clone(&mem_controller),
-                    cache_controller,
-                    cache_config: Arc::clone(&l1_config.inner),
-                    write_alloc_type: AccessKind::L1_WR_ALLOC_R,
-                    write_back_type: AccessKind::L1_WRBK_ACC,
+                    ));
+                    Some(Box::new(data_cache))
                 }
-                .build();
-                data_cache.set_top_port(mem_port.clone());
-                // let _: &dyn cache::Cache<stats::cache::PerKernel> = &data_cache;
-
-                Some(Box::new(data_cache))
             } else {
                 None
             };
@@ -202,6 +255,9 @@ impl LoadStoreUnit {
             response_fifo: VecDeque::new(),
             mem_port,
             inner,
+            shared_dispatch_reg: None,
+            shared_path_utilization: stats::utilization::Counters::default(),
+            global_path_utilization: stats::utilization::Counters::default(),
             config,
             mem_controller: Arc::new(mem_controller),
             stats,
@@ -232,9 +288,26 @@ impl LoadStoreUnit {
         }
     }
 
+    /// Invalidate the L1 data cache line holding `addr`, if present.
+    pub fn invalidate_addr(&mut self, addr: address) -> bool {
+        self.data_l1
+            .as_mut()
+            .is_some_and(|l1| l1.invalidate_addr(addr))
+    }
+
     pub fn fill(&mut self, mut fetch: MemFetch) {
         fetch.status = mem_fetch::Status::IN_SHADER_LDST_RESPONSE_FIFO;
         self.response_fifo.push_back(fetch);
+
+        let mut stats = self.stats.lock();
+        let occupancy = stats
+            .no_kernel
+            .ldst_response_buffer_occupancy
+            .entry(self.core_id)
+            .or_default();
+        occupancy.high_water_mark = occupancy
+            .high_water_mark
+            .max(self.response_fifo.len() as u64);
     }
 
     pub fn writeback(&mut self, cycle: u64) {
@@ -293,6 +366,9 @@ impl LoadStoreUnit {
                     }
                 }
                 if instr_completed {
+                    self.warps[next_writeback.warp_id]
+                        .try_lock()
+                        .instruction_retired(next_writeback.uid);
                     crate::warp_inst_complete(&mut next_writeback, &self.stats);
                 }
             }
@@ -364,6 +440,7 @@ impl LoadStoreUnit {
                             //     m_next_global->get_access_warp_mask().count());
                         }
 
+                        mem_fetch::tracker::retired(&next_global);
                         self.next_writeback = next_global.instr;
                         serviced_client = Some(next_client_id);
                     }
@@ -373,6 +450,7 @@ impl LoadStoreUnit {
                     if let Some(ref mut data_l1) = self.data_l1 {
                         if let Some(fetch) = data_l1.next_access() {
                             log::trace!("l1 cache got ready access {} cycle={}", &fetch, cycle);
+                            mem_fetch::tracker::retired(&fetch);
                             self.next_writeback = fetch.instr;
                             serviced_client = Some(next_client_id);
                         }
@@ -404,7 +482,15 @@ impl LoadStoreUnit {
         kind: &mut MemStageAccessKind,
         _cycle: u64,
     ) -> bool {
-        let Some(dispatch_instr) = &mut self.inner.dispatch_reg else {
+        let dual_path = self.config.ldst_dual_path_issue;
+        let dispatch_reg = if dual_path {
+            // with dual-path issue, shared-memory operations dispatch
+            // through their own register
+            &mut self.shared_dispatch_reg
+        } else {
+            &mut self.inner.dispatch_reg
+        };
+        let Some(dispatch_instr) = dispatch_reg else {
             return true;
         };
         log::debug!("shared cycle for instruction: {}", &dispatch_instr);
@@ -419,16 +505,24 @@ impl LoadStoreUnit {
             return true;
         }
 
-        if dispatch_instr.dispatch_delay_cycles > 0 {
+        // each unit of path width processes one bank-conflict wave per
+        // cycle
+        let path_width = if dual_path {
+            self.config.ldst_shared_path_width
+        } else {
+            1
+        };
+        for _ in 0..path_width {
+            if dispatch_instr.dispatch_delay_cycles == 0 {
+                break;
+            }
             if let Some(ref l1_cache) = self.data_l1 {
                 let mut stats = l1_cache.per_kernel_stats().lock();
                 let kernel_stats = stats.get_mut(Some(dispatch_instr.kernel_launch_id));
                 kernel_stats.num_shared_mem_bank_accesses += 1;
             }
+            dispatch_instr.dispatch_delay_cycles -= 1;
         }
-
-        dispatch_instr.dispatch_delay_cycles =
-            dispatch_instr.dispatch_delay_cycles.saturating_sub(1);
         let has_stall = dispatch_instr.dispatch_delay_cycles > 0;
         if has_stall {
             *kind = MemStageAccessKind::S_MEM;
@@ -506,6 +600,19 @@ impl LoadStoreUnit {
             }
         }
 
+        if matches!(
+            dispatch_instr.cache_operator,
+            Some(
+                CacheOperator::Streaming
+                    | CacheOperator::LastUse
+                    | CacheOperator::Volatile
+                    | CacheOperator::WriteBack
+                    | CacheOperator::WriteThrough
+            )
+        ) {
+            crate::fidelity::approximated("cache operator falls back to default caching policy");
+        }
+
         // log::warn!("bypass l1={}", bypass_l1);
         let Some(access) = dispatch_instr.mem_access_queue.back() else {
             return true;
@@ -547,13 +654,29 @@ impl LoadStoreUnit {
             // } else {
             let mut mem_port = self.mem_port.lock();
 
-            let packet_size = if dispatch_instr.is_store() || dispatch_instr.is_atomic() {
-                access.size()
+            // with dual-path issue the global path injects up to its
+            // configured width of accesses per cycle
+            let path_width = if self.config.ldst_dual_path_issue {
+                self.config.ldst_global_path_width
             } else {
-                access.control_size()
+                1
             };
+            for _ in 0..path_width {
+                let dispatch_instr = self.inner.dispatch_reg.as_ref().unwrap();
+                let Some(access) = dispatch_instr.mem_access_queue.back() else {
+                    break;
+                };
+
+                let packet_size = if dispatch_instr.is_store() || dispatch_instr.is_atomic() {
+                    access.size()
+                } else {
+                    access.control_size()
+                };
+
+                if !mem_port.can_send(&[packet_size]) {
+                    break;
+                }
 
-            if mem_port.can_send(&[packet_size]) {
                 if dispatch_instr.is_load() {
                     for out_reg in dispatch_instr.outputs() {
                         let pending = &self.pending_writes[&dispatch_instr.warp_id];
@@ -714,11 +837,16 @@ impl LoadStoreUnit {
                     fetch.inject_cycle = Some(cycle);
 
                     let data_size = fetch.data_size();
+                    if let Some(ref l1_cache) = self.data_l1 {
+                        let mut stats = l1_cache.per_kernel_stats().lock();
+                        let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+                        kernel_stats.num_l1_cache_bank_accesses += 1;
+                    }
                     *slot = Some(fetch);
 
                     if is_store {
                         let inc_ack = if l1d_config.inner.mshr_kind == mshr::Kind::SECTOR_ASSOC {
-                            data_size / mem_sub_partition::SECTOR_SIZE
+                            data_size / l1d_config.inner.sector_size
                         } else {
                             1
                         };
@@ -796,7 +924,7 @@ impl LoadStoreUnit {
         if write_sent {
             let l1d_config = self.config.data_cache_l1.as_ref().unwrap();
             let inc_ack = if l1d_config.inner.mshr_kind == mshr::Kind::SECTOR_ASSOC {
-                fetch.data_size() / mem_sub_partition::SECTOR_SIZE
+                fetch.data_size() / l1d_config.inner.sector_size
             } else {
                 1
             };
@@ -851,17 +979,17 @@ impl LoadStoreUnit {
                 let mut events = Vec::new();
 
                 let l1_cache = self.data_l1.as_mut().unwrap();
-                let access_status =
-                    l1_cache.access(fetch.addr(), fetch.clone(), &mut events, cycle);
+                let outcome = l1_cache.access(fetch.addr(), fetch.clone(), &mut events, cycle);
 
-                let write_sent = cache::event::was_write_sent(&events);
-                let read_sent = cache::event::was_read_sent(&events);
-                let write_allocate_sent = cache::event::was_writeallocate_sent(&events);
+                let access_status = outcome.status;
+                let write_sent = outcome.write_sent;
+                let read_sent = outcome.read_sent;
+                let write_allocate_sent = outcome.write_allocate_sent;
 
                 log::debug!("l1 cache access for warp={:<2} {} => {access_status:?} cycle={} [write sent={write_sent}, read sent={read_sent}, wr allocate sent={write_allocate_sent}]", fetch.warp_id, &fetch, cycle);
 
                 let dec_ack = if l1_config.inner.mshr_kind == mshr::Kind::SECTOR_ASSOC {
-                    fetch.data_size() / mem_sub_partition::SECTOR_SIZE
+                    fetch.data_size() / l1_config.inner.sector_size
                 } else {
                     1
                 };
@@ -958,6 +1086,7 @@ impl LoadStoreUnit {
                             for _ in 0..dec_ack {
                                 self.store_ack(&fetch);
                             }
+                            mem_fetch::tracker::retired(&fetch);
                         }
                     }
                 }
@@ -1007,10 +1136,15 @@ impl LoadStoreUnit {
                         }
                     }
                     if completed {
+                        self.warps[instr.warp_id]
+                            .try_lock()
+                            .instruction_retired(instr.uid);
                         crate::warp_inst_complete(instr, &self.stats);
                     }
                 }
 
+                mem_fetch::tracker::retired(&fetch);
+
                 // let dec_ack = if l1_config.inner.mshr_kind == mshr::Kind::SECTOR_ASSOC {
                 //     fetch.data_size() / mem_sub_partition::SECTOR_SIZE
                 // } else {
@@ -1030,6 +1164,40 @@ impl LoadStoreUnit {
         }
     }
 
+    /// Drain the dispatch register of the shared-memory issue path.
+    ///
+    /// Mirrors the shared-memory handling of the single dispatch
+    /// register: loads enter the shared-memory pipeline, stores exit
+    /// the pipeline here.
+    fn drain_shared_path(&mut self) {
+        let Some(ref pipe_reg) = self.shared_dispatch_reg else {
+            return;
+        };
+        let warp_id = pipe_reg.warp_id;
+        if pipe_reg.is_load() {
+            let pipe_slot_idx = self.config.shared_memory_latency - 1;
+            let pipe_slot = &mut self.inner.pipeline_reg[pipe_slot_idx];
+            if pipe_slot.is_none() {
+                // new shared memory request
+                let dispatch_reg = self.shared_dispatch_reg.take();
+                register_set::move_warp(dispatch_reg, pipe_slot);
+            }
+        } else {
+            // stores exit pipeline here
+            self.warps[warp_id].try_lock().num_instr_in_pipeline -= 1;
+            let mut dispatch_reg = self.shared_dispatch_reg.take().unwrap();
+
+            // check for deadlocks due to scoreboard:
+            //
+            // make sure stores do not use destination registers
+            assert_eq!(dispatch_reg.outputs().count(), 0);
+            self.warps[warp_id]
+                .try_lock()
+                .instruction_retired(dispatch_reg.uid);
+            crate::warp_inst_complete(&mut dispatch_reg, &self.stats);
+        }
+    }
+
     #[must_use]
     pub fn pending_writes(&self, warp_id: usize, reg_id: u32) -> Option<usize> {
         let pending = self.pending_writes.get(&warp_id)?;
@@ -1097,7 +1265,11 @@ impl fu::SimdFunctionUnit for LoadStoreUnit
 
         // m_core->incmem_stat(m_core->get_config()->warp_size, 1);
 
-        self.inner.issue(instr);
+        if self.config.ldst_dual_path_issue && instr.memory_space == Some(MemorySpace::Shared) {
+            register_set::move_warp(Some(instr), &mut self.shared_dispatch_reg);
+        } else {
+            self.inner.issue(instr);
+        }
     }
 
     fn clock_multiplier(&self) -> usize {
@@ -1111,7 +1283,15 @@ impl fu::SimdFunctionUnit for LoadStoreUnit
             | ArchOp::TENSOR_CORE_LOAD_OP
             | ArchOp::STORE_OP
             | ArchOp::TENSOR_CORE_STORE_OP
-            | ArchOp::MEMORY_BARRIER_OP => self.inner.dispatch_reg.is_none(),
+            | ArchOp::MEMORY_BARRIER_OP => {
+                if self.config.ldst_dual_path_issue
+                    && instr.memory_space == Some(MemorySpace::Shared)
+                {
+                    self.shared_dispatch_reg.is_none()
+                } else {
+                    self.inner.dispatch_reg.is_none()
+                }
+            }
             _ => false,
         }
     }
@@ -1198,6 +1378,7 @@ impl crate::engine::cycle::Component for LoadStoreUnit {
                         || (self.config.perfect_mem && fetch.is_write())
                     {
                         self.store_ack(fetch);
+                        mem_fetch::tracker::retired(fetch);
                         self.response_fifo.pop_front();
                     } else {
                         // L1 cache is write evict:
@@ -1249,12 +1430,29 @@ impl crate::engine::cycle::Component for LoadStoreUnit {
         let mut stall_kind = MemStageStallKind::NO_RC_FAIL;
         let mut access_kind = MemStageAccessKind::C_MEM;
         let mut done = true;
-        done &= self.shared_cycle(&mut stall_kind, &mut access_kind, cycle);
+        let shared_done = self.shared_cycle(&mut stall_kind, &mut access_kind, cycle);
+        done &= shared_done;
         done &= self.constant_cycle(&mut stall_kind, &mut access_kind, cycle);
         done &= self.texture_cycle(&mut stall_kind, &mut access_kind, cycle);
-        done &= self.memory_cycle(&mut stall_kind, &mut access_kind, cycle);
-
-        if !done {
+        let memory_done = self.memory_cycle(&mut stall_kind, &mut access_kind, cycle);
+        done &= memory_done;
+
+        if self.config.ldst_dual_path_issue {
+            self.shared_path_utilization
+                .add_cycle(self.shared_dispatch_reg.is_some());
+            self.global_path_utilization
+                .add_cycle(self.inner.dispatch_reg.is_some());
+
+            // the paths stall independently: a bank conflict on the
+            // shared path does not hold back the global path and vice
+            // versa
+            if shared_done {
+                self.drain_shared_path();
+            }
+            if !memory_done {
+                return;
+            }
+        } else if !done {
             // log stall types and return
             debug_assert_ne!(stall_kind, MemStageStallKind::NO_RC_FAIL);
             // num_stall_scheduler_mem += 1;
@@ -1300,6 +1498,9 @@ impl crate::engine::cycle::Component for LoadStoreUnit {
                     let mut dispatch_reg = simd_unit.dispatch_reg.take().unwrap();
 
                     if !has_pending_requests {
+                        self.warps[warp_id]
+                            .try_lock()
+                            .instruction_retired(dispatch_reg.uid);
                         crate::warp_inst_complete(&mut dispatch_reg, &self.stats);
 
                         self.scoreboard.try_write().release_all(&dispatch_reg);
@@ -1316,6 +1517,9 @@ impl crate::engine::cycle::Component for LoadStoreUnit {
                 //
                 // make sure stores do not use destination registers
                 assert_eq!(dispatch_reg.outputs().count(), 0);
+                self.warps[warp_id]
+                    .try_lock()
+                    .instruction_retired(dispatch_reg.uid);
                 crate::warp_inst_complete(&mut dispatch_reg, &self.stats);
             }
         }
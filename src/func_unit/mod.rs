@@ -10,10 +10,10 @@ pub use load_store::LoadStoreUnit;
 pub use sfu::SFU;
 pub use sp::SPUnit;
 
+use crate::sync::{Arc, Mutex};
 use crate::{config, instruction::WarpInstruction, register_set, warp};
 use bitvec::{array::BitArray, BitArr};
 use register_set::Access;
-use std::sync::Arc;
 
 pub const MAX_ALU_LATENCY: usize = 512;
 pub type OccupiedSlots = BitArr!(for MAX_ALU_LATENCY);
@@ -49,6 +49,7 @@ pub struct PipelinedSimdUnit {
     pub dispatch_reg: Option<WarpInstruction>,
     pub occupied: OccupiedSlots,
     pub config: Arc<config::GPU>,
+    pub stats: Arc<Mutex<stats::PerKernel>>,
 }
 
 impl std::fmt::Display for PipelinedSimdUnit {
@@ -70,6 +71,7 @@ impl PipelinedSimdUnit {
         result_port: Option<register_set::Ref>,
         depth: usize,
         config: Arc<config::GPU>,
+        stats: Arc<Mutex<stats::PerKernel>>,
         issue_reg_id: usize,
     ) -> Self {
         let pipeline_reg = (0..depth).map(|_| None).collect();
@@ -84,6 +86,7 @@ impl PipelinedSimdUnit {
             dispatch_reg: None,
             occupied: BitArray::ZERO,
             config,
+            stats,
         }
     }
 
@@ -155,18 +158,32 @@ impl crate::engine::cycle::Component for PipelinedSimdUnit {
         );
 
         if let Some(result_port) = &mut self.result_port {
-            if let Some(pipe_reg) = self.pipeline_reg[0].take() {
+            if self.pipeline_reg[0].is_some() {
                 // move to EX_WB result port
                 // let mut result_port = result_port.borrow_mut();
                 let mut result_port = result_port.try_lock();
-                // let msg = format!(
-                //     "{}: move pipeline[0] to result port {:?}",
-                //     self.name, result_port.stage
-                // );
-                result_port.move_in_from(Some(pipe_reg));
+                if result_port.has_free() {
+                    // let msg = format!(
+                    //     "{}: move pipeline[0] to result port {:?}",
+                    //     self.name, result_port.stage
+                    // );
+                    let pipe_reg = self.pipeline_reg[0].take();
+                    result_port.move_in_from(pipe_reg);
 
-                debug_assert!(self.active_insts_in_pipeline > 0);
-                self.active_insts_in_pipeline -= 1;
+                    debug_assert!(self.active_insts_in_pipeline > 0);
+                    self.active_insts_in_pipeline -= 1;
+                } else {
+                    // All slots of the EX_WB stage are taken by other
+                    // functional units: the completed instruction must wait
+                    // for the writeback stage to drain a slot.
+                    let instr = self.pipeline_reg[0].as_ref().unwrap();
+                    let mut stats = self.stats.lock();
+                    let kernel_stats = stats.get_mut(Some(instr.kernel_launch_id));
+                    *kernel_stats
+                        .num_writeback_stalls
+                        .entry(self.name.clone())
+                        .or_insert(0) += 1;
+                }
             }
         }
         debug_assert_eq!(
@@ -175,6 +192,11 @@ impl crate::engine::cycle::Component for PipelinedSimdUnit {
         );
         if self.active_insts_in_pipeline > 0 {
             for stage in 0..(self.pipeline_reg.len() - 1) {
+                if self.pipeline_reg[stage].is_some() {
+                    // a writeback stall propagates backwards through the
+                    // pipeline: do not overwrite the stalled instruction
+                    continue;
+                }
                 let current = self.pipeline_reg[stage + 1].take();
                 let next = &mut self.pipeline_reg[stage];
                 // let msg = format!("{} moving to next slot in pipeline register", self.name);
@@ -12,7 +12,7 @@ impl SFU {
         id: usize,
         result_port: register_set::Ref,
         config: Arc<config::GPU>,
-        _stats: &Arc<Mutex<stats::PerKernel>>,
+        stats: &Arc<Mutex<stats::PerKernel>>,
         issue_reg_id: usize,
     ) -> Self {
         let pipeline_depth = config.max_sfu_latency;
@@ -22,6 +22,7 @@ impl SFU {
             Some(result_port),
             pipeline_depth,
             config.clone(),
+            Arc::clone(stats),
             issue_reg_id,
         );
 
@@ -2,7 +2,6 @@ use crate::{
     address, barrier, config,
     kernel::Kernel,
     mem_fetch,
-    mem_sub_partition::MAX_MEMORY_ACCESS_SIZE,
     opcodes::{pascal, ArchOp, Op, Opcode},
     operand_collector as opcoll, warp,
 };
@@ -279,12 +278,16 @@ impl WarpInstruction {
         }
 
         // fill latency and init latency
-        let (mut latency, initiation_interval) = config.get_latencies(opcode.category);
+        let (mut latency, mut initiation_interval) = config.get_latencies(opcode.category);
 
         // temp workaround for per instruction pascal latencies.
         // TODO: make this configurable and discover the instruction latencies using a
         // custom disassembler in the future
-        if !config.accelsim_compat {
+        if !config.accelsim_compat && opcode.op.is_warp_level_primitive() {
+            // warp-level primitives exchange data across lanes and are
+            // not generic integer ops, even though their category says so
+            (latency, initiation_interval) = config.trace_opcode_latency_initiation_shfl;
+        } else if !config.accelsim_compat {
             latency = match opcode.op {
                 Op::IMUL | Op::IMAD => 86,
                 Op::DADD | Op::DMUL | Op::DFMA | Op::Pascal(pascal::op::Op::DMNMX) => 8,
@@ -826,37 +829,21 @@ impl WarpInstruction {
                     && thread.mem_req_addr[access] != 0
                 {
                     let addr = thread.mem_req_addr[access];
-                    let block_addr = line_size_based_tag_func(addr, segment_size);
-                    // 32-byte chunk within in a 128-byte accesses by this thread
-                    let chunk = (addr & 127) / 32;
-                    let tx = subwarp_transactions.entry(block_addr).or_default();
-
-                    tx.chunk_mask.set(chunk as usize, true);
-                    tx.active_mask.set(thread_id, true);
-                    let idx = addr & 127;
-
-                    for i in 0..data_size_coalesced {
-                        let next_idx = idx as usize + i as usize;
-                        if next_idx < (MAX_MEMORY_ACCESS_SIZE as usize) {
-                            tx.byte_mask.set(next_idx, true);
-                        }
-                    }
 
-                    // it seems like in trace driven, a thread can write to more than one
-                    // segment handle
-                    let coalesced_end_addr = addr + u64::from(data_size_coalesced) - 1;
-                    if block_addr != line_size_based_tag_func(coalesced_end_addr, segment_size) {
-                        let block_addr = line_size_based_tag_func(coalesced_end_addr, segment_size);
-                        let chunk = (coalesced_end_addr & 127) / 32;
+                    // a vectorized (8 or 16 byte) access may span multiple
+                    // 32-byte chunks and, in trace driven mode, even cross
+                    // into the next segment, so every byte is attributed to
+                    // its chunk and segment individually
+                    for byte_offset in 0..u64::from(data_size_coalesced) {
+                        let byte_addr = addr + byte_offset;
+                        let block_addr = line_size_based_tag_func(byte_addr, segment_size);
+                        // 32-byte chunk within in a 128-byte accesses by this thread
+                        let chunk = (byte_addr & 127) / 32;
                         let tx = subwarp_transactions.entry(block_addr).or_default();
+
                         tx.chunk_mask.set(chunk as usize, true);
                         tx.active_mask.set(thread_id, true);
-                        for i in 0..data_size_coalesced {
-                            let next_idx = idx as usize + i as usize;
-                            if next_idx < (MAX_MEMORY_ACCESS_SIZE as usize) {
-                                tx.byte_mask.set(next_idx, true);
-                            }
-                        }
+                        tx.byte_mask.set((byte_addr & 127) as usize, true);
                     }
 
                     access += 1;
@@ -989,3 +976,96 @@ impl WarpInstruction {
         .build()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::diff;
+
+    fn global_load(data_size: u32, addrs: &[(usize, address)]) -> WarpInstruction {
+        let mut active_mask = warp::ActiveMask::ZERO;
+        let mut threads: Vec<PerThreadInfo> = (0..32).map(|_| PerThreadInfo::default()).collect();
+        for &(tid, addr) in addrs {
+            active_mask.set(tid, true);
+            threads[tid].mem_req_addr[0] = addr;
+        }
+        WarpInstruction {
+            uid: 0,
+            kernel_launch_id: 0,
+            warp_id: 0,
+            scheduler_id: None,
+            pc: 0,
+            trace_idx: 0,
+            opcode: Opcode {
+                op: Op::LDG,
+                category: ArchOp::LOAD_OP,
+            },
+            active_mask,
+            cache_operator: Some(CacheOperator::All),
+            memory_space: Some(MemorySpace::Global),
+            barrier: None,
+            threads,
+            mem_access_queue: VecDeque::new(),
+            latency: 1,
+            issue_cycle: None,
+            initiation_interval: 1,
+            dispatch_delay_cycles: 0,
+            data_size,
+            instr_width: 16,
+            is_atomic: false,
+            outputs: [None; 8],
+            inputs: [None; 24],
+            src_arch_reg: [None; opcoll::MAX_REG_OPERANDS],
+            dest_arch_reg: [None; opcoll::MAX_REG_OPERANDS],
+        }
+    }
+
+    #[test]
+    fn test_float4_load_spans_two_sectors() {
+        // Pascal uses 32 byte (sector) segments for vectorized accesses
+        let config = config::GPU::default();
+        let instr = global_load(16, &[(0, 1048)]);
+        let accesses = instr.memory_coalescing_arch(false, AccessKind::GLOBAL_ACC_R, &config);
+        let have: Vec<_> = accesses
+            .iter()
+            .map(|access| (access.addr, access.req_size_bytes))
+            .collect();
+        diff::assert_eq!(have: have, want: vec![(1024, 32), (1056, 32)]);
+    }
+
+    #[test]
+    fn test_float4_load_spans_two_chunks_of_a_line() {
+        // Fermi uses 128 byte line segments when the L1 is not skipped
+        let config = config::GPU {
+            coalescing_arch: config::Architecture::Fermi,
+            ..config::GPU::default()
+        };
+        let instr = global_load(16, &[(0, 1048)]);
+        let accesses = instr.memory_coalescing_arch(false, AccessKind::GLOBAL_ACC_R, &config);
+        let have: Vec<_> = accesses
+            .iter()
+            .map(|access| (access.addr, access.req_size_bytes))
+            .collect();
+        // both 32 byte chunks of the lower half are used
+        diff::assert_eq!(have: have, want: vec![(1024, 64)]);
+    }
+
+    #[test]
+    fn test_coalesced_float4_loads_fill_a_line() {
+        // eight adjacent float4 loads coalesce into a single 128 byte line
+        let config = config::GPU {
+            coalescing_arch: config::Architecture::Fermi,
+            ..config::GPU::default()
+        };
+        let addrs: Vec<_> = (0..8)
+            .map(|tid| (tid, 1024 + 16 * tid as address))
+            .collect();
+        let instr = global_load(16, &addrs);
+        let accesses = instr.memory_coalescing_arch(false, AccessKind::GLOBAL_ACC_R, &config);
+        let have: Vec<_> = accesses
+            .iter()
+            .map(|access| (access.addr, access.req_size_bytes))
+            .collect();
+        diff::assert_eq!(have: have, want: vec![(1024, 128)]);
+    }
+}
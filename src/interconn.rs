@@ -1,7 +1,12 @@
 use super::mem_fetch;
 use crate::sync::{Arc, Mutex, RwLock};
 use console::style;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+
+/// Flit size in bytes.
+///
+/// This matches the accelsim `icnt_flit_size` default.
+pub const FLIT_SIZE: u32 = 32;
 
 /// Interconnect is a general interconnect
 ///
@@ -16,6 +21,14 @@ pub trait Interconnect<P>: std::fmt::Debug + Send + Sync + 'static {
 
     fn has_buffer(&self, _dest: usize, _size: u32) -> bool;
 
+    /// Per-link traffic statistics.
+    fn traffic(&self) -> stats::interconn::Interconn {
+        stats::interconn::Interconn::default()
+    }
+
+    /// Reset the per-link traffic statistics.
+    fn reset_traffic(&self) {}
+
     fn dest_queue(&self, _dest: usize) -> &Mutex<VecDeque<P>>;
 
     fn transfer(&self);
@@ -23,7 +36,13 @@ pub trait Interconnect<P>: std::fmt::Debug + Send + Sync + 'static {
 
 #[derive(Debug)]
 pub struct ToyInterconnect<P> {
-    // pub capacity: Option<usize>,
+    /// Credits per cluster for the response path.
+    ///
+    /// Packets destined for a cluster only enter the interconnect when
+    /// fewer than `capacity` packets are already buffered for it, so the
+    /// output queues towards the clusters stay bounded. `None` disables
+    /// flow control.
+    pub capacity: Option<usize>,
     pub num_cores: usize,
     pub num_mems: usize,
     pub num_subnets: usize,
@@ -33,6 +52,8 @@ pub struct ToyInterconnect<P> {
     // input_queue: Vec<Vec<Vec<Mutex<VecDeque<P>>>>>,
     pub output_queue: Vec<Vec<Vec<Mutex<VecDeque<P>>>>>,
     pub in_flight: RwLock<u64>,
+    /// Traffic per source and destination device pair.
+    traffic: Mutex<HashMap<(usize, usize), stats::interconn::LinkTraffic>>,
     // deviceID to icntID map
     // deviceID : Starts from 0 for shaders and then continues until mem nodes
     // which starts at location n_shader and then continues to n_shader+n_mem (last device)
@@ -42,6 +63,15 @@ pub struct ToyInterconnect<P> {
 impl<P> ToyInterconnect<P> {
     #[must_use]
     pub fn new(num_cores: usize, num_mems: usize) -> ToyInterconnect<P> {
+        Self::with_capacity(num_cores, num_mems, None)
+    }
+
+    #[must_use]
+    pub fn with_capacity(
+        num_cores: usize,
+        num_mems: usize,
+        capacity: Option<usize>,
+    ) -> ToyInterconnect<P> {
         let num_subnets = 2;
         let num_nodes = num_cores + num_mems;
         let num_classes = 1;
@@ -67,7 +97,7 @@ impl<P> ToyInterconnect<P> {
             }
         }
         Self {
-            // capacity,
+            capacity,
             num_cores,
             num_mems,
             num_subnets,
@@ -77,6 +107,7 @@ impl<P> ToyInterconnect<P> {
             // input_queue,
             output_queue,
             in_flight: RwLock::new(0),
+            traffic: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -112,7 +143,7 @@ where
 
     // #[inline]
     fn push(&self, src_device: usize, dest_device: usize, packet: P, size: u32) {
-        assert!(self.has_buffer(src_device, size));
+        assert!(self.has_buffer(dest_device, size));
 
         let is_memory_node = self.num_subnets > 1 && dest_device >= self.num_cores;
         let subnet = usize::from(is_memory_node);
@@ -122,6 +153,15 @@ where
         );
 
         *self.in_flight.write() += 1;
+
+        {
+            let mut traffic = self.traffic.lock();
+            let link = traffic.entry((src_device, dest_device)).or_default();
+            link.packets += 1;
+            link.flits += u64::from(size.div_ceil(FLIT_SIZE));
+            link.bytes += u64::from(size);
+        }
+
         let mut queue = self.output_queue[subnet][dest_device][0].lock();
         queue.push_back(packet);
     }
@@ -160,16 +200,29 @@ where
     }
 
     // #[inline]
-    fn has_buffer(&self, _device: usize, _size: u32) -> bool {
-        true
-        // let Some(capacity) = self.capacity else {
-        //     return true;
-        // };
-        //
-        // // TODO: using input queue makes no sense as we push into output directly
-        // let subnet = usize::from(device >= self.num_cores);
-        // let queue = self.input_queue[subnet][device][0]lock();
-        // queue.len() <= capacity
+    fn has_buffer(&self, device: usize, _size: u32) -> bool {
+        let Some(capacity) = self.capacity else {
+            return true;
+        };
+
+        // only the response path towards the clusters is flow controlled:
+        // each cluster grants one credit per slot of its ejection buffer
+        if device >= self.num_cores {
+            return true;
+        }
+        let queue = self.output_queue[0][device][0].lock();
+        queue.len() < capacity
+    }
+
+    // #[inline]
+    fn traffic(&self) -> stats::interconn::Interconn {
+        stats::interconn::Interconn {
+            links: self.traffic.lock().clone(),
+        }
+    }
+
+    fn reset_traffic(&self) {
+        self.traffic.lock().clear();
     }
 }
 
@@ -25,6 +25,63 @@ pub trait Kernel: std::fmt::Debug + std::fmt::Display + Send + Sync + 'static {
 
     fn num_running_blocks(&self) -> usize;
 
+    /// Number of blocks of the launch grid that are skipped because they
+    /// do not execute any instructions.
+    ///
+    /// Such blocks do not appear in the trace and are never issued to a
+    /// core, but still count towards the launched grid.
+    fn num_skipped_blocks(&self) -> u64 {
+        0
+    }
+
+    /// Total number of loop back-edges observed in the trace so far.
+    ///
+    /// A warp jumping back to a lower instruction offset counts as one
+    /// loop iteration, which approximates the per-iteration behavior
+    /// of persistent kernel loops.
+    fn num_trace_loop_iterations(&self) -> u64 {
+        0
+    }
+
+    /// Largest number of loop back-edges observed for a single warp.
+    fn max_trace_loop_iterations(&self) -> u64 {
+        0
+    }
+
+    /// Loops detected in the trace so far, keyed by the program counter
+    /// range of the loop body.
+    fn trace_loops(&self) -> stats::Loops {
+        stats::Loops::default()
+    }
+
+    /// Number of malformed trace records skipped in lenient mode.
+    fn num_malformed_trace_records(&self) -> u64 {
+        0
+    }
+
+    /// Digest of the store trace entries replayed so far.
+    ///
+    /// Compared against the digest captured by the tracer
+    /// ([`model::command::KernelLaunch::store_value_digest`]) when the
+    /// kernel completes, such that model changes that corrupt the
+    /// functional path are caught.
+    fn replayed_store_digest(&self) -> Option<u64> {
+        None
+    }
+
+    /// Record the cycle in which the kernel entered the launch window.
+    fn set_queued(&self, _cycle: u64) {}
+
+    /// Cycle in which the kernel entered the launch window.
+    fn queued_cycle(&self) -> Option<u64> {
+        None
+    }
+
+    /// Cycle in which the kernel was launched.
+    fn start_cycle(&self) -> Option<u64> {
+        None
+    }
+
     fn running(&self) -> bool {
         self.num_running_blocks() > 0
     }
@@ -43,6 +100,7 @@ pub mod trace {
     use crate::{config, instruction, opcodes, warp};
     use color_eyre::Help;
     use model::command::KernelLaunch;
+    use std::collections::HashMap;
     use std::path::Path;
     use trace_model as model;
 
@@ -60,6 +118,7 @@ pub mod trace {
         pub opcodes: &'static opcodes::OpcodeMap,
         pub config: KernelLaunch,
         pub memory_only: bool,
+        pub queued_cycle: Mutex<Option<u64>>,
         pub start_cycle: Mutex<Option<u64>>,
         pub completed_cycle: Mutex<Option<u64>>,
         pub start_time: Mutex<Option<std::time::Instant>>,
@@ -69,6 +128,12 @@ pub mod trace {
         next_block: RwLock<Option<model::Dim>>,
         current_block: RwLock<Option<model::Dim>>,
         running_blocks: RwLock<usize>,
+        num_traced_blocks: RwLock<u64>,
+        num_malformed_records: std::sync::Arc<std::sync::atomic::AtomicU64>,
+        trace_loop_iterations: RwLock<u64>,
+        max_warp_loop_iterations: RwLock<u64>,
+        trace_loops: Mutex<stats::Loops>,
+        replayed_store_digest: Mutex<u64>,
     }
 
     impl<T> PartialEq for KernelTrace<T>
@@ -126,11 +191,23 @@ pub mod trace {
             self.opcodes.get(opcode)
         }
 
+        fn set_queued(&self, cycle: u64) {
+            *self.queued_cycle.lock() = Some(cycle);
+        }
+
+        fn queued_cycle(&self) -> Option<u64> {
+            *self.queued_cycle.lock()
+        }
+
         fn set_started(&self, cycle: u64) {
             *self.start_time.lock() = Some(std::time::Instant::now());
             *self.start_cycle.lock() = Some(cycle);
         }
 
+        fn start_cycle(&self) -> Option<u64> {
+            *self.start_cycle.lock()
+        }
+
         fn set_completed(&self, cycle: u64) {
             *self.completed_time.lock() = Some(std::time::Instant::now());
             *self.completed_cycle.lock() = Some(cycle);
@@ -176,6 +253,37 @@ pub mod trace {
             *self.running_blocks.read()
         }
 
+        fn replayed_store_digest(&self) -> Option<u64> {
+            Some(*self.replayed_store_digest.lock())
+        }
+
+        fn num_trace_loop_iterations(&self) -> u64 {
+            *self.trace_loop_iterations.try_read()
+        }
+
+        fn max_trace_loop_iterations(&self) -> u64 {
+            *self.max_warp_loop_iterations.try_read()
+        }
+
+        fn trace_loops(&self) -> stats::Loops {
+            self.trace_loops.lock().clone()
+        }
+
+        fn num_malformed_trace_records(&self) -> u64 {
+            self.num_malformed_records
+                .load(std::sync::atomic::Ordering::Relaxed)
+        }
+
+        fn num_skipped_blocks(&self) -> u64 {
+            if self.next_block.try_read().is_some() {
+                // cannot tell how many blocks are missing from the trace
+                // before the trace stream is exhausted
+                return 0;
+            }
+            let num_traced_blocks = *self.num_traced_blocks.try_read();
+            self.config.grid.size().saturating_sub(num_traced_blocks)
+        }
+
         // #[inline]
         fn current_block(&self) -> Option<model::Point> {
             let current_block = self.current_block.try_read().clone()?;
@@ -200,6 +308,8 @@ pub mod trace {
                 return false;
             };
 
+            *self.num_traced_blocks.try_write() += 1;
+
             log::info!(
                 "{} ({}) issue block {}/{}",
                 self.name(),
@@ -208,19 +318,62 @@ pub mod trace {
                 self.config.grid,
             );
 
+            // per-warp loop back-edges of this block: a warp jumping back to a
+            // lower instruction offset marks the next loop iteration
+            let mut last_instr_offset: HashMap<usize, u32> = HashMap::new();
+            let mut loop_iterations: HashMap<usize, u64> = HashMap::new();
+
+            // back-edges of this block keyed by the (head, tail) offset
+            // range they span, and the per-offset (total, memory)
+            // instruction counts used to attribute instructions to the
+            // loop bodies
+            let mut back_edges: HashMap<(u32, u32), u64> = HashMap::new();
+            let mut instructions_per_offset: HashMap<u32, (u64, u64)> = HashMap::new();
+
             loop {
                 let Some(entry) = &trace.peek() else {
-                break;
-            };
+                    break;
+                };
                 if entry.block_id != current_block {
                     // println!("stopping with peek={:#?}", entry);
                     break;
                 }
 
                 let warp_id = entry.warp_id_in_block as usize;
+
+                match last_instr_offset.insert(warp_id, entry.instr_offset) {
+                    Some(last_offset) if entry.instr_offset < last_offset => {
+                        *loop_iterations.entry(warp_id).or_insert(0) += 1;
+                        *back_edges
+                            .entry((entry.instr_offset, last_offset))
+                            .or_insert(0) += 1;
+                    }
+                    _ => {}
+                }
+
+                let (count, mem_count) = instructions_per_offset
+                    .entry(entry.instr_offset)
+                    .or_insert((0, 0));
+                *count += 1;
+                if entry.instr_is_mem {
+                    *mem_count += 1;
+                }
+
+                // fold replayed stores into the digest that is checked
+                // against the tracer digest on kernel completion
+                if let Some(contribution) = entry.store_digest_contribution() {
+                    let mut digest = self.replayed_store_digest.lock();
+                    *digest = digest.wrapping_add(contribution);
+                }
+
                 let instr = instruction::WarpInstruction::from_trace(self, entry, config);
 
-                if !self.memory_only || instr.is_memory_instruction() {
+                // in memory-only mode, compute instructions are kept when
+                // they are modeled as scoreboard updates
+                if !self.memory_only
+                    || instr.is_memory_instruction()
+                    || config.memory_only_compute_latency.is_some()
+                {
                     let warp = warps.get_mut(warp_id).unwrap();
                     let mut warp = warp.try_lock();
                     log::trace!(
@@ -236,6 +389,25 @@ pub mod trace {
                 trace.next();
             }
 
+            let mut trace_loops = self.trace_loops.lock();
+            for ((head, tail), iterations) in back_edges {
+                let stat = trace_loops.get_mut(head as usize, tail as usize);
+                stat.iterations += iterations;
+                for (&offset, &(count, mem_count)) in &instructions_per_offset {
+                    if (head..=tail).contains(&offset) {
+                        stat.num_instructions += count;
+                        stat.num_memory_instructions += mem_count;
+                    }
+                }
+            }
+            drop(trace_loops);
+
+            *self.trace_loop_iterations.try_write() += loop_iterations.values().sum::<u64>();
+            let max_iterations = loop_iterations.values().copied().max().unwrap_or(0);
+            let mut max_warp_loop_iterations = self.max_warp_loop_iterations.try_write();
+            *max_warp_loop_iterations = (*max_warp_loop_iterations).max(max_iterations);
+            drop(max_warp_loop_iterations);
+
             let next_block = trace.peek().map(|entry| entry.block_id.clone());
             *self.next_block.try_write() = next_block.clone();
 
@@ -255,10 +427,115 @@ pub mod trace {
         }
     }
 
+    impl<T> KernelTrace<T>
+    where
+        T: Iterator<Item = model::MemAccessTraceEntry>,
+    {
+        /// Device id the kernel was traced on.
+        ///
+        /// A kernel executes on exactly one device, so the device id of
+        /// the first trace entry identifies the device of the launch.
+        pub fn device_id(&self) -> Option<u32> {
+            self.trace.try_write().peek().map(|entry| entry.device_id)
+        }
+
+        /// Take the next entry from the trace stream.
+        ///
+        /// Used by the cache-only replay mode, which consumes the trace
+        /// in trace order instead of issuing threadblocks to cores.
+        pub fn next_trace_entry(&self) -> Option<model::MemAccessTraceEntry> {
+            self.trace.try_write().next()
+        }
+    }
+
     pub type TraceIter = crossbeam::channel::IntoIter<model::MemAccessTraceEntry>;
 
+    /// Byte-counting reader used to report the offset of malformed
+    /// records.
+    struct CountingReader<R> {
+        inner: R,
+        bytes_read: std::rc::Rc<std::cell::Cell<u64>>,
+    }
+
+    impl<R: std::io::Read> std::io::Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.bytes_read.set(self.bytes_read.get() + read as u64);
+            Ok(read)
+        }
+    }
+
+    /// Streaming decoder for the trace record sequence.
+    ///
+    /// Each record is first captured as a generic value and only then
+    /// decoded into a trace entry, such that a record that does not
+    /// match the trace schema can be skipped (lenient mode) or reported
+    /// with its exact position ([`model::ReadError`]).
+    struct TraceSeqVisitor {
+        lenient: bool,
+        bytes_read: std::rc::Rc<std::cell::Cell<u64>>,
+        num_malformed: std::sync::Arc<std::sync::atomic::AtomicU64>,
+        trace_tx: crossbeam::channel::Sender<model::MemAccessTraceEntry>,
+    }
+
+    impl<'de> serde::de::Visitor<'de> for TraceSeqVisitor {
+        type Value = ();
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "a sequence of trace records")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let mut record_index = 0;
+            let mut context: Option<model::RecordContext> = None;
+            loop {
+                let offset = self.bytes_read.get();
+                // a record that is not even well-formed msgpack aborts
+                // the stream: there is no record boundary to skip to
+                let Some(record) = seq.next_element::<serde_json::Value>()? else {
+                    break;
+                };
+                match serde_json::from_value::<model::MemAccessTraceEntry>(record) {
+                    Ok(entry) => {
+                        context = Some(model::RecordContext {
+                            kernel_id: entry.kernel_id,
+                            block_id: entry.block_id.clone(),
+                            warp_id_in_block: entry.warp_id_in_block,
+                        });
+                        self.trace_tx.send(entry).unwrap();
+                    }
+                    Err(err) => {
+                        let err = model::ReadError::MalformedRecord {
+                            record_index,
+                            offset,
+                            schema_field: model::schema_field(&err.to_string()),
+                            context: context.clone(),
+                            source: Box::new(err),
+                        };
+                        if self.lenient {
+                            self.num_malformed
+                                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            log::warn!("skipping {err}");
+                        } else {
+                            Err(err).suggestion("maybe the traces does not match the most recent binary trace format, try re-generating the traces (or use --lenient to skip malformed records).").unwrap()
+                        }
+                    }
+                }
+                record_index += 1;
+            }
+            Ok(())
+        }
+    }
+
     impl KernelTrace<TraceIter> {
-        pub fn new(config: model::command::KernelLaunch, traces_dir: impl AsRef<Path>) -> Self {
+        pub fn new(
+            config: model::command::KernelLaunch,
+            traces_dir: impl AsRef<Path>,
+            lenient: bool,
+        ) -> Self {
             log::info!(
                 "parsing kernel for launch {:#?} from {}",
                 &config,
@@ -270,34 +547,62 @@ pub mod trace {
                 .with_extension("msgpack");
 
             let (trace_tx, trace_rx) = crossbeam::channel::bounded(TRACE_BUF_SIZE);
+            let num_malformed_records = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+            let num_malformed = std::sync::Arc::clone(&num_malformed_records);
 
             // spawn a decoder thread
             let reader = utils::fs::open_readable(trace_path).unwrap();
             std::thread::spawn(move || {
                 use serde::Deserializer;
+                let bytes_read = std::rc::Rc::new(std::cell::Cell::new(0));
+                let reader = CountingReader {
+                    inner: reader,
+                    bytes_read: std::rc::Rc::clone(&bytes_read),
+                };
                 let mut reader = rmp_serde::Deserializer::new(reader);
-                let decoder: nvbit_io::Decoder<model::MemAccessTraceEntry, _> =
-                    nvbit_io::Decoder::new(|access: model::MemAccessTraceEntry| {
-                        trace_tx.send(access).unwrap();
-                    });
-
-                reader.deserialize_seq(decoder).suggestion("maybe the traces does not match the most recent binary trace format, try re-generating the traces.").unwrap();
+                let visitor = TraceSeqVisitor {
+                    lenient,
+                    bytes_read,
+                    num_malformed,
+                    trace_tx,
+                };
+
+                reader.deserialize_seq(visitor).suggestion("maybe the traces does not match the most recent binary trace format, try re-generating the traces.").unwrap();
             });
 
-            let trace = trace_rx.into_iter().peekable();
+            let mut trace = trace_rx.into_iter().peekable();
+            // traces occasionally contain launches with zero recorded
+            // instructions (e.g. all threads predicated off or tracing
+            // filtered): such kernels have no blocks to issue and
+            // complete immediately with zeroed stats
+            let first_block = trace.peek().map(|entry| entry.block_id.clone());
+            if first_block.is_none() {
+                log::warn!(
+                    "kernel {} ({}): trace contains no instructions",
+                    config.unmangled_name,
+                    config.id
+                );
+            }
             let opcodes = opcodes::get_opcode_map(&config).unwrap();
             Self {
                 opcodes,
                 config,
                 memory_only: false,
+                queued_cycle: Mutex::new(None),
                 start_cycle: Mutex::new(None),
                 start_time: Mutex::new(None),
                 completed_cycle: Mutex::new(None),
                 completed_time: Mutex::new(None),
                 trace: RwLock::new(trace),
                 current_block: RwLock::new(None),
-                next_block: RwLock::new(Some(0.into())),
+                next_block: RwLock::new(first_block),
                 running_blocks: RwLock::new(0),
+                num_traced_blocks: RwLock::new(0),
+                num_malformed_records,
+                trace_loop_iterations: RwLock::new(0),
+                max_warp_loop_iterations: RwLock::new(0),
+                trace_loops: Mutex::new(stats::Loops::default()),
+                replayed_store_digest: Mutex::new(0),
             }
         }
     }
@@ -15,31 +15,47 @@ pub mod allocation;
 pub mod arbitration;
 pub mod barrier;
 pub mod cache;
+pub mod cache_only;
+pub mod calibrate;
 pub mod cluster;
 pub mod config;
 pub mod core;
 pub mod deadlock;
+pub mod dep_graph;
 pub mod dram;
+pub mod dram_trace;
+pub mod energy;
 pub mod engine;
+pub mod export;
+pub mod fidelity;
 pub mod fifo;
 pub mod func_unit;
 pub mod instruction;
 pub mod interconn;
 pub mod kernel;
+pub mod logging;
 pub mod mcu;
 pub mod mem_fetch;
 pub mod mem_partition_unit;
 pub mod mem_sub_partition;
+pub mod mem_usage;
+pub mod migrate;
 pub mod mshr;
 pub mod opcodes;
 pub mod operand_collector;
 #[cfg(feature = "parallel")]
 pub mod parallel;
+pub mod pipeview;
+pub mod plugin;
 pub mod register_set;
+pub mod report;
+pub mod sass;
 pub mod scheduler;
 pub mod scoreboard;
+pub mod stats_writer;
 pub mod sync;
 pub mod tag_array;
+pub mod tenant;
 pub mod warp;
 
 #[cfg(test)]
@@ -195,6 +211,19 @@ pub fn parse_commands(path: impl AsRef<Path>) -> eyre::Result<Vec<Command>> {
     Ok(commands)
 }
 
+/// Copy the launch configuration of a kernel into its statistics.
+///
+/// Keeps the launch metadata next to the performance numbers, such that
+/// downstream analysis does not need to re-open the trace directory.
+pub fn set_launch_metadata(sim: &mut stats::Sim, launch: &trace_model::command::KernelLaunch) {
+    sim.grid_dim = (launch.grid.x, launch.grid.y, launch.grid.z);
+    sim.block_dim = (launch.block.x, launch.block.y, launch.block.z);
+    sim.shared_mem_bytes = launch.shared_mem_bytes;
+    sim.num_registers = launch.num_registers;
+    sim.stream_id = launch.stream_id;
+    sim.tenant = tenant::tenant_of(Some(launch.id as usize));
+}
+
 pub struct Optional<T>(Option<T>);
 
 impl<'a, T> std::fmt::Display for Optional<&'a T>
@@ -341,15 +370,97 @@ pub struct MockSimulator<I> {
     kernels: VecDeque<Arc<dyn Kernel>>,
     kernel_window_size: usize,
     busy_streams: VecDeque<u64>,
+    copy_engines: Vec<CopyEngine>,
+    /// Cycle in which the last async copy per stream completes.
+    ///
+    /// Kernels on a stream wait for the outstanding copies of that
+    /// stream before launching.
+    stream_ready_cycle: HashMap<u64, u64>,
+    /// Host timestamp of the last processed command, in nanoseconds.
+    ///
+    /// Used to replay host-side gaps between commands (see
+    /// [`config::GPU::host_gaps`]). `None` until a command with a host
+    /// timestamp has been processed.
+    last_command_host_timestamp: Option<u64>,
+    /// Cycle in which the last processed command was issued.
+    last_command_cycle: u64,
     cycle_limit: Option<u64>,
     log_after_cycle: Option<u64>,
-    // gpu_stall_icnt2sh: usize,
+    reset_stats_at_cycle: Option<u64>,
     partition_replies_in_parallel: usize,
 
     core_time: f64,
     dram_time: f64,
     icnt_time: f64,
     l2_time: f64,
+
+    /// Clock frequencies currently in effect.
+    ///
+    /// These start out as the configured frequencies and are updated by
+    /// the DVFS schedule.
+    clock_frequencies: config::ClockFrequencies,
+    /// Index of the next unapplied point of the DVFS schedule.
+    dvfs_schedule_idx: usize,
+    /// Frequency epochs for segmenting statistics.
+    dvfs_epochs: Vec<DvfsEpoch>,
+    /// State of the DRAM throttling model.
+    dram_throttle: DramThrottleState,
+    /// Index of the next unapplied injected core failure.
+    core_failure_idx: usize,
+    /// Blocks harvested from failed cores, waiting out their recovery
+    /// penalty before being re-issued on another core.
+    ///
+    /// Entries are `(ready cycle, block)` in failure order.
+    pending_block_migrations: VecDeque<(u64, crate::core::MigratedBlock)>,
+
+    /// User plugins receiving engine callbacks.
+    plugins: Vec<Arc<dyn plugin::Plugin>>,
+
+    /// Asynchronous stats writer serializing kernel stats as kernels
+    /// complete.
+    stats_writer: Option<stats_writer::StatsWriter>,
+}
+
+/// A frequency epoch of the DVFS schedule.
+///
+/// An epoch spans the cycles between two frequency switches and records
+/// the number of instructions committed while it was active, such that
+/// statistics can be segmented by frequency epoch.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DvfsEpoch {
+    pub epoch: usize,
+    pub clock_frequencies: config::ClockFrequencies,
+    pub start_cycle: u64,
+    /// Cycle at which the epoch ended (`None` for the last epoch).
+    pub end_cycle: Option<u64>,
+    /// Instructions committed during this epoch (`None` while still open).
+    pub instructions: Option<u64>,
+    #[serde(skip)]
+    start_instructions: u64,
+}
+
+/// State of the DRAM throttling model.
+///
+/// See [`config::DramThrottling`].
+#[derive(Debug, Clone, Default)]
+struct DramThrottleState {
+    /// Cycle the current measurement window started.
+    window_start_cycle: u64,
+    /// Total number of DRAM accesses when the current window started.
+    window_start_accesses: u64,
+    /// Whether the DRAM clock is currently throttled.
+    throttled: bool,
+}
+
+/// State of a DMA copy engine.
+///
+/// See [`config::GPU::num_copy_engines`].
+#[derive(Debug, Clone, Default)]
+struct CopyEngine {
+    /// Cycle in which the last copy assigned to this engine completes.
+    busy_until: u64,
+    /// Cycles the engine spent copying and waiting between copies.
+    utilization: stats::utilization::Counters,
 }
 
 impl<I> std::fmt::Debug for MockSimulator<I> {
@@ -374,6 +485,9 @@ impl FromConfig for stats::Config {
             num_mem_units,
             num_sub_partitions,
             num_dram_banks,
+            max_issue_per_core: config.num_schedulers_per_core
+                * config.max_instruction_issue_per_warp,
+            perfect_scheduler: config.perfect_scheduler,
         }
     }
 }
@@ -391,6 +505,13 @@ where
 
         let num_mem_units = config.num_memory_controllers;
 
+        // the trace writer is shared by all DRAM channels, such that the
+        // dump is a single request stream
+        let dram_trace = config.dram_trace.as_ref().map(|dram_trace| {
+            let writer = dram_trace::Writer::open(&dram_trace.out_file, dram_trace.format).unwrap();
+            Arc::new(Mutex::new(writer))
+        });
+
         let mem_partition_units: Vec<_> = (0..num_mem_units)
             .map(|i| {
                 let unit = mem_partition_unit::MemoryPartitionUnit::new(
@@ -398,6 +519,7 @@ where
                     Arc::clone(&config),
                     mem_controller.clone(),
                     Arc::clone(&stats),
+                    dram_trace.clone(),
                 );
                 // Arc::new(RwLock::new(unit)) as Arc<RwLock<dyn MemoryPartitionUnit>>
                 Arc::new(RwLock::new(unit))
@@ -416,13 +538,18 @@ where
         let warp_instruction_unique_uid = Arc::new(CachePadded::new(atomic::AtomicU64::new(0)));
         let clusters: Vec<_> = (0..config.num_simt_clusters)
             .map(|i| {
+                // clusters covered by a cluster group get their own
+                // config with the overrides of the group applied
+                let cluster_config = config
+                    .cluster_config(i)
+                    .expect("apply cluster group overrides");
                 let cluster = Cluster::new(
                     i,
                     &warp_instruction_unique_uid,
                     &allocations,
                     &interconn,
                     &stats,
-                    &config,
+                    &cluster_config,
                     &(Arc::clone(&mem_controller) as Arc<dyn mcu::MemoryController>),
                 );
                 Arc::new(cluster)
@@ -434,15 +561,15 @@ where
         assert!(config.max_threads_per_core.rem_euclid(config.warp_size) == 0);
         // let _max_warps_per_shader = config.max_threads_per_core / config.warp_size;
 
-        let window_size = if config.concurrent_kernel_sm {
-            config.max_concurrent_kernels
-        } else {
-            1
-        };
+        // hardware grid management keeps up to `max_concurrent_kernels`
+        // launched grids resident; kernels on the same stream still
+        // serialize through `busy_streams`.
+        let window_size = config.max_concurrent_kernels;
         assert!(window_size > 0);
 
         // todo: make this a hashset?
         let busy_streams: VecDeque<u64> = VecDeque::new();
+        let num_copy_engines = config.num_copy_engines;
         let mut kernels: VecDeque<Arc<dyn Kernel>> = VecDeque::new();
         kernels.reserve_exact(window_size);
 
@@ -455,6 +582,16 @@ where
         // this causes first launch to use simt cluster
         let last_cluster_issue = Arc::new(Mutex::new(config.num_simt_clusters - 1));
 
+        let clock_frequencies = config.clock_frequencies.clone();
+        let dvfs_epochs = vec![DvfsEpoch {
+            epoch: 0,
+            clock_frequencies: clock_frequencies.clone(),
+            start_cycle: 0,
+            end_cycle: None,
+            instructions: None,
+            start_instructions: 0,
+        }];
+
         Self {
             config,
             mem_controller,
@@ -477,16 +614,62 @@ where
             kernels,
             kernel_window_size: window_size,
             busy_streams,
+            copy_engines: vec![CopyEngine::default(); num_copy_engines],
+            stream_ready_cycle: HashMap::new(),
+            last_command_host_timestamp: None,
+            last_command_cycle: 0,
             cycle_limit,
             log_after_cycle: None,
+            reset_stats_at_cycle: None,
             partition_replies_in_parallel: 0,
             core_time: 0.0,
             dram_time: 0.0,
             icnt_time: 0.0,
             l2_time: 0.0,
+            clock_frequencies,
+            dvfs_schedule_idx: 0,
+            dvfs_epochs,
+            dram_throttle: DramThrottleState::default(),
+            core_failure_idx: 0,
+            pending_block_migrations: VecDeque::new(),
+            plugins: Vec::new(),
+            stats_writer: None,
+        }
+    }
+
+    /// Register a plugin to receive engine callbacks.
+    pub fn add_plugin(&mut self, plugin: Arc<dyn plugin::Plugin>) {
+        self.plugins.push(plugin);
+    }
+
+    /// Install an external DRAM timing backend on every DRAM channel.
+    ///
+    /// The factory is invoked once per memory partition with its id, such
+    /// that each channel drives its own backend instance (see
+    /// [`dram_trace::Backend`]).
+    pub fn set_dram_backend(
+        &mut self,
+        mut backend: impl FnMut(usize) -> Box<dyn dram_trace::Backend>,
+    ) {
+        for (id, unit) in self.mem_partition_units.iter().enumerate() {
+            unit.write().dram_backend = Some(backend(id));
         }
     }
 
+    /// Attach an asynchronous stats writer.
+    ///
+    /// The final stats of each kernel are handed to the writer when the
+    /// kernel completes, such that serialization overlaps with the
+    /// remaining simulation.
+    pub fn set_stats_writer(&mut self, writer: stats_writer::StatsWriter) {
+        self.stats_writer = Some(writer);
+    }
+
+    /// Detach the asynchronous stats writer for finalization.
+    pub fn take_stats_writer(&mut self) -> Option<stats_writer::StatsWriter> {
+        self.stats_writer.take()
+    }
+
     pub fn add_commands(
         &mut self,
         commands_path: impl AsRef<Path>,
@@ -498,6 +681,339 @@ where
         Ok(())
     }
 
+    /// Reset the simulator state so the constructed topology can be reused
+    /// for simulating another, independent trace.
+    ///
+    /// This clears all pending commands and kernels, resets statistics and
+    /// allocations, and invalidates the caches. The expensive construction
+    /// of clusters and memory partitions is reused.
+    pub fn reset(&mut self) {
+        *self.stats.lock() = stats::PerKernel::new(stats::Config::from_config(&self.config));
+        for running in self.running_kernels.try_write().iter_mut() {
+            *running = None;
+        }
+        self.executed_kernels.try_lock().clear();
+        *self.current_kernel.lock() = None;
+
+        for cluster in &self.clusters {
+            cluster.cache_invalidate();
+        }
+        for mem_sub in &self.mem_sub_partitions {
+            mem_sub.try_lock().invalidate_l2();
+        }
+
+        // this causes first launch to use simt cluster
+        *self.last_cluster_issue.try_lock() = self.config.num_simt_clusters - 1;
+        *self.last_issued_kernel.lock() = 0;
+        *self.allocations.try_write() = Allocations::default();
+
+        self.traces_dir = None;
+        self.commands.clear();
+        self.command_idx = 0;
+        tenant::reset();
+        self.reset_stats_at_cycle = self.config.reset_stats_at_cycle;
+        self.kernels.clear();
+        self.busy_streams.clear();
+        self.copy_engines = vec![CopyEngine::default(); self.config.num_copy_engines];
+        self.stream_ready_cycle.clear();
+        self.last_command_host_timestamp = None;
+        self.last_command_cycle = 0;
+        self.partition_replies_in_parallel = 0;
+        self.core_time = 0.0;
+        self.dram_time = 0.0;
+        self.icnt_time = 0.0;
+        self.l2_time = 0.0;
+
+        self.clock_frequencies = self.config.clock_frequencies.clone();
+        self.dvfs_schedule_idx = 0;
+        self.dram_throttle = DramThrottleState::default();
+        self.core_failure_idx = 0;
+        self.pending_block_migrations.clear();
+        self.dvfs_epochs = vec![DvfsEpoch {
+            epoch: 0,
+            clock_frequencies: self.clock_frequencies.clone(),
+            start_cycle: 0,
+            end_cycle: None,
+            instructions: None,
+            start_instructions: 0,
+        }];
+    }
+
+    /// Reset all collected statistics.
+    ///
+    /// Unlike [`Self::reset`], the simulation state (cache contents,
+    /// in-flight requests, launched kernels) is kept, such that
+    /// measurement can exclude a warmup phase within a single kernel.
+    pub fn reset_stats(&mut self) {
+        *self.stats.lock() = stats::PerKernel::new(stats::Config::from_config(&self.config));
+
+        for cluster in &self.clusters {
+            for core in &cluster.cores {
+                let mut core = core.try_write();
+                *core.instr_l1_cache.per_kernel_stats().lock() = stats::cache::PerKernel::default();
+                {
+                    let mut ldst_unit = core.load_store_unit.try_lock();
+                    if let Some(data_l1) = &mut ldst_unit.data_l1 {
+                        *data_l1.per_kernel_stats().lock() = stats::cache::PerKernel::default();
+                    }
+                }
+                for scheduler in &core.schedulers {
+                    scheduler.try_lock().reset_stats();
+                }
+                core.utilization = stats::utilization::Counters::default();
+                for counters in &mut core.fu_utilization {
+                    *counters = stats::utilization::Counters::default();
+                }
+            }
+        }
+
+        for sub in &self.mem_sub_partitions {
+            let mut sub = sub.try_lock();
+            if let Some(l2_cache) = &mut sub.l2_cache {
+                *l2_cache.per_kernel_stats().lock() = stats::cache::PerKernel::default();
+            }
+            sub.utilization = stats::utilization::Counters::default();
+        }
+        for partition in &self.mem_partition_units {
+            partition.try_write().utilization = stats::utilization::Counters::default();
+        }
+
+        self.interconn.reset_traffic();
+    }
+
+    /// Change the clock domain frequencies at runtime.
+    ///
+    /// The new frequencies take effect starting with the next simulated
+    /// cycle. The current frequency epoch is closed and a new one is
+    /// started for segmenting statistics.
+    pub fn set_clock_frequencies(
+        &mut self,
+        clock_frequencies: config::ClockFrequencies,
+        cycle: u64,
+    ) {
+        let instructions = {
+            let stats = self.stats.lock();
+            stats.no_kernel.sim.instructions
+                + stats
+                    .inner
+                    .iter()
+                    .map(|kernel_stats| kernel_stats.sim.instructions)
+                    .sum::<u64>()
+        };
+        if let Some(current) = self.dvfs_epochs.last_mut() {
+            current.end_cycle = Some(cycle);
+            current.instructions = Some(instructions - current.start_instructions);
+        }
+        self.dvfs_epochs.push(DvfsEpoch {
+            epoch: self.dvfs_epochs.len(),
+            clock_frequencies: clock_frequencies.clone(),
+            start_cycle: cycle,
+            end_cycle: None,
+            instructions: None,
+            start_instructions: instructions,
+        });
+        self.clock_frequencies = clock_frequencies;
+    }
+
+    /// Frequency epochs simulated so far.
+    ///
+    /// The last epoch is still open: its end cycle and instruction count
+    /// are not yet known.
+    pub fn dvfs_epochs(&self) -> &[DvfsEpoch] {
+        &self.dvfs_epochs
+    }
+
+    /// Apply due points of the DVFS schedule.
+    ///
+    /// Points are applied in order: a point is only considered once all
+    /// points before it have been applied.
+    fn apply_dvfs_schedule(&mut self, cycle: u64, launched_kernel_id: Option<u64>) {
+        while let Some(point) = self.config.dvfs_schedule.get(self.dvfs_schedule_idx) {
+            let due = match point.trigger {
+                config::DvfsTrigger::Cycle(at_cycle) => cycle >= at_cycle,
+                config::DvfsTrigger::KernelLaunch(kernel_id) => {
+                    launched_kernel_id == Some(kernel_id)
+                }
+            };
+            if !due {
+                break;
+            }
+            log::info!(
+                "cycle {}: dvfs: switching to {:?} ({:?})",
+                cycle,
+                point.clock_frequencies,
+                point.trigger
+            );
+            let clock_frequencies = point.clock_frequencies.clone();
+            self.dvfs_schedule_idx += 1;
+            self.set_clock_frequencies(clock_frequencies, cycle);
+        }
+    }
+
+    /// Apply the configured actions at a kernel boundary.
+    ///
+    /// See [`config::KernelBoundaryAction`].
+    fn apply_kernel_boundary_actions(
+        &mut self,
+        boundary: config::KernelBoundary,
+        kernel: &dyn Kernel,
+        cycle: u64,
+    ) {
+        let actions: Vec<config::BoundaryAction> = self
+            .config
+            .kernel_boundary_actions
+            .iter()
+            .filter(|action| action.boundary == boundary)
+            .filter(|action| action.kernel.matches(kernel.config()).unwrap())
+            .map(|action| action.action.clone())
+            .collect();
+        for action in actions {
+            log::info!(
+                "cycle {}: kernel {}: applying boundary action {:?} ({:?})",
+                cycle,
+                kernel.id(),
+                action,
+                boundary,
+            );
+            match action {
+                config::BoundaryAction::FlushL1 => {
+                    for cluster in &self.clusters {
+                        cluster.cache_invalidate();
+                    }
+                }
+                config::BoundaryAction::FlushL2 => {
+                    for mem_sub in &self.mem_sub_partitions {
+                        mem_sub.try_lock().flush_l2();
+                    }
+                }
+                config::BoundaryAction::ResetStats => self.reset_stats(),
+                config::BoundaryAction::SetClocks(clock_frequencies) => {
+                    self.set_clock_frequencies(clock_frequencies, cycle);
+                }
+            }
+        }
+    }
+
+    /// Apply due injected core failures and re-issue harvested blocks.
+    ///
+    /// Failures are applied in order: a failure is only considered once
+    /// all failures before it have been applied.  Harvested blocks wait
+    /// out their recovery penalty and are then re-issued in failure
+    /// order on the first core that can take them (see
+    /// [`config::CoreFailure`]).
+    fn apply_core_failures(&mut self, cycle: u64) {
+        while let Some(failure) = self.config.core_failures.get(self.core_failure_idx) {
+            if cycle < failure.cycle {
+                break;
+            }
+            let cluster_id = self.config.global_core_id_to_cluster_id(failure.core_id);
+            let core_id = self.config.global_core_id_to_core_id(failure.core_id);
+            let blocks = self.clusters[cluster_id].cores[core_id].write().fail();
+            log::warn!(
+                "cycle {}: core {} failed ({} resident blocks harvested)",
+                cycle,
+                failure.core_id,
+                blocks.len(),
+            );
+            let mut stats = self.stats.lock();
+            for block in blocks {
+                let kernel_stats = stats.get_mut(Some(block.kernel.id() as usize));
+                kernel_stats.sim.num_reexecuted_instructions += block.num_executed_instructions;
+                self.pending_block_migrations
+                    .push_back((cycle + failure.recovery_penalty, block));
+            }
+            drop(stats);
+            self.core_failure_idx += 1;
+        }
+
+        while let Some((ready_cycle, _)) = self.pending_block_migrations.front() {
+            if cycle < *ready_cycle {
+                break;
+            }
+            let block = &self.pending_block_migrations.front().unwrap().1;
+            let target = self.clusters.iter().find_map(|cluster| {
+                cluster
+                    .cores
+                    .iter()
+                    .find(|core| core.read().can_issue_block(&*block.kernel))
+                    .map(Arc::clone)
+            });
+            let Some(core) = target else {
+                // all cores are full: retry once a block drains
+                break;
+            };
+            let (_, block) = self.pending_block_migrations.pop_front().unwrap();
+            core.write().issue_migrated_block(block, cycle);
+        }
+    }
+
+    /// Apply the DRAM throttling policy.
+    ///
+    /// Measures the achieved DRAM bandwidth over fixed windows and
+    /// scales the DRAM clock frequency while the bandwidth stays above
+    /// the configured threshold (see [`config::DramThrottling`]).
+    ///
+    /// Peak bandwidth is approximated as one sector per memory sub
+    /// partition per cycle.
+    fn apply_dram_throttling(&mut self, cycle: u64) {
+        let Some(throttling) = self.config.dram_throttling else {
+            return;
+        };
+        let window = cycle.saturating_sub(self.dram_throttle.window_start_cycle);
+        if window < throttling.window {
+            return;
+        }
+
+        let total_accesses = {
+            let mut stats = self.stats.lock();
+            if self.dram_throttle.throttled {
+                stats.get_mut(None).sim.num_dram_throttled_cycles += window;
+            }
+            stats.no_kernel.dram.total_reads()
+                + stats.no_kernel.dram.total_writes()
+                + stats
+                    .inner
+                    .iter()
+                    .map(|kernel_stats| {
+                        kernel_stats.dram.total_reads() + kernel_stats.dram.total_writes()
+                    })
+                    .sum::<u64>()
+        };
+        let window_accesses =
+            total_accesses.saturating_sub(self.dram_throttle.window_start_accesses);
+        let peak_accesses = window * self.mem_sub_partitions.len() as u64;
+        let utilization = window_accesses as f64 / peak_accesses as f64;
+
+        let should_throttle = utilization > throttling.bandwidth_threshold;
+        if should_throttle != self.dram_throttle.throttled {
+            let nominal_dram_freq_hz = self.config.clock_frequencies.dram_freq_hz;
+            let dram_freq_hz = if should_throttle {
+                (nominal_dram_freq_hz as f64 * throttling.frequency_scale) as u64
+            } else {
+                nominal_dram_freq_hz
+            };
+            log::info!(
+                "cycle {}: dram throttling: {} dram clock ({:.2}% of peak bandwidth over the last {} cycles)",
+                cycle,
+                if should_throttle { "throttling" } else { "restoring" },
+                utilization * 100.0,
+                window,
+            );
+            let clock_frequencies = config::ClockFrequenciesBuilder {
+                core_freq_hz: self.clock_frequencies.core_freq_hz,
+                interconn_freq_hz: self.clock_frequencies.interconn_freq_hz,
+                l2_freq_hz: self.clock_frequencies.l2_freq_hz,
+                dram_freq_hz,
+            }
+            .build();
+            self.set_clock_frequencies(clock_frequencies, cycle);
+            self.dram_throttle.throttled = should_throttle;
+        }
+
+        self.dram_throttle.window_start_cycle = cycle;
+        self.dram_throttle.window_start_accesses = total_accesses;
+    }
+
     /// Select the next kernel to run.
     ///
     /// Todo: used hack to allow selecting the kernel from the shader core,
@@ -522,10 +1038,31 @@ where
             launch_latency, last_kernel.id());
         }
 
+        // the highest tenant priority among ready kernels: with tenants
+        // configured, only kernels of that priority may issue
+        let max_ready_priority = if self.config.tenants.is_empty() {
+            None
+        } else {
+            running_kernels
+                .iter()
+                .flatten()
+                .filter(|(launch_latency, kernel)| {
+                    !kernel.no_more_blocks_to_run() && *launch_latency == 0
+                })
+                .map(|(_, kernel)| tenant::priority_of(Some(kernel.id() as usize)))
+                .max()
+        };
+        let may_issue = |kernel: &Arc<dyn Kernel>| {
+            max_ready_priority
+                .is_none_or(|priority| tenant::priority_of(Some(kernel.id() as usize)) >= priority)
+        };
+
         // issue same kernel again
         match running_kernels[*last_issued_kernel] {
             Some((launch_latency, ref last_kernel))
-                if !last_kernel.no_more_blocks_to_run() && launch_latency == 0 =>
+                if !last_kernel.no_more_blocks_to_run()
+                    && launch_latency == 0
+                    && may_issue(last_kernel) =>
             {
                 let launch_id = last_kernel.id();
                 executed_kernels
@@ -552,7 +1089,9 @@ where
 
             match running_kernels[idx] {
                 Some((launch_latency, ref kernel))
-                    if !kernel.no_more_blocks_to_run() && launch_latency == 0 =>
+                    if !kernel.no_more_blocks_to_run()
+                        && launch_latency == 0
+                        && may_issue(kernel) =>
                 {
                     *last_issued_kernel = idx;
                     let launch_id = kernel.id();
@@ -628,6 +1167,19 @@ where
             );
             eyre::bail!("kernel block size is too large");
         }
+        if kernel.config().max_registers.is_none() {
+            // without the launch bounds metadata, occupancy is based on
+            // the raw register count, which overestimates occupancy for
+            // kernels compiled with __launch_bounds__ or -maxrregcount
+            log::warn!(
+                "kernel {}: trace has no register cap metadata",
+                kernel.name()
+            );
+        }
+        if let Some(tenant) = self.config.tenant_for_launch(kernel.config()) {
+            let priority = self.config.tenants[tenant].priority;
+            tenant::assign(kernel.id() as usize, tenant, priority);
+        }
         let mut running_kernels = self.running_kernels.try_write();
         let free_slot = running_kernels
             .iter_mut()
@@ -638,6 +1190,16 @@ where
         // *kernel.start_time.lock() = Some(std::time::Instant::now());
         // *kernel.start_cycle.lock() = Some(cycle);
 
+        if let Some(queued_cycle) = kernel.queued_cycle() {
+            let mut stats = self.stats.lock();
+            let kernel_stats = stats.get_mut(Some(kernel.id() as usize));
+            kernel_stats.sim.kernel_launch_wait_cycles = cycle.saturating_sub(queued_cycle);
+        }
+
+        for plugin in &self.plugins {
+            plugin.kernel_launch(&*kernel, cycle);
+        }
+
         *self.current_kernel.lock() = Some(Arc::clone(&kernel));
         let launch_latency = self.config.kernel_launch_latency
             + kernel.config().num_blocks() * self.config.block_launch_latency;
@@ -688,28 +1250,65 @@ where
         if self.l2_time <= smallest {
             smallest = self.l2_time;
             mask.set(ClockDomain::L2 as usize, true);
-            self.l2_time += self.config.clock_frequencies.l2_period;
+            self.l2_time += self.clock_frequencies.l2_period;
         }
         if self.icnt_time <= smallest {
             mask.set(ClockDomain::ICNT as usize, true);
-            self.icnt_time += self.config.clock_frequencies.interconn_period;
+            self.icnt_time += self.clock_frequencies.interconn_period;
         }
         if self.dram_time <= smallest {
             mask.set(ClockDomain::DRAM as usize, true);
-            self.dram_time += self.config.clock_frequencies.dram_period;
+            self.dram_time += self.clock_frequencies.dram_period;
         }
         if self.core_time <= smallest {
             mask.set(ClockDomain::CORE as usize, true);
-            self.core_time += self.config.clock_frequencies.core_period;
+            self.core_time += self.clock_frequencies.core_period;
         }
         mask
     }
 
+    /// Apply pending coherence invalidations to the L1 data caches.
+    ///
+    /// Invalidations recorded by the L2 sharer directories this cycle
+    /// take effect before the next core cycle, without occupying the
+    /// interconnect.
+    fn apply_l1_invalidations(&mut self) {
+        let pending: Vec<_> = self
+            .mem_sub_partitions
+            .iter()
+            .flat_map(|mem_sub| mem_sub.try_lock().drain_l1_invalidations())
+            .collect();
+        for invalidation in pending {
+            let cluster_id = self
+                .config
+                .global_core_id_to_cluster_id(invalidation.core_id);
+            let core_id = self.config.global_core_id_to_core_id(invalidation.core_id);
+            let core = &self.clusters[cluster_id].cores[core_id];
+            if core.write().l1_invalidate_addr(invalidation.addr) {
+                let mut stats = self.stats.lock();
+                let coherence = &mut stats.get_mut(invalidation.kernel_launch_id).coherence;
+                coherence.num_lines_invalidated += 1;
+            }
+        }
+    }
+
     #[allow(clippy::overly_complex_bool_expr)]
     #[tracing::instrument(name = "cycle")]
     pub fn cycle(&mut self, mut cycle: u64) -> u64 {
         #[cfg(feature = "timings")]
         let start_total = std::time::Instant::now();
+        for plugin in &self.plugins {
+            plugin.start_of_cycle(cycle);
+        }
+        if !self.config.dvfs_schedule.is_empty() {
+            self.apply_dvfs_schedule(cycle, None);
+        }
+        if self.config.dram_throttling.is_some() {
+            self.apply_dram_throttling(cycle);
+        }
+        if !self.config.core_failures.is_empty() || !self.pending_block_migrations.is_empty() {
+            self.apply_core_failures(cycle);
+        }
         let clock_mask = self.next_clock_domain();
         // use bitvec::field::BitField;
         // let mut clock_mask_bits: bitvec::BitArr!(for 8, in u8) = bitvec::array::BitArray::ZERO;
@@ -786,10 +1385,19 @@ where
                         fetch.size()
                     };
                     let device = self.config.mem_id_to_device_id(i);
-                    if self.interconn.has_buffer(device, response_packet_size) {
+                    // the destination cluster only grants as many credits as its
+                    // ejection buffer has slots: hold the response back in the
+                    // sub partition until a credit becomes available
+                    let has_credit = fetch.cluster_id.map_or(true, |cluster_id| {
+                        self.interconn.has_buffer(cluster_id, response_packet_size)
+                    });
+                    if has_credit {
                         let mut fetch = mem_sub.pop().unwrap();
                         if let Some(cluster_id) = fetch.cluster_id {
                             fetch.set_status(mem_fetch::Status::IN_ICNT_TO_SHADER, 0);
+                            for plugin in &self.plugins {
+                                plugin.fetch_event(plugin::FetchEvent::ToShader, &fetch, cycle);
+                            }
                             // fetch.set_return_timestamp(gpu_sim_cycle + gpu_tot_sim_cycle);
                             // , gpu_sim_cycle + gpu_tot_sim_cycle);
                             // log::trace!("interconn push from memory sub partition {i}: {fetch} (cluster={:?}, core={:?})", fetch.cluster_id, fetch.core_id);
@@ -804,9 +1412,15 @@ where
                                 response_packet_size,
                             );
                             self.partition_replies_in_parallel += 1;
+                        } else {
+                            // responses without a destination, e.g. for
+                            // memcopy fills, are dropped
+                            mem_fetch::tracker::retired(&fetch);
                         }
                     } else {
-                        // self.gpu_stall_icnt2sh += 1;
+                        let mut stats = self.stats.lock();
+                        let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+                        kernel_stats.stall_interconn_to_shader += 1;
                     }
                 }
             }
@@ -868,6 +1482,13 @@ where
                             device
                         );
 
+                        for plugin in &self.plugins {
+                            plugin.fetch_event(
+                                plugin::FetchEvent::ToMemSubPartition,
+                                &packet.data,
+                                cycle,
+                            );
+                        }
                         mem_sub.push(packet.data, cycle);
                         // self.parallel_mem_partition_reqs += 1;
                     }
@@ -894,6 +1515,12 @@ where
                 .entry("cycle::l2")
                 .or_default()
                 .add(start.elapsed());
+
+            // apply coherence invalidations from the L2 sharer
+            // directories to the L1 data caches
+            if self.config.l1_coherence {
+                self.apply_l1_invalidations();
+            }
         }
 
         //   partiton_reqs_in_parallel += partiton_reqs_in_parallel_per_cycle;
@@ -987,6 +1614,9 @@ where
                         //     fetch.cluster_id,
                         //     fetch.core_id,
                         // );
+                        for plugin in &self.plugins {
+                            plugin.fetch_event(plugin::FetchEvent::ToMemory, &fetch, cycle);
+                        }
                         self.interconn.push(
                             core.cluster_id,
                             dest,
@@ -1110,6 +1740,10 @@ where
 
         // cycle += 1;
 
+        for plugin in &self.plugins {
+            plugin.end_of_cycle(cycle);
+        }
+
         // self.debug_non_exit();
         cycle
     }
@@ -1234,6 +1868,7 @@ where
         addr: address,
         num_bytes: u64,
         name: Option<String>,
+        stream_id: u64,
         mut cycle: u64,
     ) -> u64 {
         log::info!(
@@ -1302,7 +1937,7 @@ where
             Ok(())
         };
 
-        if self.config.fill_l2_on_memcopy {
+        if self.config.fill_l2_on_memcopy || self.config.memcopy_only {
             if self.config.accelsim_compat {
                 // todo: remove this branch because accelsim is broken
                 let chunk_size: u64 = 32;
@@ -1318,11 +1953,14 @@ where
                         self.mem_sub_partitions.len() * l2_cache.inner.total_bytes();
                     let percent = (num_bytes as f32 / l2_cache_size_bytes as f32) * 100.0;
 
-                    let should_prefetch = self
-                        .config
-                        .l2_prefetch_percent
-                        .map(|l2_prefetch_percent| percent <= l2_prefetch_percent)
-                        .unwrap_or(true);
+                    // in memcopy only mode, all memcopies are replayed
+                    // regardless of the prefetch threshold
+                    let should_prefetch = self.config.memcopy_only
+                        || self
+                            .config
+                            .l2_prefetch_percent
+                            .map(|l2_prefetch_percent| percent <= l2_prefetch_percent)
+                            .unwrap_or(true);
 
                     // find allocation
                     let allocation_id = self
@@ -1367,7 +2005,30 @@ where
                     }
 
                     if should_prefetch {
-                        cycle = self.fill_l2(addr, num_bytes, cycle);
+                        // a copy on the default stream synchronizes: it
+                        // waits for all outstanding async copies first
+                        let copy_start_cycle = if stream_id == 0 {
+                            cycle.max(self.stream_ready_cycle.values().copied().max().unwrap_or(0))
+                        } else {
+                            cycle
+                        };
+                        let end_cycle = self.fill_l2(addr, num_bytes, copy_start_cycle);
+                        let duration = end_cycle - copy_start_cycle;
+                        let copy_end_cycle = self.schedule_copy(copy_start_cycle, duration);
+                        if stream_id == 0 {
+                            // synchronous: all subsequent commands wait
+                            cycle = copy_end_cycle;
+                        } else {
+                            // asynchronous: only kernels on the same
+                            // stream wait for the copy
+                            self.stream_ready_cycle.insert(stream_id, copy_end_cycle);
+                        }
+
+                        let mut stats = self.stats.lock();
+                        let memcopy_stats = &mut stats.no_kernel.memcopy;
+                        memcopy_stats.num_memcopies += 1;
+                        memcopy_stats.num_bytes += num_bytes;
+                        memcopy_stats.cycles += duration;
                     }
 
                     if output_memcopy_l2_cache_state
@@ -1395,6 +2056,27 @@ where
         cycle
     }
 
+    /// Assign a copy to a DMA copy engine.
+    ///
+    /// The copy occupies the least busy engine for `duration` cycles,
+    /// starting when both the copy is ready and the engine is
+    /// available. Returns the cycle in which the copy completes.
+    fn schedule_copy(&mut self, start: u64, duration: u64) -> u64 {
+        let engine = self
+            .copy_engines
+            .iter_mut()
+            .min_by_key(|engine| engine.busy_until)
+            .expect("have at least one copy engine");
+        if engine.busy_until > 0 {
+            // gap between the previous copy and this one
+            engine.utilization.idle_cycles += start.saturating_sub(engine.busy_until);
+        }
+        let begin = engine.busy_until.max(start);
+        engine.busy_until = begin + duration;
+        engine.utilization.busy_cycles += duration;
+        engine.busy_until
+    }
+
     #[must_use]
     pub fn fill_l2(&mut self, addr: address, num_bytes: u64, mut cycle: u64) -> u64 {
         let chunk_size: u64 = 128;
@@ -1461,7 +2143,9 @@ where
                     };
                     let device = self.config.mem_id_to_device_id(sub_id);
                     if self.interconn.has_buffer(device, response_packet_size) {
-                        mem_sub.pop().unwrap();
+                        let fetch = mem_sub.pop().unwrap();
+                        // memcopy fill responses have no destination
+                        mem_fetch::tracker::retired(&fetch);
                     }
                 }
             }
@@ -1572,11 +2256,19 @@ where
 
     /// Collect simulation statistics.
     pub fn stats(&self) -> stats::PerKernel {
+        #[cfg(feature = "timings")]
+        let start = std::time::Instant::now();
         let mut stats: stats::PerKernel = self.stats.lock().clone();
 
         let is_release_build = !is_debug();
         stats.no_kernel.sim.is_release_build = is_release_build;
 
+        let parallel_seed = match self.config.parallelization {
+            config::Parallelization::Nondeterministic { .. } => self.config.parallelization_seed,
+            _ => None,
+        };
+        stats.no_kernel.sim.parallel_seed = parallel_seed;
+
         for (kernel_launch_id, kernel_stats) in stats.as_mut().iter_mut().enumerate() {
             if let Some(kernel) = &self.executed_kernels.lock().get(&(kernel_launch_id as u64)) {
                 let kernel_info = stats::KernelInfo {
@@ -1588,6 +2280,8 @@ where
                 kernel_stats.sim.kernel_name_mangled = kernel_info.mangled_name.clone();
                 kernel_stats.sim.kernel_launch_id = kernel_info.launch_id;
                 kernel_stats.sim.is_release_build = is_release_build;
+                kernel_stats.sim.parallel_seed = parallel_seed;
+                set_launch_metadata(&mut kernel_stats.sim, kernel.config());
 
                 kernel_stats.dram.kernel_info = kernel_info.clone();
                 kernel_stats.accesses.kernel_info = kernel_info.clone();
@@ -1626,11 +2320,64 @@ where
                 kernel_stats.l1i_stats[core.core_id] = cache_stats.clone();
             }
 
+            // idle cycles cannot be attributed to kernels
+            let utilization = &mut stats.no_kernel.utilization;
+            utilization.cores.insert(core.core_id, core.utilization);
+            for (fu, counters) in core.functional_units.iter().zip(&core.fu_utilization) {
+                let fu = fu.try_lock();
+                *utilization
+                    .functional_units
+                    .entry((core.core_id, fu.id().to_string()))
+                    .or_default() += *counters;
+            }
+
             let ldst_unit = &core.load_store_unit.try_lock();
+            if self.config.ldst_dual_path_issue {
+                for (path, counters) in [
+                    ("LdstUnit shared path", ldst_unit.shared_path_utilization),
+                    ("LdstUnit global path", ldst_unit.global_path_utilization),
+                ] {
+                    *utilization
+                        .functional_units
+                        .entry((core.core_id, path.to_string()))
+                        .or_default() += counters;
+                }
+            }
             let data_l1 = ldst_unit.data_l1.as_ref().unwrap();
-            for (kernel_launch_id, cache_stats) in per_kernel_cache_stats!(data_l1) {
-                let kernel_stats = stats.get_mut(Some(kernel_launch_id));
-                kernel_stats.l1d_stats[core.core_id] = cache_stats.clone();
+            // with a cluster-shared L1, all cores report the stats of the
+            // shared cache, which must only be recorded once per cluster
+            let record_l1d = match self.config.l1_cache_sharing {
+                config::CacheSharing::Private => true,
+                config::CacheSharing::Cluster => {
+                    self.config.global_core_id_to_core_id(core.core_id) == 0
+                }
+            };
+            if record_l1d {
+                for (kernel_launch_id, cache_stats) in per_kernel_cache_stats!(data_l1) {
+                    let kernel_stats = stats.get_mut(Some(kernel_launch_id));
+                    kernel_stats.l1d_stats[core.core_id] = cache_stats.clone();
+                }
+            }
+
+            // issue slots cannot be attributed to kernels
+            for (scheduler_id, scheduler) in core.schedulers.iter().enumerate() {
+                let scheduler_stats = scheduler.try_lock().stats();
+                // scoreboard stalls are keyed by kernel launch id and can be
+                // attributed to the stalled instruction of each kernel
+                for (&(kernel_launch_id, pc), &stall_cycles) in &scheduler_stats.stall_cycles_per_pc
+                {
+                    let kernel_stats = stats.get_mut(Some(kernel_launch_id));
+                    kernel_stats.per_pc.get_mut(pc).stall_cycles += stall_cycles;
+                }
+                stats
+                    .no_kernel
+                    .utilization
+                    .schedulers
+                    .insert((core.core_id, scheduler_id), scheduler_stats.utilization);
+                stats
+                    .no_kernel
+                    .schedulers
+                    .insert((core.core_id, scheduler_id), scheduler_stats);
             }
         }
 
@@ -1643,10 +2390,170 @@ where
             }
             stats.no_kernel.l2d_stats[sub.id] =
                 l2_cache.per_kernel_stats().try_lock().no_kernel.clone();
+            stats
+                .no_kernel
+                .utilization
+                .l2_slices
+                .insert(sub.id, sub.utilization);
         }
+
+        for (partition_id, partition) in self.mem_partition_units.iter().enumerate() {
+            let partition = partition.try_read();
+            stats
+                .no_kernel
+                .utilization
+                .dram_channels
+                .insert(partition_id, partition.utilization);
+        }
+
+        // copies cannot be attributed to kernels
+        for (engine_id, engine) in self.copy_engines.iter().enumerate() {
+            stats
+                .no_kernel
+                .utilization
+                .copy_engines
+                .insert(engine_id, engine.utilization);
+        }
+
+        // interconnect traffic cannot be attributed to kernels
+        stats.no_kernel.interconn = self.interconn.traffic();
+
+        #[cfg(feature = "timings")]
+        TIMINGS
+            .lock()
+            .entry("stats::gather")
+            .or_default()
+            .add(start.elapsed());
         stats
     }
 
+    /// Collect the final statistics of a single completed kernel.
+    ///
+    /// The per-kernel subset of [`Self::stats`], used to stream kernel
+    /// stats to the asynchronous stats writer as kernels complete.
+    /// Counters that cannot be attributed to kernels (utilization,
+    /// interconnect traffic, memcopies) are only part of the no-kernel
+    /// stats of the full collection.
+    #[must_use]
+    pub fn kernel_stats(&self, kernel: &dyn Kernel) -> stats::Stats {
+        let kernel_launch_id = kernel.id() as usize;
+        let mut kernel_stats = self.stats.lock().get_mut(Some(kernel_launch_id)).clone();
+
+        let kernel_info = stats::KernelInfo {
+            name: kernel.config().unmangled_name.clone(),
+            mangled_name: kernel.config().mangled_name.clone(),
+            launch_id: kernel_launch_id,
+        };
+        kernel_stats.sim.kernel_name = kernel_info.name.clone();
+        kernel_stats.sim.kernel_name_mangled = kernel_info.mangled_name.clone();
+        kernel_stats.sim.kernel_launch_id = kernel_info.launch_id;
+        kernel_stats.sim.is_release_build = !is_debug();
+        set_launch_metadata(&mut kernel_stats.sim, kernel.config());
+        kernel_stats.sim.parallel_seed = match self.config.parallelization {
+            config::Parallelization::Nondeterministic { .. } => self.config.parallelization_seed,
+            _ => None,
+        };
+
+        kernel_stats.dram.kernel_info = kernel_info.clone();
+        kernel_stats.accesses.kernel_info = kernel_info.clone();
+        kernel_stats.instructions.kernel_info = kernel_info.clone();
+        for cache_stats in [
+            &mut kernel_stats.l1i_stats,
+            &mut kernel_stats.l1c_stats,
+            &mut kernel_stats.l1t_stats,
+            &mut kernel_stats.l1d_stats,
+            &mut kernel_stats.l2d_stats,
+        ] {
+            cache_stats.kernel_info = kernel_info.clone();
+        }
+
+        let cores = self
+            .clusters
+            .iter()
+            .flat_map(|cluster| cluster.cores.clone());
+        for core in cores {
+            let core = core.try_read();
+            let l1i_stats = core.instr_l1_cache.per_kernel_stats().try_lock();
+            if let Some(cache_stats) = l1i_stats.as_ref().get(kernel_launch_id) {
+                kernel_stats.l1i_stats[core.core_id] = cache_stats.clone();
+            }
+
+            let ldst_unit = &core.load_store_unit.try_lock();
+            let data_l1 = ldst_unit.data_l1.as_ref().unwrap();
+            // with a cluster-shared L1, all cores report the stats of the
+            // shared cache, which must only be recorded once per cluster
+            let record_l1d = match self.config.l1_cache_sharing {
+                config::CacheSharing::Private => true,
+                config::CacheSharing::Cluster => {
+                    self.config.global_core_id_to_core_id(core.core_id) == 0
+                }
+            };
+            if record_l1d {
+                let l1d_stats = data_l1.per_kernel_stats().try_lock();
+                if let Some(cache_stats) = l1d_stats.as_ref().get(kernel_launch_id) {
+                    kernel_stats.l1d_stats[core.core_id] = cache_stats.clone();
+                }
+            }
+
+            for scheduler in &core.schedulers {
+                let scheduler_stats = scheduler.try_lock().stats();
+                for (&(launch_id, pc), &stall_cycles) in &scheduler_stats.stall_cycles_per_pc {
+                    if launch_id == kernel_launch_id {
+                        kernel_stats.per_pc.get_mut(pc).stall_cycles += stall_cycles;
+                    }
+                }
+            }
+        }
+
+        for sub in &self.mem_sub_partitions {
+            let sub = sub.try_lock();
+            let l2_cache = sub.l2_cache.as_ref().unwrap();
+            let l2_stats = l2_cache.per_kernel_stats().try_lock();
+            if let Some(cache_stats) = l2_stats.as_ref().get(kernel_launch_id) {
+                kernel_stats.l2d_stats[sub.id] = cache_stats.clone();
+            }
+        }
+
+        kernel_stats
+    }
+
+    /// Replay the host-side gap before a command.
+    ///
+    /// When the trace records host timestamps and
+    /// [`config::GPU::host_gaps`] is enabled, the GPU sits idle while
+    /// the CPU spends think-time between CUDA calls. Only host time
+    /// that the device did not already overlap with simulated work is
+    /// replayed, i.e. the simulated cycles since the last command are
+    /// subtracted from the recorded gap.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn replay_host_gap(&mut self, host_timestamp_nanos: Option<u64>, mut cycle: u64) -> u64 {
+        let Some(timestamp) = host_timestamp_nanos else {
+            return cycle;
+        };
+        let last_timestamp = self.last_command_host_timestamp.replace(timestamp);
+        if let (true, Some(last_timestamp)) = (self.config.host_gaps, last_timestamp) {
+            let gap_nanos = timestamp.saturating_sub(last_timestamp);
+            let gap_cycles =
+                (gap_nanos as f64 * 1e-9 * self.config.clock_frequencies.core_freq_hz as f64)
+                    as u64;
+            let elapsed_cycles = cycle.saturating_sub(self.last_command_cycle);
+            let idle_cycles = gap_cycles.saturating_sub(elapsed_cycles);
+            if idle_cycles > 0 {
+                log::debug!(
+                    "replaying host gap of {} idle cycles before command {}",
+                    idle_cycles,
+                    self.command_idx
+                );
+                let mut stats = self.stats.lock();
+                stats.get_mut(None).sim.num_host_gap_cycles += idle_cycles;
+                cycle += idle_cycles;
+            }
+        }
+        self.last_command_cycle = cycle;
+        cycle
+    }
+
     /// Process commands
     ///
     /// Take as many commands as possible until we have collected as many kernels to fill
@@ -1655,12 +2562,16 @@ where
     pub fn process_commands(&mut self, mut cycle: u64) -> u64 {
         while self.kernels.len() < self.kernel_window_size && self.command_idx < self.commands.len()
         {
+            let host_timestamp_nanos = self.commands[self.command_idx].host_timestamp_nanos();
+            cycle = self.replay_host_gap(host_timestamp_nanos, cycle);
             let cmd = &self.commands[self.command_idx];
             match cmd {
                 Command::MemcpyHtoD(trace_model::command::MemcpyHtoD {
                     allocation_name,
                     dest_device_addr,
                     num_bytes,
+                    stream_id,
+                    ..
                 }) => {
                     cycle = crate::timeit!(
                         "cycle::memcopy",
@@ -1668,6 +2579,7 @@ where
                             *dest_device_addr,
                             *num_bytes,
                             allocation_name.clone(),
+                            *stream_id,
                             cycle,
                         )
                     );
@@ -1677,6 +2589,7 @@ where
                     device_ptr,
                     fill_l2,
                     num_bytes,
+                    ..
                 }) => {
                     let fill_l2 = *fill_l2;
                     let device_ptr = *device_ptr;
@@ -1690,17 +2603,45 @@ where
                                 device_ptr,
                                 num_bytes,
                                 allocation_name.clone(),
+                                0,
                                 cycle,
                             )
                         );
                     }
                 }
                 Command::KernelLaunch(launch) => {
+                    if self.config.memcopy_only {
+                        log::info!(
+                            "skip kernel {} ({}) in memcopy only mode",
+                            launch.id,
+                            launch.unmangled_name
+                        );
+                        self.command_idx += 1;
+                        continue;
+                    }
                     let mut kernel = kernel::trace::KernelTrace::new(
                         launch.clone(),
                         self.traces_dir.as_ref().unwrap(),
+                        self.config.lenient_trace_loading,
                     );
                     kernel.memory_only = self.config.memory_only;
+                    kernel.set_queued(cycle);
+
+                    // traces of multi-device applications contain the
+                    // command streams of all devices
+                    if let Some(device) = self.config.trace_device {
+                        let kernel_device = kernel.device_id();
+                        if kernel_device.is_some_and(|kernel_device| kernel_device != device) {
+                            log::info!(
+                                "skip kernel {} traced on device {} (simulating device {})",
+                                kernel,
+                                kernel_device.unwrap(),
+                                device
+                            );
+                            self.command_idx += 1;
+                            continue;
+                        }
+                    }
                     // let num_running_kernels = self
                     //     .running_kernels
                     //     .try_read()
@@ -1766,7 +2707,12 @@ where
                 .busy_streams
                 .iter()
                 .any(|stream_id| *stream_id == kernel.config().stream_id);
-            if !stream_busy && self.can_start_kernel() && !kernel.launched() {
+            // wait for outstanding async copies on the same stream
+            let stream_ready = self
+                .stream_ready_cycle
+                .get(&kernel.config().stream_id)
+                .is_none_or(|ready_cycle| *ready_cycle <= cycle);
+            if !stream_busy && stream_ready && self.can_start_kernel() && !kernel.launched() {
                 self.busy_streams.push_back(kernel.config().stream_id);
                 launch_queue.push(kernel.clone());
             }
@@ -1782,7 +2728,12 @@ where
             if let Some(up_to_kernel) = up_to_kernel {
                 assert!(kernel.id() <= up_to_kernel, "launching kernel {kernel}");
             }
-            self.launch(kernel, cycle).unwrap();
+            let kernel_id = kernel.id();
+            self.launch(Arc::clone(&kernel), cycle).unwrap();
+            if !self.config.dvfs_schedule.is_empty() {
+                self.apply_dvfs_schedule(cycle, Some(kernel_id));
+            }
+            self.apply_kernel_boundary_actions(config::KernelBoundary::Launch, &*kernel, cycle);
         }
     }
 
@@ -1790,6 +2741,32 @@ where
         matches!(self.cycle_limit, Some(limit) if cycle >= limit)
     }
 
+    /// Enforce the per-kernel cycle safety limit.
+    ///
+    /// Fails when a running kernel exceeds
+    /// [`config::GPU::max_kernel_cycles`], such that persistent kernels,
+    /// which run few blocks forever, do not hang the simulation.
+    pub fn check_max_kernel_cycles(&self, cycle: u64) -> eyre::Result<()> {
+        let Some(max_kernel_cycles) = self.config.max_kernel_cycles else {
+            return Ok(());
+        };
+        let running_kernels = self.running_kernels.try_read();
+        for (_, kernel) in running_kernels.iter().filter_map(Option::as_ref) {
+            let Some(start_cycle) = kernel.start_cycle() else {
+                continue;
+            };
+            if cycle.saturating_sub(start_cycle) > max_kernel_cycles {
+                eyre::bail!(
+                    "kernel {} exceeded the limit of {} cycles (launched in cycle {}): is this a persistent kernel?",
+                    kernel.id(),
+                    max_kernel_cycles,
+                    start_cycle,
+                );
+            }
+        }
+        Ok(())
+    }
+
     pub fn commands_left(&self) -> bool {
         self.command_idx < self.commands.len()
     }
@@ -1816,6 +2793,12 @@ where
                 self.run_to_completion_parallel_nondeterministic(run_ahead)?;
             }
         }
+        if let Some(usage) = mem_usage::current() {
+            eprintln!(
+                "memory usage: {usage} (peak {} resident)",
+                human_bytes::human_bytes(mem_usage::peak_resident_bytes() as f64),
+            );
+        }
         Ok(start.elapsed())
     }
 
@@ -1832,6 +2815,7 @@ where
             .transpose()?
             .unwrap_or(5_000);
         let mut last_time = std::time::Instant::now();
+        let mut mem_monitor = mem_usage::Monitor::new(self.config.simulation_memory_limit_bytes);
 
         log::info!("serial for {} cores", self.config.total_cores());
 
@@ -1850,7 +2834,8 @@ where
                         "cycle {cycle:<10} ({:>8.4} cycle/sec)",
                         log_every as f64 / last_time.elapsed().as_secs_f64()
                     );
-                    last_time = std::time::Instant::now()
+                    last_time = std::time::Instant::now();
+                    mem_monitor.check()?;
                 }
 
                 log::info!("cycle {} active={}", cycle, &self.active());
@@ -1864,16 +2849,36 @@ where
                     // || !self.active() {
                     break;
                 }
+                self.check_max_kernel_cycles(cycle)?;
 
                 let old_cycle = cycle;
                 cycle = self.cycle(cycle);
-                assert!(cycle > old_cycle);
+                if self.config.simulate_clock_domains {
+                    // ticks of the faster clock domains do not advance
+                    // the (core clock) cycle count
+                    assert!(cycle >= old_cycle);
+                } else {
+                    assert!(cycle > old_cycle);
+                }
 
                 if !self.active() {
                     finished_kernel = self.finished_kernel();
                     if finished_kernel.is_some() {
                         break;
                     }
+                    if self.config.memcopy_only && !self.kernels_left() {
+                        // no kernel will ever finish in memcopy only mode
+                        break;
+                    }
+                }
+
+                match self.reset_stats_at_cycle {
+                    Some(ref reset_stats_at_cycle) if cycle >= *reset_stats_at_cycle => {
+                        eprintln!("resetting statistics after cycle {cycle}");
+                        self.reset_stats();
+                        self.reset_stats_at_cycle.take();
+                    }
+                    _ => {}
                 }
 
                 match self.log_after_cycle {
@@ -2004,13 +3009,58 @@ where
         // *kernel.completed_time.lock() = Some(completion_time);
         // *kernel.completed_cycle.lock() = Some(cycle);
 
+        for plugin in &self.plugins {
+            plugin.kernel_retire(kernel, cycle);
+        }
+
         let mut stats = self.stats.lock();
         let kernel_stats = stats.get_mut(Some(kernel.id() as usize));
 
         kernel_stats.sim.is_release_build = !is_debug();
+        // empty blocks never reach a core but still count towards the launched grid
+        kernel_stats.sim.num_skipped_blocks = kernel.num_skipped_blocks();
         // let elapsed_cycles = cycle - kernel.start_cycle.lock().unwrap_or(0);
         let elapsed_cycles = kernel.elapsed_cycles().unwrap_or(0);
         kernel_stats.sim.cycles = elapsed_cycles;
+
+        // per-iteration behavior detected from the trace loop structure
+        kernel_stats.sim.num_trace_loop_iterations = kernel.num_trace_loop_iterations();
+        kernel_stats.sim.num_malformed_trace_records = kernel.num_malformed_trace_records();
+        kernel_stats.sim.max_trace_loop_iterations = kernel.max_trace_loop_iterations();
+
+        // loops detected in the trace, annotated with the executed
+        // thread instructions of their body from the per-PC counters
+        kernel_stats.loops = kernel.trace_loops();
+        for (&(head, tail), stat) in &mut kernel_stats.loops.inner {
+            stat.num_thread_instructions = kernel_stats
+                .per_pc
+                .inner
+                .iter()
+                .filter(|(pc, _)| (head..=tail).contains(*pc))
+                .map(|(_, pc_stat)| pc_stat.num_thread_instructions)
+                .sum();
+        }
+
+        // persistent kernel detection: few long-running blocks that loop
+        // many times in the trace
+        const PERSISTENT_KERNEL_MIN_CYCLES_PER_BLOCK: u64 = 10_000;
+        const PERSISTENT_KERNEL_MIN_LOOP_ITERATIONS: u64 = 32;
+        let num_issued_blocks = kernel
+            .config()
+            .grid
+            .size()
+            .saturating_sub(kernel.num_skipped_blocks());
+        let cycles_per_block = elapsed_cycles.checked_div(num_issued_blocks).unwrap_or(0);
+        let is_persistent_kernel = num_issued_blocks <= self.config.total_cores() as u64
+            && cycles_per_block >= PERSISTENT_KERNEL_MIN_CYCLES_PER_BLOCK
+            && kernel.max_trace_loop_iterations() >= PERSISTENT_KERNEL_MIN_LOOP_ITERATIONS;
+        kernel_stats.sim.is_persistent_kernel = is_persistent_kernel;
+        if is_persistent_kernel {
+            log::warn!(
+                "kernel {}: persistent kernel pattern detected ({num_issued_blocks} blocks running {cycles_per_block} cycles per block): block sampling and progress heuristics may be unreliable",
+                kernel.id(),
+            );
+        }
         let elapsed = kernel.elapsed_time();
         // let elapsed = kernel
         //     .start_time
@@ -2027,10 +3077,134 @@ where
             "finished kernel {}: {kernel} in {elapsed_cycles} cycles ({elapsed:?})",
             kernel.id(),
         );
+
+        // the memory system has drained: all fetches must be retired
+        mem_fetch::tracker::assert_drained();
+
+        // functional correctness check: the stores replayed for this
+        // kernel must match the digest captured by the tracer
+        if let (Some(captured), Some(replayed)) = (
+            kernel.config().store_value_digest,
+            kernel.replayed_store_digest(),
+        ) {
+            assert_eq!(
+                captured,
+                replayed,
+                "kernel {}: replayed store digest does not match the store digest captured by the tracer",
+                kernel.id(),
+            );
+            log::info!(
+                "kernel {}: replayed store digest {replayed:#018x} matches the tracer",
+                kernel.id(),
+            );
+        }
+
+        drop(stats);
+        // hand the final stats of the kernel to the asynchronous stats
+        // writer, such that serialization overlaps with the remaining
+        // simulation
+        if self.stats_writer.is_some() {
+            let kernel_stats = self.kernel_stats(kernel);
+            if let Some(writer) = &self.stats_writer {
+                writer.write_kernel(kernel.id() as usize, kernel_stats);
+            }
+        }
+
+        self.apply_kernel_boundary_actions(config::KernelBoundary::Completion, kernel, cycle);
     }
 }
 
-pub fn save_stats_to_file(stats: &stats::PerKernel, path: &Path) -> eyre::Result<()> {
+/// Metadata embedded into stats output files.
+///
+/// Makes result files self-describing: the exact configuration,
+/// simulator version, git commit, and host that produced them are
+/// recorded alongside the statistics.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StatsMetadata {
+    pub version: String,
+    pub git_commit: String,
+    pub host: Option<String>,
+    pub os: String,
+    pub arch: String,
+    /// Simulation performance per kernel (see [`SimulationRate`]).
+    pub simulation_rates: Vec<SimulationRate>,
+    pub config: Arc<config::GPU>,
+}
+
+/// Simulation performance of a single kernel run.
+///
+/// Rates of the simulator itself, in simulated work per wall-clock
+/// second, such that performance regressions of the simulator are
+/// visible in routine result files.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SimulationRate {
+    pub kernel_launch_id: usize,
+    pub elapsed_millis: u128,
+    pub cycles_per_second: f64,
+    pub instructions_per_second: f64,
+    pub accesses_per_second: f64,
+}
+
+impl SimulationRate {
+    /// The simulation rates of a kernel, or `None` for kernels that
+    /// completed within wall-clock timer resolution.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(kernel_launch_id: usize, kernel_stats: &stats::Stats) -> Option<Self> {
+        let elapsed_millis = kernel_stats.sim.elapsed_millis;
+        if elapsed_millis == 0 {
+            return None;
+        }
+        let seconds = elapsed_millis as f64 / 1000.0;
+        let num_accesses: u64 = kernel_stats.accesses.values().sum();
+        Some(Self {
+            kernel_launch_id,
+            elapsed_millis,
+            cycles_per_second: kernel_stats.sim.cycles as f64 / seconds,
+            instructions_per_second: kernel_stats.sim.instructions as f64 / seconds,
+            accesses_per_second: num_accesses as f64 / seconds,
+        })
+    }
+}
+
+impl StatsMetadata {
+    #[must_use]
+    pub fn new(config: Arc<config::GPU>, stats: &stats::PerKernel) -> Self {
+        let host = std::env::var("HOSTNAME").ok().or_else(|| {
+            std::fs::read_to_string("/proc/sys/kernel/hostname")
+                .ok()
+                .map(|hostname| hostname.trim().to_string())
+        });
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: env!("GIT_COMMIT_HASH").to_string(),
+            host,
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            simulation_rates: stats
+                .as_ref()
+                .iter()
+                .enumerate()
+                .filter_map(|(kernel_launch_id, kernel_stats)| {
+                    SimulationRate::new(kernel_launch_id, kernel_stats)
+                })
+                .collect(),
+            config,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct StatsFile<'a> {
+    metadata: StatsMetadata,
+    stats: &'a stats::PerKernel,
+}
+
+pub fn save_stats_to_file(
+    stats: &stats::PerKernel,
+    config: &Arc<config::GPU>,
+    path: &Path,
+) -> eyre::Result<()> {
     use serde::Serialize;
 
     let path = path.with_extension("json");
@@ -2043,7 +3217,11 @@ pub fn save_stats_to_file(stats: &stats::PerKernel, path: &Path) -> eyre::Result
         output_file,
         serde_json::ser::PrettyFormatter::with_indent(b"    "),
     );
-    stats.serialize(&mut json_serializer)?;
+    let file = StatsFile {
+        metadata: StatsMetadata::new(Arc::clone(config), stats),
+        stats,
+    };
+    file.serialize(&mut json_serializer)?;
     Ok(())
 }
 
@@ -2074,17 +3252,16 @@ pub fn init_deadlock_detector() {
     });
 }
 
-pub fn accelmain(
-    traces_dir: impl AsRef<Path>,
-    config: impl Into<Arc<config::GPU>>,
-) -> eyre::Result<config::GTX1080> {
-    init_deadlock_detector();
-    let config = config.into();
+/// Resolve the traces dir and commands file from a single trace path.
+///
+/// The path may either point to the trace directory containing a
+/// `commands.json` or to the commands file itself.
+pub fn trace_commands(traces_dir: impl AsRef<Path>) -> eyre::Result<(PathBuf, PathBuf)> {
     let traces_dir = traces_dir.as_ref();
-    let (traces_dir, commands_path) = if traces_dir.is_dir() {
-        (traces_dir.to_path_buf(), traces_dir.join("commands.json"))
+    if traces_dir.is_dir() {
+        Ok((traces_dir.to_path_buf(), traces_dir.join("commands.json")))
     } else {
-        (
+        Ok((
             traces_dir.parent().map(Path::to_path_buf).ok_or_else(|| {
                 eyre::eyre!(
                     "could not determine trace dir from file {}",
@@ -2092,8 +3269,17 @@ pub fn accelmain(
                 )
             })?,
             traces_dir.to_path_buf(),
-        )
-    };
+        ))
+    }
+}
+
+pub fn accelmain(
+    traces_dir: impl AsRef<Path>,
+    config: impl Into<Arc<config::GPU>>,
+) -> eyre::Result<config::GTX1080> {
+    init_deadlock_detector();
+    let config = config.into();
+    let (traces_dir, commands_path) = trace_commands(traces_dir)?;
 
     // debugging config
     // let config = Arc::new(config::GPUConfig {
@@ -2164,6 +3350,11 @@ pub fn init_logging() {
     log_builder.filter_level(log::LevelFilter::Off);
     log_builder.parse_default_env();
     log_builder.init();
+
+    match logging::filter_from_env() {
+        Ok(filter) => logging::set_filter(filter),
+        Err(err) => log::warn!("failed to parse log filter from environment: {}", err),
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,284 @@
+//! Component and cycle scoped log filtering.
+//!
+//! The debug logs emitted from the hot simulation loops (cores, caches,
+//! memory partitions) are far too verbose to be usable for larger runs
+//! when only the global `log` level can be controlled.
+//! This module provides a lightweight facade on top of the `log` crate
+//! that additionally filters by the emitting component and the current
+//! cycle, such that e.g. only the logs of a single core in a cycle
+//! window of interest are formatted at all.
+//!
+//! The filter is configured once at startup, either using the
+//! `--log-components` and `--log-cycles` options of the simulate
+//! command or the `LOG_COMPONENTS` and `LOG_CYCLES` environment
+//! variables:
+//!
+//! ```text
+//! LOG_COMPONENTS=core:0:1,partition:2 LOG_CYCLES=1000..2000
+//! ```
+
+use crate::sync::RwLock;
+use color_eyre::eyre;
+
+pub static FILTER: once_cell::sync::Lazy<RwLock<Filter>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(Filter::default()));
+
+/// A simulation component emitting log messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component<'a> {
+    Core { cluster_id: usize, core_id: usize },
+    Partition { partition_id: usize },
+    SubPartition { id: usize },
+    Cache { name: &'a str },
+}
+
+/// A pattern matching one or more [`Component`]s.
+///
+/// Components are matched as `kind:id...`, e.g. `core:1:0` matches
+/// core 0 of cluster 1.
+/// Omitted ids act as wildcards, e.g. `core:1` matches every core of
+/// cluster 1 and `core` matches all cores.
+/// Cache names are matched as substrings of the full cache name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentFilter {
+    Core {
+        cluster_id: Option<usize>,
+        core_id: Option<usize>,
+    },
+    Partition {
+        partition_id: Option<usize>,
+    },
+    SubPartition {
+        id: Option<usize>,
+    },
+    Cache {
+        name: String,
+    },
+}
+
+impl ComponentFilter {
+    #[must_use]
+    pub fn matches(&self, component: &Component) -> bool {
+        match (self, component) {
+            (
+                Self::Core {
+                    cluster_id,
+                    core_id,
+                },
+                Component::Core {
+                    cluster_id: have_cluster_id,
+                    core_id: have_core_id,
+                },
+            ) => {
+                cluster_id.is_none_or(|want| want == *have_cluster_id)
+                    && core_id.is_none_or(|want| want == *have_core_id)
+            }
+            (
+                Self::Partition { partition_id },
+                Component::Partition {
+                    partition_id: have_partition_id,
+                },
+            ) => partition_id.is_none_or(|want| want == *have_partition_id),
+            (Self::SubPartition { id }, Component::SubPartition { id: have_id }) => {
+                id.is_none_or(|want| want == *have_id)
+            }
+            (Self::Cache { name }, Component::Cache { name: have_name }) => {
+                have_name.contains(name.as_str())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::str::FromStr for ComponentFilter {
+    type Err = eyre::Report;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.trim().split(':');
+        let kind = parts.next().unwrap_or_default();
+        let mut id = || parts.next().map(str::parse).transpose();
+        match kind {
+            "core" => Ok(Self::Core {
+                cluster_id: id()?,
+                core_id: id()?,
+            }),
+            "cluster" => Ok(Self::Core {
+                cluster_id: id()?,
+                core_id: None,
+            }),
+            "partition" => Ok(Self::Partition {
+                partition_id: id()?,
+            }),
+            "subpartition" => Ok(Self::SubPartition { id: id()? }),
+            "cache" => Ok(Self::Cache {
+                name: parts.next().unwrap_or_default().to_string(),
+            }),
+            other => Err(eyre::eyre!(
+                "unknown component {:?}: must be one of core, cluster, partition, subpartition, or cache",
+                other
+            )),
+        }
+    }
+}
+
+/// The active log filter.
+///
+/// The default filter is empty and matches every component and cycle,
+/// such that scoped logging behaves exactly like the plain `log` macros
+/// unless a filter is configured.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// Components to log.
+    ///
+    /// An empty list matches all components.
+    pub components: Vec<ComponentFilter>,
+    /// Cycle range to log.
+    ///
+    /// `None` matches all cycles.
+    pub cycles: Option<std::ops::Range<u64>>,
+}
+
+impl Filter {
+    /// Parse a filter from component patterns and an optional cycle range.
+    pub fn parse<'a>(
+        components: impl IntoIterator<Item = &'a str>,
+        cycles: Option<&str>,
+    ) -> eyre::Result<Self> {
+        let components = components
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .filter(|value| !value.trim().is_empty())
+            .map(str::parse)
+            .collect::<eyre::Result<Vec<_>>>()?;
+        let cycles = cycles.map(parse_cycle_range).transpose()?;
+        Ok(Self { components, cycles })
+    }
+}
+
+/// Parse a cycle range of the form `start..end`.
+///
+/// Either bound may be omitted, e.g. `1000..` matches all cycles from
+/// cycle 1000 onwards.
+fn parse_cycle_range(value: &str) -> eyre::Result<std::ops::Range<u64>> {
+    let (start, end) = value.trim().split_once("..").ok_or_else(|| {
+        eyre::eyre!(
+            "invalid cycle range {:?}: must be of the form start..end",
+            value
+        )
+    })?;
+    let start = if start.is_empty() { 0 } else { start.parse()? };
+    let end = if end.is_empty() {
+        u64::MAX
+    } else {
+        end.parse()?
+    };
+    Ok(start..end)
+}
+
+/// Install a log filter.
+pub fn set_filter(filter: Filter) {
+    *FILTER.try_write() = filter;
+}
+
+/// Parse the log filter from the environment.
+pub fn filter_from_env() -> eyre::Result<Filter> {
+    let components = std::env::var("LOG_COMPONENTS").ok();
+    let cycles = std::env::var("LOG_CYCLES").ok();
+    Filter::parse(components.as_deref(), cycles.as_deref())
+}
+
+/// Check if a component should be logged in a cycle.
+#[must_use]
+pub fn enabled(component: &Component, cycle: u64) -> bool {
+    let filter = FILTER.try_read();
+    if let Some(cycles) = &filter.cycles {
+        if !cycles.contains(&cycle) {
+            return false;
+        }
+    }
+    filter.components.is_empty()
+        || filter
+            .components
+            .iter()
+            .any(|component_filter| component_filter.matches(component))
+}
+
+/// Log a debug message scoped to a component and cycle.
+///
+/// The message is dropped before any formatting takes place when the
+/// configured [`Filter`] does not match, which keeps the hot simulation
+/// loops cheap to log from.
+#[macro_export]
+macro_rules! debug_scoped {
+    ($component:expr, $cycle:expr, $($arg:tt)+) => {
+        if $crate::logging::enabled(&$component, $cycle) {
+            log::debug!($($arg)+);
+        }
+    };
+}
+
+/// Log a trace message scoped to a component and cycle.
+///
+/// See [`debug_scoped`](crate::debug_scoped).
+#[macro_export]
+macro_rules! trace_scoped {
+    ($component:expr, $cycle:expr, $($arg:tt)+) => {
+        if $crate::logging::enabled(&$component, $cycle) {
+            log::trace!($($arg)+);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Component, ComponentFilter, Filter};
+    use utils::diff;
+
+    #[test]
+    fn test_parse_filter() {
+        let filter = Filter::parse(["core:0:1,partition:2", "cache:l1d"], Some("1000..2000"))
+            .expect("valid filter");
+        diff::assert_eq!(
+            have: filter,
+            want: Filter {
+                components: vec![
+                    ComponentFilter::Core {
+                        cluster_id: Some(0),
+                        core_id: Some(1),
+                    },
+                    ComponentFilter::Partition {
+                        partition_id: Some(2),
+                    },
+                    ComponentFilter::Cache {
+                        name: "l1d".to_string(),
+                    },
+                ],
+                cycles: Some(1000..2000),
+            }
+        );
+    }
+
+    #[test]
+    fn test_wildcard_component_filter() {
+        let filter: ComponentFilter = "core:1".parse().expect("valid filter");
+        assert!(filter.matches(&Component::Core {
+            cluster_id: 1,
+            core_id: 0
+        }));
+        assert!(filter.matches(&Component::Core {
+            cluster_id: 1,
+            core_id: 7
+        }));
+        assert!(!filter.matches(&Component::Core {
+            cluster_id: 0,
+            core_id: 1
+        }));
+        assert!(!filter.matches(&Component::SubPartition { id: 1 }));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        assert!(Filter::parse(["socket:1"], None).is_err());
+        assert!(Filter::parse([], Some("1000")).is_err());
+    }
+}
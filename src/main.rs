@@ -14,14 +14,97 @@ static GLOBAL: Jemalloc = Jemalloc;
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 struct Options {
-    /// Input to operate on
-    #[arg(value_name = "TRACE_DIR")]
-    pub trace_dir: PathBuf,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Simulate traced applications
+    Simulate(SimulateOptions),
+    /// Trace a CUDA application
+    Trace(TraceOptions),
+    /// Profile a CUDA application
+    Profile(ProfileOptions),
+    /// Print stats files written by the simulate subcommand
+    Stats(StatsOptions),
+    /// Export stats as long-format CSV for comparison plots
+    Export(ExportOptions),
+    /// Migrate stats files written by older schema versions
+    Migrate(MigrateOptions),
+    /// Calibrate config parameters against hardware counters
+    Calibrate(CalibrateOptions),
+    /// Convert between native and accelsim trace formats
+    Convert(ConvertOptions),
+    /// Validate the integrity of trace directories
+    Check(CheckOptions),
+    /// Compute the occupancy for a kernel resource usage
+    Occupancy(OccupancyOptions),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SimulationEngine {
+    /// The native simulation engine.
+    Native,
+    /// The bundled accel-sim reference implementation (playground).
+    ///
+    /// Requires the "playground" feature.
+    Playground,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum WritebackOrdering {
+    /// Retire completed instructions immediately (default).
+    Relaxed,
+    /// Enforce program order per warp and functional unit category.
+    PerUnit,
+    /// Enforce program order per warp.
+    Warp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SchedulerTieBreak {
+    /// Lowest supervised warp slot wins (default, matches accel-sim).
+    LowestWarpId,
+    /// Round-robin starting from the last issued warp.
+    RoundRobin,
+    /// Seeded pseudo-random permutation, drawn every scheduler cycle.
+    Random,
+}
+
+#[derive(Debug, Parser)]
+struct SimulateOptions {
+    /// Input trace directories to operate on
+    #[arg(value_name = "TRACE_DIR", num_args = 1.., required = true)]
+    pub trace_dirs: Vec<PathBuf>,
+
+    #[clap(
+        long = "engine",
+        value_enum,
+        default_value = "native",
+        help = "simulation engine used to replay the traces"
+    )]
+    pub engine: SimulationEngine,
+
+    #[clap(
+        long = "accelsim-config-dir",
+        help = "directory with the accelsim config files (gpgpusim.config) for the playground engine"
+    )]
+    pub accelsim_config_dir: Option<PathBuf>,
 
     /// Stats output file
     #[arg(short = 'o', long = "stats", value_name = "STATS_OUT")]
     pub stats_out_file: Option<PathBuf>,
 
+    #[clap(
+        long = "quiet",
+        help = "render a one-line stats summary per kernel instead of the full report"
+    )]
+    pub quiet: bool,
+
+    #[clap(long = "no-color", help = "disable colored output")]
+    pub no_color: bool,
+
     /// Turn debugging information on
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub debug: u8,
@@ -49,9 +132,269 @@ struct Options {
     )]
     pub num_threads: Option<usize>,
 
+    #[clap(
+        long = "parallel-seed",
+        help = "seed for the nondeterministic parallel simulation mode"
+    )]
+    pub parallel_seed: Option<u64>,
+
+    #[clap(
+        long = "pin-threads",
+        help = "pin worker threads to a fixed set of clusters"
+    )]
+    pub pin_threads: bool,
+
+    #[clap(
+        long = "max-memory-mib",
+        help = "abort when the simulator's own memory usage exceeds this many MiB"
+    )]
+    pub max_memory_mib: Option<u64>,
+
+    #[clap(
+        long = "max-kernel-cycles",
+        help = "abort when a kernel runs for more than this many cycles (safety limit for persistent kernels)"
+    )]
+    pub max_kernel_cycles: Option<u64>,
+
     #[clap(long = "mem-only", help = "simulate only memory instructions")]
     pub memory_only: Option<bool>,
 
+    #[clap(
+        long = "memcopy-only",
+        help = "replay only memcopy commands through the L2/DRAM path (no cores)"
+    )]
+    pub memcopy_only: bool,
+
+    #[clap(
+        long = "host-gaps",
+        help = "replay the host-side gaps between commands recorded in the trace (end-to-end time estimate)"
+    )]
+    pub host_gaps: Option<bool>,
+
+    #[clap(
+        long = "cache-only",
+        help = "replay the traced memory accesses through the cache hierarchy only (no timing)"
+    )]
+    pub cache_only: bool,
+
+    #[clap(
+        long = "tag-only",
+        help = "like --cache-only, but keep only tag state per cache line (less memory for capacity sweeps)"
+    )]
+    pub tag_only: bool,
+
+    #[clap(
+        long = "mem-only-compute-latency",
+        help = "in memory-only mode, model compute instructions as scoreboard updates with this latency"
+    )]
+    pub memory_only_compute_latency: Option<u64>,
+
+    #[clap(
+        long = "read-first-arbitration",
+        help = "prioritize demand reads over writes at the memory sub partition input"
+    )]
+    pub read_first_arbitration: bool,
+
+    #[clap(
+        long = "write-drain-threshold",
+        help = "number of ready writes that triggers a write drain (read-first arbitration)"
+    )]
+    pub write_drain_threshold: Option<usize>,
+
+    #[clap(
+        long = "arbitration-max-age",
+        help = "maximum cycles a ready request may be deferred by arbitration"
+    )]
+    pub arbitration_max_age: Option<u64>,
+
+    #[clap(
+        long = "writeback-ordering",
+        value_enum,
+        help = "ordering enforced when completed instructions retire"
+    )]
+    pub writeback_ordering: Option<WritebackOrdering>,
+
+    #[clap(
+        long = "scheduler-tie-break",
+        value_enum,
+        help = "tie break among equally prioritized warps within a scheduler"
+    )]
+    pub scheduler_tie_break: Option<SchedulerTieBreak>,
+
+    #[clap(
+        long = "scheduler-tie-break-seed",
+        help = "seed for the random scheduler tie break"
+    )]
+    pub scheduler_tie_break_seed: Option<u64>,
+
+    #[clap(
+        long = "perfect-scheduler",
+        help = "idealized scheduler without structural issue constraints; the resulting IPC is an upper bound"
+    )]
+    pub perfect_scheduler: bool,
+
+    #[clap(
+        long = "num-copy-engines",
+        help = "number of DMA copy engines available for memory copies"
+    )]
+    pub num_copy_engines: Option<usize>,
+
+    #[clap(
+        long = "lenient",
+        help = "skip malformed trace records instead of failing"
+    )]
+    pub lenient: bool,
+
+    #[clap(
+        long = "l1-coherence",
+        help = "keep L1 data caches coherent using a write-invalidate protocol with a sharer directory at the L2"
+    )]
+    pub l1_coherence: bool,
+
+    #[clap(
+        long = "l2-residency-start",
+        help = "start address of the L2 residency window; lines in the window are prioritized in L2 replacement (CUDA access policy window)"
+    )]
+    pub l2_residency_start: Option<u64>,
+
+    #[clap(
+        long = "l2-residency-size",
+        help = "size of the L2 residency window in bytes"
+    )]
+    pub l2_residency_size: Option<u64>,
+
+    #[clap(
+        long = "l2-residency-max-ways-percent",
+        help = "maximum percentage of the ways of each L2 set that the residency window may occupy [default: 50]"
+    )]
+    pub l2_residency_max_ways_percent: Option<usize>,
+
+    #[clap(
+        long = "l2-residency-kernel",
+        help = "restrict the L2 residency window to this kernel launch id"
+    )]
+    pub l2_residency_kernel: Option<usize>,
+
+    #[clap(
+        long = "estimate-dram-latency",
+        help = "estimate the DRAM latency with an M/D/1 queueing model instead of the fixed latency"
+    )]
+    pub estimate_dram_latency: bool,
+
+    #[clap(
+        long = "dram-throttle-threshold",
+        help = "fraction of peak DRAM bandwidth above which the DRAM clock is throttled"
+    )]
+    pub dram_throttle_threshold: Option<f64>,
+
+    #[clap(
+        long = "dram-throttle-window",
+        help = "length of the DRAM bandwidth measurement window in cycles"
+    )]
+    pub dram_throttle_window: Option<u64>,
+
+    #[clap(
+        long = "dram-throttle-frequency-scale",
+        help = "factor applied to the DRAM clock frequency while throttled"
+    )]
+    pub dram_throttle_frequency_scale: Option<f64>,
+
+    #[clap(
+        long = "device",
+        help = "simulate only kernels traced on this device id"
+    )]
+    pub device: Option<u32>,
+
+    #[clap(
+        long = "set",
+        help = "override a config value by dotted path, e.g. --set data_cache_l2.inner.num_sets=128"
+    )]
+    pub config_overrides: Vec<String>,
+
+    #[clap(
+        long = "cluster-group",
+        help = "config overrides for a group of clusters, e.g. --cluster-group 0..4:num_schedulers_per_core=4"
+    )]
+    pub cluster_groups: Vec<String>,
+
+    #[clap(
+        long = "tenant",
+        help = "tenant as a group of CUDA streams with a priority, e.g. --tenant 0,1:2"
+    )]
+    pub tenants: Vec<String>,
+
+    #[clap(
+        long = "kernel-boundary",
+        help = "action at a kernel boundary, e.g. --kernel-boundary 'vecadd.*:completion:flush_l2'"
+    )]
+    pub kernel_boundary_actions: Vec<String>,
+
+    #[clap(
+        long = "operand-collector-preset",
+        help = "operand collector and register file preset of a named GPU (gtx1080, titanx, rtx3070)"
+    )]
+    pub operand_collector_preset: Option<String>,
+
+    #[clap(
+        long = "fail-core",
+        help = "mark a core failed at a cycle with an optional recovery penalty, e.g. --fail-core 0:1000:500"
+    )]
+    pub core_failures: Vec<String>,
+
+    #[clap(
+        long = "log-components",
+        help = "restrict debug logs to components, e.g. core:0:1 or cache:l1d"
+    )]
+    pub log_components: Vec<String>,
+
+    #[clap(
+        long = "log-cycles",
+        help = "restrict debug logs to a cycle range, e.g. 1000..2000"
+    )]
+    pub log_cycles: Option<String>,
+
+    #[clap(
+        long = "shared-l1",
+        help = "share the L1 data cache between all cores in a cluster"
+    )]
+    pub shared_l1: bool,
+
+    #[clap(
+        long = "shared-l1-ports",
+        help = "number of accesses admitted per cycle to a cluster-shared L1 over all cores"
+    )]
+    pub shared_l1_ports: Option<usize>,
+
+    #[clap(
+        long = "track-fetches",
+        help = "track the lifecycle of memory fetches and assert that none are leaked at kernel exit"
+    )]
+    pub track_fetches: bool,
+
+    #[clap(
+        long = "follow-uid",
+        help = "print the timestamped path of the fetch with this uid through the memory system"
+    )]
+    pub follow_uid: Vec<u64>,
+
+    #[clap(
+        long = "follow-addr",
+        help = "print the timestamped path of all fetches covering this byte address"
+    )]
+    pub follow_addr: Vec<u64>,
+
+    #[clap(
+        long = "l2-compression",
+        help = "L2 cache line compression model (\"bdi\", \"fpc\", or a fixed ratio)"
+    )]
+    pub l2_compression: Option<gpucachesim::cache::compression::Algorithm>,
+
+    #[clap(
+        long = "reset-stats-at-cycle",
+        help = "reset all statistics once this cycle is reached to exclude warmup"
+    )]
+    pub reset_stats_at_cycle: Option<u64>,
+
     #[clap(long = "fill-l2", help = "fill L2 cache on CUDA memcopy")]
     pub fill_l2: Option<bool>,
 
@@ -67,16 +410,820 @@ struct Options {
     #[clap(long = "simulate-clock-domains", help = "simulate clock domains")]
     pub simulate_clock_domains: Option<bool>,
 
-    #[clap(flatten)]
-    pub accelsim: gpucachesim::config::accelsim::Config,
+    #[clap(
+        long = "pipeview",
+        help = "write a per-cycle pipeline occupancy dump (pipeview) to this file"
+    )]
+    pub pipeview_out_file: Option<PathBuf>,
+
+    #[clap(
+        long = "pipeview-cluster",
+        help = "cluster id of the core observed by the pipeview dump"
+    )]
+    pub pipeview_cluster: Option<usize>,
+
+    #[clap(
+        long = "pipeview-core",
+        help = "core id (within its cluster) of the core observed by the pipeview dump"
+    )]
+    pub pipeview_core: Option<usize>,
+
+    #[clap(
+        long = "dram-trace",
+        help = "write the stream of requests issued to DRAM to this file"
+    )]
+    pub dram_trace_out_file: Option<PathBuf>,
+
+    #[clap(
+        long = "dram-trace-format",
+        help = "line format of the DRAM trace (dramsim3, ramulator, csv)"
+    )]
+    pub dram_trace_format: Option<String>,
+
+    #[clap(
+        long = "sass",
+        help = "SASS listing of the traced binary (cuobjdump -sass) used to print an annotated hot-spot listing per kernel"
+    )]
+    pub sass_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+struct TraceOptions {
+    /// Traced executable
+    #[arg(value_name = "EXECUTABLE")]
+    pub executable: PathBuf,
+
+    /// Arguments passed to the traced executable
+    #[arg(last = true)]
+    pub args: Vec<String>,
+
+    #[clap(long = "traces-dir", help = "path to output traces dir")]
+    pub traces_dir: PathBuf,
+
+    #[clap(long = "tracer", help = "path to tracer (e.g. libtrace.so)")]
+    pub tracer: Option<PathBuf>,
+
+    #[clap(
+        long = "save-json",
+        help = "whether to also save JSON traces (default: false)"
+    )]
+    pub save_json: bool,
+
+    #[clap(
+        long = "full-trace",
+        help = "trace all instructions, including non-memory instructions (default: false)"
+    )]
+    pub full_trace: bool,
+
+    #[clap(
+        long = "capture-store-values",
+        help = "capture a digest of the store values for functional checking (default: false)"
+    )]
+    pub capture_store_values: bool,
+}
+
+#[derive(Debug, Parser)]
+struct ProfileOptions {
+    /// Profiled executable
+    #[arg(value_name = "EXECUTABLE")]
+    pub executable: PathBuf,
+
+    /// Arguments passed to the profiled executable
+    #[arg(last = true)]
+    pub args: Vec<String>,
+
+    #[clap(long = "nvprof-path", help = "path to nvprof")]
+    pub nvprof_path: Option<PathBuf>,
+
+    #[clap(long = "device", help = "index of the CUDA device to profile")]
+    pub device: Option<usize>,
+}
+
+#[derive(Debug, Parser)]
+struct StatsOptions {
+    /// Stats files written by the simulate subcommand
+    #[arg(value_name = "STATS_FILE", num_args = 1.., required = true)]
+    pub stats_files: Vec<PathBuf>,
+
+    #[clap(long = "quiet", help = "render a one-line summary per kernel")]
+    pub quiet: bool,
+
+    #[clap(long = "no-color", help = "disable colored output")]
+    pub no_color: bool,
+}
+
+#[derive(Debug, Parser)]
+struct ExportOptions {
+    /// Stats files written by the simulate subcommand
+    #[arg(value_name = "STATS_FILE", num_args = 1.., required = true)]
+    pub stats_files: Vec<PathBuf>,
+
+    #[clap(
+        long = "profile-metrics",
+        help = "profiler metrics files (JSON) merged into the export"
+    )]
+    pub profile_metrics: Vec<PathBuf>,
+
+    /// Output CSV file (defaults to stdout)
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+struct MigrateOptions {
+    /// Stats files written by the simulate subcommand
+    #[arg(value_name = "STATS_FILE", num_args = 1.., required = true)]
+    pub stats_files: Vec<PathBuf>,
+
+    #[clap(
+        long = "in-place",
+        help = "overwrite the stats files instead of writing .migrated.json copies"
+    )]
+    pub in_place: bool,
+}
+
+#[derive(Debug, Parser)]
+struct CalibrateOptions {
+    /// Input trace directories of the calibration microbenchmarks
+    #[arg(value_name = "TRACE_DIR", num_args = 1.., required = true)]
+    pub trace_dirs: Vec<PathBuf>,
+
+    #[clap(
+        long = "target",
+        help = "hardware cycle count, one per trace directory"
+    )]
+    pub targets: Vec<u64>,
+
+    #[clap(
+        long = "profile-metrics",
+        help = "profiler metrics files (JSON), one per trace directory, as an alternative to --target"
+    )]
+    pub profile_metrics: Vec<PathBuf>,
+
+    #[clap(
+        long = "cycle-metric",
+        default_value = "elapsed_cycles_sm",
+        help = "profiler metric summed into the target cycle count"
+    )]
+    pub cycle_metric: String,
+
+    #[clap(
+        long = "param",
+        help = "parameter grid as dotted path with candidate values, e.g. --param dram_latency=100,150,190 (defaults to scaling dram_latency and l2_rop_latency)"
+    )]
+    pub params: Vec<String>,
+
+    #[clap(
+        long = "set",
+        help = "override a config value by dotted path, e.g. --set data_cache_l2.inner.num_sets=128"
+    )]
+    pub config_overrides: Vec<String>,
+
+    /// Calibrated config output file
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ConvertDirection {
+    /// Convert accelsim traces (kernelslist.g + .traceg) to native traces.
+    AccelsimToNative,
+    /// Convert native traces (commands.json + msgpack) to accelsim traces.
+    NativeToAccelsim,
+}
+
+#[derive(Debug, Parser)]
+struct ConvertOptions {
+    /// Conversion direction.
+    #[arg(value_enum)]
+    pub direction: ConvertDirection,
+
+    /// Directory with the native traces (commands.json + msgpack traces).
+    #[arg(long = "native-traces-dir")]
+    pub native_traces_dir: PathBuf,
+
+    /// Directory with the accelsim traces (kernelslist.g + .traceg traces).
+    #[arg(long = "accelsim-traces-dir")]
+    pub accelsim_traces_dir: PathBuf,
+
+    /// Path to the source commands file.
+    ///
+    /// Defaults to kernelslist.g or commands.json in the source trace
+    /// directory, depending on the conversion direction.
+    #[arg(long = "commands")]
+    pub commands: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+struct OccupancyOptions {
+    /// Number of threads per block
+    #[arg(long = "threads-per-block")]
+    pub threads_per_block: usize,
+
+    /// Number of registers per thread
+    #[arg(long = "registers", default_value = "0")]
+    pub num_registers: usize,
+
+    /// Shared memory bytes per block
+    #[arg(long = "shared-mem", default_value = "0")]
+    pub shared_mem_bytes: usize,
+}
+
+#[derive(Debug, Parser)]
+struct CheckOptions {
+    /// Trace directories to validate
+    #[arg(value_name = "TRACE_DIR", num_args = 1.., required = true)]
+    pub trace_dirs: Vec<PathBuf>,
+
+    /// Write a repaired command list without the broken kernels
+    #[arg(long = "prune")]
+    pub prune: bool,
 }
 
 fn main() -> eyre::Result<()> {
     color_eyre::install()?;
+
+    let options = Options::parse();
+    match options.command {
+        Command::Simulate(options) => simulate(options),
+        Command::Trace(options) => trace(options),
+        Command::Profile(options) => profile(options),
+        Command::Stats(options) => stats(options),
+        Command::Export(options) => export(options),
+        Command::Migrate(options) => migrate(&options),
+        Command::Calibrate(options) => calibrate(&options),
+        Command::Convert(options) => convert(options),
+        Command::Check(options) => check(options),
+        Command::Occupancy(options) => occupancy(&options),
+    }
+}
+
+fn occupancy(options: &OccupancyOptions) -> eyre::Result<()> {
+    let config = gpucachesim::config::GPU::default();
+    let occupancy = config.occupancy(
+        options.threads_per_block,
+        options.num_registers,
+        options.shared_mem_bytes,
+    );
+    eprintln!("{occupancy:#?}");
+    eprintln!(
+        "blocks per core: {} (max concurrent blocks per core: {})",
+        occupancy.blocks_per_core(),
+        config.max_concurrent_blocks_per_core,
+    );
+    Ok(())
+}
+
+fn check(options: CheckOptions) -> eyre::Result<()> {
+    let mut num_broken = 0;
+    for trace_dir in &options.trace_dirs {
+        let (traces_dir, commands_path) = gpucachesim::trace_commands(trace_dir)?;
+        let commands = gpucachesim::parse_commands(&commands_path)?;
+        let mut valid_commands = Vec::with_capacity(commands.len());
+        for command in commands {
+            if let trace_model::Command::KernelLaunch(ref launch) = command {
+                match check_kernel_trace(&traces_dir, launch) {
+                    Ok(()) => {
+                        eprintln!("kernel {:>3} ({}): OK", launch.id, launch.unmangled_name);
+                    }
+                    Err(err) => {
+                        num_broken += 1;
+                        eprintln!("kernel {:>3} ({}): {err}", launch.id, launch.unmangled_name);
+                        if options.prune {
+                            continue;
+                        }
+                    }
+                }
+            }
+            valid_commands.push(command);
+        }
+        if options.prune {
+            let repaired_commands_path = traces_dir.join("commands.repaired.json");
+            serde_json::to_writer_pretty(
+                utils::fs::open_writable(&repaired_commands_path)?,
+                &valid_commands,
+            )?;
+            eprintln!(
+                "wrote repaired command list to {}",
+                repaired_commands_path.display()
+            );
+        }
+    }
+    if num_broken > 0 {
+        if options.prune {
+            eprintln!("pruned {num_broken} broken kernel launches");
+        } else {
+            eyre::bail!("found {num_broken} broken kernel launches");
+        }
+    }
+    Ok(())
+}
+
+#[allow(deprecated)]
+fn check_kernel_trace(
+    traces_dir: &std::path::Path,
+    launch: &trace_model::command::KernelLaunch,
+) -> eyre::Result<()> {
+    use std::collections::{HashMap, HashSet};
+
+    let trace_path = traces_dir
+        .join(&launch.trace_file)
+        .with_extension("msgpack");
+    if !trace_path.is_file() {
+        eyre::bail!("missing trace file {}", trace_path.display());
+    }
+    let reader = utils::fs::open_readable(trace_path)?;
+    let trace: Vec<trace_model::MemAccessTraceEntry> = rmp_serde::from_read(reader)?;
+    trace_model::is_valid_trace(&trace)?;
+
+    let mut blocks = HashSet::new();
+    let mut seen_offsets: HashMap<(trace_model::Dim, u32), HashSet<u32>> = HashMap::new();
+    let mut last_offset: HashMap<(trace_model::Dim, u32), u32> = HashMap::new();
+
+    for entry in &trace {
+        let block = &entry.block_id;
+        if block.x >= launch.grid.x || block.y >= launch.grid.y || block.z >= launch.grid.z {
+            eyre::bail!("block {} is outside of grid {}", block, launch.grid);
+        }
+        blocks.insert(block.clone());
+
+        let warp_size = entry.warp_size.max(1) as usize;
+        let num_warps_per_block = launch.threads_per_block().div_ceil(warp_size);
+        if entry.warp_id_in_block as usize >= num_warps_per_block {
+            eyre::bail!(
+                "warp {} of block {} exceeds the {} warps per {} block",
+                entry.warp_id_in_block,
+                block,
+                num_warps_per_block,
+                launch.block
+            );
+        }
+
+        // backward jumps must target an offset the warp already executed
+        // (loops); anything else indicates a corrupt program counter
+        let warp = (block.clone(), entry.warp_id_in_block);
+        let seen = seen_offsets.entry(warp.clone()).or_default();
+        if let Some(last) = last_offset.get(&warp) {
+            if entry.instr_offset < *last && !seen.contains(&entry.instr_offset) {
+                eyre::bail!(
+                    "program counter of warp {} in block {} jumps back to offset {} which was never executed (last offset {})",
+                    entry.warp_id_in_block,
+                    block,
+                    entry.instr_offset,
+                    last
+                );
+            }
+        }
+        seen.insert(entry.instr_offset);
+        last_offset.insert(warp, entry.instr_offset);
+
+        if entry.instr_is_mem && ![1, 2, 4, 8, 16].contains(&entry.instr_data_width) {
+            eyre::bail!(
+                "memory instruction {} has invalid data width {}",
+                entry.instr_opcode,
+                entry.instr_data_width
+            );
+        }
+    }
+
+    if blocks.len() > launch.num_blocks() {
+        eyre::bail!(
+            "trace contains {} blocks for a grid of {} blocks",
+            blocks.len(),
+            launch.num_blocks()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "trace")]
+fn trace(options: TraceOptions) -> eyre::Result<()> {
+    let start = Instant::now();
+    let traces_dir = utils::fs::normalize_path(&options.traces_dir);
+    utils::fs::create_dirs(&traces_dir)?;
+
+    let trace_options = invoke_trace::Options {
+        traces_dir,
+        save_json: options.save_json,
+        full_trace: options.full_trace,
+        capture_store_values: options.capture_store_values,
+        skip_kernel_prefixes: vec![],
+        validate: false,
+        tracer_so: options.tracer.as_ref().map(utils::fs::normalize_path),
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime
+        .block_on(invoke_trace::trace(
+            options.executable,
+            options.args,
+            &trace_options,
+        ))
+        .map_err(invoke_trace::Error::into_eyre)?;
+    println!("tracing done in {:?}", start.elapsed());
+    Ok(())
+}
+
+#[cfg(not(feature = "trace"))]
+fn trace(_options: TraceOptions) -> eyre::Result<()> {
+    eyre::bail!(
+        "{} was compiled without tracing support (enable the \"trace\" feature)",
+        env!("CARGO_BIN_NAME")
+    );
+}
+
+#[cfg(feature = "profile")]
+fn profile(options: ProfileOptions) -> eyre::Result<()> {
+    let start = Instant::now();
+    let profile_options = profile::nvprof::Options {
+        nvprof_path: options.nvprof_path,
+        device: options.device,
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    let output = runtime
+        .block_on(profile::nvprof::nvprof(
+            options.executable,
+            options.args,
+            &profile_options,
+        ))
+        .map_err(profile::Error::into_eyre)?;
+    println!("{:#?}", &output.metrics);
+    println!("profiling done in {:?}", start.elapsed());
+    Ok(())
+}
+
+#[cfg(not(feature = "profile"))]
+fn profile(_options: ProfileOptions) -> eyre::Result<()> {
+    eyre::bail!(
+        "{} was compiled without profiling support (enable the \"profile\" feature)",
+        env!("CARGO_BIN_NAME")
+    );
+}
+
+fn stats(options: StatsOptions) -> eyre::Result<()> {
+    /// Relevant subset of the stats files written by [`gpucachesim::save_stats_to_file`].
+    #[derive(serde::Deserialize)]
+    struct StatsFile {
+        stats: stats::PerKernel,
+    }
+
+    if options.no_color {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+
+    for stats_file in &options.stats_files {
+        eprintln!("===== {} =====", stats_file.display());
+        let reader = utils::fs::open_readable(stats_file)?;
+        let file: StatsFile = serde_json::from_reader(reader)?;
+        print_stats(&file.stats, options.quiet);
+    }
+    Ok(())
+}
+
+fn export(options: ExportOptions) -> eyre::Result<()> {
+    /// Relevant subset of the stats files written by [`gpucachesim::save_stats_to_file`].
+    #[derive(serde::Deserialize)]
+    struct StatsFile {
+        stats: stats::PerKernel,
+    }
+
+    let mut rows = Vec::new();
+    for stats_file in &options.stats_files {
+        let reader = utils::fs::open_readable(stats_file)?;
+        let file: StatsFile = serde_json::from_reader(reader)?;
+        rows.extend(gpucachesim::export::simulation_rows(&file.stats));
+    }
+    for metrics_file in &options.profile_metrics {
+        let reader = utils::fs::open_readable(metrics_file)?;
+        let metrics: Vec<serde_json::Value> = serde_json::from_reader(reader)?;
+        rows.extend(gpucachesim::export::profile_rows(&metrics));
+    }
+
+    match options.output {
+        Some(ref output) => {
+            gpucachesim::export::write_csv(&rows, utils::fs::open_writable(output)?)?;
+        }
+        None => gpucachesim::export::write_csv(&rows, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn migrate(options: &MigrateOptions) -> eyre::Result<()> {
+    use serde::Serialize;
+
+    for stats_file in &options.stats_files {
+        let reader = utils::fs::open_readable(stats_file)?;
+        let mut file: serde_json::Value = serde_json::from_reader(reader)?;
+        let stats_value = file
+            .get_mut("stats")
+            .ok_or_else(|| eyre::eyre!("{} has no stats section", stats_file.display()))?;
+        let from_version = gpucachesim::migrate::migrate_stats(stats_value)?;
+
+        // the migrated stats must deserialize with the current structs
+        let _: stats::PerKernel = serde_json::from_value(stats_value.clone())?;
+
+        if from_version == stats::SCHEMA_VERSION {
+            println!(
+                "{}: already at schema version {}",
+                stats_file.display(),
+                stats::SCHEMA_VERSION
+            );
+            continue;
+        }
+
+        let output_path = if options.in_place {
+            stats_file.clone()
+        } else {
+            stats_file.with_extension("migrated.json")
+        };
+        let output_file = utils::fs::open_writable(&output_path)?;
+        let mut json_serializer = serde_json::Serializer::with_formatter(
+            output_file,
+            serde_json::ser::PrettyFormatter::with_indent(b"    "),
+        );
+        file.serialize(&mut json_serializer)?;
+        println!(
+            "{}: migrated from schema version {} to {} ({})",
+            stats_file.display(),
+            from_version,
+            stats::SCHEMA_VERSION,
+            output_path.display()
+        );
+    }
+    Ok(())
+}
+
+fn calibrate(options: &CalibrateOptions) -> eyre::Result<()> {
+    use gpucachesim::calibrate::{Benchmark, Parameter};
+
+    gpucachesim::init_deadlock_detector();
+
+    let mut config = gpucachesim::config::GPU::default();
+    for assignment in &options.config_overrides {
+        config.apply_override(assignment)?;
+    }
+
+    let targets: Vec<u64> = if options.profile_metrics.is_empty() {
+        eyre::ensure!(
+            options.targets.len() == options.trace_dirs.len(),
+            "expected one --target or --profile-metrics per trace directory"
+        );
+        options.targets.clone()
+    } else {
+        eyre::ensure!(
+            options.profile_metrics.len() == options.trace_dirs.len(),
+            "expected one --target or --profile-metrics per trace directory"
+        );
+        options
+            .profile_metrics
+            .iter()
+            .map(|metrics_file| {
+                let reader = utils::fs::open_readable(metrics_file)?;
+                let metrics: Vec<serde_json::Value> = serde_json::from_reader(reader)?;
+                let cycles: f64 = gpucachesim::export::profile_rows(&metrics)
+                    .iter()
+                    .filter(|row| row.metric == options.cycle_metric)
+                    .map(|row| row.value)
+                    .sum();
+                eyre::ensure!(
+                    cycles > 0.0,
+                    "{} has no {:?} metric",
+                    metrics_file.display(),
+                    options.cycle_metric
+                );
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                Ok(cycles as u64)
+            })
+            .collect::<eyre::Result<_>>()?
+    };
+
+    let benchmarks: Vec<Benchmark> = options
+        .trace_dirs
+        .iter()
+        .zip(targets)
+        .map(|(traces_dir, target_cycles)| Benchmark {
+            traces_dir: traces_dir.clone(),
+            target_cycles,
+        })
+        .collect();
+
+    let parameters: Vec<Parameter> = if options.params.is_empty() {
+        ["dram_latency", "l2_rop_latency"]
+            .iter()
+            .map(|path| Parameter::default_grid(&config, path))
+            .collect::<eyre::Result<_>>()?
+    } else {
+        options
+            .params
+            .iter()
+            .map(|param| {
+                let (path, candidates) = param.split_once('=').ok_or_else(|| {
+                    eyre::eyre!("expected <path>=<value>[,<value>..], got {param:?}")
+                })?;
+                Ok(Parameter {
+                    path: path.to_string(),
+                    candidates: candidates.split(',').map(str::to_string).collect(),
+                })
+            })
+            .collect::<eyre::Result<_>>()?
+    };
+
+    let calibration = gpucachesim::calibrate::calibrate(&config, &benchmarks, &parameters)?;
+    let best = calibration.best();
+    println!(
+        "best: {} (mean relative error {:.4})",
+        best.overrides.join(" "),
+        best.mean_relative_error
+    );
+
+    if let Some(output) = &options.output {
+        let calibrated = calibration.calibrated_config(&config)?;
+        let writer = utils::fs::open_writable(output)?;
+        serde_json::to_writer_pretty(writer, &calibrated)?;
+        println!("wrote calibrated config to {}", output.display());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "accelsim")]
+fn convert(options: ConvertOptions) -> eyre::Result<()> {
+    use accelsim::tracegen;
+
+    let start = Instant::now();
+    let generated_commands_path = match options.direction {
+        ConvertDirection::AccelsimToNative => {
+            utils::fs::create_dirs(&options.native_traces_dir)?;
+            let commands_path = options
+                .commands
+                .clone()
+                .unwrap_or_else(|| options.accelsim_traces_dir.join("kernelslist.g"));
+            tracegen::convert_accelsim_to_box_traces(&tracegen::Conversion {
+                native_commands_path: &commands_path,
+                box_traces_dir: &options.native_traces_dir,
+                accelsim_traces_dir: &options.accelsim_traces_dir,
+            })?
+        }
+        ConvertDirection::NativeToAccelsim => {
+            utils::fs::create_dirs(&options.accelsim_traces_dir)?;
+            let commands_path = options
+                .commands
+                .clone()
+                .unwrap_or_else(|| options.native_traces_dir.join("commands.json"));
+            tracegen::convert_box_to_accelsim_traces(&tracegen::Conversion {
+                native_commands_path: &commands_path,
+                box_traces_dir: &options.native_traces_dir,
+                accelsim_traces_dir: &options.accelsim_traces_dir,
+            })?
+        }
+    };
+
+    println!("generated {}", generated_commands_path.display());
+    println!("conversion done in {:?}", start.elapsed());
+    Ok(())
+}
+
+#[cfg(not(feature = "accelsim"))]
+fn convert(_options: ConvertOptions) -> eyre::Result<()> {
+    eyre::bail!(
+        "{} was compiled without accel-sim support (enable the \"accelsim\" feature)",
+        env!("CARGO_BIN_NAME")
+    );
+}
+
+#[cfg(feature = "playground")]
+fn simulate_playground(options: SimulateOptions) -> eyre::Result<()> {
+    let start = Instant::now();
+
+    let config_dir = options
+        .accelsim_config_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(std::env!("CARGO_MANIFEST_DIR")).join("accelsim/gtx1080"));
+    let gpgpusim_config = config_dir.join("gpgpusim.config");
+    let trace_config = config_dir.join("gpgpusim.trace.config");
+    let inter_config = config_dir.join("config_fermi_islip.icnt");
+    for config in [&gpgpusim_config, &trace_config, &inter_config] {
+        if !config.is_file() {
+            eyre::bail!("missing accelsim config file {}", config.display());
+        }
+    }
+
+    for (trace_idx, trace_dir) in options.trace_dirs.iter().enumerate() {
+        let (traces_dir, commands_path) = gpucachesim::trace_commands(trace_dir)?;
+
+        // the playground replays accelsim traces:
+        // convert native traces on the fly unless the trace dir already
+        // contains a kernelslist.g
+        let kernelslist = traces_dir.join("kernelslist.g");
+        let kernelslist = if kernelslist.is_file() {
+            kernelslist
+        } else {
+            let accelsim_traces_dir = traces_dir.join("accelsim-trace");
+            utils::fs::create_dirs(&accelsim_traces_dir)?;
+            accelsim::tracegen::convert_box_to_accelsim_traces(&accelsim::tracegen::Conversion {
+                native_commands_path: &commands_path,
+                box_traces_dir: &traces_dir,
+                accelsim_traces_dir: &accelsim_traces_dir,
+            })?
+        };
+
+        let args: Vec<String> = [
+            "-trace",
+            &*kernelslist.to_string_lossy(),
+            "-config",
+            &*gpgpusim_config.to_string_lossy(),
+            "-config",
+            &*trace_config.to_string_lossy(),
+            "-inter_config_file",
+            &*inter_config.to_string_lossy(),
+        ]
+        .map(str::to_string)
+        .to_vec();
+
+        let play_config = playground::Config::default();
+        let play_stats = playground::run(play_config, args)?;
+
+        // the playground only reports aggregate stats over all kernels
+        let stats = stats::PerKernel {
+            schema_version: stats::SCHEMA_VERSION,
+            inner: vec![play_stats.into()],
+            no_kernel: stats::Stats::empty(),
+            config: stats::Config {
+                num_total_cores: 1,
+                num_mem_units: 1,
+                num_dram_banks: 1,
+                num_sub_partitions: 1,
+                max_issue_per_core: 0,
+                perfect_scheduler: false,
+            },
+        };
+
+        // save stats to file
+        if let Some(stats_out_file) = options.stats_out_file.as_ref() {
+            use serde::Serialize;
+
+            // same layout as [`gpucachesim::save_stats_to_file`], minus the
+            // native config metadata which does not describe a playground run
+            #[derive(serde::Serialize)]
+            struct StatsFile<'a> {
+                stats: &'a stats::PerKernel,
+            }
+
+            let stats_out_file = if options.trace_dirs.len() > 1 {
+                // per-trace stats files
+                stats_out_file.with_extension(format!("{trace_idx}.json"))
+            } else {
+                stats_out_file.with_extension("json")
+            };
+            let output_file = utils::fs::open_writable(stats_out_file)?;
+            let mut json_serializer = serde_json::Serializer::with_formatter(
+                output_file,
+                serde_json::ser::PrettyFormatter::with_indent(b"    "),
+            );
+            StatsFile { stats: &stats }.serialize(&mut json_serializer)?;
+        }
+
+        print_stats(&stats, options.quiet);
+    }
+    eprintln!("simulated in {:?}", start.elapsed());
+    Ok(())
+}
+
+#[cfg(not(feature = "playground"))]
+fn simulate_playground(_options: SimulateOptions) -> eyre::Result<()> {
+    eyre::bail!(
+        "{} was compiled without the playground engine (enable the \"playground\" feature)",
+        env!("CARGO_BIN_NAME")
+    );
+}
+
+fn simulate(options: SimulateOptions) -> eyre::Result<()> {
+    if options.no_color {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+    if options.engine == SimulationEngine::Playground {
+        return simulate_playground(options);
+    }
+
     gpucachesim::init_deadlock_detector();
 
+    if options.track_fetches {
+        gpucachesim::mem_fetch::tracker::enable();
+    }
+    for uid in &options.follow_uid {
+        gpucachesim::mem_fetch::tracker::follow(gpucachesim::mem_fetch::tracker::Filter::Uid(*uid));
+    }
+    for addr in &options.follow_addr {
+        gpucachesim::mem_fetch::tracker::follow(gpucachesim::mem_fetch::tracker::Filter::Addr(
+            *addr,
+        ));
+    }
+
     let start = Instant::now();
-    let options = Options::parse();
     #[cfg(debug_assertions)]
     std::env::set_var("RUST_BACKTRACE", "full");
 
@@ -89,6 +1236,14 @@ fn main() -> eyre::Result<()> {
         gpucachesim::init_logging();
     }
 
+    if !options.log_components.is_empty() || options.log_cycles.is_some() {
+        let filter = gpucachesim::logging::Filter::parse(
+            options.log_components.iter().map(String::as_str),
+            options.log_cycles.as_deref(),
+        )?;
+        gpucachesim::logging::set_filter(filter);
+    }
+
     let deadlock_check = std::env::var("DEADLOCK_CHECK")
         .unwrap_or_default()
         .to_lowercase()
@@ -125,9 +1280,34 @@ fn main() -> eyre::Result<()> {
         parallelization,
         deadlock_check,
         log_after_cycle,
+        reset_stats_at_cycle: options.reset_stats_at_cycle,
         simulation_threads: options.num_threads,
+        parallelization_seed: options.parallel_seed,
+        pin_threads_to_clusters: options.pin_threads,
+        simulation_memory_limit_bytes: options.max_memory_mib.map(|mib| mib << 20),
         ..gpucachesim::config::GPU::default()
     };
+    if let Some(preset) = options.operand_collector_preset.as_deref() {
+        // applied first, such that dedicated flags and generic overrides
+        // can still tweak single fields of the preset
+        let preset = gpucachesim::config::OperandCollectorPreset::from_name(preset)?;
+        config.apply_operand_collector_preset(&preset);
+    }
+    if let Some(out_file) = options.pipeview_out_file {
+        config.pipeview = Some(gpucachesim::config::Pipeview {
+            cluster_id: options.pipeview_cluster.unwrap_or(0),
+            core_id: options.pipeview_core.unwrap_or(0),
+            out_file,
+        });
+    }
+    if let Some(out_file) = options.dram_trace_out_file {
+        let format = match options.dram_trace_format.as_deref() {
+            Some(format) => gpucachesim::config::DramTraceFormat::from_name(format)?,
+            // the csv format carries all fields of a request
+            None => gpucachesim::config::DramTraceFormat::Csv,
+        };
+        config.dram_trace = Some(gpucachesim::config::DramTrace { out_file, format });
+    }
     if let Some(accelsim_compat_mode) = options.accelsim_compat_mode {
         config.fill_l2_on_memcopy &= !accelsim_compat_mode;
         config.perfect_inst_const_cache |= accelsim_compat_mode;
@@ -143,6 +1323,19 @@ fn main() -> eyre::Result<()> {
     if let Some(simulate_clock_domains) = options.simulate_clock_domains {
         config.simulate_clock_domains = simulate_clock_domains;
     }
+    if options.shared_l1 {
+        config.l1_cache_sharing = gpucachesim::config::CacheSharing::Cluster;
+    }
+    if let Some(shared_l1_ports) = options.shared_l1_ports {
+        config.l1_cache_shared_ports = shared_l1_ports;
+    }
+    if let Some(l2_compression) = options.l2_compression {
+        // the L2 cache config is not yet shared at this point
+        let l2 = config.data_cache_l2.as_mut().expect("have L2 data cache");
+        let l2 = std::sync::Arc::get_mut(l2).expect("exclusive L2 cache config");
+        let inner = std::sync::Arc::get_mut(&mut l2.inner).expect("exclusive L2 cache config");
+        inner.compression = Some(l2_compression);
+    }
     if let Some(fill_l2) = options.fill_l2 {
         config.fill_l2_on_memcopy = fill_l2;
     }
@@ -155,9 +1348,154 @@ fn main() -> eyre::Result<()> {
     if let Some(memory_only) = options.memory_only {
         config.memory_only = memory_only;
     }
+    config.memcopy_only = options.memcopy_only;
+    config.perfect_scheduler = options.perfect_scheduler;
+    if let Some(host_gaps) = options.host_gaps {
+        config.host_gaps = host_gaps;
+    }
+    config.lenient_trace_loading = options.lenient;
+    config.l1_coherence = options.l1_coherence;
+    if let (Some(start), Some(size)) = (options.l2_residency_start, options.l2_residency_size) {
+        config.l2_residency_window = Some(gpucachesim::config::L2ResidencyWindow {
+            start,
+            end: start + size,
+            max_ways_percent: options.l2_residency_max_ways_percent.unwrap_or(50),
+            kernel_launch_id: options.l2_residency_kernel,
+        });
+    }
+    if let Some(num_copy_engines) = options.num_copy_engines {
+        config.num_copy_engines = num_copy_engines;
+    }
+    config.dram_latency_estimate = options.estimate_dram_latency;
+    config.max_kernel_cycles = options.max_kernel_cycles;
+    if options.read_first_arbitration {
+        config.memory_arbitration = gpucachesim::config::MemoryArbitration::ReadFirst {
+            write_drain_threshold: options.write_drain_threshold.unwrap_or(8),
+            max_age: options.arbitration_max_age.unwrap_or(100),
+        };
+    }
+    if let Some(tie_break) = options.scheduler_tie_break {
+        config.scheduler_tie_break = match tie_break {
+            SchedulerTieBreak::LowestWarpId => gpucachesim::config::SchedulerTieBreak::LowestWarpId,
+            SchedulerTieBreak::RoundRobin => gpucachesim::config::SchedulerTieBreak::RoundRobin,
+            SchedulerTieBreak::Random => gpucachesim::config::SchedulerTieBreak::Random {
+                seed: options.scheduler_tie_break_seed.unwrap_or(0),
+            },
+        };
+    }
+    if let Some(ordering) = options.writeback_ordering {
+        config.writeback_ordering = match ordering {
+            WritebackOrdering::Relaxed => gpucachesim::config::WritebackOrdering::Relaxed,
+            WritebackOrdering::PerUnit => gpucachesim::config::WritebackOrdering::PerUnitInOrder,
+            WritebackOrdering::Warp => gpucachesim::config::WritebackOrdering::InOrder,
+        };
+    }
+    if let Some(bandwidth_threshold) = options.dram_throttle_threshold {
+        config.dram_throttling = Some(gpucachesim::config::DramThrottling {
+            bandwidth_threshold,
+            window: options.dram_throttle_window.unwrap_or(1000),
+            frequency_scale: options.dram_throttle_frequency_scale.unwrap_or(0.5),
+        });
+        // the DRAM frequency has no effect unless clock domains are simulated
+        config.simulate_clock_domains = true;
+    }
+    if let Some(latency) = options.memory_only_compute_latency {
+        config.memory_only_compute_latency = Some(latency);
+    }
+    if let Some(device) = options.device {
+        config.trace_device = Some(device);
+    }
+    // generic overrides are applied last, such that they take precedence
+    // over the dedicated flags
+    for assignment in &options.config_overrides {
+        config.apply_override(assignment)?;
+    }
+    for group in &options.cluster_groups {
+        let invalid =
+            || eyre::eyre!("expected <start>..<end>:<key=value>[,<key=value>..], got {group:?}");
+        let (clusters, overrides) = group.split_once(':').ok_or_else(invalid)?;
+        let (start, end) = clusters.split_once("..").ok_or_else(invalid)?;
+        let group = gpucachesim::config::ClusterGroup {
+            clusters: start.trim().parse()?..end.trim().parse()?,
+            overrides: overrides.split(',').map(str::to_string).collect(),
+        };
+        config.cluster_groups.push(group);
+    }
+    for tenant in &options.tenants {
+        let invalid = || eyre::eyre!("expected <stream>[,<stream>..]:<priority>, got {tenant:?}");
+        let (streams, priority) = tenant.split_once(':').ok_or_else(invalid)?;
+        let streams: Vec<u64> = streams
+            .split(',')
+            .map(|stream| stream.trim().parse())
+            .collect::<Result<_, _>>()?;
+        let tenant = gpucachesim::config::Tenant {
+            streams,
+            kernels: Vec::new(),
+            priority: priority.trim().parse()?,
+        };
+        config.tenants.push(tenant);
+    }
+    for action in &options.kernel_boundary_actions {
+        let invalid = || {
+            eyre::eyre!(
+                "expected <launch id|name regex>:<launch|completion>:<flush_l1|flush_l2|reset_stats>, got {action:?}"
+            )
+        };
+        // parse from the right: the kernel name regex may contain colons
+        let (rest, action_name) = action.rsplit_once(':').ok_or_else(invalid)?;
+        let (kernel, boundary) = rest.rsplit_once(':').ok_or_else(invalid)?;
+        let kernel = match kernel.parse::<u64>() {
+            Ok(launch_id) => gpucachesim::config::KernelMatcher::LaunchId(launch_id),
+            Err(_) => gpucachesim::config::KernelMatcher::NameRegex(kernel.to_string()),
+        };
+        let boundary = match boundary {
+            "launch" => gpucachesim::config::KernelBoundary::Launch,
+            "completion" => gpucachesim::config::KernelBoundary::Completion,
+            _ => return Err(invalid()),
+        };
+        let action = match action_name {
+            "flush_l1" => gpucachesim::config::BoundaryAction::FlushL1,
+            "flush_l2" => gpucachesim::config::BoundaryAction::FlushL2,
+            "reset_stats" => gpucachesim::config::BoundaryAction::ResetStats,
+            _ => return Err(invalid()),
+        };
+        config
+            .kernel_boundary_actions
+            .push(gpucachesim::config::KernelBoundaryAction {
+                kernel,
+                boundary,
+                action,
+            });
+    }
+    for failure in &options.core_failures {
+        let invalid =
+            || eyre::eyre!("expected <core id>:<cycle>[:<recovery penalty>], got {failure:?}");
+        let mut parts = failure.split(':');
+        let core_id = parts.next().ok_or_else(invalid)?.trim().parse()?;
+        let cycle = parts.next().ok_or_else(invalid)?.trim().parse()?;
+        let recovery_penalty = parts
+            .next()
+            .map(|penalty| penalty.trim().parse())
+            .transpose()?
+            .unwrap_or(0);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        config.core_failures.push(gpucachesim::config::CoreFailure {
+            core_id,
+            cycle,
+            recovery_penalty,
+        });
+    }
+    config.core_failures.sort_by_key(|failure| failure.cycle);
+
+    // catch operand collector port configurations that cannot sustain
+    // the configured pipeline widths
+    config.validate_operand_collector()?;
 
     dbg!(&config.accelsim_compat);
     dbg!(&config.memory_only);
+    dbg!(&config.memcopy_only);
     dbg!(&config.num_schedulers_per_core);
     dbg!(&config.num_simt_clusters);
     dbg!(&config.num_cores_per_simt_cluster);
@@ -165,72 +1503,79 @@ fn main() -> eyre::Result<()> {
     dbg!(&config.perfect_inst_const_cache);
     dbg!(&config.fill_l2_on_memcopy);
 
-    let sim = gpucachesim::accelmain(&options.trace_dir, config)?;
-    let stats = sim.stats();
+    if options.cache_only || options.tag_only {
+        let config = std::sync::Arc::new(config);
+        for (trace_idx, trace_dir) in options.trace_dirs.iter().enumerate() {
+            let (traces_dir, commands_path) = gpucachesim::trace_commands(trace_dir)?;
+            let stats = gpucachesim::cache_only::replay(
+                std::sync::Arc::clone(&config),
+                commands_path,
+                traces_dir,
+                options.tag_only,
+            )?;
+
+            // save stats to file
+            if let Some(stats_out_file) = options.stats_out_file.as_ref() {
+                let stats_out_file = if options.trace_dirs.len() > 1 {
+                    // per-trace stats files
+                    stats_out_file.with_extension(format!("{trace_idx}.json"))
+                } else {
+                    stats_out_file.clone()
+                };
+                gpucachesim::save_stats_to_file(&stats, &config, &stats_out_file)?;
+            }
 
-    // save stats to file
-    if let Some(stats_out_file) = options.stats_out_file.as_ref() {
-        gpucachesim::save_stats_to_file(&stats, stats_out_file)?;
+            print_stats(&stats, options.quiet);
+        }
+        if let Some(summary) = gpucachesim::fidelity::summary() {
+            eprintln!("{summary}");
+        }
+        eprintln!("completed in {:?}", start.elapsed());
+        return Ok(());
     }
 
-    eprintln!("STATS:\n");
-    eprintln!("SIM[no-kernel]: {:#?}", &stats.no_kernel.sim);
-    eprintln!("L1I[no-kernel]: {:#?}", &stats.no_kernel.l1i_stats.reduce());
-    eprintln!("L1D[no-kernel]: {:#?}", &stats.no_kernel.l1d_stats.reduce());
-    eprintln!("L2D[no-kernel]: {:#?}", &stats.no_kernel.l2d_stats.reduce());
-    eprintln!("DRAM[no-kernel]: {:#?}", &stats.no_kernel.dram.reduce());
-    eprintln!("ACCESSES[no-kernel]: {:#?}", &stats.no_kernel.accesses,);
+    let sass_listings = options
+        .sass_file
+        .as_ref()
+        .map(gpucachesim::sass::parse_file)
+        .transpose()?;
 
-    for (kernel_launch_id, kernel_stats) in stats.as_ref().iter().enumerate() {
-        eprintln!(
-            "\n ===== kernel launch {kernel_launch_id:<3}: {}  =====\n",
-            kernel_stats.sim.kernel_name
-        );
-        eprintln!("DRAM: {:#?}", &kernel_stats.dram.reduce());
-        eprintln!("SIM: {:#?}", &kernel_stats.sim);
-        eprintln!("INSTRUCTIONS: {:#?}", &kernel_stats.instructions);
-        eprintln!("ACCESSES: {:#?}", &kernel_stats.accesses);
+    // reuse the constructed GPU topology across all traces
+    let mut sim = gpucachesim::config::GTX1080::new(std::sync::Arc::new(config.clone()));
 
-        let l1i_stats = kernel_stats.l1i_stats.reduce();
-        eprintln!("L1I: {:#?}", &l1i_stats);
-        eprintln!(
-            "L1I hit rate: {:4.2}% ({} hits / {} accesses)",
-            &l1i_stats.hit_rate() * 100.0,
-            &l1i_stats.num_hits(),
-            &l1i_stats.num_accesses(),
-        );
+    for (trace_idx, trace_dir) in options.trace_dirs.iter().enumerate() {
+        if trace_idx > 0 {
+            sim.reset();
+        }
+        let (traces_dir, commands_path) = gpucachesim::trace_commands(trace_dir)?;
+        sim.add_commands(commands_path, traces_dir)?;
+        if options.stats_out_file.is_some() {
+            // serialize kernel stats in the background as kernels complete
+            sim.set_stats_writer(gpucachesim::stats_writer::StatsWriter::spawn());
+        }
+        sim.run()?;
+        let stats = sim.stats();
 
-        let l1d_stats = kernel_stats.l1d_stats.reduce();
-        eprintln!("L1D: {:#?}", &l1d_stats);
-        eprintln!(
-            "L1D hit rate: {:4.2}% ({} hits / {} accesses)",
-            &l1d_stats.global_hit_rate() * 100.0,
-            &l1d_stats.num_global_hits(),
-            &l1d_stats.num_global_accesses(),
-        );
+        // save stats to file
+        if let Some(stats_out_file) = options.stats_out_file.as_ref() {
+            let stats_out_file = if options.trace_dirs.len() > 1 {
+                // per-trace stats files
+                stats_out_file.with_extension(format!("{trace_idx}.json"))
+            } else {
+                stats_out_file.clone()
+            };
+            match sim.take_stats_writer() {
+                Some(writer) => writer.finalize(&stats, &sim.config, &stats_out_file)?,
+                None => gpucachesim::save_stats_to_file(&stats, &sim.config, &stats_out_file)?,
+            }
+        }
 
-        let l2d_stats = kernel_stats.l2d_stats.reduce();
-        eprintln!("L2D: {:#?}", &l2d_stats);
-        eprintln!(
-            "L2D hit rate: {:4.2}% ({} hits / {} accesses)",
-            &l2d_stats.global_hit_rate() * 100.0,
-            &l2d_stats.num_global_hits(),
-            &l2d_stats.num_global_accesses(),
-        );
-        eprintln!(
-            "L2D write hit rate: {:4.2}% ({} write hits / {} writes)",
-            &l2d_stats.global_write_hit_rate() * 100.0,
-            &l2d_stats.num_global_write_hits(),
-            &l2d_stats.num_global_writes(),
-        );
-        eprintln!(
-            "L2D read hit rate: {:4.2}% ({} read hits / {} reads)",
-            &l2d_stats.global_read_hit_rate() * 100.0,
-            &l2d_stats.num_global_read_hits(),
-            &l2d_stats.num_global_reads(),
-        );
+        print_stats(&stats, options.quiet);
+
+        if let Some(sass_listings) = sass_listings.as_ref() {
+            print_annotated_sass(&stats, sass_listings);
+        }
     }
-    eprintln!("TIMINGS:");
     let timings: Vec<_> = gpucachesim::TIMINGS
         .lock()
         .clone()
@@ -239,29 +1584,59 @@ fn main() -> eyre::Result<()> {
         .collect();
 
     let total_time = start.elapsed();
-    let norm_time = if gpucachesim::config::Parallelization::Serial != parallelization {
-        timings
-            .iter()
-            .map(|(_, dur)| dur.total())
-            .sum::<std::time::Duration>()
-        // .max()
-        // .copied()
-        // .unwrap_or(std::time::Duration::ZERO)
-    } else {
-        total_time
-    };
-    for (label, value) in timings {
-        let mean = value.mean();
-        let total = value.total();
-        let percent = total.as_secs_f64() / norm_time.as_secs_f64();
-        eprintln!(
-            "\t{:<35} {: >15} ({: >4.2}% total: {: >15})",
-            label,
-            format!("{:?}", mean),
-            percent * 100.0,
-            format!("{:?}", total),
-        );
+    // only populated with the "timings" feature
+    if !timings.is_empty() {
+        eprintln!("TIMINGS:");
+        let norm_time = if gpucachesim::config::Parallelization::Serial != parallelization {
+            timings
+                .iter()
+                .map(|(_, dur)| dur.total())
+                .sum::<std::time::Duration>()
+            // .max()
+            // .copied()
+            // .unwrap_or(std::time::Duration::ZERO)
+        } else {
+            total_time
+        };
+        for (label, value) in timings {
+            let mean = value.mean();
+            let total = value.total();
+            let percent = total.as_secs_f64() / norm_time.as_secs_f64();
+            eprintln!(
+                "\t{:<35} {: >15} ({: >4.2}% total: {: >15})",
+                label,
+                format!("{:?}", mean),
+                percent * 100.0,
+                format!("{:?}", total),
+            );
+        }
+    }
+    if let Some(summary) = gpucachesim::fidelity::summary() {
+        eprintln!("{summary}");
     }
     eprintln!("completed in {:?}", total_time);
     Ok(())
 }
+
+fn print_stats(stats: &stats::PerKernel, quiet: bool) {
+    let options = gpucachesim::report::Options { quiet };
+    eprint!("{}", gpucachesim::report::render(stats, &options));
+}
+
+fn print_annotated_sass(
+    stats: &stats::PerKernel,
+    sass_listings: &indexmap::IndexMap<String, gpucachesim::sass::Listing>,
+) {
+    for (kernel_launch_id, kernel_stats) in stats.as_ref().iter().enumerate() {
+        let mangled_name = &kernel_stats.sim.kernel_name_mangled;
+        let Some(listing) = sass_listings.get(mangled_name) else {
+            eprintln!("SASS: no listing for kernel {mangled_name}");
+            continue;
+        };
+        eprintln!(
+            "\n ===== SASS for kernel launch {kernel_launch_id:<3}: {}  =====\n",
+            kernel_stats.sim.kernel_name
+        );
+        eprint!("{}", listing.annotate(&kernel_stats.per_pc));
+    }
+}
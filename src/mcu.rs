@@ -1,7 +1,5 @@
 use crate::{address, config};
 use color_eyre::eyre::{self, WrapErr};
-use once_cell::sync::Lazy;
-use regex::Regex;
 
 // /// Base 2 logarithm of n.
 // ///
@@ -68,10 +66,32 @@ pub struct Config {
     pub burst: Mask,
 }
 
-static ACCELSIM_ADDRESS_DECODE_CONFIG_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(dramid@(?P<dramid>\d+))?;?(?P<rest>.*)").unwrap());
-
 impl Config {
+    /// Parse an accel-sim compatible address mapping configuration.
+    ///
+    /// A mapping consists of `;`-separated segments.
+    /// A segment of the form `dramid@N` fixes the start bit of the chip address
+    /// to `N`, which is required for non power of two channel counts.
+    /// It may appear before or after the mapping mask.
+    /// All remaining segments are mapping mask groups and are concatenated in
+    /// order.
+    ///
+    /// The mapping mask assigns a meaning to each address bit, written from the
+    /// most to the least significant bit:
+    ///
+    /// - `d`: chip (DRAM channel)
+    /// - `b`: bank
+    /// - `r`: row
+    /// - `c`: column
+    /// - `s`: burst (the low column bits selecting the byte within a burst,
+    ///   which also count as column bits)
+    /// - `0` or `x`: unused
+    ///
+    /// Tokens are case-insensitive and the separators `.`, `|`, `_`, and
+    /// whitespace may be inserted freely for readability.
+    /// Masks shorter than 64 bits apply to the low bits of the address, so
+    /// mappings from published papers (often written for 32 bit addresses) can
+    /// be plugged in directly.
     pub fn parse_accelsim_config(config: impl AsRef<str>) -> eyre::Result<Self> {
         let config = config.as_ref().to_lowercase();
         let mut chip_mask = 0x0;
@@ -80,25 +100,37 @@ impl Config {
         let mut col_mask = 0x0;
         let mut burst_mask = 0x0;
 
-        let captures = ACCELSIM_ADDRESS_DECODE_CONFIG_REGEX
-            .captures(&config)
-            .ok_or_else(|| eyre::eyre!("invalid config format: {:?}", config))?;
-
-        let dram_id: Option<&str> = captures.name("dramid").as_ref().map(regex::Match::as_str);
-
-        let addr_chip_start: Option<usize> = dram_id
-            .map(str::parse)
-            .transpose()
-            .wrap_err_with(|| eyre::eyre!("bad dram id: {:?}", dram_id))?;
+        let mut addr_chip_start: Option<usize> = None;
+        let mut mask = String::new();
+        for segment in config.split(';') {
+            let segment = segment.trim();
+            if let Some(dram_id) = segment.strip_prefix("dramid@") {
+                if addr_chip_start.is_some() {
+                    eyre::bail!("duplicate dram id in config: {:?}", config);
+                }
+                addr_chip_start = Some(
+                    dram_id
+                        .parse()
+                        .wrap_err_with(|| eyre::eyre!("bad dram id: {:?}", dram_id))?,
+                );
+            } else {
+                mask.push_str(segment);
+            }
+        }
 
-        let rest = captures
-            .name("rest")
-            .as_ref()
-            .map(regex::Match::as_str)
-            .unwrap_or_default();
+        let is_separator = |c: char| matches!(c, '|' | '.' | '_') || c.is_whitespace();
+        let num_bits = mask.chars().filter(|c| !is_separator(*c)).count();
+        if num_bits > 64 {
+            eyre::bail!(
+                "invalid address mapping \"{}\" (expected at most 64 bits but found {})",
+                mask,
+                num_bits,
+            );
+        }
 
-        let mut offset: i8 = 63;
-        for c in rest.chars() {
+        // mappings shorter than 64 bits apply to the low bits of the address
+        let mut offset: i8 = i8::try_from(num_bits).unwrap() - 1;
+        for c in mask.chars() {
             match c {
                 'd' => {
                     chip_mask |= 1 << offset;
@@ -121,22 +153,16 @@ impl Config {
                     col_mask |= 1 << offset;
                     offset -= 1;
                 }
-                '0' => {
+                '0' | 'x' => {
                     offset -= 1;
                 }
-                '|' | ' ' | '.' => {
+                _ if is_separator(c) => {
                     // ignore
                 }
                 other => eyre::bail!("undefined character {}", other),
             }
         }
-        if offset != -1 {
-            eyre::bail!(
-                "invalid address mapping \"{}\" (expected length 64 but found {})",
-                rest,
-                63 - offset,
-            );
-        }
+        debug_assert_eq!(offset, -1);
         Ok(Self {
             addr_chip_start,
             chip: chip_mask.into(),
@@ -618,6 +644,106 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_accelsim_decode_address_config_variants() -> eyre::Result<()> {
+        let want = super::Config::parse_accelsim_config(
+            "dramid@8;00000000.00000000.00000000.00000000.0000RRRR.RRRRRRRR.RBBBCCCC.BCCSSSSS",
+        )?;
+
+        // dramid@ may follow the mapping mask
+        let have = super::Config::parse_accelsim_config(
+            "00000000.00000000.00000000.00000000.0000RRRR.RRRRRRRR.RBBBCCCC.BCCSSSSS;dramid@8",
+        )?;
+        diff::assert_eq!(have: have, want: want);
+
+        // mask groups may be split across segments
+        let have = super::Config::parse_accelsim_config(
+            "dramid@8;00000000.00000000.00000000.00000000;0000RRRR.RRRRRRRR.RBBBCCCC.BCCSSSSS",
+        )?;
+        diff::assert_eq!(have: have, want: want);
+
+        // masks shorter than 64 bits apply to the low bits of the address
+        let have =
+            super::Config::parse_accelsim_config("dramid@8;0000RRRR.RRRRRRRR.RBBBCCCC.BCCSSSSS")?;
+        diff::assert_eq!(have: have, want: want);
+
+        // 'x' marks an unused bit, just like '0'
+        let have =
+            super::Config::parse_accelsim_config("dramid@8;xxxxRRRR.RRRRRRRR.RBBBCCCC.BCCSSSSS")?;
+        diff::assert_eq!(have: have, want: want);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_accelsim_decode_address_config() {
+        // duplicate dram id
+        assert!(super::Config::parse_accelsim_config("dramid@8;dramid@9;RRRR.CCSS").is_err());
+        // undefined token
+        assert!(super::Config::parse_accelsim_config("RRRR.CCQQ").is_err());
+        // more than 64 bits
+        assert!(super::Config::parse_accelsim_config("R".repeat(65)).is_err());
+        // bad dram id
+        assert!(super::Config::parse_accelsim_config("dramid@eight;RRRR.CCSS").is_err());
+    }
+
+    /// Inverse of [`super::packbits`]: scatter the low bits of `val` to the
+    /// positions set in `mask`.
+    fn unpackbits(mask: u64, val: u64, low: u8, high: u8) -> u64 {
+        let mut pos = 0;
+        let mut res = 0;
+        for i in low..high {
+            if mask & (1u64 << i) != 0 {
+                res |= ((val >> pos) & 1) << i;
+                pos += 1;
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn test_decode_address_round_trip() -> eyre::Result<()> {
+        let mappings = [
+            // GTX 1080
+            "dramid@8;00000000.00000000.00000000.00000000.0000RRRR.RRRRRRRR.RBBBCCCC.BCCSSSSS",
+            // explicit chip bits instead of dramid@
+            "00000000.00000000.00000000.0000DDDR.RRRRRRRR.RRRRRRRB.BBBCCCCC.CCSSSSSS",
+            // interleaved bank and column bits
+            "00000000.00000000.00000000.00000000.RRRRRRRR.RRRRRRBC.CBCCBCCC.CCCSSSSS",
+            // short, paper-style 32 bit mapping
+            "RRRRRRRR.RRRRBBBB.CCCCCCCC.CCSSSSSS",
+        ];
+        for mapping in mappings {
+            let dec = super::Config::parse_accelsim_config(mapping)?;
+
+            // the component masks must be disjoint, except for the burst bits,
+            // which are part of the column bits
+            assert_eq!(dec.burst.mask & dec.col.mask, dec.burst.mask);
+            let components = [&dec.chip, &dec.bank, &dec.row, &dec.col];
+            let mapped = dec.chip.mask | dec.bank.mask | dec.row.mask | dec.col.mask;
+            assert_eq!(components.iter().map(|c| c.mask).sum::<u64>(), mapped);
+
+            let addresses = (0..u64::BITS).map(|bit| 1u64 << bit).chain([
+                0,
+                u64::MAX,
+                0xDEAD_BEEF_CAFE_F00D,
+                0x5555_5555_5555_5555,
+            ]);
+            for addr in addresses {
+                // decompose the address and reassemble it from its components
+                let have = components
+                    .iter()
+                    .map(|component| {
+                        let value =
+                            super::packbits(component.mask, addr, component.low, component.high);
+                        unpackbits(component.mask, value, component.low, component.high)
+                    })
+                    .fold(0, std::ops::BitOr::bitor);
+                diff::assert_eq!(have: bit_str(have), want: bit_str(addr & mapped));
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_packbits() {
         use super::packbits;
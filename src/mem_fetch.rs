@@ -266,6 +266,7 @@ pub mod access {
                         self.addr,
                         alloc.start_addr
                     );
+                    crate::fidelity::approximated("access address below allocation start");
                 }
             }
             assert_eq!(self.kind.is_write(), self.is_write);
@@ -484,7 +485,7 @@ impl Builder {
         } else {
             Kind::READ_REQUEST
         };
-        MemFetch {
+        let fetch = MemFetch {
             uid: generate_uid(),
             access: self.access,
             instr: self.instr,
@@ -501,7 +502,9 @@ impl Builder {
             original_fetch: None,
             original_write_fetch: None,
             latency: 0,
-        }
+        };
+        tracker::created(&fetch);
+        fetch
     }
 }
 
@@ -630,6 +633,7 @@ impl MemFetch {
     pub fn set_status(&mut self, status: Status, time: u64) {
         self.status = status;
         self.last_status_change = Some(time);
+        tracker::status_change(self);
     }
 
     #[must_use]
@@ -659,3 +663,198 @@ impl MemFetch {
         }
     }
 }
+
+pub mod tracker {
+    //! Lifecycle tracking of memory fetches.
+    //!
+    //! A debug facility that records every created fetch together with
+    //! the stage it was last seen in. Fetches are retired when they
+    //! leave the memory system; after a kernel has drained,
+    //! [`assert_drained`] asserts that no fetch is still stuck in a
+    //! queue or MSHR. Requests dropped by bugs would otherwise silently
+    //! skew results.
+    //!
+    //! Tracking is disabled by default and must be turned on with
+    //! [`enable`] before the simulation starts.
+
+    use crate::sync::Mutex;
+    use once_cell::sync::Lazy;
+    use std::collections::HashMap;
+    use std::sync::atomic;
+
+    /// A fetch that was created but not yet retired.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct LiveFetch {
+        pub uid: u64,
+        pub kind: super::Kind,
+        pub access_kind: super::access::Kind,
+        pub addr: crate::address,
+        /// The stage the fetch was last seen in.
+        pub last_status: super::Status,
+        /// The cycle of the last stage change.
+        pub last_status_change: Option<u64>,
+    }
+
+    impl From<&super::MemFetch> for LiveFetch {
+        fn from(fetch: &super::MemFetch) -> Self {
+            Self {
+                uid: fetch.uid,
+                kind: fetch.kind,
+                access_kind: fetch.access_kind(),
+                addr: fetch.addr(),
+                last_status: fetch.status,
+                last_status_change: fetch.last_status_change,
+            }
+        }
+    }
+
+    impl std::fmt::Display for LiveFetch {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(
+                f,
+                "{:?}({:?}@{}) [uid={}] last seen in {:?}",
+                self.kind, self.access_kind, self.addr, self.uid, self.last_status,
+            )?;
+            if let Some(cycle) = self.last_status_change {
+                write!(f, " at cycle {cycle}")?;
+            }
+            Ok(())
+        }
+    }
+
+    static ENABLED: atomic::AtomicBool = atomic::AtomicBool::new(false);
+
+    static LIVE: Lazy<Mutex<HashMap<u64, LiveFetch>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+    static FOLLOWING: atomic::AtomicBool = atomic::AtomicBool::new(false);
+
+    static FOLLOW: Lazy<Mutex<Vec<Filter>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+    /// A filter selecting the fetches followed through the memory system.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Filter {
+        /// The fetch with the given uid.
+        Uid(u64),
+        /// All fetches covering the given byte address.
+        Addr(crate::address),
+    }
+
+    impl Filter {
+        #[must_use]
+        fn matches(self, fetch: &super::MemFetch) -> bool {
+            match self {
+                Filter::Uid(uid) => fetch.uid == uid,
+                Filter::Addr(addr) => {
+                    let start = fetch.addr();
+                    (start..start + u64::from(fetch.data_size())).contains(&addr)
+                }
+            }
+        }
+    }
+
+    /// Follow matching fetches through the memory system.
+    ///
+    /// Every status change of a matching fetch is printed together with
+    /// the cycle it happened in, giving the full timestamped path of
+    /// the packet across the components it traverses. Used to debug why
+    /// the latency of a particular address is anomalous.
+    pub fn follow(filter: Filter) {
+        FOLLOW.lock().push(filter);
+        FOLLOWING.store(true, atomic::Ordering::Relaxed);
+    }
+
+    fn followed(fetch: &super::MemFetch) -> bool {
+        if !FOLLOWING.load(atomic::Ordering::Relaxed) {
+            return false;
+        }
+        FOLLOW.lock().iter().any(|filter| filter.matches(fetch))
+    }
+
+    fn print_followed(fetch: &super::MemFetch, stage: &str) {
+        eprint!(
+            "follow: {:?}({:?}@{}) [uid={}] {stage}",
+            fetch.kind,
+            fetch.access_kind(),
+            fetch.addr(),
+            fetch.uid,
+        );
+        match fetch.last_status_change {
+            Some(cycle) => eprintln!(" at cycle {cycle}"),
+            None => eprintln!(),
+        }
+    }
+
+    /// Enable fetch lifecycle tracking.
+    pub fn enable() {
+        ENABLED.store(true, atomic::Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_enabled() -> bool {
+        ENABLED.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Record a newly created fetch.
+    pub(crate) fn created(fetch: &super::MemFetch) {
+        if followed(fetch) {
+            print_followed(fetch, "created");
+        }
+        if !is_enabled() {
+            return;
+        }
+        LIVE.lock().insert(fetch.uid, fetch.into());
+    }
+
+    /// Record the stage a fetch was last seen in.
+    pub(crate) fn status_change(fetch: &super::MemFetch) {
+        if followed(fetch) {
+            print_followed(fetch, &format!("=> {:?}", fetch.status));
+        }
+        if !is_enabled() {
+            return;
+        }
+        if let Some(live) = LIVE.lock().get_mut(&fetch.uid) {
+            live.last_status = fetch.status;
+            live.last_status_change = fetch.last_status_change;
+        }
+    }
+
+    /// Retire a fetch that left the memory system.
+    pub(crate) fn retired(fetch: &super::MemFetch) {
+        if followed(fetch) {
+            print_followed(fetch, "retired");
+        }
+        if !is_enabled() {
+            return;
+        }
+        LIVE.lock().remove(&fetch.uid);
+    }
+
+    /// The fetches created but not yet retired, sorted by uid.
+    #[must_use]
+    pub fn live_fetches() -> Vec<LiveFetch> {
+        let mut live: Vec<_> = LIVE.lock().values().cloned().collect();
+        live.sort_by_key(|fetch| fetch.uid);
+        live
+    }
+
+    /// Assert that no fetch is still in flight.
+    ///
+    /// Must only be called when the memory system has drained, e.g.
+    /// after a kernel completed. Panics with a report of each leaked
+    /// fetch and the stage it was last seen in.
+    pub fn assert_drained() {
+        if !is_enabled() {
+            return;
+        }
+        let live = live_fetches();
+        if live.is_empty() {
+            return;
+        }
+        eprintln!("{} leaked fetches:", live.len());
+        for fetch in &live {
+            eprintln!(" => {fetch}");
+        }
+        panic!("leaked {} fetches", live.len());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::sync::{Arc, Mutex};
 use crate::{
-    address, arbitration, config, dram, ic::Packet, mcu, mem_fetch,
+    address, arbitration, config, dram, dram_trace, ic::Packet, mcu, mem_fetch,
     mem_sub_partition::MemorySubPartition,
 };
 use console::style;
@@ -15,8 +15,32 @@ pub struct MemoryPartitionUnit {
     pub arbiter: Box<dyn arbitration::Arbiter>,
 
     config: Arc<config::GPU>,
-    #[allow(dead_code)]
     stats: Arc<Mutex<stats::PerKernel>>,
+
+    /// Busy and idle cycles of this DRAM channel.
+    ///
+    /// The channel is busy as long as requests wait in its latency
+    /// queue.
+    pub utilization: stats::utilization::Counters,
+
+    /// M/D/1 latency estimate for this DRAM channel.
+    ///
+    /// The estimator replaces the fixed [`config::GPU::dram_latency`]
+    /// when [`config::GPU::dram_latency_estimate`] is set and otherwise
+    /// runs passively for calibration against the detailed model.
+    pub latency_estimator: dram::LatencyEstimator,
+
+    /// DRAM request trace dump (see [`crate::dram_trace`]).
+    ///
+    /// The writer is shared by all DRAM channels, such that the dump is
+    /// a single request stream.
+    pub dram_trace: Option<Arc<Mutex<dram_trace::Writer>>>,
+
+    /// External DRAM timing backend for this channel.
+    ///
+    /// When set, the backend latency replaces the built-in latency
+    /// model (see [`dram_trace::Backend`]).
+    pub dram_backend: Option<Box<dyn dram_trace::Backend>>,
 }
 
 impl std::fmt::Debug for MemoryPartitionUnit {
@@ -31,6 +55,7 @@ impl MemoryPartitionUnit {
         config: Arc<config::GPU>,
         mem_controller: Arc<dyn mcu::MemoryController>,
         stats: Arc<Mutex<stats::PerKernel>>,
+        dram_trace: Option<Arc<Mutex<dram_trace::Writer>>>,
     ) -> Self {
         let num_sub_partitions = config.num_sub_partitions_per_memory_controller;
         let sub_partitions: Vec<_> = (0..num_sub_partitions)
@@ -48,6 +73,7 @@ impl MemoryPartitionUnit {
             .collect();
 
         let dram = dram::DRAM::new(&config, stats.clone());
+        let latency_estimator = dram::LatencyEstimator::new(&config);
         let arb_config: arbitration::Config = (&(*config)).into();
         let arbiter = Box::new(arbitration::ArbitrationUnit::new(&arb_config));
         Self {
@@ -55,9 +81,13 @@ impl MemoryPartitionUnit {
             config,
             stats,
             dram,
+            latency_estimator,
             dram_latency_queue: VecDeque::new(),
             arbiter,
             sub_partitions,
+            utilization: stats::utilization::Counters::default(),
+            dram_trace,
+            dram_backend: None,
         }
     }
 
@@ -126,6 +156,9 @@ impl MemoryPartitionUnit {
     pub fn simple_dram_cycle(&mut self, cycle: u64) {
         use mem_fetch::access::Kind as AccessKind;
         log::debug!("{} ...", style("simple dram cycle").red());
+
+        self.utilization
+            .add_cycle(!self.dram_latency_queue.is_empty());
         // pop completed memory request from dram and push it to dram-to-L2 queue
         // of the original sub partition
         // if !self.dram_latency_queue.is_empty() &&
@@ -146,6 +179,14 @@ impl MemoryPartitionUnit {
                 );
 
                 let (_, returned_fetch) = self.dram_latency_queue.pop_front().unwrap();
+                let (latency, estimated_latency) = self.latency_estimator.complete(cycle);
+                {
+                    let mut stats = self.stats.lock();
+                    let kernel_stats = stats.get_mut(returned_fetch.kernel_launch_id());
+                    kernel_stats.dram.total_latency += latency;
+                    kernel_stats.dram.total_estimated_latency += estimated_latency;
+                    kernel_stats.dram.num_latency_samples += 1;
+                }
                 self.set_done(&returned_fetch);
             }
             Some((ready_cycle, returned_fetch)) if cycle >= *ready_cycle => {
@@ -170,6 +211,14 @@ impl MemoryPartitionUnit {
                     // panic!("fyi: simple dram model stall");
                 } else {
                     let (_, mut returned_fetch) = self.dram_latency_queue.pop_front().unwrap();
+                    let (latency, estimated_latency) = self.latency_estimator.complete(cycle);
+                    {
+                        let mut stats = self.stats.lock();
+                        let kernel_stats = stats.get_mut(returned_fetch.kernel_launch_id());
+                        kernel_stats.dram.total_latency += latency;
+                        kernel_stats.dram.total_estimated_latency += estimated_latency;
+                        kernel_stats.dram.num_latency_samples += 1;
+                    }
                     // dbg!(&returned_fetch);
                     // returned_fetch.set_reply();
 
@@ -199,8 +248,23 @@ impl MemoryPartitionUnit {
         // L2->DRAM queue to DRAM latency queue
         // Arbitrate among multiple L2 subpartitions
         let last_issued_partition = self.arbiter.last_borrower();
-        for sub_id in 0..self.sub_partitions.len() {
-            let spid = (sub_id + last_issued_partition + 1) % self.sub_partitions.len();
+        let mut issue_order: Vec<usize> = (0..self.sub_partitions.len())
+            .map(|sub_id| (sub_id + last_issued_partition + 1) % self.sub_partitions.len())
+            .collect();
+        if !self.config.tenants.is_empty() {
+            // prefer the sub partition whose oldest request belongs to
+            // the highest-priority tenant (ties keep the round-robin
+            // order)
+            issue_order.sort_by_key(|&spid| {
+                let sub = self.sub_partitions[spid].try_lock();
+                let l2_to_dram_queue = sub.l2_to_dram_queue.try_lock();
+                let priority = l2_to_dram_queue
+                    .first()
+                    .map(|fetch| crate::tenant::priority_of(fetch.kernel_launch_id()));
+                std::cmp::Reverse(priority)
+            });
+        }
+        for spid in issue_order {
             let sub = self.sub_partitions[spid].try_lock();
 
             let sub_partition_contention = sub.dram_to_l2_queue.full();
@@ -253,6 +317,12 @@ impl MemoryPartitionUnit {
                     if self.dram.full(fetch.is_write()) {
                         break;
                     }
+                    // the tag lookup overhead also applies to misses
+                    // before they proceed to DRAM (see
+                    // [`config::GPU::l2_tag_lookup_latency`])
+                    if cycle < fetch.time + self.config.l2_tag_lookup_latency {
+                        continue;
+                    }
 
                     let mut fetch = l2_to_dram_queue.dequeue().unwrap().into_inner();
                     log::debug!(
@@ -264,9 +334,24 @@ impl MemoryPartitionUnit {
                     //     "issue mem_fetch request {:?} from sub partition {} to dram",
                     //     fetch, spid
                     // );
-                    let ready_cycle = cycle + self.config.dram_latency as u64;
+                    if let Some(dram_trace) = &self.dram_trace {
+                        dram_trace.lock().record(cycle, &fetch).unwrap();
+                    }
+                    let estimated_latency = self.latency_estimator.estimate(cycle);
+                    let latency = if let Some(backend) = &mut self.dram_backend {
+                        backend.issue(cycle, &fetch)
+                    } else if self.config.dram_latency_estimate {
+                        crate::fidelity::approximated(
+                            "DRAM latency estimated with M/D/1 queueing model",
+                        );
+                        estimated_latency
+                    } else {
+                        self.config.dram_latency as u64
+                    };
+                    let ready_cycle = cycle + latency;
                     fetch.set_status(mem_fetch::Status::IN_PARTITION_DRAM_LATENCY_QUEUE, 0);
                     self.dram_latency_queue.push_back((ready_cycle, fetch));
+                    self.latency_estimator.issue(cycle, estimated_latency);
                     self.arbiter.borrow_credit(spid);
 
                     // DRAM should only accept one request per cycle
@@ -1,17 +1,37 @@
 use crate::sync::{Arc, Mutex};
-use crate::{address, cache, config, fifo::Fifo, interconn::Packet, mcu, mem_fetch};
+use crate::{address, cache, config, fifo::Fifo, interconn::Packet, logging, mcu, mem_fetch};
 use console::style;
-use std::collections::{HashSet, VecDeque};
+use indexmap::IndexSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use trace_model::ToBitString;
 
 pub const MAX_MEMORY_ACCESS_SIZE: u32 = 128;
 
-/// A 128KB cache line is broken down into four 32B sectors.
+/// Maximum number of sectors per cache line.
+///
+/// By default, a 128B cache line is broken down into four 32B sectors.
+/// The per-cache sector geometry (see [`config::Cache::sector_size`])
+/// must fit within this limit, which sizes the sector masks and the
+/// per-sector state of a cache line.
 pub const NUM_SECTORS: usize = 4;
 
-/// Sector size is 32 bytes.
+/// Default sector size of 32 bytes.
 pub const SECTOR_SIZE: u32 = 32;
 
+/// Arbitration class of a request at the sub partition input.
+///
+/// Used as the key of the per-class queueing delay stats and by the
+/// read-first arbitration policy.
+#[must_use]
+pub fn arbitration_class(fetch: &mem_fetch::MemFetch) -> &'static str {
+    use mem_fetch::access::Kind;
+    match fetch.access_kind() {
+        Kind::L1_WRBK_ACC | Kind::L2_WRBK_ACC => "writeback",
+        Kind::GLOBAL_ACC_W | Kind::LOCAL_ACC_W => "write",
+        _ => "read",
+    }
+}
+
 // pub struct MemorySubPartition<Q = Fifo<mem_fetch::MemFetch>> {
 pub struct MemorySubPartition {
     pub id: usize,
@@ -27,12 +47,47 @@ pub struct MemorySubPartition {
     pub dram_to_l2_queue: Fifo<Packet<mem_fetch::MemFetch>>,
     /// L2 cache hit response queue
     pub l2_to_interconn_queue: Fifo<Packet<mem_fetch::MemFetch>>,
-    pub rop_queue: VecDeque<(u64, mem_fetch::MemFetch)>,
+    pub rop_queue: Fifo<(u64, mem_fetch::MemFetch)>,
+    /// L2 hit replies waiting out the configured hit latency.
+    ///
+    /// Only used with a nonzero [`config::GPU::l2_hit_latency`] or
+    /// [`config::GPU::l2_tag_lookup_latency`]: replies are released
+    /// into the L2-to-interconnect queue at their ready cycle.
+    pub l2_hit_queue: Fifo<(u64, Packet<mem_fetch::MemFetch>)>,
 
     pub l2_cache: Option<Box<dyn cache::Cache<stats::cache::PerKernel>>>,
 
     num_pending_requests: usize,
-    request_tracker: HashSet<mem_fetch::MemFetch>,
+    request_tracker: IndexSet<mem_fetch::MemFetch>,
+
+    /// Busy and idle cycles of this L2 slice.
+    ///
+    /// The slice is busy as long as it tracks an in-flight request.
+    pub utilization: stats::utilization::Counters,
+
+    /// Sharer directory of the L1 coherence protocol.
+    ///
+    /// Tracks per L1 line which cores may hold a copy in their L1 data
+    /// cache. Empty unless [`config::GPU::l1_coherence`] is enabled.
+    l1_directory: HashMap<address, HashSet<usize>>,
+    /// Coherence invalidations waiting to be applied to the L1s at the
+    /// end of the cycle.
+    pending_l1_invalidations: Vec<L1Invalidation>,
+}
+
+/// A pending L1 coherence invalidation.
+///
+/// Recorded by the sharer directory when a global write hits a line
+/// that other cores may hold, and applied to the L1 data caches at the
+/// end of the cycle without occupying the interconnect.
+#[derive(Debug, Clone, Copy)]
+pub struct L1Invalidation {
+    /// Global core id of the L1 to invalidate.
+    pub core_id: usize,
+    /// Address of the line to invalidate.
+    pub addr: address,
+    /// Kernel of the write that triggered the invalidation.
+    pub kernel_launch_id: Option<usize>,
 }
 
 impl std::fmt::Debug for MemorySubPartition {
@@ -103,9 +158,13 @@ impl MemorySubPartition {
             l2_to_dram_queue,
             dram_to_l2_queue,
             l2_to_interconn_queue,
-            rop_queue: VecDeque::new(),
-            request_tracker: HashSet::new(),
+            rop_queue: Fifo::new(None, None),
+            l2_hit_queue: Fifo::new(None, None),
+            request_tracker: IndexSet::new(),
             num_pending_requests: 0,
+            utilization: stats::utilization::Counters::default(),
+            l1_directory: HashMap::new(),
+            pending_l1_invalidations: Vec::new(),
         }
     }
 
@@ -124,7 +183,8 @@ impl MemorySubPartition {
 
     fn breakdown_request_to_sector_requests(
         &self,
-        fetch: mem_fetch::MemFetch,
+        mut fetch: mem_fetch::MemFetch,
+        cache_config: &config::Cache,
         sector_requests: &mut [Option<mem_fetch::MemFetch>; NUM_SECTORS],
     ) {
         log::trace!(
@@ -136,13 +196,29 @@ impl MemorySubPartition {
         struct SectorFetch<'c> {
             addr: address,
             sector: usize,
+            sector_size: u32,
             byte_mask: mem_fetch::ByteMask,
             original_fetch: mem_fetch::MemFetch,
             mem_controller: &'c dyn mcu::MemoryController,
             // config: &'c config::GPU,
         }
 
-        assert_ne!(fetch.access_kind(), mem_fetch::access::Kind::INST_ACC_R);
+        // sector geometry of the cache this fetch is destined for
+        let num_sectors = cache_config.num_sectors();
+        let sector_size = cache_config.sector_size as usize;
+
+        // Instruction fetches are not issued by the LDST unit and may carry
+        // an empty sector mask: derive the mask from the requested bytes so
+        // the fetch breaks down like any other access.
+        if fetch.access_kind() == mem_fetch::access::Kind::INST_ACC_R
+            && fetch.access.sector_mask.not_any()
+        {
+            let first_sector = ((fetch.addr() / sector_size as u64) % num_sectors as u64) as usize;
+            let num_fetch_sectors = fetch.data_size().div_ceil(cache_config.sector_size) as usize;
+            for sector in first_sector..(first_sector + num_fetch_sectors).min(num_sectors) {
+                fetch.access.sector_mask.set(sector, true);
+            }
+        }
 
         impl<'a> Into<mem_fetch::MemFetch> for SectorFetch<'a> {
             fn into(self) -> mem_fetch::MemFetch {
@@ -154,35 +230,39 @@ impl MemorySubPartition {
 
                 let access = mem_fetch::access::MemAccess {
                     addr: self.addr,
-                    req_size_bytes: SECTOR_SIZE,
+                    req_size_bytes: self.sector_size,
                     byte_mask: self.byte_mask,
                     sector_mask,
                     ..self.original_fetch.access.clone()
                 };
 
-                mem_fetch::MemFetch {
+                let fetch = mem_fetch::MemFetch {
                     uid: mem_fetch::generate_uid(),
                     original_fetch: Some(Box::new(self.original_fetch.clone())),
                     access,
                     physical_addr,
                     partition_addr,
                     ..self.original_fetch
-                }
+                };
+                mem_fetch::tracker::created(&fetch);
+                fetch
             }
         }
 
-        let num_sectors = NUM_SECTORS as usize;
-        let sector_size = SECTOR_SIZE as usize;
+        let line_size = num_sectors * sector_size;
 
-        if fetch.data_size() == SECTOR_SIZE && fetch.access.sector_mask.count_ones() == 1 {
+        if fetch.data_size() == cache_config.sector_size
+            && fetch.access.sector_mask.count_ones() == 1
+        {
             sector_requests[0] = Some(fetch.clone());
-        } else if fetch.data_size() == MAX_MEMORY_ACCESS_SIZE {
+        } else if fetch.data_size() as usize == line_size {
             // break down every sector
             let mut byte_mask = mem_fetch::ByteMask::ZERO;
             for sector in 0..num_sectors {
                 byte_mask[sector * sector_size..(sector + 1) * sector_size].fill(true);
                 let sector_fetch = SectorFetch {
                     sector,
+                    sector_size: cache_config.sector_size,
                     addr: fetch.addr() + (sector_size * sector) as u64,
                     byte_mask: fetch.access.byte_mask & byte_mask,
                     original_fetch: fetch.clone(),
@@ -190,19 +270,24 @@ impl MemorySubPartition {
                 };
                 sector_requests[sector] = Some(sector_fetch.into());
             }
-        } else if fetch.data_size() == 64
+        } else if fetch.data_size() as usize == line_size / 2
             && (fetch.access.sector_mask.all() || fetch.access.sector_mask.not_any())
         {
             // This is for constant cache
-            let addr_is_cache_line_aligned = fetch.addr() % MAX_MEMORY_ACCESS_SIZE as u64 == 0;
-            let sector_start = if addr_is_cache_line_aligned { 0 } else { 2 };
+            let addr_is_cache_line_aligned = fetch.addr() % line_size as u64 == 0;
+            let sector_start = if addr_is_cache_line_aligned {
+                0
+            } else {
+                num_sectors / 2
+            };
 
             let mut byte_mask = mem_fetch::ByteMask::ZERO;
-            for sector in sector_start..(sector_start + 2) {
+            for sector in sector_start..(sector_start + num_sectors / 2) {
                 byte_mask[sector * sector_size..(sector + 1) * sector_size].fill(true);
 
                 let sector_fetch = SectorFetch {
                     sector,
+                    sector_size: cache_config.sector_size,
                     addr: fetch.addr(),
                     byte_mask: fetch.access.byte_mask & byte_mask,
                     original_fetch: fetch.clone(),
@@ -225,6 +310,7 @@ impl MemorySubPartition {
 
                     let sector_fetch = SectorFetch {
                         sector,
+                        sector_size: cache_config.sector_size,
                         addr: fetch.addr() + (sector_size * sector) as u64,
                         byte_mask: fetch.access.byte_mask & byte_mask,
                         original_fetch: fetch.clone(),
@@ -247,55 +333,59 @@ impl MemorySubPartition {
             sector_requests.iter().any(|req| req.is_some()),
             "no fetch sent"
         );
+        // the original fetch is replaced by its sector requests
+        if sector_requests
+            .iter()
+            .flatten()
+            .all(|sector| sector.uid != fetch.uid)
+        {
+            mem_fetch::tracker::retired(&fetch);
+        }
     }
 
     pub fn push(&mut self, fetch: mem_fetch::MemFetch, time: u64) {
+        if self.config.l1_coherence {
+            self.update_l1_directory(&fetch);
+        }
+
         let mut sector_requests: [Option<mem_fetch::MemFetch>; NUM_SECTORS] =
             [(); NUM_SECTORS].map(|_| None);
 
-        // let l2_config = self.config.data_cache_l2.as_ref().unwrap();
-        if self.config.accelsim_compat {
-            let sectored = self
-                .config
+        // the cache config determines the sector geometry of the breakdown
+        let cache_config: Option<&config::Cache> = if self.config.accelsim_compat {
+            self.config
                 .data_cache_l2
                 .as_ref()
-                .map(|l2_cache| l2_cache.inner.kind == config::CacheKind::Sector)
-                .unwrap_or(false);
-            if sectored {
-                self.breakdown_request_to_sector_requests(fetch, &mut sector_requests);
-            } else {
-                let mut sector_fetch = fetch;
-                sector_fetch.access.sector_mask.fill(true);
-                sector_requests[0] = Some(sector_fetch);
-            };
+                .map(|l2_cache| l2_cache.inner.as_ref())
         } else {
-            let sectored = match fetch.access_kind() {
-                mem_fetch::access::Kind::INST_ACC_R => self
-                    .config
-                    .inst_cache_l1
-                    .as_ref()
-                    .map(|inst_cache| inst_cache.kind == config::CacheKind::Sector)
-                    .unwrap_or(false),
+            match fetch.access_kind() {
+                mem_fetch::access::Kind::INST_ACC_R => self.config.inst_cache_l1.as_deref(),
                 _ => self
                     .config
                     .data_cache_l2
                     .as_ref()
-                    .map(|inst_cache| inst_cache.inner.kind == config::CacheKind::Sector)
-                    .unwrap_or(false),
-            };
-
-            if sectored {
-                self.breakdown_request_to_sector_requests(fetch, &mut sector_requests);
-            } else {
-                let mut sector_fetch = fetch;
-                sector_fetch.access.sector_mask.fill(true);
-                sector_requests[0] = Some(sector_fetch);
+                    .map(|l2_cache| l2_cache.inner.as_ref()),
             }
-            // if let mem_fetch::access::Kind::INST_ACC_R = fetch.access_kind() {
-            //     sector_requests[0] = Some(fetch);
-            //     return;
-            // }
+        };
+        let sectored = cache_config
+            .map(|cache_config| cache_config.kind == config::CacheKind::Sector)
+            .unwrap_or(false);
+
+        if sectored {
+            self.breakdown_request_to_sector_requests(
+                fetch,
+                cache_config.unwrap(),
+                &mut sector_requests,
+            );
+        } else {
+            let mut sector_fetch = fetch;
+            sector_fetch.access.sector_mask.fill(true);
+            sector_requests[0] = Some(sector_fetch);
         }
+        // if let mem_fetch::access::Kind::INST_ACC_R = fetch.access_kind() {
+        //     sector_requests[0] = Some(fetch);
+        //     return;
+        // }
 
         for mut fetch in sector_requests
             .into_iter()
@@ -314,11 +404,65 @@ impl MemorySubPartition {
                 let ready_cycle = time + self.config.l2_rop_latency;
                 fetch.status = mem_fetch::Status::IN_PARTITION_ROP_DELAY;
                 log::debug!("{}: {fetch}", style("PUSH TO ROP").red());
-                self.rop_queue.push_back((ready_cycle, fetch));
+                self.rop_queue.enqueue_at(ready_cycle, fetch);
             }
         }
     }
 
+    /// Update the sharer directory of the L1 coherence protocol.
+    ///
+    /// Global reads register the requesting core as a sharer of the
+    /// line. Global writes send an invalidation to every other sharer
+    /// and leave the writer as the only registered sharer.
+    fn update_l1_directory(&mut self, fetch: &mem_fetch::MemFetch) {
+        use mem_fetch::access::Kind;
+        let Some(core_id) = fetch.core_id else {
+            return;
+        };
+        // the directory tracks lines at L1 line granularity
+        let line_size = self
+            .config
+            .data_cache_l1
+            .as_ref()
+            .map_or(u64::from(MAX_MEMORY_ACCESS_SIZE), |l1| {
+                u64::from(l1.inner.line_size)
+            });
+        let line_addr = (fetch.addr() / line_size) * line_size;
+        match fetch.access_kind() {
+            Kind::GLOBAL_ACC_R => {
+                self.l1_directory
+                    .entry(line_addr)
+                    .or_default()
+                    .insert(core_id);
+            }
+            Kind::GLOBAL_ACC_W => {
+                let kernel_launch_id = fetch.kernel_launch_id();
+                let sharers = self.l1_directory.entry(line_addr).or_default();
+                let mut num_invalidations = 0;
+                for &sharer in sharers.iter().filter(|&&sharer| sharer != core_id) {
+                    self.pending_l1_invalidations.push(L1Invalidation {
+                        core_id: sharer,
+                        addr: line_addr,
+                        kernel_launch_id,
+                    });
+                    num_invalidations += 1;
+                }
+                sharers.retain(|&sharer| sharer == core_id);
+
+                let mut stats = self.stats.lock();
+                let coherence = &mut stats.get_mut(kernel_launch_id).coherence;
+                coherence.num_directory_writes += 1;
+                coherence.num_invalidations_sent += num_invalidations;
+            }
+            _ => {}
+        }
+    }
+
+    /// Take the coherence invalidations recorded since the last drain.
+    pub fn drain_l1_invalidations(&mut self) -> Vec<L1Invalidation> {
+        std::mem::take(&mut self.pending_l1_invalidations)
+    }
+
     #[must_use]
     pub fn busy(&self) -> bool {
         !self.request_tracker.is_empty()
@@ -339,7 +483,7 @@ impl MemorySubPartition {
         use mem_fetch::access::Kind as AccessKind;
 
         let fetch = self.l2_to_interconn_queue.dequeue()?.into_inner();
-        self.request_tracker.remove(&fetch);
+        self.request_tracker.swap_remove(&fetch);
         self.num_pending_requests = self.num_pending_requests.saturating_sub(1);
         if fetch.is_atomic() {
             unimplemented!("atomic memory operation");
@@ -359,7 +503,7 @@ impl MemorySubPartition {
             .map(|packet| packet.data.access_kind())
         {
             let fetch = self.l2_to_interconn_queue.dequeue().unwrap();
-            self.request_tracker.remove(&fetch);
+            self.request_tracker.swap_remove(&fetch.data);
             self.num_pending_requests = self.num_pending_requests.saturating_sub(1);
             return None;
         }
@@ -369,13 +513,17 @@ impl MemorySubPartition {
 
     pub fn set_done(&mut self, fetch: &mem_fetch::MemFetch) {
         self.num_pending_requests = self.num_pending_requests.saturating_sub(1);
-        self.request_tracker.remove(fetch);
+        self.request_tracker.swap_remove(fetch);
+        mem_fetch::tracker::retired(fetch);
     }
 
     #[tracing::instrument]
     pub fn cycle(&mut self, cycle: u64) {
         use mem_fetch::{access::Kind as AccessKind, Status};
 
+        self.utilization.add_cycle(self.busy());
+
+        let component = logging::Component::SubPartition { id: self.id };
         let log_line = || {
             style(format!(
                 " => memory sub partition[{}] cache cycle {}",
@@ -384,7 +532,9 @@ impl MemorySubPartition {
             .blue()
         };
 
-        log::debug!(
+        crate::debug_scoped!(
+            component,
+            cycle,
             "{}: rop queue={:?}, icnt to l2 queue={}, l2 to icnt queue={}, l2 to dram queue={}",
             log_line(),
             self.rop_queue
@@ -396,11 +546,22 @@ impl MemorySubPartition {
             self.l2_to_dram_queue.try_lock(),
         );
 
+        // L2 hit replies that waited out the configured hit latency
+        while let Some((ready_cycle, _)) = self.l2_hit_queue.first() {
+            if cycle < *ready_cycle || self.l2_to_interconn_queue.full() {
+                break;
+            }
+            let (_, reply) = self.l2_hit_queue.dequeue().unwrap();
+            self.l2_to_interconn_queue.enqueue(reply);
+        }
+
         // L2 fill responses
         if let Some(ref mut l2_cache) = self.l2_cache {
             let queue_full = self.l2_to_interconn_queue.full();
 
-            log::debug!(
+            crate::debug_scoped!(
+                component,
+                cycle,
                 "{}: l2 cache ready accesses={:?} l2 to icnt queue full={}",
                 log_line(),
                 l2_cache
@@ -442,7 +603,8 @@ impl MemorySubPartition {
                         todo!("fetch on write: l2 to icnt queue");
                     }
                     self.num_pending_requests = self.num_pending_requests.saturating_sub(1);
-                    self.request_tracker.remove(&fetch);
+                    self.request_tracker.swap_remove(&fetch);
+                    mem_fetch::tracker::retired(&fetch);
                 }
             }
         }
@@ -456,13 +618,18 @@ impl MemorySubPartition {
                 Some(ref mut l2_cache) if l2_cache.waiting_for_fill(reply) => {
                     if l2_cache.has_free_fill_port() {
                         let mut reply = self.dram_to_l2_queue.dequeue().unwrap().into_inner();
-                        log::debug!("filling L2 with {}", &reply);
+                        crate::debug_scoped!(component, cycle, "filling L2 with {}", &reply);
                         reply.set_status(mem_fetch::Status::IN_PARTITION_L2_FILL_QUEUE, 0);
                         l2_cache.fill(reply, mem_copy_time);
                         // reply will be gone forever at this point
                         // m_dram_L2_queue->pop();
                     } else {
-                        log::debug!("skip filling L2 with {}: no free fill port", &reply);
+                        crate::debug_scoped!(
+                            component,
+                            cycle,
+                            "skip filling L2 with {}: no free fill port",
+                            &reply
+                        );
                     }
                 }
                 _ if !self.l2_to_interconn_queue.full() => {
@@ -471,11 +638,13 @@ impl MemorySubPartition {
                         reply.set_status(mem_fetch::Status::IN_PARTITION_L2_TO_ICNT_QUEUE, 0);
                     }
                     // m_gpu->gpu_sim_cycle + m_gpu->gpu_tot_sim_cycle);
-                    log::debug!("pushing {} to interconn queue", &reply);
+                    crate::debug_scoped!(component, cycle, "pushing {} to interconn queue", &reply);
                     self.l2_to_interconn_queue.enqueue(reply);
                 }
                 _ => {
-                    log::debug!(
+                    crate::debug_scoped!(
+                        component,
+                        cycle,
                         "skip pushing {} to interconn queue: l2 to interconn queue full",
                         &reply
                     );
@@ -492,28 +661,47 @@ impl MemorySubPartition {
         let mut l2_to_dram_queue = self.l2_to_dram_queue.try_lock();
         if !l2_to_dram_queue.full() {
             if let Some(fetch) = self.interconn_to_l2_queue.first().map(Packet::as_ref) {
-                if let Some(ref mut l2_cache) = self.l2_cache {
-                    if !self.config.data_cache_l2_texture_only || fetch.is_texture() {
+                // non-texture accesses bypass a texture-only L2
+                let bypass_l2 = self.config.data_cache_l2_texture_only && !fetch.is_texture();
+                match self.l2_cache {
+                    Some(ref mut l2_cache) if !bypass_l2 => {
                         // L2 is enabled and access is for L2
                         let output_full = self.l2_to_interconn_queue.full();
                         let port_free = l2_cache.has_free_data_port();
 
                         if !output_full && port_free {
                             let mut events = Vec::new();
-                            let status = l2_cache.access(
+                            let outcome = l2_cache.access(
                                 fetch.addr(),
                                 fetch.clone(),
                                 &mut events,
                                 mem_copy_time,
                             );
-                            let write_sent = cache::event::was_write_sent(&events);
-                            let read_sent = cache::event::was_read_sent(&events);
-                            log::debug!(
+                            let status = outcome.status;
+                            let write_sent = outcome.write_sent;
+                            let read_sent = outcome.read_sent;
+                            crate::debug_scoped!(
+                                component,
+                                cycle,
                                 "probing L2 cache address={}, status={:?}",
                                 fetch.addr(),
                                 status
                             );
 
+                            if let Some(ref window) = self.config.l2_residency_window {
+                                if status != cache::RequestStatus::RESERVATION_FAIL
+                                    && window.matches_kernel(fetch.kernel_launch_id())
+                                    && window.contains(fetch.addr())
+                                {
+                                    let mut stats = self.stats.lock();
+                                    let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+                                    kernel_stats.l2_residency.num_accesses += 1;
+                                    if status == cache::RequestStatus::HIT {
+                                        kernel_stats.l2_residency.num_hits += 1;
+                                    }
+                                }
+                            }
+
                             if status == cache::RequestStatus::HIT {
                                 let mut fetch = self.interconn_to_l2_queue.dequeue().unwrap();
                                 if write_sent {
@@ -522,17 +710,24 @@ impl MemorySubPartition {
                                     // L2 cache replies
                                     assert!(!read_sent);
                                     if fetch.access_kind() == mem_fetch::access::Kind::L1_WRBK_ACC {
-                                        self.request_tracker.remove(&fetch);
+                                        self.request_tracker.swap_remove(&fetch.data);
 
                                         self.num_pending_requests =
                                             self.num_pending_requests.saturating_sub(1);
+                                        mem_fetch::tracker::retired(&fetch.data);
                                     } else {
                                         fetch.set_reply();
                                         fetch.set_status(
                                             mem_fetch::Status::IN_PARTITION_L2_TO_ICNT_QUEUE,
                                             0,
                                         );
-                                        self.l2_to_interconn_queue.enqueue(fetch);
+                                        let hit_latency = self.config.l2_tag_lookup_latency
+                                            + self.config.l2_hit_latency;
+                                        if hit_latency > 0 {
+                                            self.l2_hit_queue.enqueue((cycle + hit_latency, fetch));
+                                        } else {
+                                            self.l2_to_interconn_queue.enqueue(fetch);
+                                        }
                                     }
                                 }
                             } else if status != cache::RequestStatus::RESERVATION_FAIL {
@@ -544,14 +739,13 @@ impl MemorySubPartition {
                                     cache::config::WriteAllocatePolicy::FETCH_ON_WRITE
                                         | cache::config::WriteAllocatePolicy::LAZY_FETCH_ON_READ
                                 );
-                                if fetch.is_write()
-                                    && should_fetch
-                                    && !cache::event::was_writeallocate_sent(&events)
+                                if fetch.is_write() && should_fetch && !outcome.write_allocate_sent
                                 {
                                     if fetch.access_kind() == mem_fetch::access::Kind::L1_WRBK_ACC {
-                                        self.request_tracker.remove(&fetch);
+                                        self.request_tracker.swap_remove(&fetch.data);
                                         self.num_pending_requests =
                                             self.num_pending_requests.saturating_sub(1);
+                                        mem_fetch::tracker::retired(&fetch.data);
                                     } else {
                                         fetch.set_reply();
                                         fetch.set_status(
@@ -571,33 +765,255 @@ impl MemorySubPartition {
                             }
                         }
                     }
-                } else {
-                    // L2 is disabled or non-texture access to texture-only L2
-                    let mut fetch = self.interconn_to_l2_queue.dequeue().unwrap();
-                    fetch.set_status(mem_fetch::Status::IN_PARTITION_L2_TO_DRAM_QUEUE, 0);
+                    _ => {
+                        // L2 is disabled or non-texture access to texture-only L2
+                        let mut fetch = self.interconn_to_l2_queue.dequeue().unwrap();
+                        if bypass_l2 {
+                            let mut stats = self.stats.lock();
+                            let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+                            kernel_stats.num_l2_bypassed += 1;
+                        }
+                        fetch.set_status(mem_fetch::Status::IN_PARTITION_L2_TO_DRAM_QUEUE, 0);
 
-                    l2_to_dram_queue.enqueue(fetch);
+                        l2_to_dram_queue.enqueue(fetch);
+                    }
                 }
             }
         }
 
+        drop(l2_to_dram_queue);
+
         // rop delay queue
         // if (!m_rop.empty() && (cycle >= m_rop.front().ready_cycle) &&
         //     !m_icnt_L2_queue->full()) {
         if !self.interconn_to_l2_queue.full() {
-            match self.rop_queue.front() {
-                Some((ready_cycle, _)) if cycle >= *ready_cycle => {
-                    let (_, mut fetch) = self.rop_queue.pop_front().unwrap();
-                    log::debug!("{}: {fetch}", style("POP FROM ROP").red());
-                    fetch.set_status(mem_fetch::Status::IN_PARTITION_ICNT_TO_L2_QUEUE, 0);
-                    // m_gpu->gpu_sim_cycle + m_gpu->gpu_tot_sim_cycle);
-                    self.interconn_to_l2_queue.enqueue(Packet {
-                        data: fetch,
-                        time: cycle,
-                    });
+            let next = if self.config.tenants.is_empty() {
+                match self.config.memory_arbitration {
+                    config::MemoryArbitration::Fifo => {
+                        let ready_cycle =
+                            self.rop_queue.first().map(|(ready_cycle, _)| *ready_cycle);
+                        match ready_cycle {
+                            Some(ready_cycle) if cycle >= ready_cycle => self
+                                .rop_queue
+                                .dequeue()
+                                .map(|(_, fetch)| (ready_cycle, fetch)),
+                            _ => None,
+                        }
+                    }
+                    config::MemoryArbitration::ReadFirst {
+                        write_drain_threshold,
+                        max_age,
+                    } => self.arbitrate_read_first(cycle, write_drain_threshold, max_age),
                 }
-                _ => {}
+            } else {
+                // tenant priorities override the arbitration policy at
+                // the sub partition input
+                self.arbitrate_tenant_priority(cycle)
+            };
+            if let Some((ready_cycle, mut fetch)) = next {
+                log::debug!("{}: {fetch}", style("POP FROM ROP").red());
+
+                // cycles the request waited in the queue after it became ready
+                let queueing_delay = cycle.saturating_sub(ready_cycle);
+                let mut stats = self.stats.lock();
+                let kernel_stats = stats.get_mut(fetch.kernel_launch_id());
+                let delay = kernel_stats
+                    .l2_arbitration_delays
+                    .entry(arbitration_class(&fetch).to_string())
+                    .or_default();
+                delay.total_delay += queueing_delay;
+                delay.num_requests += 1;
+                drop(stats);
+
+                fetch.set_status(mem_fetch::Status::IN_PARTITION_ICNT_TO_L2_QUEUE, 0);
+                // m_gpu->gpu_sim_cycle + m_gpu->gpu_tot_sim_cycle);
+                self.interconn_to_l2_queue.enqueue(Packet {
+                    data: fetch,
+                    time: cycle,
+                });
+            }
+        }
+    }
+
+    /// Select the next ready request under read-first arbitration.
+    ///
+    /// Demand reads bypass ready writes and writebacks, unless the
+    /// number of ready writes reaches the drain threshold or the oldest
+    /// ready request exceeded the age cap.
+    fn arbitrate_read_first(
+        &mut self,
+        cycle: u64,
+        write_drain_threshold: usize,
+        max_age: u64,
+    ) -> Option<(u64, mem_fetch::MemFetch)> {
+        // the front entry is the oldest request: readiness is in
+        // insertion order
+        let (oldest_ready_cycle, _) = self.rop_queue.first()?;
+        let oldest_ready_cycle = *oldest_ready_cycle;
+        if cycle < oldest_ready_cycle {
+            return None;
+        }
+        if cycle.saturating_sub(oldest_ready_cycle) > max_age {
+            // age cap exceeded: the oldest request wins regardless of class
+            return self.rop_queue.dequeue();
+        }
+
+        let mut first_read: Option<usize> = None;
+        let mut first_write: Option<usize> = None;
+        let mut num_ready_writes = 0;
+        for (i, (ready_cycle, fetch)) in self.rop_queue.iter().enumerate() {
+            if cycle < *ready_cycle {
+                break;
             }
+            if arbitration_class(fetch) == "read" {
+                first_read.get_or_insert(i);
+            } else {
+                num_ready_writes += 1;
+                first_write.get_or_insert(i);
+            }
+        }
+
+        let selected = if num_ready_writes >= write_drain_threshold {
+            // drain writes before they fill up the input queue
+            first_write
+        } else {
+            first_read.or(first_write)
+        };
+        self.rop_queue.remove(selected?)
+    }
+
+    /// Select the next ready request under tenant priority arbitration.
+    ///
+    /// Among the ready requests, the oldest request of the
+    /// highest-priority tenant wins (see [`config::Tenant`]). Strict
+    /// priorities can starve lower-priority tenants.
+    fn arbitrate_tenant_priority(&mut self, cycle: u64) -> Option<(u64, mem_fetch::MemFetch)> {
+        let mut selected: Option<(usize, usize)> = None;
+        for (i, (ready_cycle, fetch)) in self.rop_queue.iter().enumerate() {
+            if cycle < *ready_cycle {
+                // the front entry is the oldest request: readiness is in
+                // insertion order
+                break;
+            }
+            let priority = crate::tenant::priority_of(fetch.kernel_launch_id());
+            if selected.is_none_or(|(_, best_priority)| priority > best_priority) {
+                selected = Some((i, priority));
+            }
+        }
+        self.rop_queue.remove(selected?.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sync::{Arc, Mutex};
+    use crate::{config, mcu, mem_fetch};
+    use color_eyre::eyre;
+
+    #[test]
+    fn test_sectored_l1i_breakdown() -> eyre::Result<()> {
+        let mut config = config::GPU::default();
+        let inst_cache_l1 = Arc::make_mut(config.inst_cache_l1.as_mut().unwrap());
+        inst_cache_l1.kind = config::CacheKind::Sector;
+        let config = Arc::new(config);
+
+        let mem_controller: Arc<dyn mcu::MemoryController> =
+            Arc::new(mcu::MemoryControllerUnit::new(&config)?);
+        let stats = Arc::new(Mutex::new(stats::PerKernel::new(stats::Config::default())));
+        let mut sub = super::MemorySubPartition::new(0, 0, config, mem_controller.clone(), stats);
+
+        let fetch_addr = 4_026_531_840;
+        let access = mem_fetch::access::Builder {
+            kind: mem_fetch::access::Kind::INST_ACC_R,
+            addr: fetch_addr,
+            kernel_launch_id: Some(0),
+            allocation: None,
+            req_size_bytes: super::MAX_MEMORY_ACCESS_SIZE,
+            is_write: false,
+            warp_active_mask: crate::warp::ActiveMask::ZERO,
+            byte_mask: mem_fetch::ByteMask::ZERO,
+            sector_mask: mem_fetch::SectorMask::ZERO,
+        }
+        .build();
+
+        let fetch = mem_fetch::Builder {
+            instr: None,
+            access,
+            warp_id: 0,
+            core_id: None,
+            cluster_id: None,
+            physical_addr: mem_controller.to_physical_address(fetch_addr),
+            partition_addr: mem_controller.memory_partition_address(fetch_addr),
+        }
+        .build();
+
+        // an instruction fetch for a full line is broken down into one
+        // request per sector
+        sub.push(fetch, 0);
+        assert_eq!(sub.rop_queue.len(), super::NUM_SECTORS);
+        for (sector, (_, sector_fetch)) in sub.rop_queue.iter().enumerate() {
+            assert_eq!(sector_fetch.data_size(), super::SECTOR_SIZE);
+            assert_eq!(
+                sector_fetch.addr(),
+                fetch_addr + (sector as u64 * u64::from(super::SECTOR_SIZE))
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_l2_texture_only_bypass() -> eyre::Result<()> {
+        let mut config = config::GPU::default();
+        config.data_cache_l2_texture_only = true;
+        let config = Arc::new(config);
+
+        let mem_controller: Arc<dyn mcu::MemoryController> =
+            Arc::new(mcu::MemoryControllerUnit::new(&config)?);
+        let stats = Arc::new(Mutex::new(stats::PerKernel::new(stats::Config::default())));
+        let mut sub = super::MemorySubPartition::new(
+            0,
+            0,
+            config,
+            mem_controller.clone(),
+            Arc::clone(&stats),
+        );
+        assert!(sub.l2_cache.is_some());
+
+        let fetch_addr = 4_026_531_840;
+        let access = mem_fetch::access::Builder {
+            kind: mem_fetch::access::Kind::GLOBAL_ACC_R,
+            addr: fetch_addr,
+            kernel_launch_id: Some(0),
+            allocation: None,
+            req_size_bytes: super::SECTOR_SIZE,
+            is_write: false,
+            warp_active_mask: crate::warp::ActiveMask::ZERO,
+            byte_mask: mem_fetch::ByteMask::ZERO,
+            sector_mask: mem_fetch::SectorMask::ZERO,
+        }
+        .build();
+
+        let fetch = mem_fetch::Builder {
+            instr: None,
+            access,
+            warp_id: 0,
+            core_id: None,
+            cluster_id: None,
+            physical_addr: mem_controller.to_physical_address(fetch_addr),
+            partition_addr: mem_controller.memory_partition_address(fetch_addr),
         }
+        .build();
+
+        // a non-texture access bypasses the texture-only L2 straight to DRAM
+        sub.interconn_to_l2_queue.enqueue(super::Packet {
+            data: fetch,
+            time: 0,
+        });
+        sub.cycle(0);
+
+        assert!(sub.interconn_to_l2_queue.is_empty());
+        assert_eq!(sub.l2_to_dram_queue.try_lock().len(), 1);
+        assert_eq!(stats.lock().get_mut(Some(0)).num_l2_bypassed, 1);
+        Ok(())
     }
 }
@@ -0,0 +1,112 @@
+//! Tracking of the simulator's own memory consumption.
+//!
+//! Usage is sampled from the jemalloc statistics, hence samples are only
+//! available when built with the "jemalloc" feature (and allocator).
+//! An optional hard limit aborts the simulation cleanly with a helpful
+//! message instead of getting OOM-killed mid-run.
+use color_eyre::eyre;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Peak resident memory observed so far (in bytes).
+static PEAK_RESIDENT: AtomicU64 = AtomicU64::new(0);
+
+/// A sample of the simulator's own memory usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    /// Bytes in physically resident data pages mapped by the allocator.
+    pub resident_bytes: u64,
+    /// Bytes allocated by the application.
+    pub allocated_bytes: u64,
+}
+
+impl std::fmt::Display for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} resident ({} allocated)",
+            human_bytes::human_bytes(self.resident_bytes as f64),
+            human_bytes::human_bytes(self.allocated_bytes as f64),
+        )
+    }
+}
+
+#[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
+fn sample() -> Option<Usage> {
+    use tikv_jemalloc_ctl::{epoch, stats};
+    // advance the epoch to refresh the cached statistics
+    epoch::advance().ok()?;
+    Some(Usage {
+        resident_bytes: stats::resident::read().ok()? as u64,
+        allocated_bytes: stats::allocated::read().ok()? as u64,
+    })
+}
+
+#[cfg(not(all(feature = "jemalloc", not(target_env = "msvc"))))]
+fn sample() -> Option<Usage> {
+    None
+}
+
+/// Sample the current memory usage.
+///
+/// Returns `None` when built without the "jemalloc" feature.
+#[must_use]
+pub fn current() -> Option<Usage> {
+    let usage = sample()?;
+    PEAK_RESIDENT.fetch_max(usage.resident_bytes, Ordering::Relaxed);
+    Some(usage)
+}
+
+/// Peak resident memory observed by any sample so far (in bytes).
+#[must_use]
+pub fn peak_resident_bytes() -> u64 {
+    PEAK_RESIDENT.load(Ordering::Relaxed)
+}
+
+/// Periodically reports memory usage and enforces an optional hard limit.
+#[derive(Debug)]
+pub struct Monitor {
+    limit_bytes: Option<u64>,
+    last_report: Instant,
+}
+
+impl Monitor {
+    const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+    #[must_use]
+    pub fn new(limit_bytes: Option<u64>) -> Self {
+        if limit_bytes.is_some() && sample().is_none() {
+            log::warn!(
+                "memory limit is configured but memory usage tracking is unavailable (built without the \"jemalloc\" feature)"
+            );
+        }
+        Self {
+            limit_bytes,
+            last_report: Instant::now(),
+        }
+    }
+
+    /// Sample the memory usage, report it periodically, and enforce the limit.
+    ///
+    /// # Errors
+    /// When the resident memory exceeds the configured limit.
+    pub fn check(&mut self) -> eyre::Result<()> {
+        let Some(usage) = current() else {
+            return Ok(());
+        };
+        if let Some(limit_bytes) = self.limit_bytes {
+            if usage.resident_bytes > limit_bytes {
+                eyre::bail!(
+                    "simulator exceeds the memory limit of {}: {} (simulate fewer traces at once or raise the limit)",
+                    human_bytes::human_bytes(limit_bytes as f64),
+                    usage,
+                );
+            }
+        }
+        if self.last_report.elapsed() >= Self::REPORT_INTERVAL {
+            eprintln!("memory usage: {usage}");
+            self.last_report = Instant::now();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+//! Migration of serialized stats between schema versions.
+//!
+//! Version 1 predates the `schema_version` field: files without the field
+//! are treated as version 1.  So far every newer version only *adds*
+//! fields, so migration fills in missing fields with their defaults from
+//! an empty [`stats::Stats`] and leaves the present values untouched.
+//!
+//! Migration operates on raw [`serde_json::Value`] trees instead of the
+//! typed structs: the whole point is reading files the current structs no
+//! longer deserialize.
+
+use color_eyre::eyre;
+
+/// Migrate the serialized `stats` section of a stats file in place.
+///
+/// Returns the schema version the file was migrated from.
+pub fn migrate_stats(stats_value: &mut serde_json::Value) -> eyre::Result<u64> {
+    let from_version = stats_value
+        .get("schema_version")
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(1);
+    if from_version > stats::SCHEMA_VERSION {
+        eyre::bail!(
+            "stats file has schema version {} but this build only knows version {}",
+            from_version,
+            stats::SCHEMA_VERSION
+        );
+    }
+
+    let stats_object = stats_value
+        .as_object_mut()
+        .ok_or_else(|| eyre::eyre!("serialized stats are not a JSON object"))?;
+
+    let config_value = stats_object
+        .get_mut("config")
+        .ok_or_else(|| eyre::eyre!("serialized stats are missing the config section"))?;
+    merge_defaults(
+        config_value,
+        &serde_json::to_value(stats::Config::default())?,
+    );
+    let config: stats::Config = serde_json::from_value(config_value.clone())?;
+
+    let default_stats = serde_json::to_value(stats::Stats::new(&config))?;
+    if let Some(no_kernel) = stats_object.get_mut("no_kernel") {
+        merge_defaults(no_kernel, &default_stats);
+    }
+    if let Some(kernels) = stats_object
+        .get_mut("inner")
+        .and_then(|inner| inner.as_array_mut())
+    {
+        for kernel_stats in kernels {
+            merge_defaults(kernel_stats, &default_stats);
+        }
+    }
+
+    stats_object.insert(
+        "schema_version".to_string(),
+        serde_json::Value::from(stats::SCHEMA_VERSION),
+    );
+    Ok(from_version)
+}
+
+/// Recursively fill in missing object keys from a default value.
+///
+/// Present values are left untouched; arrays and scalars are never
+/// descended into, as their layout has not changed between versions.
+fn merge_defaults(value: &mut serde_json::Value, default: &serde_json::Value) {
+    let (Some(object), Some(default_object)) = (value.as_object_mut(), default.as_object()) else {
+        return;
+    };
+    for (key, default_value) in default_object {
+        match object.get_mut(key) {
+            Some(present) => merge_defaults(present, default_value),
+            None => {
+                object.insert(key.clone(), default_value.clone());
+            }
+        }
+    }
+}
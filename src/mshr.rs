@@ -11,17 +11,45 @@ pub enum Kind {
     SECTOR_ASSOC,    // S
 }
 
+impl Kind {
+    /// Whether misses are tracked at sector granularity.
+    #[must_use]
+    pub fn is_sectored(self) -> bool {
+        matches!(self, Kind::SECTOR_TEX_FIFO | Kind::SECTOR_ASSOC)
+    }
+
+    /// Whether responses must be returned in miss order.
+    #[must_use]
+    pub fn is_fifo(self) -> bool {
+        matches!(self, Kind::TEX_FIFO | Kind::SECTOR_TEX_FIFO)
+    }
+}
+
 /// Miss status handling entry.
 #[derive(Debug)]
 pub struct Entry<F> {
     requests: VecDeque<F>,
     has_atomic: bool,
+    /// Union of the sector masks of the merged requests.
+    ///
+    /// Only tracked for sectored kinds.
+    pending_sectors: mem_fetch::SectorMask,
+    /// If the entry received its fill response.
+    ///
+    /// Only used for FIFO kinds, where a filled entry must still wait
+    /// for all older misses to be filled.
+    filled: bool,
 }
 
 impl<F> Entry<F> {
     pub fn len(&self) -> usize {
         self.requests.len()
     }
+
+    #[must_use]
+    pub fn pending_sectors(&self) -> &mem_fetch::SectorMask {
+        &self.pending_sectors
+    }
 }
 
 impl<F> Default for Entry<F> {
@@ -29,6 +57,8 @@ impl<F> Default for Entry<F> {
         Self {
             requests: VecDeque::new(),
             has_atomic: false,
+            pending_sectors: mem_fetch::SectorMask::ZERO,
+            filled: false,
         }
     }
 }
@@ -36,6 +66,7 @@ impl<F> Default for Entry<F> {
 /// Miss status handling entry.
 #[derive(Debug)]
 pub struct Table<F> {
+    kind: Kind,
     num_entries: usize,
     max_merged: usize,
     entries: HashMap<address, Entry<F>>,
@@ -43,6 +74,11 @@ pub struct Table<F> {
     ///
     /// it may take several cycles to process the merged requests
     current_response: VecDeque<address>,
+    /// Entries in miss order.
+    ///
+    /// Only tracked for FIFO kinds, where responses drain strictly in
+    /// miss order.
+    miss_order: VecDeque<address>,
 }
 
 pub trait MSHR<F> {
@@ -82,6 +118,8 @@ impl MSHR<mem_fetch::MemFetch> for Table<mem_fetch::MemFetch> {
     // #[inline]
     fn clear(&mut self) {
         self.entries.clear();
+        self.current_response.clear();
+        self.miss_order.clear();
     }
 
     // #[inline]
@@ -96,12 +134,23 @@ impl MSHR<mem_fetch::MemFetch> for Table<mem_fetch::MemFetch> {
 
     // #[inline]
     fn add(&mut self, block_addr: address, fetch: mem_fetch::MemFetch) {
-        let entry = self.entries.entry(block_addr).or_default();
+        let entry = match self.entries.entry(block_addr) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                if self.kind.is_fifo() {
+                    self.miss_order.push_back(block_addr);
+                }
+                entry.insert(Entry::default())
+            }
+        };
 
         assert!(entry.requests.len() <= self.max_merged);
 
         // indicate that this MSHR entry contains an atomic operation
         entry.has_atomic |= fetch.is_atomic();
+        if self.kind.is_sectored() {
+            entry.pending_sectors |= fetch.access.sector_mask;
+        }
         entry.requests.push_back(fetch);
         assert!(self.entries.len() <= self.num_entries);
     }
@@ -109,18 +158,59 @@ impl MSHR<mem_fetch::MemFetch> for Table<mem_fetch::MemFetch> {
     // #[inline]
     fn remove(&mut self, block_addr: address) {
         self.entries.remove(&block_addr);
+        self.miss_order.retain(|addr| *addr != block_addr);
     }
 }
 
 impl Table<mem_fetch::MemFetch> {
     #[must_use]
-    pub fn new(num_entries: usize, max_merged: usize) -> Self {
+    pub fn new(kind: Kind, num_entries: usize, max_merged: usize) -> Self {
         let entries = HashMap::with_capacity(2 * num_entries);
         Self {
+            kind,
             num_entries,
             max_merged,
             entries,
             current_response: VecDeque::new(),
+            miss_order: VecDeque::new(),
+        }
+    }
+
+    /// Checks if an access can be merged into an existing entry.
+    ///
+    /// For sectored kinds, the requested sectors must already be pending,
+    /// otherwise a new memory request for the missing sectors must be
+    /// generated.
+    #[must_use]
+    pub fn can_merge(&self, block_addr: address, sector_mask: &mem_fetch::SectorMask) -> bool {
+        match self.entries.get(&block_addr) {
+            None => false,
+            Some(entry) => {
+                if self.kind.is_sectored() {
+                    let missing = *sector_mask & !entry.pending_sectors;
+                    missing.not_any()
+                } else {
+                    true
+                }
+            }
+        }
+    }
+
+    /// The block address of the next ready response, if any.
+    ///
+    /// For FIFO kinds, responses drain strictly in miss order: the oldest
+    /// miss must have been filled before any response is ready.
+    #[must_use]
+    fn ready_response_addr(&self) -> Option<&address> {
+        if self.kind.is_fifo() {
+            let block_addr = self.miss_order.front()?;
+            if self.entries.get(block_addr)?.filled {
+                Some(block_addr)
+            } else {
+                None
+            }
+        } else {
+            self.current_response.front()
         }
     }
 
@@ -130,7 +220,11 @@ impl Table<mem_fetch::MemFetch> {
     /// If the ready mshr entry is an atomic
     pub fn mark_ready(&mut self, block_addr: address, fetch: mem_fetch::MemFetch) -> Option<bool> {
         let has_atomic = if let Some(entry) = self.entries.get_mut(&block_addr) {
-            self.current_response.push_back(block_addr);
+            if self.kind.is_fifo() {
+                entry.filled = true;
+            } else {
+                self.current_response.push_back(block_addr);
+            }
             if let Some(old_fetch) = entry.requests.iter_mut().find(|f| *f == &fetch) {
                 *old_fetch = fetch;
             }
@@ -150,13 +244,13 @@ impl Table<mem_fetch::MemFetch> {
     /// Returns true if ready accesses exist
     #[must_use]
     pub fn has_ready_accesses(&self) -> bool {
-        !self.current_response.is_empty()
+        self.ready_response_addr().is_some()
     }
 
     /// Returns next ready accesses
     #[must_use]
     pub fn ready_accesses(&self) -> Option<&VecDeque<mem_fetch::MemFetch>> {
-        let Some(block_addr) = self.current_response.front() else {
+        let Some(block_addr) = self.ready_response_addr() else {
             return None;
         };
         let Some(entry) = self.entries.get(block_addr) else {
@@ -173,10 +267,10 @@ impl Table<mem_fetch::MemFetch> {
 
     /// Returns mutable reference to the next ready accesses
     pub fn ready_accesses_mut(&mut self) -> Option<&mut VecDeque<mem_fetch::MemFetch>> {
-        let Some(block_addr) = self.current_response.front() else {
+        let Some(block_addr) = self.ready_response_addr().copied() else {
             return None;
         };
-        let Some(entry) = self.entries.get_mut(block_addr) else {
+        let Some(entry) = self.entries.get_mut(&block_addr) else {
             return None;
         };
         Some(&mut entry.requests)
@@ -184,11 +278,11 @@ impl Table<mem_fetch::MemFetch> {
 
     /// Returns next ready access
     pub fn next_access(&mut self) -> Option<mem_fetch::MemFetch> {
-        let Some(block_addr) = self.current_response.front() else {
+        let Some(block_addr) = self.ready_response_addr().copied() else {
             return None;
         };
 
-        let Some(entry) = self.entries.get_mut(block_addr) else {
+        let Some(entry) = self.entries.get_mut(&block_addr) else {
             return None;
         };
 
@@ -197,8 +291,12 @@ impl Table<mem_fetch::MemFetch> {
 
         let should_remove = entry.requests.is_empty();
         if should_remove {
-            self.entries.remove(block_addr);
-            self.current_response.pop_front();
+            self.entries.remove(&block_addr);
+            if self.kind.is_fifo() {
+                self.miss_order.pop_front();
+            } else {
+                self.current_response.pop_front();
+            }
         }
         fetch
     }
@@ -207,15 +305,52 @@ impl Table<mem_fetch::MemFetch> {
 #[cfg(test)]
 mod tests {
     use super::MSHR;
-    use crate::{config, mcu, mem_fetch};
+    use crate::{address, config, mcu, mem_fetch};
     use color_eyre::eyre;
 
+    fn sector_mask(sectors: &[usize]) -> mem_fetch::SectorMask {
+        let mut mask = mem_fetch::SectorMask::ZERO;
+        for sector in sectors {
+            mask.set(*sector, true);
+        }
+        mask
+    }
+
+    fn make_fetch(addr: address, sector_mask: mem_fetch::SectorMask) -> mem_fetch::MemFetch {
+        let access = mem_fetch::access::Builder {
+            kind: mem_fetch::access::Kind::GLOBAL_ACC_R,
+            addr,
+            kernel_launch_id: Some(0),
+            allocation: None,
+            req_size_bytes: 32,
+            is_write: false,
+            warp_active_mask: crate::warp::ActiveMask::ZERO,
+            byte_mask: mem_fetch::ByteMask::ZERO,
+            sector_mask,
+        }
+        .build();
+        mem_fetch::Builder {
+            instr: None,
+            access,
+            warp_id: 0,
+            core_id: None,
+            cluster_id: None,
+            physical_addr: mcu::PhysicalAddress::default(),
+            partition_addr: 0,
+        }
+        .build()
+    }
+
     #[test]
     fn test_mshr_table() -> eyre::Result<()> {
         let config = config::GPU::default();
         let cache_config = config.inst_cache_l1.as_ref().unwrap();
 
-        let mut mshrs = super::Table::new(cache_config.mshr_entries, cache_config.mshr_max_merge);
+        let mut mshrs = super::Table::new(
+            cache_config.mshr_kind,
+            cache_config.mshr_entries,
+            cache_config.mshr_max_merge,
+        );
 
         let fetch_addr = 4_026_531_848;
         let access = mem_fetch::access::Builder {
@@ -256,4 +391,60 @@ mod tests {
         // TODO: test against bridge here
         Ok(())
     }
+
+    #[test]
+    fn test_sector_merge() {
+        let mut mshrs = super::Table::new(super::Kind::SECTOR_ASSOC, 32, 8);
+
+        // sector 0 and sector 1 of the same 128B line
+        let line_addr = 1024;
+        let sector_size = u64::from(crate::mem_sub_partition::SECTOR_SIZE);
+
+        mshrs.add(line_addr, make_fetch(line_addr, sector_mask(&[0])));
+
+        // the same sector can be merged
+        assert!(mshrs.can_merge(line_addr, &sector_mask(&[0])));
+
+        // a different sector of the same line cannot be merged:
+        // its sector is not pending yet
+        assert!(!mshrs.can_merge(line_addr, &sector_mask(&[1])));
+        assert!(!mshrs.can_merge(line_addr + sector_size, &sector_mask(&[1])));
+
+        // a second miss for sector 1 allocates its own entry
+        mshrs.add(
+            line_addr + sector_size,
+            make_fetch(line_addr + sector_size, sector_mask(&[1])),
+        );
+        assert!(mshrs.can_merge(line_addr + sector_size, &sector_mask(&[1])));
+
+        // merging unions the pending sectors
+        mshrs.add(line_addr, make_fetch(line_addr, sector_mask(&[0, 1])));
+        assert!(mshrs.can_merge(line_addr, &sector_mask(&[0, 1])));
+        assert_eq!(
+            mshrs.get(line_addr).unwrap().pending_sectors(),
+            &sector_mask(&[0, 1])
+        );
+    }
+
+    #[test]
+    fn test_fifo_miss_order() {
+        let mut mshrs = super::Table::new(super::Kind::TEX_FIFO, 32, 8);
+
+        let first = make_fetch(1024, mem_fetch::SectorMask::ZERO);
+        let second = make_fetch(2048, mem_fetch::SectorMask::ZERO);
+        mshrs.add(1024, first.clone());
+        mshrs.add(2048, second.clone());
+
+        // the second miss is filled first: responses must still drain
+        // in miss order, so no access is ready yet
+        mshrs.mark_ready(2048, second);
+        assert!(!mshrs.has_ready_accesses());
+        assert!(mshrs.next_access().is_none());
+
+        mshrs.mark_ready(1024, first);
+        assert!(mshrs.has_ready_accesses());
+        assert_eq!(mshrs.next_access().map(|fetch| fetch.addr()), Some(1024));
+        assert_eq!(mshrs.next_access().map(|fetch| fetch.addr()), Some(2048));
+        assert!(!mshrs.has_ready_accesses());
+    }
 }
@@ -171,6 +171,18 @@ impl std::fmt::Debug for Op {
     }
 }
 
+impl Op {
+    /// Warp-level primitives exchange data or predicates between the
+    /// lanes of a warp (shuffle, vote, ballot).
+    #[must_use]
+    pub fn is_warp_level_primitive(&self) -> bool {
+        matches!(
+            self,
+            Op::SHFL | Op::VOTE | Op::VOTE_VTG | Op::Turing(turing::op::Op::VOTEU)
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum ArchOpKind {
     UN_OP,
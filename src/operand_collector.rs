@@ -468,15 +468,6 @@ impl Arbiter {
             // log::trace!("request: {:?}", &Self::compat(&request[bank]));
         }
 
-        // #[cfg(feature = "timings")]
-        // {
-        //     crate::TIMINGS
-        //         .lock()
-        //         .entry("allocate_reads_prepare")
-        //         .or_default()
-        //         .add(start.elapsed());
-        // }
-
         // log::trace!("inmatch: {:?}", &Self::compat(inmatch));
 
         // wavefront allocator from booksim
@@ -537,15 +528,6 @@ impl Arbiter {
             }
         }
 
-        // #[cfg(feature = "timings")]
-        // {
-        //     crate::TIMINGS
-        //         .lock()
-        //         .entry("allocate_reads_search_diagonal")
-        //         .or_default()
-        //         .add(start.elapsed());
-        // }
-
         // allocated
 
         log::debug!(
@@ -569,15 +551,6 @@ impl Arbiter {
             self.allocate_bank_for_read(bank, read.clone());
             read_ops.insert(bank, read);
         }
-        // #[cfg(feature = "timings")]
-        // {
-        //     crate::TIMINGS
-        //         .lock()
-        //         .entry("allocate_reads_register_banks")
-        //         .or_default()
-        //         .add(start.elapsed());
-        // }
-
         read_ops
     }
 
@@ -811,12 +784,15 @@ impl RegisterFileUnit {
 
     pub fn step(&mut self) {
         log::debug!("{}", style("operand collector::step()").green());
-        self.dispatch_ready_cu();
-        self.allocate_reads();
+        crate::timeit!(
+            "operand_collector::dispatch_ready_cu",
+            self.dispatch_ready_cu()
+        );
+        crate::timeit!("operand_collector::allocate_reads", self.allocate_reads());
 
         debug_assert!(!self.in_ports.is_empty());
         for port_num in 0..self.in_ports.len() {
-            self.allocate_cu(port_num);
+            crate::timeit!("operand_collector::allocate_cu", self.allocate_cu(port_num));
         }
         self.process_banks();
     }
@@ -32,6 +32,8 @@ where
 
             let log_every = 10_000;
             let mut last_time = std::time::Instant::now();
+            let mut mem_monitor =
+                crate::mem_usage::Monitor::new(self.config.simulation_memory_limit_bytes);
 
             while (self.commands_left() || self.kernels_left()) && !self.reached_limit(cycle) {
                 cycle = self.process_commands(cycle);
@@ -46,13 +48,15 @@ where
                             "cycle {cycle:<10} ({:>8.4} cycle/sec)",
                             log_every as f64 / last_time.elapsed().as_secs_f64()
                         );
-                        last_time = std::time::Instant::now()
+                        last_time = std::time::Instant::now();
+                        mem_monitor.check()?;
                     }
 
                     // if self.reached_limit(cycle) || !self.active() {
                     if self.reached_limit(cycle) {
                         break;
                     }
+                    self.check_max_kernel_cycles(cycle)?;
 
                     crate::timeit!("serial::cycle", self.serial_cycle(cycle));
 
@@ -163,6 +167,10 @@ where
                         if finished_kernel.is_some() {
                             break;
                         }
+                        if self.config.memcopy_only && !self.kernels_left() {
+                            // no kernel will ever finish in memcopy only mode
+                            break;
+                        }
                     }
                 }
 
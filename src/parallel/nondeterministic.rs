@@ -8,6 +8,7 @@ use crate::{
 };
 use color_eyre::eyre;
 use ndarray::prelude::*;
+use rand::SeedableRng;
 use rayon::prelude::*;
 use std::collections::{HashMap, VecDeque};
 use std::time::Instant;
@@ -96,7 +97,12 @@ fn new_serial_cycle<I>(
                 fetch.size()
             };
             let device = config.mem_id_to_device_id(i);
-            if interconn.has_buffer(device, response_packet_size) {
+            // responses wait in the sub partition until the destination
+            // cluster has a free credit
+            let has_credit = fetch.cluster_id.map_or(true, |cluster_id| {
+                interconn.has_buffer(cluster_id, response_packet_size)
+            });
+            if has_credit {
                 let mut fetch = mem_sub.pop().unwrap();
                 if let Some(cluster_id) = fetch.cluster_id {
                     fetch.set_status(mem_fetch::Status::IN_ICNT_TO_SHADER, 0);
@@ -110,6 +116,11 @@ fn new_serial_cycle<I>(
                         response_packet_size,
                     );
                 }
+            } else {
+                let kernel_launch_id = fetch.kernel_launch_id();
+                let mut stats = mem_sub.stats.lock();
+                let kernel_stats = stats.get_mut(kernel_launch_id);
+                kernel_stats.stall_interconn_to_shader += 1;
             }
         }
     }
@@ -256,9 +267,44 @@ where
             let serial_lock = Arc::new(Mutex::new(()));
             let issue_guard = Arc::new(Mutex::new(()));
 
+            // fixed mapping of cores to worker tasks: one task per core,
+            // or one task per chunk of clusters when threads are pinned
+            let mut task_cores: Vec<Vec<(usize, usize)>> = if self.config.pin_threads_to_clusters {
+                let chunk_size = num_clusters.div_ceil(num_threads);
+                (0..num_clusters)
+                    .collect::<Vec<_>>()
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        chunk
+                            .iter()
+                            .flat_map(|&cluster_id| {
+                                (0..num_cores_per_cluster)
+                                    .map(move |core_id| (cluster_id, core_id))
+                            })
+                            .collect()
+                    })
+                    .collect()
+            } else {
+                (0..num_clusters)
+                    .flat_map(|cluster_id| {
+                        (0..num_cores_per_cluster).map(move |core_id| vec![(cluster_id, core_id)])
+                    })
+                    .collect()
+            };
+
+            // seeded rng controlling the dispatch order of the core cycle
+            // tasks: together with pinned threads, runs with the same seed
+            // replay the same interleaving
+            let mut dispatch_rng = self
+                .config
+                .parallelization_seed
+                .map(rand::rngs::StdRng::seed_from_u64);
+
             let mut cycle: u64 = 0;
             let log_every = 10_000;
             let mut last_time = std::time::Instant::now();
+            let mut mem_monitor =
+                crate::mem_usage::Monitor::new(self.config.simulation_memory_limit_bytes);
 
             let mut active_clusters = vec![false; num_clusters];
 
@@ -277,26 +323,32 @@ where
                             "cycle {cycle:<10} ({:>8.4} cycle/sec)",
                             log_every as f64 / last_time.elapsed().as_secs_f64()
                         );
-                        last_time = std::time::Instant::now()
+                        last_time = std::time::Instant::now();
+                        mem_monitor.check()?;
                     }
 
                     // if self.reached_limit(cycle) || !self.active() {
                     if self.reached_limit(cycle) {
                         break;
                     }
+                    self.check_max_kernel_cycles(cycle)?;
 
                     let span = tracing::span!(tracing::Level::INFO, "wave", cycle, run_ahead);
                     let enter = span.enter();
 
+                    if let Some(rng) = &mut dispatch_rng {
+                        use rand::seq::SliceRandom;
+                        task_cores.shuffle(rng);
+                        for task in &mut task_cores {
+                            task.shuffle(rng);
+                        }
+                    }
+
                     if interleave_serial {
                         rayon::scope_fifo(|wave| {
                             for i in 0..run_ahead {
-                                for (cluster_id, _cluster_arc) in
-                                    clusters.iter().cloned().enumerate()
                                 {
-                                    for (core_id, core) in
-                                        cores[cluster_id].iter().cloned().enumerate()
-                                    {
+                                    for task in task_cores.iter().cloned() {
                                         let progress = Arc::clone(&progress);
 
                                         let sim_orders = Arc::clone(&sim_orders);
@@ -324,7 +376,8 @@ where
                                         let serial_lock = Arc::clone(&serial_lock);
 
                                         wave.spawn_fifo(move |_| {
-                                            let mut core = core.write();
+                                            for (cluster_id, core_id) in task {
+                                            let mut core = cores[cluster_id][core_id].write();
 
                                             let kernels_completed = running_kernels
                                                 .try_read()
@@ -441,6 +494,7 @@ where
 
                                                 drop(guard);
                                             }
+                                            }
                                         });
                                     }
                                 }
@@ -707,6 +761,10 @@ where
                         if finished_kernel.is_some() {
                             break;
                         }
+                        if self.config.memcopy_only && !self.kernels_left() {
+                            // no kernel will ever finish in memcopy only mode
+                            break;
+                        }
                     }
                 }
 
@@ -753,7 +811,12 @@ where
                     fetch.size()
                 };
                 let device = self.config.mem_id_to_device_id(i);
-                if self.interconn.has_buffer(device, response_packet_size) {
+                // responses wait in the sub partition until the destination
+                // cluster has a free credit
+                let has_credit = fetch.cluster_id.map_or(true, |cluster_id| {
+                    self.interconn.has_buffer(cluster_id, response_packet_size)
+                });
+                if has_credit {
                     let mut fetch = mem_sub.pop().unwrap();
                     if let Some(cluster_id) = fetch.cluster_id {
                         fetch.set_status(mem_fetch::Status::IN_ICNT_TO_SHADER, 0);
@@ -767,6 +830,11 @@ where
                             response_packet_size,
                         );
                     }
+                } else {
+                    let kernel_launch_id = fetch.kernel_launch_id();
+                    let mut stats = mem_sub.stats.lock();
+                    let kernel_stats = stats.get_mut(kernel_launch_id);
+                    kernel_stats.stall_interconn_to_shader += 1;
                 }
             }
         }
@@ -0,0 +1,132 @@
+//! Per-cycle pipeline occupancy dump (pipeview).
+//!
+//! Each line of a dump records the occupancy of all pipeline register sets
+//! of a single core for one core cycle in a compact character based format,
+//! similar to gem5's O3 pipeview. [`render`] converts a dump into a
+//! per-stage timeline, such that pipeline bubbles and structural hazards
+//! can be inspected visually.
+
+use crate::register_set;
+use std::io::{BufRead, Write as _};
+use std::path::Path;
+
+/// Cell marker for an occupied pipeline register.
+pub const OCCUPIED: char = 'X';
+/// Cell marker for a free pipeline register.
+pub const FREE: char = '.';
+
+/// Writes the per-cycle pipeline occupancy records of a core.
+pub struct Writer {
+    out: std::io::BufWriter<std::fs::File>,
+    wrote_header: bool,
+}
+
+impl std::fmt::Debug for Writer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Writer").finish()
+    }
+}
+
+impl Writer {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, utils::fs::Error> {
+        Ok(Self {
+            out: utils::fs::open_writable(path)?,
+            wrote_header: false,
+        })
+    }
+
+    /// Record the pipeline register occupancy for one core cycle.
+    ///
+    /// The first record is preceded by a header line naming the pipeline
+    /// stages in column order.
+    pub fn record(
+        &mut self,
+        cycle: u64,
+        pipeline_reg: &[register_set::Ref],
+    ) -> std::io::Result<()> {
+        if !self.wrote_header {
+            write!(self.out, "cycle")?;
+            for reg in pipeline_reg {
+                write!(self.out, " {:?}", reg.try_lock().stage)?;
+            }
+            writeln!(self.out)?;
+            self.wrote_header = true;
+        }
+        write!(self.out, "{cycle}")?;
+        for reg in pipeline_reg {
+            let reg = reg.try_lock();
+            write!(self.out, " ")?;
+            for slot in &reg.regs {
+                let cell = if slot.is_some() { OCCUPIED } else { FREE };
+                write!(self.out, "{cell}")?;
+            }
+        }
+        writeln!(self.out)
+    }
+}
+
+/// Render a pipeview dump as a per-stage timeline.
+///
+/// Each pipeline stage becomes one row with one cell per recorded cycle.
+/// A cell shows the number of occupied registers of the stage in that
+/// cycle (capped at 9), or [`FREE`] when the stage held no instruction.
+pub fn render(reader: impl BufRead) -> std::io::Result<String> {
+    let mut lines = reader.lines();
+    let Some(header) = lines.next().transpose()? else {
+        return Ok(String::new());
+    };
+    let stages: Vec<&str> = header.split_whitespace().skip(1).collect();
+
+    let mut first_cycle: Option<u64> = None;
+    let mut last_cycle = 0;
+    let mut rows: Vec<String> = vec![String::new(); stages.len()];
+    for line in lines {
+        let line = line?;
+        let mut cells = line.split_whitespace();
+        let Some(cycle) = cells.next().and_then(|cycle| cycle.parse().ok()) else {
+            continue;
+        };
+        first_cycle.get_or_insert(cycle);
+        last_cycle = cycle;
+        for (row, stage_cells) in rows.iter_mut().zip(cells) {
+            let occupied = stage_cells.chars().filter(|cell| *cell == OCCUPIED).count();
+            match char::from_digit(occupied.min(9) as u32, 10) {
+                Some(occupied) if occupied != '0' => row.push(occupied),
+                _ => row.push(FREE),
+            }
+        }
+    }
+
+    let width = stages.iter().map(|stage| stage.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    if let Some(first_cycle) = first_cycle {
+        out.push_str(&format!("cycles {first_cycle}..={last_cycle}\n"));
+    }
+    for (stage, row) in stages.iter().zip(rows) {
+        out.push_str(&format!("{stage:>width$} |{row}|\n"));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::eyre;
+    use utils::diff;
+
+    #[test]
+    fn test_render() -> eyre::Result<()> {
+        let dump = "cycle ID_OC_SP OC_EX_SP EX_WB
+10 X. .. ..
+11 XX X. ..
+12 .. .X X.
+";
+        let rendered = super::render(dump.as_bytes())?;
+        let expected = "cycles 10..=12
+ID_OC_SP |12.|
+OC_EX_SP |.11|
+   EX_WB |..1|
+";
+        diff::assert_eq!(have: rendered, want: expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+//! Hooks into the simulation engine for user plugins.
+//!
+//! Plugins are trait objects registered on the simulator and are invoked
+//! synchronously from the simulation loop, such that external crates can
+//! implement custom instrumentation without forking the engine internals.
+
+use crate::{kernel::Kernel, mem_fetch};
+
+/// Lifecycle points of a memory fetch observed by the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FetchEvent {
+    /// The fetch was pushed into the interconnect towards memory.
+    ToMemory,
+    /// The fetch was ejected from the interconnect into its memory sub
+    /// partition.
+    ToMemSubPartition,
+    /// The response was pushed into the interconnect towards its cluster.
+    ToShader,
+}
+
+/// Hooks into the simulation engine.
+///
+/// All hooks default to a no-op, such that plugins only implement the
+/// events they are interested in. Hooks are invoked synchronously from
+/// the simulation loop and should be cheap.
+#[allow(unused_variables)]
+pub trait Plugin: Send + Sync + 'static {
+    /// Invoked at the start of each cycle.
+    fn start_of_cycle(&self, cycle: u64) {}
+
+    /// Invoked at the end of each cycle.
+    fn end_of_cycle(&self, cycle: u64) {}
+
+    /// Invoked when a kernel is launched.
+    fn kernel_launch(&self, kernel: &dyn Kernel, cycle: u64) {}
+
+    /// Invoked when a completed kernel is retired.
+    fn kernel_retire(&self, kernel: &dyn Kernel, cycle: u64) {}
+
+    /// Invoked when a memory fetch passes an observed lifecycle point.
+    fn fetch_event(&self, event: FetchEvent, fetch: &mem_fetch::MemFetch, cycle: u64) {}
+}
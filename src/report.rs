@@ -0,0 +1,515 @@
+//! Human-readable stats report.
+//!
+//! Renders the per-kernel statistics as an aligned, hierarchical report
+//! for the command line. The report is for humans only: machine-readable
+//! output is written separately as JSON (see
+//! [`crate::save_stats_to_file`]).
+
+use console::style;
+use std::fmt::Write;
+
+/// Width of the row labels.
+const LABEL_WIDTH: usize = 28;
+/// Width of the value column.
+const VALUE_WIDTH: usize = 14;
+/// Number of interconnect links shown in the hot link table.
+const NUM_HOT_LINKS: usize = 10;
+
+/// Report rendering options.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Render a one-line summary per kernel instead of the full report.
+    pub quiet: bool,
+}
+
+/// Render the stats as a human-readable report.
+///
+/// Colors are applied based on the global terminal detection of
+/// [`console`] and can be disabled using
+/// [`console::set_colors_enabled`].
+#[must_use]
+pub fn render(stats: &stats::PerKernel, options: &Options) -> String {
+    let mut out = String::new();
+    if options.quiet {
+        render_quiet(&mut out, stats);
+    } else {
+        render_full(&mut out, stats);
+    }
+    out
+}
+
+fn render_quiet(out: &mut String, stats: &stats::PerKernel) {
+    for (kernel_launch_id, kernel_stats) in stats.as_ref().iter().enumerate() {
+        let l2d = kernel_stats.l2d_stats.reduce();
+        writeln!(
+            out,
+            "kernel {kernel_launch_id:<3} {:<30} {:>10} cycles {:>12} instructions  L2D {:>6} hit rate",
+            kernel_stats.sim.kernel_name,
+            kernel_stats.sim.cycles,
+            kernel_stats.sim.instructions,
+            percent(f64::from(l2d.global_hit_rate())),
+        )
+        .unwrap();
+    }
+}
+
+fn render_full(out: &mut String, stats: &stats::PerKernel) {
+    render_kernel(out, "no kernel", &stats.no_kernel, &stats.config);
+    render_no_kernel_sections(out, &stats.no_kernel);
+
+    for (kernel_launch_id, kernel_stats) in stats.as_ref().iter().enumerate() {
+        let title = format!(
+            "kernel launch {kernel_launch_id}: {}",
+            kernel_stats.sim.kernel_name
+        );
+        render_kernel(out, &title, kernel_stats, &stats.config);
+    }
+
+    render_tenants(out, stats);
+}
+
+/// Only rendered when tenants are configured.
+fn render_tenants(out: &mut String, stats: &stats::PerKernel) {
+    let mut tenants: std::collections::BTreeMap<usize, (u64, u64, u64)> = Default::default();
+    for kernel_stats in stats.as_ref() {
+        if let Some(tenant) = kernel_stats.sim.tenant {
+            let (num_kernels, cycles, instructions) = tenants.entry(tenant).or_default();
+            *num_kernels += 1;
+            *cycles += kernel_stats.sim.cycles;
+            *instructions += kernel_stats.sim.instructions;
+        }
+    }
+    if tenants.is_empty() {
+        return;
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "===== {} =====", style("tenants").bold()).unwrap();
+    section(out, "tenants");
+    for (tenant, (num_kernels, cycles, instructions)) in tenants {
+        row(
+            out,
+            &format!("tenant {tenant}"),
+            &format!(
+                "{} kernels {} cycles {} instructions",
+                group_digits(num_kernels),
+                group_digits(cycles),
+                group_digits(instructions),
+            ),
+        );
+    }
+}
+
+fn render_kernel(out: &mut String, title: &str, stats: &stats::Stats, config: &stats::Config) {
+    writeln!(out).unwrap();
+    writeln!(out, "===== {} =====", style(title).bold()).unwrap();
+
+    section(out, "simulation");
+    if stats.sim.grid_dim != (0, 0, 0) {
+        let (gx, gy, gz) = stats.sim.grid_dim;
+        let (bx, by, bz) = stats.sim.block_dim;
+        row(
+            out,
+            "launch config",
+            &format!("grid ({gx},{gy},{gz}) block ({bx},{by},{bz})"),
+        );
+        row(
+            out,
+            "registers per thread",
+            &stats.sim.num_registers.to_string(),
+        );
+        if stats.sim.shared_mem_bytes > 0 {
+            row(
+                out,
+                "static shared memory",
+                &human_bytes::human_bytes(f64::from(stats.sim.shared_mem_bytes)),
+            );
+        }
+    }
+    if let Some(tenant) = stats.sim.tenant {
+        row(out, "tenant", &tenant.to_string());
+    }
+    row(out, "cycles", &group_digits(stats.sim.cycles));
+    row(out, "instructions", &group_digits(stats.sim.instructions));
+    row(out, "blocks", &group_digits(stats.sim.num_blocks));
+    if stats.sim.num_skipped_blocks > 0 {
+        row(
+            out,
+            "skipped blocks",
+            &group_digits(stats.sim.num_skipped_blocks),
+        );
+    }
+    if stats.sim.num_reexecuted_instructions > 0 {
+        row(
+            out,
+            "re-executed instructions",
+            &group_digits(stats.sim.num_reexecuted_instructions),
+        );
+    }
+    if stats.sim.kernel_launch_wait_cycles > 0 {
+        row(
+            out,
+            "launch wait cycles",
+            &group_digits(stats.sim.kernel_launch_wait_cycles),
+        );
+    }
+    if stats.sim.num_trace_loop_iterations > 0 {
+        row(
+            out,
+            "trace loop iterations",
+            &group_digits(stats.sim.num_trace_loop_iterations),
+        );
+    }
+    if stats.sim.num_malformed_trace_records > 0 {
+        row(
+            out,
+            "malformed trace records",
+            &group_digits(stats.sim.num_malformed_trace_records),
+        );
+    }
+    if stats.sim.is_persistent_kernel {
+        row(out, "persistent kernel", "yes");
+    }
+    if stats.sim.num_dram_throttled_cycles > 0 {
+        row(
+            out,
+            "DRAM throttled cycles",
+            &group_digits(stats.sim.num_dram_throttled_cycles),
+        );
+    }
+    if stats.sim.num_host_gap_cycles > 0 {
+        row(
+            out,
+            "host gap cycles",
+            &group_digits(stats.sim.num_host_gap_cycles),
+        );
+    }
+    if stats.sim.elapsed_millis > 0 {
+        // wall-clock rates of the simulator itself, such that simulator
+        // performance regressions are visible in routine runs
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let per_second = |count: u64| {
+            group_digits((count as f64 * 1000.0 / stats.sim.elapsed_millis as f64) as u64)
+        };
+        let num_accesses: u64 = stats.accesses.values().sum();
+        row(
+            out,
+            "simulation rate",
+            &format!(
+                "{} cycles/s {} instr/s {} accesses/s",
+                per_second(stats.sim.cycles),
+                per_second(stats.sim.instructions),
+                per_second(num_accesses),
+            ),
+        );
+    }
+
+    render_issue(out, stats, config);
+    render_loops(out, stats);
+    render_caches(out, stats);
+    render_residency(out, stats);
+    render_coherence(out, stats);
+    render_dram(out, stats);
+
+    let mut classes: Vec<_> = stats.l2_arbitration_delays.iter().collect();
+    classes.sort_by_key(|(class, _)| (*class).clone());
+    let delays: Vec<_> = classes
+        .into_iter()
+        .filter_map(|(class, delay)| Some((class, delay.mean()?, delay.num_requests)))
+        .collect();
+    if !delays.is_empty() {
+        section(out, "L2 arbitration queueing delay");
+        for (class, mean, num_requests) in delays {
+            row(
+                out,
+                class,
+                &format!("{mean:.2} cycles ({} requests)", group_digits(num_requests)),
+            );
+        }
+    }
+}
+
+/// Achieved issue rate per core against the peak issue slots.
+fn render_issue(out: &mut String, stats: &stats::Stats, config: &stats::Config) {
+    if stats.sim.cycles == 0 || stats.num_issued_per_core.is_empty() {
+        return;
+    }
+    let mut cores: Vec<_> = stats.num_issued_per_core.iter().collect();
+    cores.sort_by_key(|(core_id, _)| **core_id);
+    let peak = config.max_issue_per_core as f64;
+
+    section(out, "issue rate per core");
+    if config.perfect_scheduler {
+        // the perfect scheduler is not bound by the issue slots of the
+        // realistic scheduler: the rate is an upper bound and the
+        // percentage against the realistic peak shows the gap
+        row(out, "scheduler", "perfect (IPC is an upper bound)");
+    }
+    for (core_id, issued) in cores {
+        let achieved = *issued as f64 / stats.sim.cycles as f64;
+        let value = if peak > 0.0 {
+            format!(
+                "{achieved:.2} of {peak:.0} IPC ({})",
+                percent(achieved / peak)
+            )
+        } else {
+            format!("{achieved:.2} IPC")
+        };
+        row(out, &format!("core {core_id}"), &value);
+    }
+}
+
+/// Loops detected in the instruction trace.
+///
+/// Breaks down iteration counts, memory intensity and IPC contribution
+/// per loop, which approximates kernel-internal phase information
+/// without access to the kernel source.
+fn render_loops(out: &mut String, stats: &stats::Stats) {
+    if stats.loops.is_empty() {
+        return;
+    }
+    section(out, "trace loops");
+    for ((head, tail), stat) in stats.loops.sorted() {
+        let instr_per_iteration = stat.num_instructions as f64 / stat.iterations.max(1) as f64;
+        let memory_fraction =
+            stat.num_memory_instructions as f64 / stat.num_instructions.max(1) as f64;
+        let ipc = stat.num_thread_instructions as f64 / stats.sim.cycles.max(1) as f64;
+        row(
+            out,
+            &format!("0x{head:x}..0x{tail:x}"),
+            &format!(
+                "{} iterations, {instr_per_iteration:.1} instr/iter, {} memory, {ipc:.2} IPC",
+                group_digits(stat.iterations),
+                percent(memory_fraction),
+            ),
+        );
+    }
+}
+
+fn render_caches(out: &mut String, stats: &stats::Stats) {
+    let caches = [
+        ("L1I", stats.l1i_stats.reduce()),
+        ("L1C", stats.l1c_stats.reduce()),
+        ("L1T", stats.l1t_stats.reduce()),
+        ("L1D", stats.l1d_stats.reduce()),
+        ("L2D", stats.l2d_stats.reduce()),
+    ];
+    let caches: Vec<_> = caches
+        .into_iter()
+        .filter(|(_, cache)| cache.num_accesses() > 0)
+        .collect();
+    if caches.is_empty() {
+        return;
+    }
+
+    section(out, "caches");
+    // pad before styling: the width specifier counts escape codes
+    let header = format!(
+        "  {:<6}{:>14}{:>14}{:>14}{:>10}",
+        "cache", "accesses", "hits", "misses", "hit rate",
+    );
+    writeln!(out, "{}", style(header).dim()).unwrap();
+    for (name, cache) in &caches {
+        writeln!(
+            out,
+            "  {:<6}{:>14}{:>14}{:>14}{:>10}",
+            name,
+            group_digits(cache.num_accesses()),
+            group_digits(cache.num_hits()),
+            group_digits(cache.num_misses()),
+            percent(f64::from(cache.hit_rate())),
+        )
+        .unwrap();
+    }
+
+    let l2d = stats.l2d_stats.reduce();
+    if l2d.num_global_reads() > 0 {
+        row(
+            out,
+            "L2D global read hit rate",
+            &percent(f64::from(l2d.global_read_hit_rate())),
+        );
+    }
+    if l2d.num_global_writes() > 0 {
+        row(
+            out,
+            "L2D global write hit rate",
+            &percent(f64::from(l2d.global_write_hit_rate())),
+        );
+    }
+    if l2d.num_global_accesses() > 0 {
+        row(
+            out,
+            "L2D imbalance (max/mean)",
+            &format!("{:.2}", stats.l2d_stats.imbalance()),
+        );
+    }
+}
+
+/// L2 accesses to the configured residency window.
+///
+/// Only rendered when an L2 residency window is configured.
+fn render_residency(out: &mut String, stats: &stats::Stats) {
+    if stats.l2_residency.num_accesses == 0 {
+        return;
+    }
+    section(out, "L2 residency window");
+    row(
+        out,
+        "accesses",
+        &group_digits(stats.l2_residency.num_accesses),
+    );
+    row(out, "hits", &group_digits(stats.l2_residency.num_hits));
+    row(
+        out,
+        "hit rate",
+        &percent(stats.l2_residency.num_hits as f64 / stats.l2_residency.num_accesses as f64),
+    );
+}
+
+/// L1 coherence protocol activity.
+///
+/// Only rendered when the optional L1 coherence protocol is enabled.
+fn render_coherence(out: &mut String, stats: &stats::Stats) {
+    if stats.coherence.num_directory_writes == 0 {
+        return;
+    }
+    section(out, "L1 coherence");
+    row(
+        out,
+        "directory writes",
+        &group_digits(stats.coherence.num_directory_writes),
+    );
+    row(
+        out,
+        "invalidations sent",
+        &group_digits(stats.coherence.num_invalidations_sent),
+    );
+    row(
+        out,
+        "L1 lines invalidated",
+        &group_digits(stats.coherence.num_lines_invalidated),
+    );
+}
+
+fn render_dram(out: &mut String, stats: &stats::Stats) {
+    let reads = stats.dram.total_reads();
+    let writes = stats.dram.total_writes();
+    if reads + writes == 0 {
+        return;
+    }
+
+    section(out, "DRAM");
+    row(out, "reads", &group_digits(reads));
+    row(out, "writes", &group_digits(writes));
+    row(
+        out,
+        "data volume",
+        &human_bytes::human_bytes(
+            ((reads + writes) * u64::from(crate::mem_sub_partition::SECTOR_SIZE)) as f64,
+        ),
+    );
+    if let (Some(observed), Some(estimated)) = (
+        stats.dram.mean_latency(),
+        stats.dram.mean_estimated_latency(),
+    ) {
+        row(out, "latency (detailed)", &format!("{observed:.2} cycles"));
+        row(
+            out,
+            "latency (estimated, M/D/1)",
+            &format!("{estimated:.2} cycles"),
+        );
+    }
+}
+
+/// Sections that are only populated for the no-kernel stats.
+fn render_no_kernel_sections(out: &mut String, stats: &stats::Stats) {
+    if stats.memcopy.num_memcopies > 0 {
+        section(out, "memcopy");
+        row(out, "memcopies", &group_digits(stats.memcopy.num_memcopies));
+        row(
+            out,
+            "bytes copied",
+            &human_bytes::human_bytes(stats.memcopy.num_bytes as f64),
+        );
+        row(out, "cycles", &group_digits(stats.memcopy.cycles));
+    }
+
+    if stats.ldst_return_path_stall_cycles > 0 {
+        section(out, "LD/ST return path");
+        row(
+            out,
+            "stall cycles",
+            &group_digits(stats.ldst_return_path_stall_cycles),
+        );
+    }
+
+    let utilization: Vec<_> = stats
+        .utilization
+        .reduce()
+        .into_iter()
+        .filter(|(_, counters)| counters.total_cycles() > 0)
+        .collect();
+    if !utilization.is_empty() {
+        section(out, "utilization");
+        for (kind, counters) in utilization {
+            row(
+                out,
+                &kind,
+                &format!(
+                    "{} busy ({} busy / {} clocked cycles)",
+                    percent(counters.utilization()),
+                    group_digits(counters.busy_cycles),
+                    group_digits(counters.total_cycles()),
+                ),
+            );
+        }
+    }
+
+    let hot_links = stats.interconn.hot_links();
+    let hot_links = &hot_links[..hot_links.len().min(NUM_HOT_LINKS)];
+    if hot_links.iter().any(|(_, traffic)| traffic.flits > 0) {
+        section(out, "interconnect hot links");
+        for ((src, dest), traffic) in hot_links {
+            row(
+                out,
+                &format!("{src} -> {dest}"),
+                &format!(
+                    "{} flits {} packets {}",
+                    group_digits(traffic.flits),
+                    group_digits(traffic.packets),
+                    human_bytes::human_bytes(traffic.bytes as f64),
+                ),
+            );
+        }
+    }
+}
+
+fn section(out: &mut String, title: &str) {
+    writeln!(out, "{}:", style(title).cyan()).unwrap();
+}
+
+fn row(out: &mut String, label: &str, value: &str) {
+    writeln!(out, "  {label:<LABEL_WIDTH$}{value:>VALUE_WIDTH$}").unwrap();
+}
+
+/// Format a ratio in `0..=1` as a percentage.
+fn percent(ratio: f64) -> String {
+    format!("{:.2}%", ratio * 100.0)
+}
+
+/// Format an integer with `_` digit group separators.
+fn group_digits(value: u64) -> String {
+    let digits: Vec<char> = value.to_string().chars().rev().collect();
+    let mut grouped = String::new();
+    for (i, digit) in digits.iter().enumerate() {
+        if i > 0 && i % 3 == 0 {
+            grouped.push('_');
+        }
+        grouped.push(*digit);
+    }
+    grouped.chars().rev().collect()
+}
@@ -0,0 +1,172 @@
+//! SASS listing correlation.
+//!
+//! Parses the SASS listings that `cuobjdump -sass` produces for a traced
+//! binary and correlates them with the per program counter execution
+//! counters of the simulation ([`stats::PerPC`]), producing an annotated
+//! hot-spot listing per kernel, similar to nsight's source counters.
+//!
+//! Program counters in the traces are the byte offsets of the
+//! instructions within their kernel, which match the offsets in the
+//! listing.
+
+use color_eyre::eyre;
+use indexmap::IndexMap;
+use std::io::BufRead;
+use std::path::Path;
+
+/// A single instruction of a SASS listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// Byte offset of the instruction within its kernel.
+    pub pc: usize,
+    /// Disassembled instruction text.
+    pub text: String,
+}
+
+/// The SASS listing of a single kernel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Listing {
+    /// Instructions in listing order.
+    pub instructions: Vec<Instruction>,
+}
+
+impl Listing {
+    /// Annotate the listing with per program counter execution counters.
+    ///
+    /// Each instruction is prefixed with the number of warp issues, the
+    /// number of executed thread instructions, and the scoreboard stall
+    /// cycles recorded for its program counter.
+    #[must_use]
+    pub fn annotate(&self, per_pc: &stats::PerPC) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "{:>12} {:>12} {:>12}\n",
+            "issued", "thread inst.", "stall cycles"
+        ));
+        for instruction in &self.instructions {
+            let counters = per_pc.get(instruction.pc).copied().unwrap_or_default();
+            out.push_str(&format!(
+                "{:>12} {:>12} {:>12}  /*{:04x}*/ {}\n",
+                counters.num_issued,
+                counters.num_thread_instructions,
+                counters.stall_cycles,
+                instruction.pc,
+                instruction.text,
+            ));
+        }
+        out
+    }
+}
+
+/// Parse the SASS listings of all kernels in a `cuobjdump -sass` dump.
+///
+/// Listings are keyed by the mangled name of their kernel and hold the
+/// instructions in listing order. Scheduler control words and section
+/// headers are skipped.
+pub fn parse(reader: impl BufRead) -> eyre::Result<IndexMap<String, Listing>> {
+    let mut listings: IndexMap<String, Listing> = IndexMap::new();
+    let mut current: Option<usize> = None;
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(mangled_name) = line.strip_prefix("Function : ") {
+            let entry = listings.entry(mangled_name.trim().to_string());
+            current = Some(entry.index());
+            entry.or_default();
+            continue;
+        }
+        // instruction lines start with their byte offset, e.g.
+        // `/*0008*/ MOV R1, c[0x0][0x20] ; /* 0x4c98078000870001 */`
+        let Some(rest) = line.strip_prefix("/*") else {
+            continue;
+        };
+        let Some((offset, rest)) = rest.split_once("*/") else {
+            continue;
+        };
+        // scheduler control words (`/* 0x001fc800fec007f6 */`) are not
+        // valid offsets and are skipped here
+        let Ok(pc) = usize::from_str_radix(offset.trim(), 16) else {
+            continue;
+        };
+        let Some(listing) = current.and_then(|idx| listings.get_index_mut(idx)) else {
+            eyre::bail!("instruction at {pc:#x} before the first function header");
+        };
+        let (_mangled_name, listing) = listing;
+        // strip the trailing encoding comment
+        let text = match rest.split_once("/*") {
+            Some((text, _encoding)) => text,
+            None => rest,
+        };
+        listing.instructions.push(Instruction {
+            pc,
+            text: text.trim().to_string(),
+        });
+    }
+    Ok(listings)
+}
+
+/// Parse the SASS listings of all kernels in a `cuobjdump -sass` dump file.
+pub fn parse_file(path: impl AsRef<Path>) -> eyre::Result<IndexMap<String, Listing>> {
+    let file = std::fs::File::open(path.as_ref())?;
+    parse(std::io::BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::eyre;
+    use utils::diff;
+
+    const LISTING: &str = r#"
+	code for sm_61
+		Function : _Z9vectoraddIfEvPKT_S2_PS0_m
+	.headerflags    @"EF_CUDA_SM61 EF_CUDA_PTX_SM(EF_CUDA_SM61)"
+                                                                       /* 0x001fc800fec007f6 */
+        /*0008*/                   MOV R1, c[0x0][0x20] ;              /* 0x4c98078000870001 */
+        /*0010*/                   S2R R0, SR_CTAID.X ;                /* 0xf0c8000002570000 */
+        /*0018*/                   S2R R2, SR_TID.X ;                  /* 0xf0c8000002170002 */
+		..........................
+"#;
+
+    #[test]
+    fn test_parse() -> eyre::Result<()> {
+        let listings = super::parse(LISTING.as_bytes())?;
+        let (mangled_name, listing) = listings.first().unwrap();
+        diff::assert_eq!(have: mangled_name, want: "_Z9vectoraddIfEvPKT_S2_PS0_m");
+        let want = vec![
+            super::Instruction {
+                pc: 0x0008,
+                text: "MOV R1, c[0x0][0x20] ;".to_string(),
+            },
+            super::Instruction {
+                pc: 0x0010,
+                text: "S2R R0, SR_CTAID.X ;".to_string(),
+            },
+            super::Instruction {
+                pc: 0x0018,
+                text: "S2R R2, SR_TID.X ;".to_string(),
+            },
+        ];
+        diff::assert_eq!(have: listing.instructions, want: want);
+        Ok(())
+    }
+
+    #[test]
+    fn test_annotate() -> eyre::Result<()> {
+        let listings = super::parse(LISTING.as_bytes())?;
+        let listing = &listings["_Z9vectoraddIfEvPKT_S2_PS0_m"];
+        let mut per_pc = stats::PerPC::default();
+        *per_pc.get_mut(0x0008) = stats::pc::PC {
+            num_issued: 4,
+            num_thread_instructions: 128,
+            stall_cycles: 2,
+        };
+        let annotated = listing.annotate(&per_pc);
+        let want = "      issued thread inst. stall cycles
+           4          128            2  /*0008*/ MOV R1, c[0x0][0x20] ;
+           0            0            0  /*0010*/ S2R R0, SR_CTAID.X ;
+           0            0            0  /*0018*/ S2R R2, SR_TID.X ;
+";
+        diff::assert_eq!(have: annotated, want: want);
+        Ok(())
+    }
+}
@@ -42,10 +42,11 @@ impl Scheduler {
 
 impl super::Scheduler for Scheduler {
     fn order_warps(&mut self, core: &dyn WarpIssuer) {
+        let tie_break_keys = self.inner.tie_break_keys();
         self.inner.order_by_priority(
             super::ordering::Ordering::GREEDY_THEN_PRIORITY_FUNC,
             |lhs: &(usize, warp::Ref), rhs: &(usize, warp::Ref)| {
-                super::ordering::sort_warps_by_oldest_dynamic_id(lhs, rhs, core)
+                super::ordering::sort_warps_by_oldest_dynamic_id(lhs, rhs, &tie_break_keys, core)
             },
         );
     }
@@ -58,6 +59,14 @@ impl super::Scheduler for Scheduler {
         self.inner.prioritized_warps()
     }
 
+    fn stats(&self) -> stats::scheduler::Scheduler {
+        self.inner.stats.try_lock().clone()
+    }
+
+    fn reset_stats(&mut self) {
+        *self.inner.stats.try_lock() = stats::scheduler::Scheduler::default();
+    }
+
     fn issue_to(&mut self, core: &dyn WarpIssuer, cycle: u64) {
         log::debug!(
             "gto scheduler[{}]: BEFORE: prioritized warp ids: {:?}",
@@ -70,7 +79,7 @@ impl super::Scheduler for Scheduler {
             self.debug_dynamic_warp_ids()
         );
 
-        self.order_warps(core);
+        crate::timeit!("scheduler::order_warps", self.order_warps(core));
 
         log::debug!(
             "gto scheduler[{}]: AFTER: prioritized warp ids: {:?}",
@@ -83,6 +92,6 @@ impl super::Scheduler for Scheduler {
             self.debug_dynamic_warp_ids()
         );
 
-        self.inner.issue_to(core, cycle);
+        crate::timeit!("scheduler::issue", self.inner.issue_to(core, cycle));
     }
 }
@@ -37,6 +37,12 @@ pub trait Scheduler: Send + Sync + std::fmt::Debug + 'static {
 
     /// Order warps based on scheduling policy.
     fn order_warps(&mut self, core: &dyn WarpIssuer);
+
+    /// Issue statistics of this scheduler.
+    fn stats(&self) -> stats::scheduler::Scheduler;
+
+    /// Reset the issue statistics of this scheduler.
+    fn reset_stats(&mut self);
 }
 
 impl std::fmt::Debug for &dyn WarpIssuer {
@@ -68,6 +74,13 @@ pub struct Base {
     last_supervised_issued_idx: usize,
     num_issued_last_cycle: usize,
 
+    /// Number of pseudo-random tie break permutations drawn so far.
+    ///
+    /// Only advanced by the random tie break, such that the permutation
+    /// changes every scheduler cycle while remaining deterministic for
+    /// a fixed seed.
+    num_tie_break_draws: u64,
+
     scoreboard: Arc<RwLock<scoreboard::Scoreboard>>,
 
     config: Arc<config::GPU>,
@@ -94,6 +107,7 @@ impl Base {
             last_supervised_issued_idx: 0,
             warps,
             num_issued_last_cycle: 0,
+            num_tie_break_draws: 0,
             stats,
             scoreboard,
             config,
@@ -122,8 +136,9 @@ impl Base {
         //     panic!("we do use the sfu unit");
         // }
         let free_register = core.has_free_register(stage, self.id);
-        let can_dual_issue =
-            !self.config.dual_issue_only_to_different_exec_units || prev_issued_exec_unit != unit;
+        let can_dual_issue = self.config.perfect_scheduler
+            || !self.config.dual_issue_only_to_different_exec_units
+            || prev_issued_exec_unit != unit;
 
         if free_register && can_dual_issue {
             core.issue_warp(stage, warp, self.id, cycle).is_ok()
@@ -169,11 +184,17 @@ impl Base {
             let mut num_issued = 0;
 
             let mut prev_issued_exec_unit = ExecUnitKind::NONE;
-            let max_issue = self.config.max_instruction_issue_per_warp;
+            // the perfect scheduler ignores the issue width and dual
+            // issue restrictions for upper-bound analysis
+            let max_issue = if self.config.perfect_scheduler {
+                usize::MAX
+            } else {
+                self.config.max_instruction_issue_per_warp
+            };
             // In this mode, we only allow dual issue to diff execution
             // units (as in Maxwell and Pascal)
-            let dual_issue_only_to_different_exec_units =
-                self.config.dual_issue_only_to_different_exec_units;
+            let dual_issue_only_to_different_exec_units = !self.config.perfect_scheduler
+                && self.config.dual_issue_only_to_different_exec_units;
 
             if log::log_enabled!(log::Level::Debug) && inst_count > 1 {
                 if next_warp.ibuffer_empty() {
@@ -234,6 +255,12 @@ impl Base {
                         dyn_warp_id,
                         style("fails scoreboard").yellow(),
                     );
+                    *self
+                        .stats
+                        .lock()
+                        .stall_cycles_per_pc
+                        .entry((instr.kernel_launch_id, instr.pc))
+                        .or_insert(0) += 1;
                     continue;
                 }
 
@@ -431,12 +458,18 @@ impl Base {
                 } else {
                     stats.num_dual_issue += 1;
                 }
-                break;
+                *stats.num_issued_per_warp.entry(warp_id).or_insert(0) += num_issued as u64;
+                // the perfect scheduler keeps issuing ready warps
+                // instead of stopping after the first issuing warp
+                if !self.config.perfect_scheduler {
+                    break;
+                }
             }
         }
 
         // issue stall statistics
         let mut stats = self.stats.lock();
+        stats.utilization.add_cycle(issued_inst);
         if !valid_inst {
             // idle or control hazard
             stats.issue_raw_hazard_stall += 1;
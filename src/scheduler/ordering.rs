@@ -19,9 +19,22 @@ pub fn all_different<T>(values: &[Arc<Mutex<T>>]) -> bool {
     true
 }
 
+/// `SplitMix64` mixing function.
+///
+/// Used to derive the pseudo-random tie break permutation without
+/// pulling in a full rng (which is only available with the `parallel`
+/// feature).
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 pub fn sort_warps_by_oldest_dynamic_id(
     lhs: &(usize, warp::Ref),
     rhs: &(usize, warp::Ref),
+    tie_break_keys: &[u64],
     issuer: &dyn crate::core::WarpIssuer,
 ) -> std::cmp::Ordering {
     let mut lhs_warp = lhs.1.try_lock();
@@ -40,11 +53,12 @@ pub fn sort_warps_by_oldest_dynamic_id(
         (false, true) => std::cmp::Ordering::Less,
         (true, true) => {
             // both blocked
-            (lhs.0).cmp(&(rhs.0))
+            tie_break_keys[lhs.0].cmp(&tie_break_keys[rhs.0])
         }
         (false, false) => {
             // both unblocked
-            (lhs_warp.dynamic_warp_id(), lhs.0).cmp(&(rhs_warp.dynamic_warp_id(), rhs.0))
+            (lhs_warp.dynamic_warp_id(), tie_break_keys[lhs.0])
+                .cmp(&(rhs_warp.dynamic_warp_id(), tie_break_keys[rhs.0]))
         }
     }
 
@@ -71,6 +85,32 @@ pub enum Ordering {
 }
 
 impl super::Base {
+    /// Tie break keys for the supervised warps of this scheduler.
+    ///
+    /// Returns one key per supervised warp slot; warps the priority
+    /// function considers equal win in ascending key order (see
+    /// [`crate::config::SchedulerTieBreak`]).
+    pub fn tie_break_keys(&mut self) -> Vec<u64> {
+        let num_warps = self.supervised_warps.len();
+        match self.config.scheduler_tie_break {
+            crate::config::SchedulerTieBreak::LowestWarpId => (0..num_warps as u64).collect(),
+            crate::config::SchedulerTieBreak::RoundRobin => (0..num_warps)
+                .map(|idx| {
+                    let distance =
+                        (idx + num_warps - self.last_supervised_issued_idx - 1) % num_warps;
+                    distance as u64
+                })
+                .collect(),
+            crate::config::SchedulerTieBreak::Random { seed } => {
+                let draw = splitmix64(seed ^ self.num_tie_break_draws);
+                self.num_tie_break_draws += 1;
+                (0..num_warps as u64)
+                    .map(|idx| splitmix64(draw ^ idx))
+                    .collect()
+            }
+        }
+    }
+
     pub fn order_by_priority<F>(&mut self, ordering: Ordering, priority_func: F)
     where
         F: FnMut(&(usize, warp::Ref), &(usize, warp::Ref)) -> std::cmp::Ordering,
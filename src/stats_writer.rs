@@ -0,0 +1,130 @@
+//! Asynchronous stats writer.
+//!
+//! For very large stats (per allocation, per core), serializing the
+//! final JSON output can take minutes and would otherwise happen after
+//! the simulation has already finished. The writer serializes the stats
+//! of each kernel on a background thread as the kernel completes, such
+//! that serialization overlaps with the remaining simulation and
+//! finalizing the stats file only has to serialize the no-kernel stats
+//! and any kernels that were not streamed.
+
+use crate::config;
+use crate::sync::Arc;
+use color_eyre::eyre;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Serializes kernel stats on a background thread.
+///
+/// Kernel stats are handed to the writer as each kernel completes (see
+/// [`crate::MockSimulator::set_stats_writer`]) and must be finalized
+/// with [`StatsWriter::finalize`] to produce the stats file.
+pub struct StatsWriter {
+    sender: std::sync::mpsc::Sender<(usize, stats::Stats)>,
+    handle: std::thread::JoinHandle<HashMap<usize, String>>,
+}
+
+impl StatsWriter {
+    /// Spawn the background serializer thread.
+    #[must_use]
+    pub fn spawn() -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel::<(usize, stats::Stats)>();
+        let handle = std::thread::spawn(move || {
+            let mut serialized = HashMap::new();
+            while let Ok((kernel_launch_id, kernel_stats)) = receiver.recv() {
+                match serde_json::to_string(&kernel_stats) {
+                    Ok(json) => {
+                        serialized.insert(kernel_launch_id, json);
+                    }
+                    Err(err) => {
+                        log::error!(
+                            "failed to serialize stats of kernel {kernel_launch_id}: {err}"
+                        );
+                    }
+                }
+            }
+            serialized
+        });
+        Self { sender, handle }
+    }
+
+    /// Hand the final stats of a completed kernel to the writer.
+    pub fn write_kernel(&self, kernel_launch_id: usize, kernel_stats: stats::Stats) {
+        // a failed send leaves the kernel to be serialized on finalize
+        self.sender.send((kernel_launch_id, kernel_stats)).ok();
+    }
+
+    /// Write the stats file, reusing the kernel stats serialized during
+    /// the run.
+    ///
+    /// The file has the same layout as [`crate::save_stats_to_file`],
+    /// except that the streamed kernel stats are embedded in compact
+    /// form.
+    pub fn finalize(
+        self,
+        stats: &stats::PerKernel,
+        config: &Arc<config::GPU>,
+        path: &Path,
+    ) -> eyre::Result<()> {
+        use serde::Serialize;
+
+        let Self { sender, handle } = self;
+        drop(sender);
+        let mut serialized = handle
+            .join()
+            .map_err(|_| eyre::eyre!("stats writer thread panicked"))?;
+
+        // kernels that were not streamed during the run are serialized now
+        let inner = stats
+            .as_ref()
+            .iter()
+            .enumerate()
+            .map(|(kernel_launch_id, kernel_stats)| {
+                let json = match serialized.remove(&kernel_launch_id) {
+                    Some(json) => json,
+                    None => serde_json::to_string(kernel_stats)?,
+                };
+                serde_json::value::RawValue::from_string(json)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let path = path.with_extension("json");
+        if let Some(parent) = &path.parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        let output_file = utils::fs::open_writable(path)?;
+        let mut json_serializer = serde_json::Serializer::with_formatter(
+            output_file,
+            serde_json::ser::PrettyFormatter::with_indent(b"    "),
+        );
+        let file = StatsFileParts {
+            metadata: crate::StatsMetadata::new(Arc::clone(config), stats),
+            stats: PerKernelParts {
+                schema_version: stats.schema_version,
+                inner,
+                no_kernel: &stats.no_kernel,
+                config: &stats.config,
+            },
+        };
+        file.serialize(&mut json_serializer)?;
+        Ok(())
+    }
+}
+
+/// Mirrors the serialized layout of [`stats::PerKernel`] with
+/// pre-serialized kernel stats.
+#[derive(serde::Serialize)]
+struct PerKernelParts<'a> {
+    schema_version: u64,
+    inner: Vec<Box<serde_json::value::RawValue>>,
+    no_kernel: &'a stats::Stats,
+    config: &'a stats::Config,
+}
+
+/// Mirrors the serialized layout of the stats file written by
+/// [`crate::save_stats_to_file`].
+#[derive(serde::Serialize)]
+struct StatsFileParts<'a> {
+    metadata: crate::StatsMetadata,
+    stats: PerKernelParts<'a>,
+}
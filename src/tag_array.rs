@@ -24,7 +24,7 @@ pub struct AccessStatus {
     pub status: cache::RequestStatus,
 }
 
-/// Tag array configuration.
+// Tag array configuration.
 // #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
 // pub struct Config {
 //     allocate_policy: config::CacheAllocatePolicy,
@@ -33,6 +33,29 @@ pub struct AccessStatus {
 //     addr_translation: Box<dyn CacheAddressTranslation>,
 // }
 
+/// Per-set access and miss counters.
+///
+/// Optionally recorded per tag array and exported as a CSV heatmap to
+/// visualize set-camping caused by the set-index function or
+/// allocation placement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessHeatmap {
+    /// Number of accesses per set.
+    pub accesses: Vec<u64>,
+    /// Number of misses (including sector misses) per set.
+    pub misses: Vec<u64>,
+}
+
+impl AccessHeatmap {
+    #[must_use]
+    pub fn new(num_sets: usize) -> Self {
+        Self {
+            accesses: vec![0; num_sets],
+            misses: vec![0; num_sets],
+        }
+    }
+}
+
 /// Tag array.
 #[derive(Debug)]
 pub struct TagArray<B, CC> {
@@ -50,6 +73,12 @@ pub struct TagArray<B, CC> {
     cache_controller: CC,
     cache_config: cache::Config,
     pending_lines: LineTable,
+    access_heatmap: Option<AccessHeatmap>,
+    /// Address range prioritized during replacement.
+    ///
+    /// Only set for the L2 tag arrays (see
+    /// [`config::L2ResidencyWindow`]).
+    pub l2_residency_window: Option<config::L2ResidencyWindow>,
 }
 
 impl<B, CC> TagArray<B, CC>
@@ -63,6 +92,12 @@ where
 
         let cache_config = cache::Config::new(config, accelsim_compat);
 
+        let access_heatmap = if cache_config.record_access_heatmap {
+            Some(AccessHeatmap::new(cache_config.num_sets))
+        } else {
+            None
+        };
+
         Self {
             lines,
             is_used: false,
@@ -75,6 +110,8 @@ where
             cache_config: cache_config.clone(),
             cache_controller,
             pending_lines: LineTable::new(),
+            access_heatmap,
+            l2_residency_window: None,
         }
     }
 }
@@ -95,6 +132,16 @@ pub trait Access<B> {
     /// This effectively resets the tag array.
     fn invalidate(&mut self);
 
+    /// Invalidates the line holding `addr`, if present.
+    ///
+    /// Modified and reserved lines are left alone: the L1 coherence
+    /// protocol only invalidates clean copies, and a reserved line is
+    /// overwritten by its pending fill anyway.
+    ///
+    /// # Returns
+    /// Whether a line was invalidated.
+    fn invalidate_addr(&mut self, addr: address) -> bool;
+
     /// The maximum number of tags this array can hold.
     #[must_use]
     fn size(&self) -> usize;
@@ -125,6 +172,11 @@ where
         self.num_access += 1;
         self.is_used = true;
 
+        if let Some(ref mut heatmap) = self.access_heatmap {
+            let set_index = self.cache_controller.set_index(addr) as usize;
+            heatmap.accesses[set_index] += 1;
+        }
+
         // let mut writeback = false;
         let mut evicted = None;
 
@@ -152,6 +204,10 @@ where
             }
             cache::RequestStatus::MISS => {
                 self.num_miss += 1;
+                if let Some(ref mut heatmap) = self.access_heatmap {
+                    let set_index = self.cache_controller.set_index(addr) as usize;
+                    heatmap.misses[set_index] += 1;
+                }
                 let line = &mut self.lines[cache_index];
 
                 log::trace!(
@@ -196,6 +252,10 @@ where
             cache::RequestStatus::SECTOR_MISS => {
                 // debug_assert_eq!(self.cache_config.kind, config::CacheKind::Sector);
                 // self.num_sector_miss += 1;
+                if let Some(ref mut heatmap) = self.access_heatmap {
+                    let set_index = self.cache_controller.set_index(addr) as usize;
+                    heatmap.misses[set_index] += 1;
+                }
                 if self.cache_config.allocate_policy == cache::config::AllocatePolicy::ON_MISS {
                     let line = &mut self.lines[cache_index];
                     let was_modified_before = line.is_modified();
@@ -248,6 +308,24 @@ where
         self.num_dirty = 0;
     }
 
+    // #[inline]
+    fn invalidate_addr(&mut self, addr: address) -> bool {
+        let block_addr = self.cache_controller.block_addr(addr);
+        let set_index = self.cache_controller.set_index(block_addr) as usize;
+        let tag = self.cache_controller.tag(block_addr);
+        for way in 0..self.cache_config.associativity {
+            let idx = set_index * self.cache_config.associativity + way;
+            let line = &mut self.lines[idx];
+            if line.tag() == tag && line.is_valid() && !line.is_reserved() && !line.is_modified() {
+                for sector in 0..NUM_SECTORS {
+                    line.set_status(cache::block::Status::INVALID, sector);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
     // #[inline]
     fn size(&self) -> usize {
         self.lines.len()
@@ -320,8 +398,30 @@ where
         let mut valid_line = None;
         let mut valid_time = u64::MAX;
 
+        // least recently used line inside the L2 residency window:
+        // only evicted when the set holds no other candidate
+        let mut persistent_line = None;
+        let mut persistent_time = u64::MAX;
+
         let mut all_reserved = true;
 
+        // lines inside the L2 residency window are skipped during
+        // victim selection as long as the persistent lines occupy at
+        // most the set-aside ways of the set
+        let residency_window = self
+            .l2_residency_window
+            .as_ref()
+            .filter(|window| window.matches_kernel(fetch.and_then(|f| f.kernel_launch_id())));
+        let max_persistent_ways = residency_window.map_or(0, |window| {
+            window.max_ways_percent * self.cache_config.associativity / 100
+        });
+        let num_persistent_lines = residency_window.map_or(0, |window| {
+            (0..self.cache_config.associativity)
+                .map(|way| &self.lines[set_index * self.cache_config.associativity + way])
+                .filter(|line| line.is_valid() && window.contains(line.block_addr()))
+                .count()
+        });
+
         // percentage of dirty lines in the cache
         // number of dirty lines / total lines in the cache
         let dirty_line_percent = self.num_dirty as f64 / self.cache_config.total_lines as f64;
@@ -393,19 +493,27 @@ where
                         invalid_line = Some(idx);
                     } else {
                         // valid line: keep track of most appropriate replacement candidate
+                        let persistent = num_persistent_lines <= max_persistent_ways
+                            && residency_window
+                                .is_some_and(|window| window.contains(line.block_addr()));
+                        let (candidate, candidate_time) = if persistent {
+                            (&mut persistent_line, &mut persistent_time)
+                        } else {
+                            (&mut valid_line, &mut valid_time)
+                        };
                         if self.cache_config.replacement_policy
                             == cache::config::ReplacementPolicy::LRU
                         {
-                            if line.last_access_time() < valid_time {
-                                valid_time = line.last_access_time();
-                                valid_line = Some(idx);
+                            if line.last_access_time() < *candidate_time {
+                                *candidate_time = line.last_access_time();
+                                *candidate = Some(idx);
                             }
                         } else if self.cache_config.replacement_policy
                             == cache::config::ReplacementPolicy::FIFO
-                            && line.alloc_time() < valid_time
+                            && line.alloc_time() < *candidate_time
                         {
-                            valid_time = line.alloc_time();
-                            valid_line = Some(idx);
+                            *candidate_time = line.alloc_time();
+                            *candidate = Some(idx);
                         }
                     }
                 }
@@ -435,9 +543,15 @@ where
             (_, Some(invalid)) => invalid,
             (Some(valid), None) => valid,
             (None, None) => {
-                // if an unreserved block exists,
-                // it is either invalid or replaceable
-                panic!("found neither a valid nor invalid cache line");
+                // all unreserved lines of the set are persistent: evict
+                // the least recently used persistent line rather than
+                // stalling the cache indefinitely
+                let Some(persistent) = persistent_line else {
+                    // if an unreserved block exists,
+                    // it is either invalid or replaceable
+                    panic!("found neither a valid nor invalid cache line");
+                };
+                persistent
             }
         };
         Some((cache_idx, cache::RequestStatus::MISS))
@@ -570,6 +684,45 @@ where
         }
         Ok(())
     }
+
+    /// The recorded per-set access heatmap, if enabled.
+    #[must_use]
+    pub fn access_heatmap(&self) -> Option<&AccessHeatmap> {
+        self.access_heatmap.as_ref()
+    }
+
+    /// Write the recorded per-set access heatmap to csv file.
+    ///
+    /// Does nothing unless heatmap recording is enabled for this cache
+    /// via [`crate::config::Cache::record_access_heatmap`].
+    pub fn write_access_heatmap(
+        &self,
+        csv_writer: &mut csv::Writer<std::io::BufWriter<std::fs::File>>,
+    ) -> eyre::Result<()> {
+        #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+        struct CsvRow {
+            pub set_index: usize,
+            pub accesses: u64,
+            pub misses: u64,
+        }
+
+        let Some(ref heatmap) = self.access_heatmap else {
+            return Ok(());
+        };
+        for (set_index, (accesses, misses)) in heatmap
+            .accesses
+            .iter()
+            .zip(heatmap.misses.iter())
+            .enumerate()
+        {
+            csv_writer.serialize(CsvRow {
+                set_index,
+                accesses: *accesses,
+                misses: *misses,
+            })?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
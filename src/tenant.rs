@@ -0,0 +1,46 @@
+//! Tenant bookkeeping for multi-tenant simulation.
+//!
+//! Tenants group the kernels of co-located applications (see
+//! [`crate::config::Tenant`]). Kernel launches are assigned to their
+//! tenant when they are launched, such that the arbitration points in
+//! the memory system can look up the tenant priority of a request from
+//! its kernel launch id alone.
+
+use crate::sync::RwLock;
+use std::collections::HashMap;
+
+/// Tenant index and priority per kernel launch id.
+static ASSIGNMENTS: once_cell::sync::Lazy<RwLock<HashMap<usize, (usize, usize)>>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(HashMap::default()));
+
+/// Assign a kernel launch to a tenant.
+pub fn assign(kernel_launch_id: usize, tenant: usize, priority: usize) {
+    ASSIGNMENTS
+        .write()
+        .insert(kernel_launch_id, (tenant, priority));
+}
+
+/// The tenant a kernel launch was assigned to.
+#[must_use]
+pub fn tenant_of(kernel_launch_id: Option<usize>) -> Option<usize> {
+    let kernel_launch_id = kernel_launch_id?;
+    ASSIGNMENTS
+        .read()
+        .get(&kernel_launch_id)
+        .map(|(tenant, _)| *tenant)
+}
+
+/// The tenant priority of a kernel launch.
+///
+/// Kernels not covered by any tenant run at the lowest priority.
+#[must_use]
+pub fn priority_of(kernel_launch_id: Option<usize>) -> usize {
+    kernel_launch_id
+        .and_then(|id| ASSIGNMENTS.read().get(&id).map(|(_, priority)| *priority))
+        .unwrap_or(0)
+}
+
+/// Clear all tenant assignments.
+pub fn reset() {
+    ASSIGNMENTS.write().clear();
+}
@@ -1,5 +1,7 @@
 use crate::sync::{Arc, Mutex};
-use crate::{instruction::WarpInstruction, kernel::Kernel};
+use crate::{
+    dep_graph::DependencyGraph, instruction::WarpInstruction, kernel::Kernel, opcodes::ArchOp,
+};
 use std::collections::VecDeque;
 pub use trace_model::{active_mask::Inner as ActiveMaskInner, ActiveMask, WARP_SIZE};
 
@@ -14,10 +16,15 @@ pub struct Warp {
     pub trace_pc: usize,
     pub active_mask: ActiveMask,
     pub trace_instructions: VecDeque<WarpInstruction>,
+    /// Register dependency graph of the trace instructions.
+    pub dep_graph: DependencyGraph,
 
     // state
     pub done_exit: bool,
     pub num_instr_in_pipeline: usize,
+    /// Uid and functional unit category of issued but not yet retired
+    /// instructions, in issue (program) order.
+    pub in_flight_instructions: VecDeque<(u64, ArchOp)>,
     pub num_outstanding_stores: usize,
     pub num_outstanding_atomics: usize,
     pub waiting_for_memory_barrier: bool,
@@ -52,9 +59,11 @@ impl Default for Warp {
             kernel: None,
             trace_pc: 0,
             trace_instructions: VecDeque::new(),
+            dep_graph: DependencyGraph::default(),
             active_mask: ActiveMask::ZERO,
             done_exit: false,
             num_instr_in_pipeline: 0,
+            in_flight_instructions: VecDeque::new(),
             num_outstanding_stores: 0,
             num_outstanding_atomics: 0,
             has_imiss_pending: false,
@@ -85,6 +94,7 @@ impl Warp {
     pub fn reset(&mut self) {
         debug_assert_eq!(self.num_outstanding_stores, 0);
         debug_assert_eq!(self.num_instr_in_pipeline, 0);
+        debug_assert!(self.in_flight_instructions.is_empty());
         self.has_imiss_pending = false;
         self.warp_id = u32::MAX as usize;
         self.dynamic_warp_id = u32::MAX as usize;
@@ -130,6 +140,20 @@ impl Warp {
     pub fn clear(&mut self) {
         self.trace_pc = 0;
         self.trace_instructions.clear();
+        self.dep_graph = DependencyGraph::default();
+    }
+
+    /// Rebuild the register dependency graph for the loaded trace
+    /// instructions.
+    pub fn compute_dependencies(&mut self) {
+        self.dep_graph = DependencyGraph::new(self.trace_instructions.iter());
+    }
+
+    /// Indices of the earlier trace instructions that the instruction
+    /// at `trace_pc` depends on.
+    #[must_use]
+    pub fn instruction_dependencies(&self, trace_pc: usize) -> &[usize] {
+        self.dep_graph.dependencies(trace_pc)
     }
 
     pub fn ibuffer_fill(&mut self, slot: usize, instr: WarpInstruction) {
@@ -169,6 +193,40 @@ impl Warp {
         self.next = (self.next + 1) % IBUFFER_SIZE;
     }
 
+    /// Record an issued instruction for in-flight tracking.
+    ///
+    /// Called at issue, right after the instruction receives its uid,
+    /// such that the in-flight list is ordered by uid (program order).
+    pub fn instruction_issued(&mut self, instr: &WarpInstruction) {
+        self.in_flight_instructions
+            .push_back((instr.uid, instr.opcode.category));
+    }
+
+    /// Remove a retired instruction from the in-flight tracking.
+    ///
+    /// Idempotent: retirement sites may overlap for some instruction
+    /// classes and only the first call removes the entry.
+    pub fn instruction_retired(&mut self, uid: u64) {
+        if let Some(idx) = self
+            .in_flight_instructions
+            .iter()
+            .position(|(in_flight_uid, _)| *in_flight_uid == uid)
+        {
+            self.in_flight_instructions.remove(idx);
+        }
+    }
+
+    /// Whether no older instruction of this warp is still in flight.
+    ///
+    /// With `same_category_only`, only older instructions that issued
+    /// to the same functional unit category are considered.
+    #[must_use]
+    pub fn is_oldest_in_flight(&self, instr: &WarpInstruction, same_category_only: bool) -> bool {
+        !self.in_flight_instructions.iter().any(|(uid, category)| {
+            *uid < instr.uid && (!same_category_only || *category == instr.opcode.category)
+        })
+    }
+
     #[must_use]
     pub fn done_exit(&self) -> bool {
         self.done_exit
@@ -0,0 +1,108 @@
+//! Aggregation of per-kernel metrics into whole-application metrics.
+//!
+//! Naively averaging per-kernel rates over kernels is wrong: each rate
+//! must be weighted by the amount of work it describes. All rates the
+//! simulator derives reduce to a ratio of two counter sums, so the
+//! aggregation sums the integer counters first and divides once at the
+//! end. Integer sums do not depend on summation order, which keeps the
+//! derived rates bit-stable across platforms and across the number of
+//! threads used for a parallel simulation.
+
+/// Ratio of `(numerator, denominator)` pair sums.
+///
+/// Equivalent to the arithmetic mean of the per-pair ratios weighted by
+/// their denominators (per-kernel hit rates weighted by accesses give
+/// the hit rate over the combined accesses), and to the harmonic mean
+/// of the per-pair ratios weighted by their numerators (per-kernel IPC
+/// values weighted by instructions give the whole-application IPC).
+///
+/// The counters are summed in integers, such that the single final
+/// division is exact up to rounding and independent of the pair order.
+/// The ratio of an empty sequence or a zero denominator sum is zero.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn ratio_of_sums(values: impl IntoIterator<Item = (u64, u64)>) -> f64 {
+    let mut total_numerator: u64 = 0;
+    let mut total_denominator: u64 = 0;
+    for (numerator, denominator) in values {
+        total_numerator += numerator;
+        total_denominator += denominator;
+    }
+    if total_denominator == 0 {
+        0.0
+    } else {
+        total_numerator as f64 / total_denominator as f64
+    }
+}
+
+impl crate::PerKernel {
+    /// Whole-application instructions per cycle.
+    ///
+    /// Total instructions over total cycles, which is identical to the
+    /// harmonic mean of the per-kernel IPC values weighted by
+    /// instructions.
+    #[must_use]
+    pub fn ipc(&self) -> f64 {
+        ratio_of_sums(
+            self.inner
+                .iter()
+                .map(|kernel_stats| (kernel_stats.sim.instructions, kernel_stats.sim.cycles)),
+        )
+    }
+
+    /// Whole-application L1 data cache hit rate.
+    ///
+    /// Total hits over total accesses, which is identical to the mean
+    /// of the per-kernel hit rates weighted by accesses.
+    #[must_use]
+    pub fn l1d_hit_rate(&self) -> f64 {
+        ratio_of_sums(self.inner.iter().map(|kernel_stats| {
+            let l1d_stats = kernel_stats.l1d_stats.reduce();
+            (l1d_stats.num_hits(), l1d_stats.num_accesses())
+        }))
+    }
+
+    /// Whole-application L2 data cache hit rate.
+    ///
+    /// Total hits over total accesses, which is identical to the mean
+    /// of the per-kernel hit rates weighted by accesses.
+    #[must_use]
+    pub fn l2d_hit_rate(&self) -> f64 {
+        ratio_of_sums(self.inner.iter().map(|kernel_stats| {
+            let l2d_stats = kernel_stats.l2d_stats.reduce();
+            (l2d_stats.num_hits(), l2d_stats.num_accesses())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ratio_of_sums;
+
+    #[test]
+    fn ratio_of_sums_is_order_independent() {
+        // the same counters partitioned differently, as different
+        // thread counts would produce them
+        let pairs = [(3, 7), (1_000_000_007, 2_000_000_011), (0, 5), (42, 0)];
+        let mut reversed = pairs;
+        reversed.reverse();
+        assert_eq!(
+            ratio_of_sums(pairs).to_bits(),
+            ratio_of_sums(reversed).to_bits()
+        );
+
+        // pre-aggregated per-thread partial sums combine to the same
+        // bit pattern as the unpartitioned counters
+        for chunk_size in [1, 2, 4] {
+            let partial_sums = pairs.chunks(chunk_size).map(|chunk| {
+                chunk.iter().fold((0, 0), |(numerator, denominator), pair| {
+                    (numerator + pair.0, denominator + pair.1)
+                })
+            });
+            assert_eq!(
+                ratio_of_sums(pairs).to_bits(),
+                ratio_of_sums(partial_sums).to_bits()
+            );
+        }
+    }
+}
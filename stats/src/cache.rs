@@ -1,6 +1,7 @@
 use super::mem::AccessKind;
+use crate::add_counter;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 #[derive(
     Debug,
@@ -182,15 +183,34 @@ pub struct CsvRow {
     pub access_kind: AccessKind,
     pub is_write: bool,
     pub access_status: AccessStat,
-    pub num_accesses: usize,
+    pub num_accesses: u64,
 }
 
 #[derive(Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Cache {
-    pub inner: HashMap<(Option<usize>, AccessStatus), usize>,
+    #[serde(with = "crate::as_entries")]
+    pub inner: IndexMap<(Option<usize>, AccessStatus), u64>,
+    /// Accesses per named address range of the config.
+    ///
+    /// The range id is the index of the range in the config.
+    #[serde(with = "crate::as_entries")]
+    pub per_range: IndexMap<(usize, AccessStatus), u64>,
+    /// Write-through writes forwarded to the next memory level.
+    ///
+    /// Writes are only forwarded on a hit when the effective write
+    /// policy of the access is write-through.
+    pub num_write_through_sent: IndexMap<AccessKind, u64>,
+    pub num_l1_cache_bank_accesses: u64,
     pub num_l1_cache_bank_conflicts: u64,
     pub num_shared_mem_bank_accesses: u64,
     pub num_shared_mem_bank_conflicts: u64,
+    /// Accesses rejected because all ports of a shared cache were taken
+    /// this cycle.
+    pub num_shared_cache_port_conflicts: u64,
+    /// Uncompressed bytes filled into a compressed cache.
+    pub num_fill_bytes_uncompressed: u64,
+    /// Compressed bytes filled into a compressed cache.
+    pub num_fill_bytes_compressed: u64,
 
     #[cfg(feature = "detailed-stats")]
     pub accesses: Vec<(crate::mem::Access, Option<usize>, AccessStatus)>,
@@ -199,7 +219,7 @@ pub struct Cache {
 impl Default for Cache {
     fn default() -> Self {
         use strum::IntoEnumIterator;
-        let mut inner = HashMap::new();
+        let mut inner = IndexMap::new();
         for access_kind in AccessKind::iter() {
             for status in RequestStatus::iter() {
                 inner.insert(
@@ -222,17 +242,23 @@ impl Default for Cache {
         }
         Self {
             inner,
+            per_range: IndexMap::new(),
+            num_write_through_sent: IndexMap::new(),
             num_shared_mem_bank_accesses: 0,
             num_shared_mem_bank_conflicts: 0,
+            num_l1_cache_bank_accesses: 0,
             num_l1_cache_bank_conflicts: 0,
+            num_shared_cache_port_conflicts: 0,
+            num_fill_bytes_uncompressed: 0,
+            num_fill_bytes_compressed: 0,
             #[cfg(feature = "detailed-stats")]
             accesses: Vec::new(),
         }
     }
 }
 
-impl AsRef<HashMap<(Option<usize>, AccessStatus), usize>> for Cache {
-    fn as_ref(&self) -> &HashMap<(Option<usize>, AccessStatus), usize> {
+impl AsRef<IndexMap<(Option<usize>, AccessStatus), u64>> for Cache {
+    fn as_ref(&self) -> &IndexMap<(Option<usize>, AccessStatus), u64> {
         &self.inner
     }
 }
@@ -259,8 +285,42 @@ impl AsRef<HashMap<(Option<usize>, AccessStatus), usize>> for Cache {
 impl std::ops::AddAssign for Cache {
     fn add_assign(&mut self, other: Self) {
         for (k, v) in other.inner {
-            *self.inner.entry(k).or_insert(0) += v;
+            add_counter!(*self.inner.entry(k).or_insert(0), v);
         }
+        for (k, v) in other.per_range {
+            add_counter!(*self.per_range.entry(k).or_insert(0), v);
+        }
+        for (k, v) in other.num_write_through_sent {
+            add_counter!(*self.num_write_through_sent.entry(k).or_insert(0), v);
+        }
+        add_counter!(
+            self.num_l1_cache_bank_accesses,
+            other.num_l1_cache_bank_accesses
+        );
+        add_counter!(
+            self.num_l1_cache_bank_conflicts,
+            other.num_l1_cache_bank_conflicts
+        );
+        add_counter!(
+            self.num_shared_mem_bank_accesses,
+            other.num_shared_mem_bank_accesses
+        );
+        add_counter!(
+            self.num_shared_mem_bank_conflicts,
+            other.num_shared_mem_bank_conflicts
+        );
+        add_counter!(
+            self.num_shared_cache_port_conflicts,
+            other.num_shared_cache_port_conflicts
+        );
+        add_counter!(
+            self.num_fill_bytes_uncompressed,
+            other.num_fill_bytes_uncompressed
+        );
+        add_counter!(
+            self.num_fill_bytes_compressed,
+            other.num_fill_bytes_compressed
+        );
     }
 }
 
@@ -305,7 +365,7 @@ impl std::fmt::Debug for Cache {
 
 impl Cache {
     #[must_use]
-    pub fn new(inner: HashMap<(Option<usize>, AccessStatus), usize>) -> Self {
+    pub fn new(inner: IndexMap<(Option<usize>, AccessStatus), u64>) -> Self {
         Self {
             inner,
             ..Self::default()
@@ -317,7 +377,7 @@ impl Cache {
         alloc_id: Option<usize>,
         kind: impl Into<AccessKind>,
         status: impl Into<AccessStat>,
-    ) -> Option<usize> {
+    ) -> Option<u64> {
         let kind = kind.into();
         let status = status.into();
         let access_stat = AccessStatus((kind, status));
@@ -328,8 +388,8 @@ impl Cache {
     pub fn union<'a>(
         &'a self,
         other: &'a Self,
-    ) -> impl Iterator<Item = (&'a (Option<usize>, AccessStatus), (usize, usize))> {
-        let keys: std::collections::HashSet<_> =
+    ) -> impl Iterator<Item = (&'a (Option<usize>, AccessStatus), (u64, u64))> {
+        let keys: indexmap::IndexSet<_> =
             self.as_ref().keys().chain(other.as_ref().keys()).collect();
         keys.into_iter().map(|k| {
             let left = self.as_ref().get(k).copied().unwrap_or(0);
@@ -341,7 +401,7 @@ impl Cache {
     #[must_use]
     pub fn reduce_allocations(self) -> Self {
         let mut reduced = Self {
-            inner: HashMap::default(),
+            inner: IndexMap::default(),
             ..self.clone()
         };
         for ((_, access_status), value) in self.inner {
@@ -350,9 +410,7 @@ impl Cache {
         reduced
     }
 
-    pub fn iter(
-        &self,
-    ) -> std::collections::hash_map::Iter<'_, (Option<usize>, AccessStatus), usize> {
+    pub fn iter(&self) -> indexmap::map::Iter<'_, (Option<usize>, AccessStatus), u64> {
         self.inner.iter()
     }
 
@@ -362,7 +420,7 @@ impl Cache {
 
     #[must_use]
     pub fn merge_allocations(self) -> Cache {
-        let mut inner = HashMap::new();
+        let mut inner = IndexMap::new();
         for ((_, access), count) in self.inner {
             *inner.entry((None, access)).or_insert(0) += count;
         }
@@ -373,7 +431,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn count_accesses_of_kind(&self, kind: AccessKind) -> usize {
+    pub fn count_accesses_of_kind(&self, kind: AccessKind) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.kind() == &kind)
@@ -393,7 +451,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn count_accesses(&self, access: &AccessStatus) -> usize {
+    pub fn count_accesses(&self, access: &AccessStatus) -> u64 {
         self.inner
             .iter()
             .filter(|((_, acc), _)| acc == access)
@@ -426,7 +484,7 @@ impl Cache {
     // }
 
     #[must_use]
-    pub fn num_accesses(&self) -> usize {
+    pub fn num_accesses(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -445,7 +503,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_accesses(&self) -> usize {
+    pub fn num_global_accesses(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -465,9 +523,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn pending_hits(
-        &self,
-    ) -> impl Iterator<Item = ((Option<usize>, AccessStatus), usize)> + '_ {
+    pub fn pending_hits(&self) -> impl Iterator<Item = ((Option<usize>, AccessStatus), u64)> + '_ {
         self.inner
             .iter()
             .map(|(k, v)| (k.clone(), v.clone()))
@@ -475,9 +531,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn sector_misses(
-        &self,
-    ) -> impl Iterator<Item = ((Option<usize>, AccessStatus), usize)> + '_ {
+    pub fn sector_misses(&self) -> impl Iterator<Item = ((Option<usize>, AccessStatus), u64)> + '_ {
         self.inner
             .iter()
             .map(|(k, v)| (k.clone(), v.clone()))
@@ -485,7 +539,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_read_misses(&self) -> usize {
+    pub fn num_read_misses(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_read() && access.is_miss())
@@ -494,7 +548,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_read_misses(&self) -> usize {
+    pub fn num_global_read_misses(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_global() && access.is_read() && access.is_miss())
@@ -503,7 +557,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_write_misses(&self) -> usize {
+    pub fn num_write_misses(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_write() && access.is_miss())
@@ -512,7 +566,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_write_misses(&self) -> usize {
+    pub fn num_global_write_misses(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_global() && access.is_write() && access.is_miss())
@@ -521,7 +575,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_misses(&self) -> usize {
+    pub fn num_misses(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_miss())
@@ -530,7 +584,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_misses(&self) -> usize {
+    pub fn num_global_misses(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_global() && access.is_miss())
@@ -539,7 +593,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_read_hits(&self) -> usize {
+    pub fn num_read_hits(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -550,7 +604,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_read_hits(&self) -> usize {
+    pub fn num_global_read_hits(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -563,7 +617,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_reads(&self) -> usize {
+    pub fn num_reads(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -574,7 +628,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_reads(&self) -> usize {
+    pub fn num_global_reads(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -587,7 +641,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_writes(&self) -> usize {
+    pub fn num_writes(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -599,7 +653,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_writes(&self) -> usize {
+    pub fn num_global_writes(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -612,7 +666,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_write_hits(&self) -> usize {
+    pub fn num_write_hits(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -623,7 +677,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_write_hits(&self) -> usize {
+    pub fn num_global_write_hits(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -636,7 +690,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_hits(&self) -> usize {
+    pub fn num_hits(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_hit() || access.is_pending_hit())
@@ -645,7 +699,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_global_hits(&self) -> usize {
+    pub fn num_global_hits(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| {
@@ -685,8 +739,21 @@ impl Cache {
         self.num_global_read_hits() as f32 / self.num_global_reads() as f32
     }
 
+    /// The compression ratio achieved over all filled lines.
+    ///
+    /// Returns 1.0 when the cache is uncompressed or no lines were
+    /// filled.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn compression_ratio(&self) -> f64 {
+        if self.num_fill_bytes_compressed == 0 {
+            return 1.0;
+        }
+        self.num_fill_bytes_uncompressed as f64 / self.num_fill_bytes_compressed as f64
+    }
+
     #[must_use]
-    pub fn num_pending_hits(&self) -> usize {
+    pub fn num_pending_hits(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_pending_hit())
@@ -695,7 +762,7 @@ impl Cache {
     }
 
     #[must_use]
-    pub fn num_reservation_fails(&self) -> usize {
+    pub fn num_reservation_fails(&self) -> u64 {
         self.inner
             .iter()
             .filter(|((_, access), _)| access.is_reservation_fail())
@@ -714,8 +781,28 @@ impl Cache {
         let kind = kind.into();
         let status = status.into();
         let access_stat = AccessStatus((kind, status));
+        let alloc_id = if cfg!(feature = "per-allocation-stats") && crate::collect::per_allocation()
+        {
+            alloc_id
+        } else {
+            // aggregate over all allocations
+            None
+        };
         // println!("inc access stat: {access_stat}");
-        *self.inner.entry((alloc_id, access_stat)).or_insert(0) += count;
+        *self.inner.entry((alloc_id, access_stat)).or_insert(0) += count as u64;
+    }
+
+    /// Count an access towards a named address range.
+    // #[inline]
+    pub fn inc_range(
+        &mut self,
+        range_id: usize,
+        kind: impl Into<AccessKind>,
+        status: impl Into<AccessStat>,
+        count: usize,
+    ) {
+        let access_stat = AccessStatus((kind.into(), status.into()));
+        *self.per_range.entry((range_id, access_stat)).or_insert(0) += count as u64;
     }
 }
 
@@ -832,10 +919,36 @@ impl PerCache {
     }
 
     #[must_use]
-    pub fn total_accesses(&self) -> usize {
+    pub fn total_accesses(&self) -> u64 {
         self.reduce().num_accesses()
     }
 
+    /// Number of accesses per cache.
+    #[must_use]
+    pub fn accesses_per_cache(&self) -> Vec<u64> {
+        self.inner.iter().map(Cache::num_accesses).collect()
+    }
+
+    /// Imbalance of accesses across the per-unit caches.
+    ///
+    /// The ratio of the maximum number of accesses to a single cache over
+    /// the mean number of accesses per cache.
+    /// A value of 1.0 means perfectly balanced traffic.
+    #[must_use]
+    pub fn imbalance(&self) -> f32 {
+        let max = self
+            .inner
+            .iter()
+            .map(Cache::num_accesses)
+            .max()
+            .unwrap_or(0);
+        if max == 0 {
+            return 0.0;
+        }
+        let mean = self.total_accesses() as f32 / self.inner.len() as f32;
+        max as f32 / mean
+    }
+
     #[must_use]
     pub fn reduce(&self) -> Cache {
         let mut out = Cache::default();
@@ -1,4 +1,5 @@
 use super::mem::AccessKind;
+use crate::add_counter;
 use indexmap::IndexMap;
 use itertools::Itertools;
 use ndarray::prelude::*;
@@ -27,6 +28,24 @@ pub struct DRAM {
     pub kernel_info: super::KernelInfo,
     /// Bank accesses
     pub bank_accesses: ndarray::Array4<u64>,
+    /// Accesses per named address range of the config.
+    ///
+    /// The range id is the index of the range in the config.
+    #[serde(with = "crate::as_entries")]
+    pub range_accesses: std::collections::HashMap<(usize, AccessKind), u64>,
+    /// Sum of the observed DRAM request latencies in cycles.
+    ///
+    /// The latency of a request covers issue to DRAM until return to
+    /// the sub partition.
+    pub total_latency: u64,
+    /// Sum of the M/D/1 estimated DRAM request latencies in cycles.
+    ///
+    /// The estimate is computed at issue time from the arrival rate
+    /// measured so far and can be compared against the observed latency
+    /// for calibration.
+    pub total_estimated_latency: u64,
+    /// Number of requests contributing to the latency sums.
+    pub num_latency_samples: u64,
     /// Number of cores
     pub num_cores: usize,
     /// Number of DRAM chips
@@ -41,7 +60,14 @@ impl std::ops::AddAssign for DRAM {
         assert_eq!(self.num_chips, other.num_chips);
         assert_eq!(self.num_banks, other.num_banks);
 
+        // element-wise ndarray addition has no checked variant
         self.bank_accesses = other.bank_accesses + self.bank_accesses.view_mut();
+        for (k, v) in other.range_accesses {
+            add_counter!(*self.range_accesses.entry(k).or_insert(0), v);
+        }
+        add_counter!(self.total_latency, other.total_latency);
+        add_counter!(self.total_estimated_latency, other.total_estimated_latency);
+        add_counter!(self.num_latency_samples, other.num_latency_samples);
     }
 }
 
@@ -56,6 +82,10 @@ impl DRAM {
                 num_banks,
                 AccessKind::count(),
             )),
+            range_accesses: std::collections::HashMap::new(),
+            total_latency: 0,
+            total_estimated_latency: 0,
+            num_latency_samples: 0,
             num_banks,
             num_cores: num_total_cores,
             num_chips: num_mem_units,
@@ -103,6 +133,26 @@ impl DRAM {
             .collect()
     }
 
+    /// Mean observed DRAM request latency in cycles.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean_latency(&self) -> Option<f64> {
+        if self.num_latency_samples == 0 {
+            return None;
+        }
+        Some(self.total_latency as f64 / self.num_latency_samples as f64)
+    }
+
+    /// Mean M/D/1 estimated DRAM request latency in cycles.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean_estimated_latency(&self) -> Option<f64> {
+        if self.num_latency_samples == 0 {
+            return None;
+        }
+        Some(self.total_estimated_latency as f64 / self.num_latency_samples as f64)
+    }
+
     #[must_use]
     pub fn total_reads(&self) -> u64 {
         AccessKind::reads()
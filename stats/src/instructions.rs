@@ -1,3 +1,4 @@
+use crate::add_counter;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -54,7 +55,10 @@ pub struct CsvRow {
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InstructionCounts {
     pub kernel_info: super::KernelInfo,
+    #[serde(with = "crate::as_entries")]
     pub inner: HashMap<(Option<usize>, MemorySpace, bool), u64>,
+    /// Executed thread instructions per opcode category.
+    pub op_counts: HashMap<String, u64>,
 }
 
 impl Default for InstructionCounts {
@@ -68,6 +72,7 @@ impl Default for InstructionCounts {
         Self {
             inner,
             kernel_info: super::KernelInfo::default(),
+            op_counts: HashMap::new(),
         }
     }
 }
@@ -75,7 +80,10 @@ impl Default for InstructionCounts {
 impl std::ops::AddAssign for InstructionCounts {
     fn add_assign(&mut self, other: Self) {
         for (k, v) in other.inner {
-            *self.inner.entry(k).or_insert(0) += v;
+            add_counter!(*self.inner.entry(k).or_insert(0), v);
+        }
+        for (k, v) in other.op_counts {
+            add_counter!(*self.op_counts.entry(k).or_insert(0), v);
         }
     }
 }
@@ -0,0 +1,52 @@
+use crate::add_counter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Traffic over a single interconnect link.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkTraffic {
+    pub packets: u64,
+    pub flits: u64,
+    pub bytes: u64,
+}
+
+impl std::ops::AddAssign for LinkTraffic {
+    fn add_assign(&mut self, other: Self) {
+        add_counter!(self.packets, other.packets);
+        add_counter!(self.flits, other.flits);
+        add_counter!(self.bytes, other.bytes);
+    }
+}
+
+/// Interconnect traffic statistics.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interconn {
+    /// Traffic per source and destination device pair.
+    ///
+    /// Device ids follow the interconnect numbering: clusters come
+    /// first, followed by the memory sub partitions.
+    #[serde(with = "crate::as_entries")]
+    pub links: HashMap<(usize, usize), LinkTraffic>,
+}
+
+impl Interconn {
+    /// Links sorted by the number of flits transferred in descending order.
+    #[must_use]
+    pub fn hot_links(&self) -> Vec<((usize, usize), LinkTraffic)> {
+        let mut links: Vec<_> = self
+            .links
+            .iter()
+            .map(|(link, traffic)| (*link, *traffic))
+            .collect();
+        links.sort_by_key(|(link, traffic)| (std::cmp::Reverse(traffic.flits), *link));
+        links
+    }
+}
+
+impl std::ops::AddAssign for Interconn {
+    fn add_assign(&mut self, other: Self) {
+        for (link, traffic) in other.links {
+            *self.links.entry(link).or_default() += traffic;
+        }
+    }
+}
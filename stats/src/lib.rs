@@ -4,23 +4,110 @@
     clippy::missing_panics_doc
 )]
 
+pub mod aggregate;
 pub mod cache;
 pub mod dram;
 pub mod instructions;
+pub mod interconn;
+pub mod loops;
 pub mod mem;
+pub mod pc;
 pub mod scheduler;
 pub mod sim;
+pub mod utilization;
 
 pub use cache::{Cache, PerCache};
 pub use dram::DRAM;
 pub use instructions::InstructionCounts;
+pub use loops::Loops;
 pub use mem::Accesses;
+pub use pc::PerPC;
 pub use sim::Sim;
 pub use utils::box_slice;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// (De)serialize a map as a sequence of `(key, value)` entries.
+///
+/// JSON objects only allow string keys: maps keyed by tuples or other
+/// composite types are written as entry lists instead.
+pub mod as_entries {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<M, K, V, S>(map: &M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, M, K, V, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        M: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let entries = Vec::<(K, V)>::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+/// Add a value onto a stat counter, panicking on overflow.
+///
+/// Aggregation sums per-kernel counters into totals over arbitrarily
+/// long runs, where a silently wrapped counter is worse than an aborted
+/// run. Unlike `+=`, the check is also performed in release builds.
+macro_rules! add_counter {
+    ($counter:expr, $value:expr) => {{
+        let counter = &mut $counter;
+        *counter = counter
+            .checked_add($value)
+            .unwrap_or_else(|| panic!("stat counter {} overflowed", stringify!($counter)));
+    }};
+}
+pub(crate) use add_counter;
+
+/// Runtime toggles for expensive stat categories.
+///
+/// Each category can also be disabled at compile time using the
+/// feature of the same name. The runtime toggles allow disabling
+/// collection without recompiling and are read from the environment
+/// once on first use.
+pub mod collect {
+    use std::sync::OnceLock;
+
+    fn env_flag(name: &str) -> bool {
+        !matches!(
+            std::env::var(name).ok().as_deref(),
+            Some("0" | "no" | "off" | "false")
+        )
+    }
+
+    /// Collect cache stats per allocation.
+    ///
+    /// Set `STATS_PER_ALLOCATION=0` to aggregate cache stats over all
+    /// allocations.
+    #[must_use]
+    pub fn per_allocation() -> bool {
+        static PER_ALLOCATION: OnceLock<bool> = OnceLock::new();
+        *PER_ALLOCATION.get_or_init(|| env_flag("STATS_PER_ALLOCATION"))
+    }
+
+    /// Collect detailed (per access) stats.
+    ///
+    /// Set `STATS_DETAILED=0` to disable.
+    #[must_use]
+    pub fn detailed() -> bool {
+        static DETAILED: OnceLock<bool> = OnceLock::new();
+        *DETAILED.get_or_init(|| env_flag("STATS_DETAILED"))
+    }
+}
+
 /// Statistics configuration.
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct KernelInfo {
@@ -36,6 +123,17 @@ pub struct Config {
     pub num_mem_units: usize,
     pub num_sub_partitions: usize,
     pub num_dram_banks: usize,
+    /// Peak number of issue slots per core per cycle.
+    ///
+    /// Zero when the simulator configuration is not known (e.g. for
+    /// converted stats).
+    pub max_issue_per_core: usize,
+    /// Whether the run used the idealized perfect scheduler.
+    ///
+    /// The reported IPC is an upper bound in that case and should be
+    /// compared against a run with the realistic scheduler.
+    #[serde(default)]
+    pub perfect_scheduler: bool,
 }
 
 /// Per kernel statistics.
@@ -43,11 +141,28 @@ pub struct Config {
 /// Stats at index `i` correspond to the kernel with launch id `i`.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PerKernel {
+    /// Schema version of the serialized stats.
+    ///
+    /// Files written before versioning lack this field and are read as
+    /// version 1 (see [`SCHEMA_VERSION`]).
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u64,
     pub inner: Vec<Stats>,
     pub no_kernel: Stats,
     pub config: Config,
 }
 
+/// Current schema version of serialized stats.
+///
+/// Bumped whenever the serialized layout changes such that files written
+/// by older versions need migration before newer tooling can read them.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// Schema version assumed for files without a `schema_version` field.
+fn legacy_schema_version() -> u64 {
+    1
+}
+
 impl AsRef<Vec<Stats>> for PerKernel {
     fn as_ref(&self) -> &Vec<Stats> {
         &self.inner
@@ -65,6 +180,7 @@ impl PerKernel {
     pub fn new(config: Config) -> Self {
         let no_kernel = Stats::new(&config);
         Self {
+            schema_version: SCHEMA_VERSION,
             config,
             no_kernel,
             inner: Vec::new(),
@@ -106,6 +222,8 @@ impl std::ops::AddAssign for Stats {
     fn add_assign(&mut self, other: Self) {
         self.accesses += other.accesses;
         self.instructions += other.instructions;
+        self.per_pc += other.per_pc;
+        self.loops += other.loops;
         self.sim += other.sim;
         self.dram += other.dram;
         self.l1i_stats += other.l1i_stats;
@@ -113,7 +231,54 @@ impl std::ops::AddAssign for Stats {
         self.l1t_stats += other.l1t_stats;
         self.l1d_stats += other.l1d_stats;
         self.l2d_stats += other.l2d_stats;
-        self.stall_dram_full += other.stall_dram_full;
+        self.interconn += other.interconn;
+        for (scheduler_id, scheduler) in other.schedulers {
+            *self.schedulers.entry(scheduler_id).or_default() += scheduler;
+        }
+        add_counter!(self.stall_dram_full, other.stall_dram_full);
+        add_counter!(self.num_l2_bypassed, other.num_l2_bypassed);
+        add_counter!(
+            self.stall_interconn_to_shader,
+            other.stall_interconn_to_shader
+        );
+        add_counter!(
+            self.ldst_return_path_stall_cycles,
+            other.ldst_return_path_stall_cycles
+        );
+        for (unit, stalls) in other.num_writeback_stalls {
+            add_counter!(*self.num_writeback_stalls.entry(unit).or_insert(0), stalls);
+        }
+        for (core_id, issued) in other.num_issued_per_core {
+            add_counter!(
+                *self.num_issued_per_core.entry(core_id).or_insert(0),
+                issued
+            );
+        }
+        for (unit, conflicts) in other.num_reorder_conflicts {
+            add_counter!(
+                *self.num_reorder_conflicts.entry(unit).or_insert(0),
+                conflicts
+            );
+        }
+        for (class, delay) in other.l2_arbitration_delays {
+            *self.l2_arbitration_delays.entry(class).or_default() += delay;
+        }
+        for (cluster_id, occupancy) in other.ejection_buffer_occupancy {
+            *self
+                .ejection_buffer_occupancy
+                .entry(cluster_id)
+                .or_default() += occupancy;
+        }
+        for (core_id, occupancy) in other.ldst_response_buffer_occupancy {
+            *self
+                .ldst_response_buffer_occupancy
+                .entry(core_id)
+                .or_default() += occupancy;
+        }
+        self.utilization += other.utilization;
+        self.memcopy += other.memcopy;
+        self.coherence += other.coherence;
+        self.l2_residency += other.l2_residency;
     }
 }
 
@@ -168,6 +333,16 @@ pub struct Stats {
     pub accesses: Accesses,
     /// Instruction count breakdown per memory space and kind.
     pub instructions: InstructionCounts,
+    /// Execution counters per program counter.
+    ///
+    /// The counters can be correlated with a SASS listing of the kernel
+    /// to produce an annotated hot-spot listing.
+    pub per_pc: PerPC,
+    /// Loops detected in the instruction trace.
+    ///
+    /// Keyed by the program counter range of the loop body, see
+    /// [`loops::Loops`].
+    pub loops: Loops,
     /// High-level simulation metrics.
     pub sim: Sim,
     /// DRAM access stats.
@@ -182,8 +357,204 @@ pub struct Stats {
     pub l1d_stats: PerCache,
     /// L2 data cache stats.
     pub l2d_stats: PerCache,
+    /// Interconnect traffic per link.
+    ///
+    /// Traffic cannot be attributed to kernels, hence this is only
+    /// populated for the no-kernel stats.
+    pub interconn: interconn::Interconn,
+    /// Warp scheduler stats per (core id, scheduler id).
+    ///
+    /// Issue slots cannot be attributed to kernels, hence this is only
+    /// populated for the no-kernel stats.
+    #[serde(with = "as_entries")]
+    pub schedulers: HashMap<(usize, usize), scheduler::Scheduler>,
     // where should those go? stall reasons? per core?
     pub stall_dram_full: u64,
+    /// Number of accesses that bypassed a texture-only L2 cache.
+    pub num_l2_bypassed: u64,
+    /// Cycles a memory sub partition could not eject a response because
+    /// the interconnect has no credits left for the destination cluster.
+    pub stall_interconn_to_shader: u64,
+    /// Cycles a ready LD/ST response could not leave the cluster
+    /// ejection buffer because the configured return path bandwidth
+    /// was exhausted.
+    ///
+    /// Only counted with a configured return path bandwidth, and only
+    /// populated for the no-kernel stats.
+    pub ldst_return_path_stall_cycles: u64,
+    /// Writeback stalls per functional unit.
+    ///
+    /// A functional unit stalls when it completed an instruction but
+    /// cannot move it into the EX|WB pipeline register because all
+    /// slots of the configured writeback width are taken.
+    pub num_writeback_stalls: HashMap<String, u64>,
+    /// Number of warp instructions issued per core (global core id).
+    ///
+    /// Together with [`Config::max_issue_per_core`] and the kernel
+    /// cycles, this relates the achieved issue rate of each core to the
+    /// machine's issue limit.
+    pub num_issued_per_core: HashMap<usize, u64>,
+    /// Reorder conflicts at the writeback stage per functional unit
+    /// category.
+    ///
+    /// With relaxed writeback ordering, counts instructions that retired
+    /// while an older instruction of the same warp was still in flight.
+    /// With enforced in-order writeback, counts the cycles completed
+    /// instructions were held in the reorder buffer waiting for older
+    /// instructions to retire.
+    pub num_reorder_conflicts: HashMap<String, u64>,
+    /// Cluster ejection buffer occupancy per cluster id.
+    ///
+    /// Buffer occupancy cannot be attributed to kernels, hence this is
+    /// only populated for the no-kernel stats.
+    pub ejection_buffer_occupancy: HashMap<usize, BufferOccupancy>,
+    /// Queueing delay at the memory sub partition input per arbitration
+    /// class ("read", "write", "writeback").
+    ///
+    /// Counts the cycles a request spent waiting in the sub partition
+    /// input queue after it became ready, such that the delay induced by
+    /// the arbitration policy can be compared between the classes.
+    pub l2_arbitration_delays: HashMap<String, QueueingDelay>,
+    /// Load store unit response buffer occupancy per core id.
+    ///
+    /// Buffer occupancy cannot be attributed to kernels, hence this is
+    /// only populated for the no-kernel stats.
+    pub ldst_response_buffer_occupancy: HashMap<usize, BufferOccupancy>,
+    /// Busy and idle cycles per hardware component.
+    ///
+    /// Idle cycles cannot be attributed to kernels, hence this is only
+    /// populated for the no-kernel stats.
+    pub utilization: utilization::Utilization,
+    /// Memcopy commands replayed through the L2/DRAM path.
+    ///
+    /// Memcopies cannot be attributed to kernels, hence this is only
+    /// populated for the no-kernel stats.
+    pub memcopy: Memcopy,
+    /// L1 coherence protocol activity.
+    ///
+    /// Only populated when the optional L1 coherence protocol is
+    /// enabled; real GPUs do not keep their L1 data caches coherent.
+    pub coherence: Coherence,
+    /// L2 accesses to the configured residency window.
+    ///
+    /// Only populated when an L2 residency window is configured.
+    pub l2_residency: L2Residency,
+}
+
+/// Queueing delay of a class of memory requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueingDelay {
+    /// Total number of cycles the requests waited in the queue after
+    /// they became ready.
+    pub total_delay: u64,
+    /// Number of requests that passed through the queue.
+    pub num_requests: u64,
+}
+
+impl std::ops::AddAssign for QueueingDelay {
+    fn add_assign(&mut self, other: Self) {
+        add_counter!(self.total_delay, other.total_delay);
+        add_counter!(self.num_requests, other.num_requests);
+    }
+}
+
+impl QueueingDelay {
+    /// Mean queueing delay in cycles.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean(&self) -> Option<f64> {
+        if self.num_requests == 0 {
+            return None;
+        }
+        Some(self.total_delay as f64 / self.num_requests as f64)
+    }
+}
+
+/// Occupancy of a bounded buffer.
+///
+/// Tracks how close a buffer comes to its configured capacity and how
+/// often it stalls its producer, to guide sizing the buffer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BufferOccupancy {
+    /// Highest number of entries observed in the buffer.
+    pub high_water_mark: u64,
+    /// Number of cycles the buffer was full and could not accept a new
+    /// entry.
+    pub full_stall_cycles: u64,
+}
+
+impl std::ops::AddAssign for BufferOccupancy {
+    fn add_assign(&mut self, other: Self) {
+        self.high_water_mark = self.high_water_mark.max(other.high_water_mark);
+        add_counter!(self.full_stall_cycles, other.full_stall_cycles);
+    }
+}
+
+/// Memcopy replay stats.
+///
+/// Only memcopies that are replayed through the L2/DRAM path (when
+/// filling the L2 on memcopy or in memcopy-only mode) are counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Memcopy {
+    /// Number of replayed memcopy commands.
+    pub num_memcopies: u64,
+    /// Total number of bytes copied.
+    pub num_bytes: u64,
+    /// Total number of cycles spent replaying memcopies.
+    pub cycles: u64,
+}
+
+impl std::ops::AddAssign for Memcopy {
+    fn add_assign(&mut self, other: Self) {
+        add_counter!(self.num_memcopies, other.num_memcopies);
+        add_counter!(self.num_bytes, other.num_bytes);
+        add_counter!(self.cycles, other.cycles);
+    }
+}
+
+/// L1 coherence protocol activity.
+///
+/// The protocol keeps the L1 data caches coherent using a sharer
+/// directory at each L2 slice that invalidates the copies of all other
+/// sharers on a global write.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Coherence {
+    /// Number of global writes that checked a sharer directory.
+    pub num_directory_writes: u64,
+    /// Number of invalidation messages sent from a directory to an L1.
+    pub num_invalidations_sent: u64,
+    /// Number of invalidations that found and invalidated a live L1
+    /// line.
+    pub num_lines_invalidated: u64,
+}
+
+impl std::ops::AddAssign for Coherence {
+    fn add_assign(&mut self, other: Self) {
+        add_counter!(self.num_directory_writes, other.num_directory_writes);
+        add_counter!(self.num_invalidations_sent, other.num_invalidations_sent);
+        add_counter!(self.num_lines_invalidated, other.num_lines_invalidated);
+    }
+}
+
+/// L2 accesses to the configured residency window.
+///
+/// Lines in the residency window are prioritized during L2 replacement,
+/// mirroring the CUDA access policy window. The hit rate within the
+/// window shows whether the set-aside capacity holds the persistent
+/// working set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L2Residency {
+    /// Number of L2 accesses to addresses inside the window.
+    pub num_accesses: u64,
+    /// Number of L2 hits for addresses inside the window.
+    pub num_hits: u64,
+}
+
+impl std::ops::AddAssign for L2Residency {
+    fn add_assign(&mut self, other: Self) {
+        add_counter!(self.num_accesses, other.num_accesses);
+        add_counter!(self.num_hits, other.num_hits);
+    }
 }
 
 impl Stats {
@@ -196,6 +567,8 @@ impl Stats {
         Self {
             accesses: Accesses::default(),
             instructions: InstructionCounts::default(),
+            per_pc: PerPC::default(),
+            loops: Loops::default(),
             sim: Sim::default(),
             dram: DRAM::new(num_total_cores, num_mem_units, num_dram_banks),
             l1i_stats: PerCache::new(num_total_cores),
@@ -203,7 +576,22 @@ impl Stats {
             l1t_stats: PerCache::new(num_total_cores),
             l1d_stats: PerCache::new(num_total_cores),
             l2d_stats: PerCache::new(num_sub_partitions),
+            interconn: interconn::Interconn::default(),
+            schedulers: HashMap::new(),
             stall_dram_full: 0,
+            num_l2_bypassed: 0,
+            stall_interconn_to_shader: 0,
+            ldst_return_path_stall_cycles: 0,
+            num_writeback_stalls: HashMap::new(),
+            num_issued_per_core: HashMap::new(),
+            num_reorder_conflicts: HashMap::new(),
+            l2_arbitration_delays: HashMap::new(),
+            ejection_buffer_occupancy: HashMap::new(),
+            ldst_response_buffer_occupancy: HashMap::new(),
+            utilization: utilization::Utilization::default(),
+            memcopy: Memcopy::default(),
+            coherence: Coherence::default(),
+            l2_residency: L2Residency::default(),
         }
     }
 
@@ -212,6 +600,8 @@ impl Stats {
         Self {
             accesses: Accesses::default(),
             instructions: InstructionCounts::default(),
+            per_pc: PerPC::default(),
+            loops: Loops::default(),
             sim: Sim::default(),
             dram: DRAM::new(
                 config.num_total_cores,
@@ -223,7 +613,22 @@ impl Stats {
             l1t_stats: PerCache::new(config.num_total_cores),
             l1d_stats: PerCache::new(config.num_total_cores),
             l2d_stats: PerCache::new(config.num_sub_partitions),
+            interconn: interconn::Interconn::default(),
+            schedulers: HashMap::new(),
             stall_dram_full: 0,
+            num_l2_bypassed: 0,
+            stall_interconn_to_shader: 0,
+            ldst_return_path_stall_cycles: 0,
+            num_writeback_stalls: HashMap::new(),
+            num_issued_per_core: HashMap::new(),
+            num_reorder_conflicts: HashMap::new(),
+            l2_arbitration_delays: HashMap::new(),
+            ejection_buffer_occupancy: HashMap::new(),
+            ldst_response_buffer_occupancy: HashMap::new(),
+            utilization: utilization::Utilization::default(),
+            memcopy: Memcopy::default(),
+            coherence: Coherence::default(),
+            l2_residency: L2Residency::default(),
         }
     }
 }
@@ -250,3 +655,44 @@ where
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Sim, Stats};
+
+    #[test]
+    fn aggregate_near_overflow_counters() {
+        let mut total = Sim {
+            instructions: u64::MAX - 100,
+            ..Sim::default()
+        };
+        total += Sim {
+            instructions: 100,
+            ..Sim::default()
+        };
+        assert_eq!(total.instructions, u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "stat counter")]
+    fn aggregate_overflowing_counters() {
+        let mut total = Sim {
+            instructions: u64::MAX - 100,
+            ..Sim::default()
+        };
+        total += Sim {
+            instructions: 101,
+            ..Sim::default()
+        };
+    }
+
+    #[test]
+    #[should_panic(expected = "stat counter")]
+    fn aggregate_overflowing_map_counters() {
+        let mut total = Stats::empty();
+        let mut other = Stats::empty();
+        total.num_issued_per_core.insert(0, u64::MAX - 10);
+        other.num_issued_per_core.insert(0, 11);
+        total += other;
+    }
+}
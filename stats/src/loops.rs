@@ -0,0 +1,73 @@
+use crate::add_counter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Counters for a single loop detected in the instruction trace.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Loop {
+    /// Number of back-edges taken over all warps.
+    pub iterations: u64,
+    /// Warp instructions traced inside the loop body.
+    pub num_instructions: u64,
+    /// Memory instructions traced inside the loop body.
+    pub num_memory_instructions: u64,
+    /// Executed thread instructions inside the loop body.
+    ///
+    /// Filled from the per-PC execution counters when the kernel
+    /// completes, hence zero while the kernel is still running.
+    pub num_thread_instructions: u64,
+}
+
+impl std::ops::AddAssign for Loop {
+    fn add_assign(&mut self, other: Self) {
+        add_counter!(self.iterations, other.iterations);
+        add_counter!(self.num_instructions, other.num_instructions);
+        add_counter!(self.num_memory_instructions, other.num_memory_instructions);
+        add_counter!(self.num_thread_instructions, other.num_thread_instructions);
+    }
+}
+
+/// Loops detected in the instruction trace.
+///
+/// Loops are keyed by the program counter range `(head, tail)` of their
+/// body, where the head is the jump target of the back-edge and the tail
+/// is the instruction the back-edge was taken from. The program counters
+/// can be correlated with a SASS listing of the kernel, just like the
+/// per-PC execution counters ([`crate::PerPC`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Loops {
+    #[serde(with = "crate::as_entries")]
+    pub inner: HashMap<(usize, usize), Loop>,
+}
+
+impl Loops {
+    pub fn get_mut(&mut self, head: usize, tail: usize) -> &mut Loop {
+        self.inner.entry((head, tail)).or_default()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Loops sorted by the program counter of their head (outermost
+    /// loops first for the common nesting layout).
+    #[must_use]
+    pub fn sorted(&self) -> Vec<((usize, usize), Loop)> {
+        let mut loops: Vec<_> = self
+            .inner
+            .iter()
+            .map(|(range, stat)| (*range, *stat))
+            .collect();
+        loops.sort_by_key(|(range, _)| *range);
+        loops
+    }
+}
+
+impl std::ops::AddAssign for Loops {
+    fn add_assign(&mut self, other: Self) {
+        for (range, stat) in other.inner {
+            *self.inner.entry(range).or_default() += stat;
+        }
+    }
+}
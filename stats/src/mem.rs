@@ -1,3 +1,4 @@
+use crate::add_counter;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -190,6 +191,7 @@ pub struct Access {
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Accesses {
     pub kernel_info: super::KernelInfo,
+    #[serde(with = "crate::as_entries")]
     pub inner: HashMap<(Option<usize>, AccessKind), u64>,
 }
 
@@ -209,7 +211,7 @@ impl Default for Accesses {
 impl std::ops::AddAssign for Accesses {
     fn add_assign(&mut self, other: Self) {
         for (alloc_id, count) in other.inner {
-            *self.inner.entry(alloc_id).or_insert(0) += count;
+            add_counter!(*self.inner.entry(alloc_id).or_insert(0), count);
         }
     }
 }
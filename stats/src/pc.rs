@@ -0,0 +1,66 @@
+use crate::add_counter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Execution counters for a single program counter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PC {
+    /// Number of times a warp completed the instruction.
+    pub num_issued: u64,
+    /// Executed thread instructions (issues times active threads).
+    pub num_thread_instructions: u64,
+    /// Cycles a warp could not issue the instruction because of a
+    /// scoreboard collision.
+    pub stall_cycles: u64,
+}
+
+impl std::ops::AddAssign for PC {
+    fn add_assign(&mut self, other: Self) {
+        add_counter!(self.num_issued, other.num_issued);
+        add_counter!(self.num_thread_instructions, other.num_thread_instructions);
+        add_counter!(self.stall_cycles, other.stall_cycles);
+    }
+}
+
+/// Execution counters per program counter.
+///
+/// The program counter is the byte offset of the instruction within its
+/// kernel, which allows correlating the counters with a SASS listing of
+/// the kernel (e.g. from cuobjdump).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerPC {
+    pub inner: HashMap<usize, PC>,
+}
+
+impl PerPC {
+    pub fn get_mut(&mut self, pc: usize) -> &mut PC {
+        self.inner.entry(pc).or_default()
+    }
+
+    #[must_use]
+    pub fn get(&self, pc: usize) -> Option<&PC> {
+        self.inner.get(&pc)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Program counters sorted by the number of executed thread
+    /// instructions (hottest first).
+    #[must_use]
+    pub fn hottest(&self) -> Vec<(usize, PC)> {
+        let mut per_pc: Vec<_> = self.inner.iter().map(|(pc, stat)| (*pc, *stat)).collect();
+        per_pc.sort_by_key(|(pc, stat)| (std::cmp::Reverse(stat.num_thread_instructions), *pc));
+        per_pc
+    }
+}
+
+impl std::ops::AddAssign for PerPC {
+    fn add_assign(&mut self, other: Self) {
+        for (pc, stat) in other.inner {
+            *self.inner.entry(pc).or_default() += stat;
+        }
+    }
+}
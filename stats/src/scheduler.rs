@@ -1,10 +1,80 @@
+use crate::add_counter;
+use crate::utilization;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Scheduler {
+    /// Busy and idle cycles of this scheduler.
+    ///
+    /// The scheduler is busy in cycles in which it issued at least one
+    /// instruction.
+    pub utilization: utilization::Counters,
     pub num_single_issue: u64,
     pub num_dual_issue: u64,
     pub issue_raw_hazard_stall: u64,
     pub issue_control_hazard_stall: u64,
     pub issue_pipeline_stall: u64,
+    /// Instructions issued per warp id.
+    pub num_issued_per_warp: HashMap<usize, u64>,
+    /// Scoreboard stall cycles per (kernel launch id, program counter).
+    ///
+    /// Counts the cycles a warp could not issue its next instruction
+    /// because of a scoreboard collision, attributed to the stalled
+    /// instruction.
+    #[serde(with = "crate::as_entries")]
+    pub stall_cycles_per_pc: HashMap<(usize, usize), u64>,
+}
+
+impl Scheduler {
+    /// Jain's fairness index over the instructions issued per warp.
+    ///
+    /// Ranges from `1 / n` (a single warp received all issue slots) to
+    /// `1.0` (all supervised warps issued equally often). Returns `None`
+    /// if no instructions were issued.
+    ///
+    /// The sums are accumulated in integers: the map iteration order is
+    /// arbitrary, and summing floats in arbitrary order would make the
+    /// index differ between otherwise identical runs.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn issue_fairness(&self) -> Option<f64> {
+        let num_warps = self.num_issued_per_warp.len() as u128;
+        let issued = self
+            .num_issued_per_warp
+            .values()
+            .map(|issued| u128::from(*issued));
+        let sum: u128 = issued.clone().sum();
+        let sum_of_squares: u128 = issued.map(|issued| issued * issued).sum();
+        if sum_of_squares == 0 {
+            return None;
+        }
+        Some((sum * sum) as f64 / ((num_warps * sum_of_squares) as f64))
+    }
+}
+
+impl std::ops::AddAssign for Scheduler {
+    fn add_assign(&mut self, other: Self) {
+        self.utilization += other.utilization;
+        add_counter!(self.num_single_issue, other.num_single_issue);
+        add_counter!(self.num_dual_issue, other.num_dual_issue);
+        add_counter!(self.issue_raw_hazard_stall, other.issue_raw_hazard_stall);
+        add_counter!(
+            self.issue_control_hazard_stall,
+            other.issue_control_hazard_stall
+        );
+        add_counter!(self.issue_pipeline_stall, other.issue_pipeline_stall);
+        for (warp_id, issued) in other.num_issued_per_warp {
+            add_counter!(
+                *self.num_issued_per_warp.entry(warp_id).or_insert(0),
+                issued
+            );
+        }
+        for (pc, stall_cycles) in other.stall_cycles_per_pc {
+            add_counter!(
+                *self.stall_cycles_per_pc.entry(pc).or_insert(0),
+                stall_cycles
+            );
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use crate::add_counter;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -5,19 +6,113 @@ pub struct Sim {
     pub kernel_name: String,
     pub kernel_name_mangled: String,
     pub kernel_launch_id: usize,
+    /// Grid dimensions of the kernel launch.
+    ///
+    /// Zero for the no-kernel statistics, for which no launch exists.
+    pub grid_dim: (u32, u32, u32),
+    /// Block dimensions of the kernel launch.
+    pub block_dim: (u32, u32, u32),
+    /// Static shared memory used per block in bytes.
+    pub shared_mem_bytes: u32,
+    /// Registers used per thread.
+    pub num_registers: u32,
+    /// CUDA stream the kernel was launched on.
+    pub stream_id: u64,
+    /// Index of the tenant the kernel belongs to, if any.
+    pub tenant: Option<usize>,
     pub cycles: u64,
     pub instructions: u64,
     pub num_blocks: u64,
+    /// Number of blocks that were never issued because their trace
+    /// contains no instructions.
+    pub num_skipped_blocks: u64,
+    /// Number of instructions re-executed because their block was
+    /// re-issued after an injected core failure.
+    pub num_reexecuted_instructions: u64,
+    /// Number of cycles the kernel waited in the launch queue before a
+    /// slot for it became available.
+    pub kernel_launch_wait_cycles: u64,
     pub elapsed_millis: u128,
     pub is_release_build: bool,
+    /// Seed of the nondeterministic parallel run.
+    ///
+    /// `None` for serial and deterministic parallel runs or when no
+    /// seed was configured.
+    pub parallel_seed: Option<u64>,
+    /// Length of the longest register dependency chain of any warp
+    /// (critical path), in instructions.
+    pub longest_dependency_chain: u64,
+    /// Whether the kernel matches the persistent kernel pattern
+    /// (few blocks that each run for a very long time).
+    ///
+    /// Block sampling and progress heuristics are unreliable for such
+    /// kernels, since almost all work happens inside few blocks.
+    pub is_persistent_kernel: bool,
+    /// Total number of loop back-edges observed in the trace
+    /// (a warp jumping back to a lower instruction offset).
+    ///
+    /// Approximates the per-iteration behavior of the kernel: dividing
+    /// instructions or cycles by the iteration count gives per-iteration
+    /// averages for persistent kernel loops.
+    pub num_trace_loop_iterations: u64,
+    /// Largest number of loop back-edges observed for a single warp.
+    pub max_trace_loop_iterations: u64,
+    /// Number of cycles the DRAM clock was running at a reduced
+    /// frequency due to the throttling model.
+    ///
+    /// Throttling is a global effect, hence this is only recorded in
+    /// the no-kernel statistics.
+    pub num_dram_throttled_cycles: u64,
+    /// Number of cycles the GPU was idle waiting for the host between
+    /// commands, replayed from the host timestamps of the trace.
+    ///
+    /// Host gaps happen between kernels, hence this is only recorded in
+    /// the no-kernel statistics.
+    pub num_host_gap_cycles: u64,
+    /// Number of malformed trace records that were skipped in lenient
+    /// trace loading mode.
+    ///
+    /// Always zero in strict mode, where a malformed record aborts the
+    /// simulation.
+    pub num_malformed_trace_records: u64,
 }
 
 impl std::ops::AddAssign for Sim {
     fn add_assign(&mut self, other: Self) {
-        self.cycles += other.cycles;
-        self.instructions += other.instructions;
-        self.num_blocks += other.num_blocks;
-        self.elapsed_millis += other.elapsed_millis;
+        add_counter!(self.cycles, other.cycles);
+        add_counter!(self.instructions, other.instructions);
+        add_counter!(self.num_blocks, other.num_blocks);
+        add_counter!(self.num_skipped_blocks, other.num_skipped_blocks);
+        add_counter!(
+            self.num_reexecuted_instructions,
+            other.num_reexecuted_instructions
+        );
+        add_counter!(
+            self.kernel_launch_wait_cycles,
+            other.kernel_launch_wait_cycles
+        );
+        add_counter!(self.elapsed_millis, other.elapsed_millis);
         self.is_release_build |= other.is_release_build;
+        self.parallel_seed = self.parallel_seed.or(other.parallel_seed);
+        self.longest_dependency_chain = self
+            .longest_dependency_chain
+            .max(other.longest_dependency_chain);
+        self.is_persistent_kernel |= other.is_persistent_kernel;
+        add_counter!(
+            self.num_trace_loop_iterations,
+            other.num_trace_loop_iterations
+        );
+        self.max_trace_loop_iterations = self
+            .max_trace_loop_iterations
+            .max(other.max_trace_loop_iterations);
+        add_counter!(
+            self.num_dram_throttled_cycles,
+            other.num_dram_throttled_cycles
+        );
+        add_counter!(self.num_host_gap_cycles, other.num_host_gap_cycles);
+        add_counter!(
+            self.num_malformed_trace_records,
+            other.num_malformed_trace_records
+        );
     }
 }
@@ -0,0 +1,126 @@
+use crate::add_counter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Busy and idle cycle counts of a hardware component.
+///
+/// A component is busy in a cycle when it has work, and idle otherwise
+/// (it could be clock gated). The counts only cover cycles in which the
+/// component was clocked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counters {
+    /// Cycles the component had work.
+    pub busy_cycles: u64,
+    /// Cycles the component had no work.
+    pub idle_cycles: u64,
+}
+
+impl Counters {
+    /// Count one cycle as busy or idle.
+    pub fn add_cycle(&mut self, busy: bool) {
+        if busy {
+            self.busy_cycles += 1;
+        } else {
+            self.idle_cycles += 1;
+        }
+    }
+
+    /// Total number of cycles the component was clocked.
+    #[must_use]
+    pub fn total_cycles(&self) -> u64 {
+        self.busy_cycles + self.idle_cycles
+    }
+
+    /// The fraction of clocked cycles the component was busy.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn utilization(&self) -> f64 {
+        if self.total_cycles() == 0 {
+            return 0.0;
+        }
+        self.busy_cycles as f64 / self.total_cycles() as f64
+    }
+}
+
+impl std::ops::AddAssign for Counters {
+    fn add_assign(&mut self, other: Self) {
+        add_counter!(self.busy_cycles, other.busy_cycles);
+        add_counter!(self.idle_cycles, other.idle_cycles);
+    }
+}
+
+/// Busy and idle cycles per hardware component.
+///
+/// Shows which resources are underutilized for a workload without
+/// reading timelines.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Utilization {
+    /// Per core (global core id).
+    pub cores: HashMap<usize, Counters>,
+    /// Per warp scheduler (global core id, scheduler id).
+    #[serde(with = "crate::as_entries")]
+    pub schedulers: HashMap<(usize, usize), Counters>,
+    /// Per functional unit (global core id, unit name).
+    #[serde(with = "crate::as_entries")]
+    pub functional_units: HashMap<(usize, String), Counters>,
+    /// Per L2 slice (sub partition id).
+    pub l2_slices: HashMap<usize, Counters>,
+    /// Per DRAM channel (partition id).
+    pub dram_channels: HashMap<usize, Counters>,
+    /// Per DMA copy engine (engine id).
+    ///
+    /// Copy engines are only clocked between their first and their last
+    /// copy, hence idle cycles are the gaps between copies.
+    pub copy_engines: HashMap<usize, Counters>,
+}
+
+impl std::ops::AddAssign for Utilization {
+    fn add_assign(&mut self, other: Self) {
+        for (core_id, counters) in other.cores {
+            *self.cores.entry(core_id).or_default() += counters;
+        }
+        for (scheduler_id, counters) in other.schedulers {
+            *self.schedulers.entry(scheduler_id).or_default() += counters;
+        }
+        for (unit, counters) in other.functional_units {
+            *self.functional_units.entry(unit).or_default() += counters;
+        }
+        for (slice_id, counters) in other.l2_slices {
+            *self.l2_slices.entry(slice_id).or_default() += counters;
+        }
+        for (channel_id, counters) in other.dram_channels {
+            *self.dram_channels.entry(channel_id).or_default() += counters;
+        }
+        for (engine_id, counters) in other.copy_engines {
+            *self.copy_engines.entry(engine_id).or_default() += counters;
+        }
+    }
+}
+
+impl Utilization {
+    /// Busy and idle cycles per component kind, reduced over all
+    /// component instances.
+    #[must_use]
+    pub fn reduce(&self) -> Vec<(String, Counters)> {
+        let mut reduced = Vec::new();
+        for (kind, counters) in [
+            ("cores", reduce(self.cores.values())),
+            ("schedulers", reduce(self.schedulers.values())),
+            ("functional units", reduce(self.functional_units.values())),
+            ("l2 slices", reduce(self.l2_slices.values())),
+            ("dram channels", reduce(self.dram_channels.values())),
+            ("copy engines", reduce(self.copy_engines.values())),
+        ] {
+            reduced.push((kind.to_string(), counters));
+        }
+        reduced
+    }
+}
+
+fn reduce<'a>(counters: impl Iterator<Item = &'a Counters>) -> Counters {
+    let mut reduced = Counters::default();
+    for counter in counters {
+        reduced += *counter;
+    }
+    reduced
+}
@@ -40,6 +40,9 @@ typedef struct {
   uint32_t active_mask;
   uint32_t predicate_mask;
   uint64_t addrs[32];
+  // store data (first 32 bit register) per thread, zero unless store
+  // value capture is enabled
+  uint32_t store_values[32];
   uint32_t thread_idx_x[32];
   uint32_t thread_idx_y[32];
   uint32_t thread_idx_z[32];
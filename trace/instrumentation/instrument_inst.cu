@@ -25,8 +25,8 @@ instrument_inst(uint32_t pred, uint32_t instr_data_width,
                 uint32_t instr_opcode_id, uint32_t instr_offset,
                 uint32_t instr_idx, uint32_t line_num, uint32_t instr_mem_space,
                 uint32_t instr_predicate_num, uint32_t instr_flags,
-                uint64_t ptr_reg_info, uint64_t addr, uint64_t ptr_channel_dev,
-                uint64_t kernel_id) {
+                uint64_t ptr_reg_info, uint64_t addr, uint32_t store_data,
+                uint64_t ptr_channel_dev, uint64_t kernel_id) {
 
   // if thread is predicated off, do NOT return!
   // otherwise we end up with different number of instructions for each thread !
@@ -57,6 +57,11 @@ instrument_inst(uint32_t pred, uint32_t instr_data_width,
     } else {
       ma.addrs[i] = 0;
     }
+    if (instr_is_store) {
+      ma.store_values[i] = __shfl_sync(active_mask, store_data, i);
+    } else {
+      ma.store_values[i] = 0;
+    }
     unsigned volatile tid_x = threadIdx.x;
     unsigned volatile tid_y = threadIdx.y;
     unsigned volatile tid_z = threadIdx.z;
@@ -36,6 +36,11 @@ pub struct Options {
         help = "trace all instructions, including non-memory instructions (default: false)"
     )]
     pub full_trace: bool,
+    #[clap(
+        long = "capture-store-values",
+        help = "capture a digest of the store values (default: false)"
+    )]
+    pub capture_store_values: bool,
     #[clap(
         long = "validate",
         help = "perform validation on the traces after collection"
@@ -82,6 +87,7 @@ async fn main() -> eyre::Result<()> {
         traces_dir,
         save_json,
         full_trace,
+        capture_store_values,
         validate,
         tracer,
     } = options;
@@ -103,6 +109,7 @@ async fn main() -> eyre::Result<()> {
         traces_dir,
         save_json,
         full_trace,
+        capture_store_values,
         skip_kernel_prefixes: vec![],
         validate,
         tracer_so,
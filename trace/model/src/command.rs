@@ -36,6 +36,30 @@ pub struct KernelLaunch {
     pub nvbit_version: String,
     /// Properties of the device that traced this kernel launch
     pub device_properties: DeviceProperties,
+    /// Digest of the store values of the kernel.
+    ///
+    /// The wrapping sum of the store digest contributions of all trace
+    /// entries of the kernel
+    /// (see [`crate::MemAccessTraceEntry::store_digest_contribution`]).
+    /// Only captured when the tracer is run with store value capture
+    /// enabled. Absent in older traces.
+    #[serde(default)]
+    pub store_value_digest: Option<u64>,
+    /// Register cap the kernel was compiled with.
+    ///
+    /// Set when the kernel was compiled with `__launch_bounds__` or
+    /// `-maxrregcount`, which cap the per-thread register allocation
+    /// and therefore determine occupancy instead of the raw register
+    /// count. Absent in older traces.
+    #[serde(default)]
+    pub max_registers: Option<u32>,
+    /// Host-side timestamp when the kernel was launched, in
+    /// nanoseconds since the start of the traced application.
+    ///
+    /// The gaps between the timestamps of consecutive commands capture
+    /// CPU think-time between CUDA calls. Absent in older traces.
+    #[serde(default)]
+    pub host_timestamp_nanos: Option<u64>,
 }
 
 impl std::cmp::Ord for KernelLaunch {
@@ -79,6 +103,15 @@ impl KernelLaunch {
     pub fn name(&self) -> &str {
         &self.unmangled_name
     }
+
+    /// The register count that determines occupancy.
+    ///
+    /// The compile-time register cap (see [`KernelLaunch::max_registers`])
+    /// when present, otherwise the raw register count.
+    #[must_use]
+    pub fn occupancy_registers(&self) -> u32 {
+        self.max_registers.unwrap_or(self.num_registers)
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -86,6 +119,19 @@ pub struct MemcpyHtoD {
     pub allocation_name: Option<String>,
     pub dest_device_addr: u64,
     pub num_bytes: u64,
+    /// CUDA stream ID the copy was issued on.
+    ///
+    /// Zero (the default stream) synchronizes with all other work;
+    /// copies on other streams may overlap with kernels and with each
+    /// other. Absent in older traces.
+    #[serde(default)]
+    pub stream_id: u64,
+    /// Host-side timestamp when the copy was issued, in nanoseconds
+    /// since the start of the traced application.
+    ///
+    /// Absent in older traces (see [`KernelLaunch::host_timestamp_nanos`]).
+    #[serde(default)]
+    pub host_timestamp_nanos: Option<u64>,
 }
 
 impl std::fmt::Display for MemcpyHtoD {
@@ -104,6 +150,12 @@ pub struct MemAlloc {
     pub device_ptr: u64,
     pub fill_l2: bool,
     pub num_bytes: u64,
+    /// Host-side timestamp when the allocation was made, in nanoseconds
+    /// since the start of the traced application.
+    ///
+    /// Absent in older traces (see [`KernelLaunch::host_timestamp_nanos`]).
+    #[serde(default)]
+    pub host_timestamp_nanos: Option<u64>,
 }
 
 impl std::fmt::Display for MemAlloc {
@@ -123,6 +175,19 @@ pub enum Command {
     KernelLaunch(KernelLaunch),
 }
 
+impl Command {
+    /// Host-side timestamp when the command was issued, in nanoseconds
+    /// since the start of the traced application.
+    #[must_use]
+    pub fn host_timestamp_nanos(&self) -> Option<u64> {
+        match self {
+            Self::MemAlloc(inner) => inner.host_timestamp_nanos,
+            Self::MemcpyHtoD(inner) => inner.host_timestamp_nanos,
+            Self::KernelLaunch(inner) => inner.host_timestamp_nanos,
+        }
+    }
+}
+
 impl std::fmt::Display for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -140,6 +140,46 @@ pub struct MemAccessTraceEntry {
     /// which means that accesses to address 0 should generally not occur.
     pub addrs: [u64; 32],
     pub thread_indices: [(u32, u32, u32); 32],
+    /// Digest of the store data of the active threads of the warp.
+    ///
+    /// Only captured for store instructions when the tracer is run with
+    /// store value capture enabled (see [`store_data_digest`]).
+    /// Absent in older traces.
+    #[serde(default)]
+    pub store_data_digest: Option<u64>,
+}
+
+/// Offset basis of the 64 bit FNV-1a hash.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Prime of the 64 bit FNV-1a hash.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64 bit FNV-1a hash over a stream of words.
+fn fnv1a(words: impl IntoIterator<Item = u64>) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for word in words {
+        for byte in word.to_le_bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// Digest of the store data of the active threads of a warp.
+///
+/// Hashes the lane id and store value of every active thread, such
+/// that permuted or corrupted values produce a different digest.
+#[must_use]
+pub fn store_data_digest(active_mask: ActiveMask, values: &[u32; WARP_SIZE]) -> u64 {
+    fnv1a(
+        values
+            .iter()
+            .enumerate()
+            .filter(|(tid, _)| active_mask[*tid])
+            .flat_map(|(tid, value)| [tid as u64, u64::from(*value)]),
+    )
 }
 
 impl MemAccessTraceEntry {
@@ -165,6 +205,35 @@ impl MemAccessTraceEntry {
             .copied()
     }
 
+    /// Contribution of this entry to the store digest of its kernel.
+    ///
+    /// The contribution identifies the store by its position in the
+    /// grid and hashes the accessed addresses, the active mask, and the
+    /// captured store data digest. The contributions of all store
+    /// entries of a kernel are combined with a wrapping sum into
+    /// [`command::KernelLaunch::store_value_digest`], which is order
+    /// independent such that a simulator can replay thread blocks in
+    /// any order.
+    ///
+    /// Returns `None` when the trace was captured without store values.
+    #[must_use]
+    pub fn store_digest_contribution(&self) -> Option<u64> {
+        let store_data_digest = self.store_data_digest?;
+        Some(fnv1a(
+            [
+                u64::from(self.block_id.x),
+                u64::from(self.block_id.y),
+                u64::from(self.block_id.z),
+                u64::from(self.warp_id_in_block),
+                u64::from(self.instr_offset),
+                u64::from(self.active_mask.as_u32()),
+                store_data_digest,
+            ]
+            .into_iter()
+            .chain(self.valid_addresses()),
+        ))
+    }
+
     pub fn source_registers(&self) -> &[u32] {
         &self.src_regs[0..self.num_src_regs as usize]
     }
@@ -230,6 +299,72 @@ pub enum ValidationError {
     DuplicateWarpIds(Vec<(u64, dim::Dim, u32)>),
 }
 
+/// Position of a record within a kernel trace.
+///
+/// Taken from the last record that decoded successfully, since a
+/// malformed record cannot describe itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordContext {
+    pub kernel_id: u64,
+    pub block_id: Dim,
+    pub warp_id_in_block: u32,
+}
+
+impl std::fmt::Display for RecordContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "kernel {} block {} warp {}",
+            self.kernel_id, self.block_id, self.warp_id_in_block
+        )
+    }
+}
+
+/// Error reading a trace stream.
+///
+/// Unlike a generic report, the error pinpoints the malformed record by
+/// its index, byte offset, schema field and the position of the last
+/// record that decoded successfully.
+#[derive(thiserror::Error, Debug)]
+pub enum ReadError {
+    #[error("failed to read trace: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(
+        "malformed record {record_index} at byte offset {offset}{}{}: {source}",
+        schema_field.as_deref().map(|field| format!(" (field `{field}`)")).unwrap_or_default(),
+        context.as_ref().map(|context| format!(" (after {context})")).unwrap_or_default(),
+    )]
+    MalformedRecord {
+        /// Index of the record in the trace stream.
+        record_index: usize,
+        /// Byte offset at which decoding of the record started.
+        offset: u64,
+        /// Schema field that failed to decode, if known.
+        ///
+        /// Traces encoded as compact arrays carry no field names, in
+        /// which case only the decode error describes the mismatch.
+        schema_field: Option<String>,
+        /// Position of the last record that decoded successfully.
+        context: Option<RecordContext>,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+}
+
+/// Extract the offending schema field from a serde error message.
+///
+/// Serde reports field mismatches as ``missing field `name` `` or
+/// ``unknown field `name` ``; other decode errors yield [`None`].
+#[must_use]
+pub fn schema_field(message: &str) -> Option<String> {
+    if !message.contains("field") {
+        return None;
+    }
+    let (_, rest) = message.split_once('`')?;
+    let (field, _) = rest.split_once('`')?;
+    Some(field.to_string())
+}
+
 impl MemAccessTrace {
     #[must_use]
     // #[inline]
@@ -27,6 +27,8 @@ pub struct Args {
     // num_dest_regs: u32,
     pub num_src_regs: u32,
     pub src_regs: [u32; common::MAX_SRC as usize],
+    // store data register captured at runtime (store value capture)
+    pub store_data_reg: Option<u32>,
     // receiver channel
     pub ptr_channel_dev: u64,
     pub line_num: u32,
@@ -72,6 +74,12 @@ impl Args {
             instr.add_call_arg_const_val64(u64::MAX);
         }
 
+        // runtime value of the store data register (store value capture)
+        match self.store_data_reg {
+            Some(reg_num) => instr.add_call_arg_reg_val(reg_num.try_into().unwrap()),
+            None => instr.add_call_arg_const_val32(0),
+        }
+
         // pointer to device channel for sending packets
         instr.add_call_arg_const_val64(self.ptr_channel_dev);
 
@@ -67,6 +67,9 @@ pub struct Instrumentor<'c> {
     allocations: Mutex<Vec<trace_model::MemAllocation>>,
     commands: Mutex<Vec<trace_model::command::Command>>,
     kernels: Mutex<Vec<trace_model::command::KernelLaunch>>,
+    // per-kernel store digest (wrapping sum of the store digest
+    // contributions of all received store packets)
+    store_digests: Mutex<HashMap<u64, u64>>,
 
     pub start: Instant,
     pub instr_begin_interval: usize,
@@ -74,6 +77,7 @@ pub struct Instrumentor<'c> {
     pub traces_dir: PathBuf,
     pub validate: bool,
     pub full_trace: bool,
+    pub capture_store_values: bool,
     pub save_json: bool,
     pub skip_kernel_prefixes: Vec<String>,
     pub rmp_trace_file_path: PathBuf,
@@ -100,6 +104,7 @@ impl Instrumentor<'static> {
             validate = should_validate;
         }
         let save_json = bool_env("SAVE_JSON").unwrap_or(false);
+        let capture_store_values = bool_env("CAPTURE_STORE_VALUES").unwrap_or(false);
 
         let skip_kernel_prefixes: Vec<_> = std::env::var("SKIP_KERNEL_PREFIXES")
             .as_deref()
@@ -144,12 +149,14 @@ impl Instrumentor<'static> {
             traces_dir,
             full_trace,
             validate,
+            capture_store_values,
             save_json,
             skip_kernel_prefixes,
             rmp_trace_file_path,
             allocations: Mutex::new(Vec::new()),
             commands: Mutex::new(Vec::new()),
             kernels: Mutex::new(Vec::new()),
+            store_digests: Mutex::new(HashMap::new()),
         });
 
         // start receiving from the channel
@@ -231,6 +238,17 @@ impl<'c> Instrumentor<'c> {
                 );
             }
 
+            let store_data_digest = if self.capture_store_values && packet.instr_is_store {
+                let active_mask =
+                    trace_model::ActiveMask::from(packet.active_mask & packet.predicate_mask);
+                Some(trace_model::store_data_digest(
+                    active_mask,
+                    &packet.store_values,
+                ))
+            } else {
+                None
+            };
+
             let entry = trace_model::MemAccessTraceEntry {
                 cuda_ctx,
                 device_id: packet.device_id,
@@ -261,8 +279,15 @@ impl<'c> Instrumentor<'c> {
                 num_src_regs: packet.num_src_regs,
                 addrs: packet.addrs,
                 thread_indices,
+                store_data_digest,
             };
 
+            if let Some(contribution) = entry.store_digest_contribution() {
+                let mut store_digests = self.store_digests.lock().unwrap();
+                let digest = store_digests.entry(entry.kernel_id).or_insert(0);
+                *digest = digest.wrapping_add(contribution);
+            }
+
             rmp_encoder
                 .encode::<trace_model::MemAccessTraceEntry>(&entry)
                 .unwrap();
@@ -360,6 +385,11 @@ impl<'c> Instrumentor<'c> {
                 local_mem_addr_limit: nvbit_rs::local_mme_addr_limit(ctx),
                 nvbit_version: nvbit_rs::version().to_string(),
                 device_properties: trace_model::DeviceProperties::default(),
+                // only complete once the kernel trace has been received
+                store_value_digest: None,
+                // not exposed by nvbit
+                max_registers: None,
+                host_timestamp_nanos: Some(self.start.elapsed().as_nanos() as u64),
             };
             log::info!("KERNEL LAUNCH: {:#?}", &kernel_info);
             self.kernels.lock().unwrap().push(kernel_info.clone());
@@ -410,6 +440,10 @@ impl<'c> Instrumentor<'c> {
                             allocation_name: None,
                             dest_device_addr: dest_device.as_ptr(),
                             num_bytes,
+                            // the tracer does not record the stream of
+                            // a copy
+                            stream_id: 0,
+                            host_timestamp_nanos: Some(self.start.elapsed().as_nanos() as u64),
                         },
                     ));
             }
@@ -445,6 +479,7 @@ impl<'c> Instrumentor<'c> {
                             fill_l2: false,
                             device_ptr,
                             num_bytes,
+                            host_timestamp_nanos: Some(self.start.elapsed().as_nanos() as u64),
                         },
                     ));
             }
@@ -490,6 +525,7 @@ impl<'c> Instrumentor<'c> {
             let mut src_num: usize = 0;
             let mut dest_operand: Option<u32> = None;
             let mut mem_operand_idx: Option<usize> = None;
+            let mut store_data_reg: Option<u32> = None;
 
             // find dst reg and handle the special case if the oprd[0] is mem...
             // (e.g. store and RED)
@@ -525,6 +561,12 @@ impl<'c> Instrumentor<'c> {
                         assert!(src_num < common::MAX_SRC as usize);
                         src_operands[src_num] = num.try_into().unwrap();
                         src_num += 1;
+                        // the first register operand after the memory
+                        // reference holds the (first 32 bit of the)
+                        // store data
+                        if mem_operand_idx.is_some() && store_data_reg.is_none() {
+                            store_data_reg = Some(num.try_into().unwrap());
+                        }
                     }
                     model::OperandKind::CBank {
                         id,
@@ -567,6 +609,11 @@ impl<'c> Instrumentor<'c> {
                 dest_reg: dest_operand,
                 num_src_regs: src_num.try_into().unwrap(),
                 src_regs: src_operands,
+                store_data_reg: if self.capture_store_values && instr.is_store() {
+                    store_data_reg
+                } else {
+                    None
+                },
                 ptr_channel_dev: channel_dev_lock.as_mut_ptr() as u64,
                 line_num,
             };
@@ -770,7 +817,18 @@ impl<'c> Instrumentor<'c> {
     pub fn save_command_trace(&self) {
         let command_trace_file_path = self.traces_dir.join("commands.json");
         let mut serializer = json_serializer(&command_trace_file_path);
-        let commands = self.commands.lock().unwrap();
+        let mut commands = self.commands.lock().unwrap();
+        if self.capture_store_values {
+            // the digests are only complete once the receiver thread
+            // has drained the channel
+            let store_digests = self.store_digests.lock().unwrap();
+            for command in commands.iter_mut() {
+                if let trace_model::Command::KernelLaunch(kernel) = command {
+                    kernel.store_value_digest =
+                        Some(store_digests.get(&kernel.id).copied().unwrap_or(0));
+                }
+            }
+        }
         commands.serialize(&mut serializer).unwrap();
 
         log::info!(
@@ -470,6 +470,7 @@ pub async fn profile(
             } else {
                 let options = profile::nvprof::Options {
                     nvprof_path: profile_options.nvprof_path.clone(),
+                    device: None,
                 };
                 let output = profile::nvprof::nvprof(&bench.executable_path, &bench.args, &options)
                     .await
@@ -529,6 +530,7 @@ pub async fn profile(
             } else {
                 let options = profile::nsight::Options {
                     nsight_path: profile_options.nsight_path.clone(),
+                    device: None,
                 };
                 let output = profile::nsight::nsight(&bench.executable_path, &bench.args, &options)
                     .await
@@ -47,6 +47,7 @@ pub async fn trace(
         save_json,
         validate,
         full_trace,
+        capture_store_values: false,
     };
     let dur = invoke_trace::trace(&bench.executable_path, &bench.args, &options)
         .await
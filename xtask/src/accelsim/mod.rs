@@ -25,6 +25,25 @@ pub enum Command {
         #[clap(short = 'o', long = "output", help = "converted output file path")]
         output: PathBuf,
     },
+    /// Run the accel-sim reference simulator on a trace and convert its
+    /// statistics into the stats JSON schema used by this crate.
+    Sim {
+        #[clap(help = "directory containing accelsim traces (kernelslist.g)")]
+        traces_dir: PathBuf,
+        #[clap(long = "kernels", help = "path to kernelslist.g file")]
+        kernelslist: Option<PathBuf>,
+        #[clap(long = "config-dir", help = "path to accelsim config directory")]
+        config_dir: PathBuf,
+        #[clap(short = 'o', long = "stats", help = "output stats JSON file path")]
+        stats: Option<PathBuf>,
+        #[clap(long = "timeout", help = "simulation timeout in seconds")]
+        timeout_secs: Option<u64>,
+        #[clap(
+            long = "upstream",
+            help = "use upstream accelsim implementation (unmodified)"
+        )]
+        use_upstream: Option<bool>,
+    },
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -68,6 +87,64 @@ pub fn run(options: Options) -> eyre::Result<()> {
             }
             println!("wrote config to {}", output.display());
         }
+        Command::Sim {
+            traces_dir,
+            kernelslist,
+            config_dir,
+            stats,
+            timeout_secs,
+            use_upstream,
+        } => {
+            let kernelslist = kernelslist.unwrap_or_else(|| traces_dir.join("kernelslist.g"));
+            if !kernelslist.is_file() {
+                eyre::bail!("missing kernelslist {}", kernelslist.display());
+            }
+
+            let config = accelsim::SimConfig {
+                config_dir: Some(config_dir),
+                ..accelsim::SimConfig::default()
+            };
+
+            let extra_sim_args: &[String] = &[];
+            let stream_output = false;
+            let timeout = timeout_secs.map(std::time::Duration::from_secs);
+
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let (output, dur) = runtime.block_on(accelsim_sim::simulate_trace(
+                &traces_dir,
+                &kernelslist,
+                &config,
+                timeout,
+                extra_sim_args,
+                stream_output,
+                use_upstream.unwrap_or(true),
+            ))?;
+            println!("simulated {} in {:?}", traces_dir.display(), dur);
+
+            // parse the reference simulator log into our stats schema
+            let parse_options = accelsim::parser::Options::default();
+            let log_reader = std::io::Cursor::new(&output.stdout);
+            let raw_stats = accelsim::Stats {
+                is_release_build: !accelsim_sim::is_debug(),
+                ..accelsim::parser::parse_stats(log_reader, &parse_options)?
+            };
+
+            let mut per_kernel_stats: stats::PerKernel = raw_stats.try_into()?;
+            let num_kernels = per_kernel_stats.as_ref().len().max(1);
+            let per_kernel_dur = dur.as_millis() / num_kernels as u128;
+            for kernel_stats in per_kernel_stats.as_mut().iter_mut() {
+                kernel_stats.sim.elapsed_millis = per_kernel_dur;
+            }
+
+            let stats_path = stats.unwrap_or_else(|| traces_dir.join("stats.json"));
+            serde_json::to_writer_pretty(
+                utils::fs::open_writable(&stats_path)?,
+                &per_kernel_stats,
+            )?;
+            println!("wrote stats to {}", stats_path.display());
+        }
     }
     Ok(())
 }